@@ -8,8 +8,15 @@ use crate::util::{const_ptr_as_ref, mut_ptr_as_mut};
 use crate::Error;
 
 /// Secret key for the prover
-#[derive(PartialEq, Eq, Debug, Clone)]
+#[derive(PartialEq, Eq, Debug)]
 pub struct SecretKey(pub(crate) wallet::secret_key::SecretKey);
+
+/// Collections hand out copies of their elements over FFI
+impl Clone for SecretKey {
+    fn clone(&self) -> Self {
+        SecretKey(self.0.clone_secret())
+    }
+}
 pub type SecretKeyPtr = *mut SecretKey;
 pub type ConstSecretKeyPtr = *const SecretKey;
 
@@ -59,7 +66,7 @@ pub unsafe fn secret_key_to_bytes(
     output: *mut u8,
 ) -> Result<(), Error> {
     let secret_key = const_ptr_as_ref(secret_key_ptr, "secret_key_ptr")?;
-    let src = secret_key.0.to_bytes();
+    let src = secret_key.0.expose_secret_bytes();
     std::ptr::copy_nonoverlapping(src.as_ptr(), output, DlogProverInput::SIZE_BYTES);
     Ok(())
 }
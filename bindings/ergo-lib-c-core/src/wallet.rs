@@ -1,5 +1,7 @@
 //! Wallet-like features
 
+use ergo_lib::ergotree_ir::serialization::SigmaSerializable;
+use ergo_lib::ergotree_ir::sigma_protocol::sigma_boolean::ProveDlog;
 use ergo_lib::ergotree_ir::sigma_protocol::sigma_boolean::SigmaBoolean;
 
 use std::str::FromStr;
@@ -98,7 +100,7 @@ pub unsafe fn wallet_from_secrets(
     let secret_keys = const_ptr_as_ref(secret_keys_ptr, "secret_keys_ptr")?;
     let wallet_out = mut_ptr_as_mut(wallet_out, "wallet_out")?;
     *wallet_out = Box::into_raw(Box::new(Wallet(ergo_lib::wallet::Wallet::from_secrets(
-        secret_keys.0.clone().into_iter().map(|s| s.0).collect(),
+        secret_keys.0.iter().map(|s| s.0.clone_secret()).collect(),
     ))));
     Ok(())
 }
@@ -110,7 +112,7 @@ pub unsafe fn wallet_add_secret(
 ) -> Result<(), Error> {
     let wallet = mut_ptr_as_mut(wallet_ptr, "wallet_ptr")?;
     let sk = mut_ptr_as_mut(secret_key_ptr, "secret_key_ptr")?;
-    wallet.0.add_secret(sk.0.clone());
+    wallet.0.add_secret(sk.0.clone_secret());
     Ok(())
 }
 
@@ -311,3 +313,116 @@ pub unsafe fn verify_signature(
         ))
     }
 }
+
+/// Set of public keys, one of which signs a message without revealing which one
+pub struct KeyRing(ergo_lib::wallet::ring_signature::KeyRing);
+pub type KeyRingPtr = *mut KeyRing;
+pub type ConstKeyRingPtr = *const KeyRing;
+
+fn p2pk_key(address: &Address) -> Result<ProveDlog, Error> {
+    if let Address(ergo_lib::ergotree_ir::chain::address::Address::P2Pk(d)) = address {
+        Ok(d.clone())
+    } else {
+        Err(Error::Misc("key_ring: Address:P2Pk expected".into()))
+    }
+}
+
+/// Create a ring with the key of the given P2PK address
+pub unsafe fn key_ring_from_p2pk_address(
+    address_ptr: ConstAddressPtr,
+    key_ring_out: *mut KeyRingPtr,
+) -> Result<(), Error> {
+    let address = const_ptr_as_ref(address_ptr, "address_ptr")?;
+    let key_ring_out = mut_ptr_as_mut(key_ring_out, "key_ring_out")?;
+    let ring = ergo_lib::wallet::ring_signature::KeyRing::new(vec![p2pk_key(address)?])?;
+    *key_ring_out = Box::into_raw(Box::new(KeyRing(ring)));
+    Ok(())
+}
+
+/// Add the key of the given P2PK address to the ring
+pub unsafe fn key_ring_add_p2pk_address(
+    key_ring_ptr: KeyRingPtr,
+    address_ptr: ConstAddressPtr,
+) -> Result<(), Error> {
+    let key_ring = mut_ptr_as_mut(key_ring_ptr, "key_ring_ptr")?;
+    let address = const_ptr_as_ref(address_ptr, "address_ptr")?;
+    key_ring.0.add(p2pk_key(address)?)?;
+    Ok(())
+}
+
+/// Number of keys in the ring
+pub unsafe fn key_ring_len(key_ring_ptr: ConstKeyRingPtr) -> Result<usize, Error> {
+    let key_ring = const_ptr_as_ref(key_ring_ptr, "key_ring_ptr")?;
+    Ok(key_ring.0.keys().len())
+}
+
+/// Parse from the compact encoding (number of keys and compressed keys)
+pub unsafe fn key_ring_from_bytes(
+    bytes_ptr: *const u8,
+    len: usize,
+    key_ring_out: *mut KeyRingPtr,
+) -> Result<(), Error> {
+    if bytes_ptr.is_null() {
+        return Err(Error::Misc("bytes_ptr is null".into()));
+    }
+    let bytes = std::slice::from_raw_parts(bytes_ptr, len);
+    let key_ring_out = mut_ptr_as_mut(key_ring_out, "key_ring_out")?;
+    let ring = ergo_lib::wallet::ring_signature::KeyRing::sigma_parse_bytes(bytes)?;
+    *key_ring_out = Box::into_raw(Box::new(KeyRing(ring)));
+    Ok(())
+}
+
+/// Return length of the compact encoding of the ring
+pub unsafe fn key_ring_bytes_len(key_ring_ptr: ConstKeyRingPtr) -> Result<usize, Error> {
+    let key_ring = const_ptr_as_ref(key_ring_ptr, "key_ring_ptr")?;
+    Ok(key_ring.0.sigma_serialize_bytes().map(|v| v.len())?)
+}
+
+/// Convert to the compact encoding. Key assumption: enough memory has been allocated at the
+/// address pointed-to by `output`. Use `key_ring_bytes_len` to determine the length of the byte
+/// array.
+pub unsafe fn key_ring_to_bytes(
+    key_ring_ptr: ConstKeyRingPtr,
+    output: *mut u8,
+) -> Result<(), Error> {
+    let key_ring = const_ptr_as_ref(key_ring_ptr, "key_ring_ptr")?;
+    let src = key_ring.0.sigma_serialize_bytes()?;
+    std::ptr::copy_nonoverlapping(src.as_ptr(), output, src.len());
+    Ok(())
+}
+
+/// Sign an arbitrary message with one of the wallet's keys in the ring without revealing which
+/// one
+pub unsafe fn wallet_sign_ring_message(
+    wallet_ptr: ConstWalletPtr,
+    key_ring_ptr: ConstKeyRingPtr,
+    message_ptr: *const u8,
+    message_length: usize,
+    signed_message_out: *mut SignedMessagePtr,
+) -> Result<(), Error> {
+    let wallet = const_ptr_as_ref(wallet_ptr, "wallet_ptr")?;
+    let key_ring = const_ptr_as_ref(key_ring_ptr, "key_ring_ptr")?;
+    let msg = std::slice::from_raw_parts(message_ptr, message_length);
+    let signed_message_out = mut_ptr_as_mut(signed_message_out, "signed_message_out")?;
+    let sig = wallet.0.sign_ring_message(&key_ring.0, msg)?;
+    *signed_message_out = Box::into_raw(Box::new(SignedMessage(sig)));
+    Ok(())
+}
+
+/// Verify that the signature was produced by one of the ring's keys for the message
+pub unsafe fn verify_ring_signature(
+    key_ring_ptr: ConstKeyRingPtr,
+    message_ptr: *const u8,
+    message_length: usize,
+    signed_message_ptr: ConstSignedMessagePtr,
+) -> Result<bool, Error> {
+    let key_ring = const_ptr_as_ref(key_ring_ptr, "key_ring_ptr")?;
+    let msg = std::slice::from_raw_parts(message_ptr, message_length);
+    let signed_message = const_ptr_as_ref(signed_message_ptr, "signed_message_ptr")?;
+    let res = ergo_lib::wallet::ring_signature::verify_ring_signature(
+        &key_ring.0,
+        msg,
+        signed_message.0.as_slice(),
+    )?;
+    Ok(res)
+}
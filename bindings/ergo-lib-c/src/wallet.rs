@@ -1,6 +1,7 @@
 //! Wallet-like features
 
 use crate::ReturnBool;
+use crate::ReturnNum;
 use crate::ReturnString;
 use ergo_lib_c_core::{
     address::ConstAddressPtr,
@@ -279,3 +280,129 @@ pub unsafe extern "C" fn ergo_lib_signed_message_delete(ptr: SignedMessagePtr) {
 pub unsafe extern "C" fn ergo_lib_wallet_delete(ptr: WalletPtr) {
     delete_ptr(ptr)
 }
+
+/// Create a ring with the key of the given P2PK address
+#[no_mangle]
+pub unsafe extern "C" fn ergo_lib_key_ring_from_p2pk_address(
+    address_ptr: ConstAddressPtr,
+    key_ring_out: *mut KeyRingPtr,
+) -> ErrorPtr {
+    let res = key_ring_from_p2pk_address(address_ptr, key_ring_out);
+    Error::c_api_from(res)
+}
+
+/// Add the key of the given P2PK address to the ring
+#[no_mangle]
+pub unsafe extern "C" fn ergo_lib_key_ring_add_p2pk_address(
+    key_ring_ptr: KeyRingPtr,
+    address_ptr: ConstAddressPtr,
+) -> ErrorPtr {
+    let res = key_ring_add_p2pk_address(key_ring_ptr, address_ptr);
+    Error::c_api_from(res)
+}
+
+/// Number of keys in the ring
+#[no_mangle]
+pub unsafe extern "C" fn ergo_lib_key_ring_len(key_ring_ptr: ConstKeyRingPtr) -> ReturnNum<usize> {
+    match key_ring_len(key_ring_ptr) {
+        Ok(value) => ReturnNum {
+            value,
+            error: std::ptr::null_mut(),
+        },
+        Err(e) => ReturnNum {
+            value: 0, // Just a dummy value
+            error: Error::c_api_from(Err(e)),
+        },
+    }
+}
+
+/// Parse from the compact encoding (number of keys and compressed keys)
+#[no_mangle]
+pub unsafe extern "C" fn ergo_lib_key_ring_from_bytes(
+    bytes_ptr: *const u8,
+    len: usize,
+    key_ring_out: *mut KeyRingPtr,
+) -> ErrorPtr {
+    let res = key_ring_from_bytes(bytes_ptr, len, key_ring_out);
+    Error::c_api_from(res)
+}
+
+/// Determine number of bytes of the compact encoding of the ring
+#[no_mangle]
+pub unsafe extern "C" fn ergo_lib_key_ring_bytes_len(
+    key_ring_ptr: ConstKeyRingPtr,
+) -> ReturnNum<usize> {
+    match key_ring_bytes_len(key_ring_ptr) {
+        Ok(value) => ReturnNum {
+            value,
+            error: std::ptr::null_mut(),
+        },
+        Err(e) => ReturnNum {
+            value: 0, // Just a dummy value
+            error: Error::c_api_from(Err(e)),
+        },
+    }
+}
+
+/// Convert to the compact encoding. **Key assumption:** enough memory has been allocated at the
+/// address pointed-to by `output`. Use `ergo_lib_key_ring_bytes_len` to determine the length of
+/// the byte array.
+#[no_mangle]
+pub unsafe extern "C" fn ergo_lib_key_ring_to_bytes(
+    key_ring_ptr: ConstKeyRingPtr,
+    output: *mut u8,
+) -> ErrorPtr {
+    let res = key_ring_to_bytes(key_ring_ptr, output);
+    Error::c_api_from(res)
+}
+
+/// Drop `KeyRing`
+#[no_mangle]
+pub unsafe extern "C" fn ergo_lib_key_ring_delete(ptr: KeyRingPtr) {
+    delete_ptr(ptr)
+}
+
+/// Sign an arbitrary message with one of the wallet's keys in the ring without revealing which
+/// one
+#[no_mangle]
+pub unsafe extern "C" fn ergo_lib_wallet_sign_ring_message(
+    wallet_ptr: ConstWalletPtr,
+    key_ring_ptr: ConstKeyRingPtr,
+    message_ptr: *const u8,
+    message_length: usize,
+    signed_message_out: *mut SignedMessagePtr,
+) -> ErrorPtr {
+    let res = wallet_sign_ring_message(
+        wallet_ptr,
+        key_ring_ptr,
+        message_ptr,
+        message_length,
+        signed_message_out,
+    );
+    Error::c_api_from(res)
+}
+
+/// Verify that the signature was produced by one of the ring's keys for the message
+#[no_mangle]
+pub unsafe extern "C" fn ergo_lib_verify_ring_signature(
+    key_ring_ptr: ConstKeyRingPtr,
+    message_ptr: *const u8,
+    message_length: usize,
+    signed_message_ptr: ConstSignedMessagePtr,
+) -> ReturnBool {
+    match verify_ring_signature(
+        key_ring_ptr,
+        message_ptr,
+        message_length,
+        signed_message_ptr,
+    ) {
+        Ok(value) => ReturnBool {
+            value,
+            error: std::ptr::null_mut(),
+        },
+        Err(e) => ReturnBool {
+            value: false, // Just a dummy value
+            error: Error::c_api_from(Err(e)),
+        },
+    }
+}
@@ -22,6 +22,7 @@ use ergo_lib::wallet::derivation_path::ChildIndexError;
 use ergo_lib::wallet::derivation_path::DerivationPathError;
 use ergo_lib::wallet::ext_pub_key::ExtPubKeyError;
 use ergo_lib::wallet::ext_secret_key::ExtSecretKeyError;
+use ergo_lib::wallet::ring_signature::KeyRingError;
 use ergo_lib::wallet::signing::TxSigningError;
 use ergo_lib::wallet::tx_context::TransactionContextError;
 use ergo_lib::{
@@ -98,6 +99,7 @@ from_error_to_wrap!(ConvError);
 from_error_to_wrap!(TransactionContextError);
 from_error_to_wrap!(TxVerifyError);
 from_error_to_wrap!(RegisterValueError);
+from_error_to_wrap!(KeyRingError);
 
 macro_rules! from_error_to_wrap_via_debug {
     ($t:ident) => {
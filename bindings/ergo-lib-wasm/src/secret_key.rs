@@ -13,8 +13,8 @@ use derive_more::{From, Into};
 
 /// Secret key for the prover
 #[wasm_bindgen]
-#[derive(PartialEq, Eq, Debug, Clone, From, Into)]
-pub struct SecretKey(wallet::secret_key::SecretKey);
+#[derive(PartialEq, Eq, Debug, From, Into)]
+pub struct SecretKey(pub(crate) wallet::secret_key::SecretKey);
 
 #[wasm_bindgen]
 impl SecretKey {
@@ -110,19 +110,21 @@ impl SecretKey {
     /// Serialized secret key (32 bytes for Dlog, 32(secret)+33(g)+33(h)+33(u)+33(v)=164 bytes for DHT)
     /// DHT format is the same as in from_bytes
     pub fn to_bytes(&self) -> Vec<u8> {
-        self.0.to_bytes()
+        self.0.expose_secret_bytes().to_vec()
     }
 
     /// Parse secret key from JSON string (Dlog expected as base16-encoded bytes, DHT in node REST API format)
     pub fn from_json(json_str: &str) -> Result<SecretKey, JsValue> {
-        serde_json::from_str(json_str)
+        wallet::secret_key::SecretKey::from_json(json_str)
             .map(SecretKey)
             .map_err(|e| JsValue::from_str(&format!("failed to parse SecretKey from JSON: {}", e)))
     }
 
     /// Encode secret key to JSON string (Dlog as base16-encoded bytes, DHT in node REST API format)
     pub fn to_json(&self) -> Result<String, JsValue> {
-        serde_json::to_string(&self.0)
+        self.0
+            .expose_secret_json()
+            .map(|json| json.to_string())
             .map_err(|e| JsValue::from_str(&format!("failed to encode SecretKey to JSON: {}", e)))
     }
 }
@@ -146,18 +148,18 @@ impl SecretKeys {
 
     /// Returns the element of the collection with a given index
     pub fn get(&self, index: usize) -> SecretKey {
-        self.0[index].clone()
+        SecretKey(self.0[index].0.clone_secret())
     }
 
     /// Adds an elements to the collection
     pub fn add(&mut self, elem: &SecretKey) {
-        self.0.push(elem.clone());
+        self.0.push(SecretKey(elem.0.clone_secret()));
     }
 }
 
 impl From<&SecretKeys> for Vec<wallet::secret_key::SecretKey> {
     fn from(v: &SecretKeys) -> Self {
-        v.0.clone().iter().map(|i| i.0.clone()).collect()
+        v.0.iter().map(|i| i.0.clone_secret()).collect()
     }
 }
 impl From<Vec<wallet::secret_key::SecretKey>> for SecretKeys {
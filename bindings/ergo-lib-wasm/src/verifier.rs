@@ -5,7 +5,7 @@ use std::convert::TryFrom;
 use ergo_lib::ergotree_ir::sigma_protocol::sigma_boolean::SigmaBoolean;
 use wasm_bindgen::{prelude::*, JsValue};
 
-use crate::{address::Address, error_conversion::to_js};
+use crate::{address::Address, error_conversion::to_js, wallet::key_ring::KeyRing};
 
 /// Verify that the signature is presented to satisfy SigmaProp conditions.
 #[wasm_bindgen]
@@ -26,3 +26,42 @@ pub fn verify_signature(
         ))
     }
 }
+
+/// Parse the signature of the address (P2PK) for the message into the proof tree with the
+/// challenges, responses and recomputed commitments, encoded as JSON. For debugging signatures that
+/// fail to verify.
+#[wasm_bindgen]
+pub fn inspect_signature(
+    address: &Address,
+    message: &[u8],
+    signature: &[u8],
+) -> Result<String, JsValue> {
+    if let Address(ergo_lib::ergotree_ir::chain::address::Address::P2Pk(d)) = address.clone() {
+        let sb = SigmaBoolean::try_from(d).map_err(|e| JsValue::from_str(&format!("{:?}", e)))?;
+        let inspection =
+            ergo_lib::ergotree_interpreter::sigma_protocol::proof_inspection::inspect_proof(
+                &sb, message, signature,
+            )
+            .map_err(to_js)?;
+        serde_json::to_string_pretty(&inspection).map_err(to_js)
+    } else {
+        Err(JsValue::from_str(
+            "wallet::inspect_signature: Address:P2Pk expected",
+        ))
+    }
+}
+
+/// Verify that the signature was produced by one of the ring's keys for the message
+#[wasm_bindgen]
+pub fn verify_ring_signature(
+    ring: &KeyRing,
+    message: &[u8],
+    signature: &[u8],
+) -> Result<bool, JsValue> {
+    ergo_lib::wallet::ring_signature::verify_ring_signature(
+        &ring.clone().into(),
+        message,
+        signature,
+    )
+    .map_err(to_js)
+}
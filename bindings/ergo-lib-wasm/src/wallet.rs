@@ -7,6 +7,7 @@ use wasm_bindgen::prelude::*;
 pub mod derivation_path;
 pub mod ext_pub_key;
 pub mod ext_secret_key;
+pub mod key_ring;
 pub mod mnemonic;
 
 use crate::address::Address;
@@ -21,6 +22,7 @@ use crate::{
     transaction::Transaction,
     transaction::UnsignedTransaction,
 };
+use key_ring::KeyRing;
 
 /// A collection of secret keys. This simplified signing by matching the secret keys to the correct inputs automatically.
 #[wasm_bindgen]
@@ -47,7 +49,7 @@ impl Wallet {
     /// Add a secret to the wallets prover
     #[wasm_bindgen]
     pub fn add_secret(&mut self, secret: &SecretKey) {
-        self.0.add_secret(secret.clone().into());
+        self.0.add_secret(secret.0.clone_secret());
     }
 
     /// Sign a transaction:
@@ -194,6 +196,16 @@ impl Wallet {
         }
     }
 
+    /// Sign an arbitrary message with one of the wallet's keys in the ring without revealing
+    /// which one
+    #[wasm_bindgen]
+    pub fn sign_ring_message(&self, ring: &KeyRing, message: &[u8]) -> Result<Uint8Array, JsValue> {
+        self.0
+            .sign_ring_message(&ring.clone().into(), message)
+            .map_err(to_js)
+            .map(|v| Uint8Array::from(v.as_slice()))
+    }
+
     /// Sign a given tx input
     #[wasm_bindgen]
     pub fn sign_tx_input(
@@ -16,7 +16,7 @@ use derive_more::{From, Into};
 
 /// Extented secret key implemented according to BIP-32
 #[wasm_bindgen]
-#[derive(PartialEq, Eq, Debug, Clone, From, Into)]
+#[derive(PartialEq, Eq, Debug, From, Into)]
 pub struct ExtSecretKey(InnerExtSecretKey);
 
 #[wasm_bindgen]
@@ -58,7 +58,7 @@ impl ExtSecretKey {
 
     /// The bytes of the associated secret key
     pub fn secret_key_bytes(&self) -> Result<Vec<u8>, JsValue> {
-        Ok(self.0.secret_key_bytes().into())
+        Ok(self.0.expose_secret_bytes().to_vec())
     }
 
     /// The extended public key associated with this secret key
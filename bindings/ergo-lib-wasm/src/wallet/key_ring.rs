@@ -0,0 +1,57 @@
+//! Key rings for ring signatures
+
+use ergo_lib::ergotree_ir::serialization::SigmaSerializable;
+use ergo_lib::ergotree_ir::sigma_protocol::sigma_boolean::ProveDlog;
+use ergo_lib::wallet::ring_signature::KeyRing as InnerKeyRing;
+use wasm_bindgen::prelude::*;
+
+use crate::address::Address;
+use crate::error_conversion::to_js;
+
+extern crate derive_more;
+use derive_more::{From, Into};
+
+/// Set of public keys, one of which signs a message without revealing which one
+#[wasm_bindgen]
+#[derive(PartialEq, Eq, Debug, Clone, From, Into)]
+pub struct KeyRing(InnerKeyRing);
+
+#[wasm_bindgen]
+impl KeyRing {
+    /// Create a ring with the key of the given P2PK address
+    pub fn from_p2pk_address(address: &Address) -> Result<KeyRing, JsValue> {
+        InnerKeyRing::new(vec![p2pk_key(address)?])
+            .map(KeyRing)
+            .map_err(to_js)
+    }
+
+    /// Add the key of the given P2PK address to the ring
+    pub fn add_p2pk_address(&mut self, address: &Address) -> Result<(), JsValue> {
+        self.0.add(p2pk_key(address)?).map_err(to_js)
+    }
+
+    /// Number of keys in the ring
+    pub fn len(&self) -> usize {
+        self.0.keys().len()
+    }
+
+    /// Parse from the compact encoding (number of keys and compressed keys)
+    pub fn from_bytes(data: Vec<u8>) -> Result<KeyRing, JsValue> {
+        InnerKeyRing::sigma_parse_bytes(&data)
+            .map(KeyRing)
+            .map_err(to_js)
+    }
+
+    /// Compact encoding (number of keys and compressed keys)
+    pub fn sigma_serialize_bytes(&self) -> Result<Vec<u8>, JsValue> {
+        self.0.sigma_serialize_bytes().map_err(to_js)
+    }
+}
+
+fn p2pk_key(address: &Address) -> Result<ProveDlog, JsValue> {
+    if let Address(ergo_lib::ergotree_ir::chain::address::Address::P2Pk(d)) = address.clone() {
+        Ok(d)
+    } else {
+        Err(JsValue::from_str("KeyRing: Address:P2Pk expected"))
+    }
+}
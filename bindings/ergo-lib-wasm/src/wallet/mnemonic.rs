@@ -15,6 +15,8 @@ impl Mnemonic {
     /// Convert a mnemonic phrase into a mnemonic seed
     /// mnemonic_pass is optional and is used to salt the seed
     pub fn to_seed(mnemonic_phrase: &str, mnemonic_pass: &str) -> Vec<u8> {
-        InnerMnemonic::to_seed(mnemonic_phrase, mnemonic_pass).into()
+        InnerMnemonic::to_seed(mnemonic_phrase, mnemonic_pass)
+            .expose_secret_bytes()
+            .to_vec()
     }
 }
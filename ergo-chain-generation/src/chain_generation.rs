@@ -202,7 +202,7 @@ fn prove_block(
     let target_b = order.clone() / ergo_nipopow::decode_compact_bits(header.n_bits);
 
     let x = DlogProverInput::random();
-    let x_bigint = BigInt::from_bytes_be(Sign::Plus, &x.to_bytes());
+    let x_bigint = BigInt::from_bytes_be(Sign::Plus, x.expose_secret_bytes().as_ref());
 
     use byteorder::{BigEndian, WriteBytesExt};
     let mut height_bytes = Vec::with_capacity(4);
@@ -60,9 +60,9 @@ pub(crate) fn default_miner_secret() -> (ExtSecretKey, BigInt) {
         "ozone drill grab fiber curtain grace pudding thank cruise elder eight picnic";
     let seed = Mnemonic::to_seed(test_mnemonic, "");
     let default_root_secret = ExtSecretKey::derive_master(seed).unwrap();
-    let bytes = default_root_secret.secret_key_bytes();
+    let bytes = default_root_secret.expose_secret_bytes();
     (
         default_root_secret,
-        BigInt::from_bytes_be(Sign::Plus, &bytes),
+        BigInt::from_bytes_be(Sign::Plus, bytes.as_ref()),
     )
 }
@@ -6,6 +6,8 @@ use ergotree_interpreter::sigma_protocol::prover::ProofBytes;
 
 pub(crate) mod context_extension;
 pub(crate) mod hint;
+pub(crate) mod multi_sig;
+pub(crate) mod secret_key;
 pub(crate) mod transaction;
 
 /// Serde remote type
@@ -0,0 +1,56 @@
+use std::convert::TryFrom;
+
+use ergotree_ir::serialization::SigmaSerializable;
+use ergotree_ir::sigma_protocol::sigma_boolean::SigmaBoolean;
+use serde::{Deserialize, Serialize};
+
+use crate::chain::transaction::reduced::ReducedTransaction;
+use crate::chain::transaction::Transaction;
+use crate::wallet::multi_sig::session::SigningSession;
+use crate::wallet::multi_sig::TransactionHintsBag;
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+pub struct SigningSessionJson {
+    /// Base16-encoded serialized reduced transaction
+    #[serde(rename = "reducedTx")]
+    reduced_tx: String,
+    signers: Vec<SigmaBoolean>,
+    committed: Vec<SigmaBoolean>,
+    signed: Vec<SigmaBoolean>,
+    hints: TransactionHintsBag,
+    transaction: Option<Transaction>,
+}
+
+impl From<SigningSession> for SigningSessionJson {
+    fn from(s: SigningSession) -> Self {
+        #[allow(clippy::unwrap_used)]
+        // reduced tx serialization can only fail on OOM
+        let reduced_tx_bytes = s.reduced_tx.sigma_serialize_bytes().unwrap();
+        SigningSessionJson {
+            reduced_tx: base16::encode_lower(&reduced_tx_bytes),
+            signers: s.signers,
+            committed: s.committed,
+            signed: s.signed,
+            hints: s.hints,
+            transaction: s.transaction,
+        }
+    }
+}
+
+impl TryFrom<SigningSessionJson> for SigningSession {
+    type Error = String;
+
+    fn try_from(sj: SigningSessionJson) -> Result<Self, Self::Error> {
+        let bytes = base16::decode(&sj.reduced_tx).map_err(|e| e.to_string())?;
+        let reduced_tx =
+            ReducedTransaction::sigma_parse_bytes(&bytes).map_err(|e| e.to_string())?;
+        Ok(SigningSession {
+            reduced_tx,
+            signers: sj.signers,
+            committed: sj.committed,
+            signed: sj.signed,
+            hints: sj.hints,
+            transaction: sj.transaction,
+        })
+    }
+}
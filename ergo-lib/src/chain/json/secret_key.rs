@@ -0,0 +1,54 @@
+use ergotree_interpreter::sigma_protocol::private_input::DhTupleProverInput;
+use ergotree_interpreter::sigma_protocol::private_input::DlogProverInput;
+use ergotree_interpreter::sigma_protocol::wscalar::Wscalar;
+use ergotree_ir::sigma_protocol::sigma_boolean::ProveDhTuple;
+use serde::{Deserialize, Serialize};
+use zeroize::Zeroize;
+
+use crate::wallet::secret_key::SecretKey;
+
+/// Dlog secret as base16-encoded bytes, DHT in node REST API format
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+pub(crate) enum SecretKeyJson {
+    Dlog(Wscalar),
+    Dht {
+        secret: Wscalar,
+        #[serde(flatten)]
+        common_input: ProveDhTuple,
+    },
+}
+
+impl Zeroize for SecretKeyJson {
+    fn zeroize(&mut self) {
+        match self {
+            SecretKeyJson::Dlog(secret) | SecretKeyJson::Dht { secret, .. } => secret.zeroize(),
+        }
+    }
+}
+
+impl From<&SecretKey> for SecretKeyJson {
+    fn from(sk: &SecretKey) -> Self {
+        match sk {
+            SecretKey::DlogSecretKey(dlog) => {
+                SecretKeyJson::Dlog((*dlog.expose_secret_scalar()).clone())
+            }
+            SecretKey::DhtSecretKey(dht) => SecretKeyJson::Dht {
+                secret: (*dht.expose_secret_scalar()).clone(),
+                common_input: dht.common_input.clone(),
+            },
+        }
+    }
+}
+
+impl From<SecretKeyJson> for SecretKey {
+    fn from(sk: SecretKeyJson) -> Self {
+        match sk {
+            SecretKeyJson::Dlog(secret) => DlogProverInput::from(secret).into(),
+            SecretKeyJson::Dht {
+                secret,
+                common_input,
+            } => DhTupleProverInput::new(secret, common_input).into(),
+        }
+    }
+}
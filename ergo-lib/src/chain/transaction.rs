@@ -10,7 +10,9 @@ use ergo_chain_types::blake2b256_hash;
 pub use ergotree_interpreter::eval::context::TxIoVec;
 use ergotree_interpreter::eval::env::Env;
 use ergotree_interpreter::eval::extract_sigma_boolean;
+use ergotree_interpreter::eval::reduce_to_crypto;
 use ergotree_interpreter::eval::EvalError;
+use ergotree_interpreter::sigma_protocol::verifier::batch::BatchVerifier;
 use ergotree_interpreter::sigma_protocol::verifier::verify_signature;
 use ergotree_interpreter::sigma_protocol::verifier::TestVerifier;
 use ergotree_interpreter::sigma_protocol::verifier::Verifier;
@@ -31,6 +33,7 @@ use ergotree_ir::serialization::SigmaParsingError;
 use ergotree_ir::serialization::SigmaSerializable;
 use ergotree_ir::serialization::SigmaSerializationError;
 use ergotree_ir::serialization::SigmaSerializeResult;
+use ergotree_ir::sigma_protocol::sigma_boolean::SigmaBoolean;
 pub use input::*;
 
 use crate::wallet::signing::make_context;
@@ -44,7 +47,7 @@ use indexmap::IndexSet;
 use std::convert::TryFrom;
 use std::convert::TryInto;
 use std::iter::FromIterator;
-use std::rc::Rc;
+use std::sync::Arc;
 
 use super::ergo_state_context::ErgoStateContext;
 
@@ -363,7 +366,7 @@ pub fn verify_tx_input_proof(
     let input_box = tx_context
         .get_input_box(&input.box_id)
         .ok_or(TransactionContextError::InputBoxNotFound(input_idx))?;
-    let ctx = Rc::new(make_context(state_context, tx_context, input_idx)?);
+    let ctx = Arc::new(make_context(state_context, tx_context, input_idx)?);
     let verifier = TestVerifier;
     let message_to_sign = tx_context.spending_tx.bytes_to_sign()?;
     Ok(verifier
@@ -377,6 +380,117 @@ pub fn verify_tx_input_proof(
         .result)
 }
 
+/// Verify the proofs of all the inputs of the transaction concurrently on the rayon thread pool.
+/// Returns the results in the order of inputs
+#[cfg(feature = "parallel")]
+pub fn par_verify_tx_input_proofs(
+    tx_context: &TransactionContext<Transaction>,
+    state_context: &ErgoStateContext,
+) -> Result<Vec<bool>, TxVerifyError> {
+    use rayon::prelude::*;
+    (0..tx_context.spending_tx.inputs.len())
+        .into_par_iter()
+        .map(|input_idx| verify_tx_input_proof(tx_context, state_context, input_idx))
+        .collect()
+}
+
+/// Verify the proofs of all the inputs of the transactions (e.g. of a block) with
+/// [`BatchVerifier::par_verify`] on the rayon thread pool. Returns the result for every input of
+/// every transaction, in the order of transactions and their inputs, an error in one input does
+/// not stop the verification of the rest
+#[cfg(feature = "parallel")]
+pub fn par_verify_txs_input_proofs(
+    tx_contexts: &[TransactionContext<Transaction>],
+    state_context: &ErgoStateContext,
+) -> Vec<Vec<Result<bool, TxVerifyError>>> {
+    let mut batch = BatchVerifier::new();
+    let added = add_txs_input_proofs(tx_contexts, state_context, &mut batch);
+    batch_results(added, batch.par_verify())
+}
+
+/// Verify the proofs of all the inputs of the transactions (e.g. of a block) with
+/// [`BatchVerifier`]. Returns the result for every input of every transaction, in the order of
+/// transactions and their inputs, an error in one input does not stop the verification of the rest
+pub fn verify_txs_input_proofs(
+    tx_contexts: &[TransactionContext<Transaction>],
+    state_context: &ErgoStateContext,
+) -> Vec<Vec<Result<bool, TxVerifyError>>> {
+    let mut batch = BatchVerifier::new();
+    let added = add_txs_input_proofs(tx_contexts, state_context, &mut batch);
+    batch_results(added, batch.verify())
+}
+
+/// Reduces the input scripts to sigma propositions and adds their proofs to the batch (in the order
+/// of transactions and their inputs). Returns the reduction error for every input not added.
+fn add_txs_input_proofs(
+    tx_contexts: &[TransactionContext<Transaction>],
+    state_context: &ErgoStateContext,
+    batch: &mut BatchVerifier,
+) -> Vec<Vec<Result<(), TxVerifyError>>> {
+    tx_contexts
+        .iter()
+        .map(|tx_context| {
+            let message_to_sign = tx_context.spending_tx.bytes_to_sign();
+            (0..tx_context.spending_tx.inputs.len())
+                .map(|input_idx| {
+                    let message = message_to_sign.clone()?;
+                    let (sigma_tree, proof) =
+                        reduce_tx_input(tx_context, state_context, input_idx)?;
+                    batch.add(sigma_tree, message, proof.into());
+                    Ok(())
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Input script reduced to the sigma proposition to prove, along with the input's proof
+fn reduce_tx_input(
+    tx_context: &TransactionContext<Transaction>,
+    state_context: &ErgoStateContext,
+    input_idx: usize,
+) -> Result<(SigmaBoolean, ProofBytes), TxVerifyError> {
+    let input = tx_context
+        .spending_tx
+        .inputs
+        .get(input_idx)
+        .ok_or(TransactionContextError::InputBoxNotFound(input_idx))?;
+    let input_box = tx_context
+        .get_input_box(&input.box_id)
+        .ok_or(TransactionContextError::InputBoxNotFound(input_idx))?;
+    let ctx = Arc::new(make_context(state_context, tx_context, input_idx)?);
+    let expr = input_box
+        .ergo_tree
+        .proposition()
+        .map_err(VerifierError::from)?;
+    let sigma_tree = reduce_to_crypto(&expr, &Env::empty(), ctx)
+        .map_err(VerifierError::from)?
+        .sigma_prop;
+    Ok((sigma_tree, input.spending_proof.proof.clone()))
+}
+
+/// Per-input results: the batch results for the added inputs (they are in the same order), the
+/// reduction errors for the rest
+fn batch_results(
+    added: Vec<Vec<Result<(), TxVerifyError>>>,
+    results: Vec<Result<bool, VerifierError>>,
+) -> Vec<Vec<Result<bool, TxVerifyError>>> {
+    let mut results = results.into_iter();
+    added
+        .into_iter()
+        .map(|tx_added| {
+            tx_added
+                .into_iter()
+                .map(|res| {
+                    res?;
+                    // the batch has a result for every added proof, a missing one is rejected
+                    Ok(results.next().unwrap_or(Ok(false))?)
+                })
+                .collect()
+        })
+        .collect()
+}
+
 /// Arbitrary impl
 #[cfg(feature = "arbitrary")]
 #[allow(clippy::unwrap_used)]
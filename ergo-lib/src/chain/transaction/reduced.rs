@@ -1,7 +1,7 @@
 //! Represent `reduced` transaction, i.e. unsigned transaction where each unsigned input
 //! is augmented with ReducedInput which contains a script reduction result.
 
-use std::rc::Rc;
+use std::sync::Arc;
 
 use ergotree_interpreter::eval::env::Env;
 use ergotree_interpreter::eval::reduce_to_crypto;
@@ -77,7 +77,7 @@ pub fn reduce_tx(
             let input_box = tx_context
                 .get_input_box(&input.box_id)
                 .ok_or(TransactionContextError::InputBoxNotFound(idx))?;
-            let ctx = Rc::new(make_context(state_context, &tx_context, idx)?);
+            let ctx = Arc::new(make_context(state_context, &tx_context, idx)?);
             let expr = input_box
                 .ergo_tree
                 .proposition()
@@ -1,23 +1,38 @@
 //! Wallet-related features for Ergo
 
+pub mod account_discovery;
 pub mod box_selector;
 pub mod derivation_path;
+pub mod ext_key_encoding;
 pub mod ext_pub_key;
 pub mod ext_secret_key;
+#[cfg(feature = "keystore")]
+pub mod keystore;
 pub mod miner_fee;
 pub mod mnemonic;
 #[cfg(feature = "mnemonic_gen")]
 pub mod mnemonic_generator;
+#[cfg(feature = "mnemonic_gen")]
+pub mod mnemonic_validator;
 pub mod multi_sig;
+pub mod ring_signature;
 pub mod secret_key;
 pub mod signing;
 pub mod tx_builder;
 pub mod tx_context;
 
+use ergotree_interpreter::sigma_protocol::musig::MusigError;
+use ergotree_interpreter::sigma_protocol::musig::MusigPartialSignature;
+use ergotree_interpreter::sigma_protocol::musig::MusigSecretNonce;
+use ergotree_interpreter::sigma_protocol::musig::MusigSession;
 use ergotree_interpreter::sigma_protocol::private_input::PrivateInput;
+use ergotree_interpreter::sigma_protocol::prover::external_signer::ExternalSigner;
+use ergotree_interpreter::sigma_protocol::prover::DeterministicNonceProver;
+use ergotree_interpreter::sigma_protocol::prover::ExternalSignerProver;
 use ergotree_interpreter::sigma_protocol::prover::Prover;
 use ergotree_interpreter::sigma_protocol::prover::ProverError;
 use ergotree_interpreter::sigma_protocol::prover::TestProver;
+use ring_signature::KeyRing;
 use secret_key::SecretKey;
 use signing::{sign_transaction, TxSigningError};
 use thiserror::Error;
@@ -27,8 +42,11 @@ use crate::chain::transaction::reduced::ReducedTransaction;
 use crate::chain::transaction::unsigned::UnsignedTransaction;
 use crate::chain::transaction::Input;
 use crate::chain::transaction::Transaction;
+use crate::chain::transaction::TxId;
 use crate::ergotree_ir::sigma_protocol::sigma_boolean::SigmaBoolean;
 use crate::wallet::mnemonic::Mnemonic;
+use crate::wallet::multi_sig::secret_commitments::zeroize_own_commitments;
+use crate::wallet::multi_sig::secret_commitments::SecretCommitments;
 use crate::wallet::multi_sig::{
     generate_commitments, generate_commitments_for, TransactionHintsBag,
 };
@@ -42,7 +60,7 @@ use self::signing::TransactionContext;
 
 /// Wallet
 pub struct Wallet {
-    prover: Box<dyn Prover>,
+    prover: Box<dyn Prover + Send + Sync>,
 }
 
 /// Wallet errors
@@ -60,6 +78,19 @@ pub enum WalletError {
 
     #[error("error parsing SecretKey from ExtSecretKey.bytes")]
     SecretKeyParsingError,
+
+    #[error("MusigError: {0}")]
+    MusigError(#[from] MusigError),
+
+    #[error("wallet has no secret for any of the MuSig session keys")]
+    MusigNoSecret,
+
+    #[error("commitments were generated for transaction {actual}, expected {expected}")]
+    CommitmentsTxMismatch { expected: TxId, actual: TxId },
+
+    #[cfg(feature = "keystore")]
+    #[error("KeystoreError: {0}")]
+    KeystoreError(#[from] keystore::KeystoreError),
 }
 
 impl Wallet {
@@ -74,6 +105,16 @@ impl Wallet {
         Ok(Wallet::from_secrets(vec![ext_sk.secret_key()]))
     }
 
+    /// Create wallet instance loading secret key from the encrypted keystore
+    #[cfg(feature = "keystore")]
+    pub fn from_keystore(
+        keystore: &keystore::Keystore,
+        password: &str,
+    ) -> Result<Wallet, WalletError> {
+        let ext_sk = keystore.ext_secret_key(password)?;
+        Ok(Wallet::from_secrets(vec![ext_sk.secret_key()]))
+    }
+
     /// Create Wallet from secrets
     pub fn from_secrets(secrets: Vec<SecretKey>) -> Wallet {
         let prover = TestProver {
@@ -84,6 +125,33 @@ impl Wallet {
         }
     }
 
+    /// Create Wallet from secrets deriving the randomness of the proofs from the secrets and the
+    /// signed message instead of the RNG (see
+    /// [`ergotree_interpreter::sigma_protocol::prover::NonceGeneration::Deterministic`])
+    pub fn from_secrets_with_deterministic_nonces(secrets: Vec<SecretKey>) -> Wallet {
+        let prover = DeterministicNonceProver {
+            secrets: secrets.into_iter().map(PrivateInput::from).collect(),
+        };
+        Wallet {
+            prover: Box::new(prover),
+        }
+    }
+
+    /// Create Wallet from secrets and external signers (hardware wallets, HSMs) computing
+    /// the proofs for the secrets they hold without exposing them
+    pub fn from_external_signers(
+        secrets: Vec<SecretKey>,
+        external_signers: Vec<Box<dyn ExternalSigner>>,
+    ) -> Wallet {
+        let prover = ExternalSignerProver {
+            secrets: secrets.into_iter().map(PrivateInput::from).collect(),
+            external_signers,
+        };
+        Wallet {
+            prover: Box::new(prover),
+        }
+    }
+
     /// Add a new secret to the wallet prover
     pub fn add_secret(&mut self, secret: SecretKey) {
         self.prover.append_secret(secret.into())
@@ -145,6 +213,61 @@ impl Wallet {
         Ok(tx_hints)
     }
 
+    /// Generate commitments for the transaction, keeping the secret randomness in the returned
+    /// handle. Only [`SecretCommitments::public_commitments`] are to be shared with the co-signers.
+    pub fn generate_secret_commitments(
+        &self,
+        tx_context: TransactionContext<UnsignedTransaction>,
+        state_context: &ErgoStateContext,
+    ) -> Result<SecretCommitments, WalletError> {
+        let tx_id = tx_context.spending_tx.id();
+        let hints = self.generate_commitments(tx_context, state_context)?;
+        Ok(SecretCommitments::new(tx_id, hints))
+    }
+
+    /// Generate commitments for the reduced transaction, keeping the secret randomness in the
+    /// returned handle (see [`Wallet::generate_secret_commitments`])
+    pub fn generate_secret_commitments_for_reduced_transaction(
+        &self,
+        reduced_tx: ReducedTransaction,
+    ) -> Result<SecretCommitments, WalletError> {
+        let tx_id = reduced_tx.unsigned_tx.id();
+        let hints = self.generate_commitments_for_reduced_transaction(reduced_tx)?;
+        Ok(SecretCommitments::new(tx_id, hints))
+    }
+
+    /// Signs a transaction with the commitments from [`Wallet::generate_secret_commitments`] and
+    /// the hints of the co-signers (commitments, partial proofs). The commitments are consumed.
+    pub fn sign_transaction_with_commitments(
+        &self,
+        tx_context: TransactionContext<UnsignedTransaction>,
+        state_context: &ErgoStateContext,
+        commitments: SecretCommitments,
+        co_signers_hints: &TransactionHintsBag,
+    ) -> Result<Transaction, WalletError> {
+        check_commitments_tx_id(&commitments, tx_context.spending_tx.id())?;
+        let mut tx_hints = commitments.into_signing_hints(co_signers_hints);
+        let res = self.sign_transaction(tx_context, state_context, Some(&tx_hints));
+        zeroize_own_commitments(&mut tx_hints);
+        res
+    }
+
+    /// Signs a reduced transaction with the commitments from
+    /// [`Wallet::generate_secret_commitments_for_reduced_transaction`] and the hints of the
+    /// co-signers (commitments, partial proofs). The commitments are consumed.
+    pub fn sign_reduced_transaction_with_commitments(
+        &self,
+        reduced_tx: ReducedTransaction,
+        commitments: SecretCommitments,
+        co_signers_hints: &TransactionHintsBag,
+    ) -> Result<Transaction, WalletError> {
+        check_commitments_tx_id(&commitments, reduced_tx.unsigned_tx.id())?;
+        let mut tx_hints = commitments.into_signing_hints(co_signers_hints);
+        let res = self.sign_reduced_transaction(reduced_tx, Some(&tx_hints));
+        zeroize_own_commitments(&mut tx_hints);
+        res
+    }
+
     /// Signs a message
     pub fn sign_message(
         &self,
@@ -154,6 +277,37 @@ impl Wallet {
         sign_message(self.prover.as_ref(), sigma_tree, msg).map_err(WalletError::from)
     }
 
+    /// Signs a message with one of the wallet's keys in the ring without revealing which one
+    /// (see [`ring_signature::verify_ring_signature`])
+    pub fn sign_ring_message(&self, ring: &KeyRing, msg: &[u8]) -> Result<Vec<u8>, WalletError> {
+        self.sign_message(ring.proposition(), msg)
+    }
+
+    /// Computes the partial signature of the wallet's key in the MuSig session.
+    /// The signature for the aggregated key is then produced with [`Wallet::sign_message`] of a
+    /// wallet created by [`Wallet::from_external_signers`] with the signer returned by
+    /// [`MusigSession::into_signer`].
+    pub fn musig_partial_sign(
+        &self,
+        session: &MusigSession,
+        nonce: MusigSecretNonce,
+    ) -> Result<MusigPartialSignature, WalletError> {
+        let secret = self
+            .prover
+            .secrets()
+            .iter()
+            .find_map(|s| match s {
+                PrivateInput::DlogProverInput(dlog)
+                    if session.keys().contains(&dlog.public_image()) =>
+                {
+                    Some(dlog)
+                }
+                PrivateInput::DlogProverInput(_) | PrivateInput::DhTupleProverInput(_) => None,
+            })
+            .ok_or(WalletError::MusigNoSecret)?;
+        Ok(session.partial_sign(secret, nonce)?)
+    }
+
     /// Signs a transaction input
     pub fn sign_tx_input(
         &self,
@@ -174,3 +328,29 @@ impl Wallet {
         )?)
     }
 }
+
+fn check_commitments_tx_id(
+    commitments: &SecretCommitments,
+    tx_id: TxId,
+) -> Result<(), WalletError> {
+    if commitments.tx_id() == tx_id {
+        Ok(())
+    } else {
+        Err(WalletError::CommitmentsTxMismatch {
+            expected: tx_id,
+            actual: commitments.tx_id(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_send_sync<T: Send + Sync>() {}
+
+    #[test]
+    fn wallet_is_send_sync() {
+        assert_send_sync::<Wallet>();
+    }
+}
@@ -0,0 +1,138 @@
+//! Account discovery according to
+//! BIP-44 <https://github.com/bitcoin/bips/blob/master/bip-0044.mediawiki#account-discovery>
+//! and EIP-3 <https://github.com/ergoplatform/eips/blob/master/eip-0003.md>
+
+use ergotree_ir::chain::address::Address;
+use thiserror::Error;
+
+use super::derivation_path::ChildIndexError;
+use super::derivation_path::ChildIndexHardened;
+use super::derivation_path::ChildIndexNormal;
+use super::derivation_path::DerivationPath;
+use super::ext_pub_key::ExtPubKey;
+use super::ext_secret_key::ExtSecretKey;
+use super::ext_secret_key::ExtSecretKeyError;
+
+/// Number of consecutive unused addresses after which an account is considered unused
+/// (as recommended in BIP-44)
+pub const DEFAULT_ADDRESS_GAP_LIMIT: u32 = 20;
+
+/// Account found during discovery
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct DiscoveredAccount {
+    /// Account index (`acc'` in `m/44'/429'/acc'/0`)
+    pub index: ChildIndexHardened,
+    /// Extended public key of the account (for path `m/44'/429'/acc'/0`)
+    pub ext_pub_key: ExtPubKey,
+}
+
+/// Account discovery errors
+#[derive(Error, PartialEq, Eq, Debug, Clone)]
+pub enum AccountDiscoveryError {
+    /// Account key derivation error
+    #[error("ExtSecretKeyError: {0}")]
+    ExtSecretKeyError(#[from] ExtSecretKeyError),
+    /// Account or address index overflow
+    #[error("child index error: {0}")]
+    ChildIndexError(#[from] ChildIndexError),
+}
+
+/// Discover used accounts of the wallet with the given master key.
+/// Accounts are scanned sequentially starting from index 0. For each account, addresses
+/// `m/44'/429'/acc'/0/0` up to `m/44'/429'/acc'/0/{gap_limit - 1}` are checked with the
+/// `is_address_used` predicate, and the discovery stops at the first account without any used
+/// address. Returns all used accounts found before it.
+pub fn discover_accounts<F>(
+    master_key: &ExtSecretKey,
+    gap_limit: u32,
+    mut is_address_used: F,
+) -> Result<Vec<DiscoveredAccount>, AccountDiscoveryError>
+where
+    F: FnMut(&Address) -> bool,
+{
+    let mut accounts = Vec::new();
+    let mut index = ChildIndexHardened::from_31_bit(0)?;
+    loop {
+        let ext_pub_key = master_key
+            .derive(DerivationPath::new(index, vec![]))?
+            .public_key()?;
+        if !is_account_used(&ext_pub_key, gap_limit, &mut is_address_used)? {
+            return Ok(accounts);
+        }
+        accounts.push(DiscoveredAccount { index, ext_pub_key });
+        index = index.next()?;
+    }
+}
+
+fn is_account_used<F>(
+    account_key: &ExtPubKey,
+    gap_limit: u32,
+    is_address_used: &mut F,
+) -> Result<bool, AccountDiscoveryError>
+where
+    F: FnMut(&Address) -> bool,
+{
+    for i in 0..gap_limit {
+        let address: Address = account_key.child(ChildIndexNormal::normal(i)?).into();
+        if is_address_used(&address) {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use crate::wallet::mnemonic::Mnemonic;
+
+    use super::*;
+
+    fn address(master_key: &ExtSecretKey, path: &str) -> Address {
+        master_key
+            .derive(path.parse().unwrap())
+            .unwrap()
+            .public_key()
+            .unwrap()
+            .into()
+    }
+
+    #[test]
+    fn discovers_accounts_until_unused() {
+        let seed = Mnemonic::to_seed("edge talent poet tortoise trumpet dose", "");
+        let master_key = ExtSecretKey::derive_master(seed).unwrap();
+        let used = vec![
+            address(&master_key, "m/44'/429'/0'/0/0"),
+            address(&master_key, "m/44'/429'/1'/0/5"),
+            // beyond unused account 2, must not be discovered
+            address(&master_key, "m/44'/429'/3'/0/0"),
+        ];
+        let accounts =
+            discover_accounts(&master_key, DEFAULT_ADDRESS_GAP_LIMIT, |a| used.contains(a))
+                .unwrap();
+        assert_eq!(
+            accounts.iter().map(|a| a.index).collect::<Vec<_>>(),
+            vec![
+                ChildIndexHardened::from_31_bit(0).unwrap(),
+                ChildIndexHardened::from_31_bit(1).unwrap()
+            ]
+        );
+        assert_eq!(
+            Address::from(
+                accounts[1]
+                    .ext_pub_key
+                    .child(ChildIndexNormal::normal(5).unwrap())
+            ),
+            used[1]
+        );
+    }
+
+    #[test]
+    fn address_beyond_gap_limit_is_not_found() {
+        let seed = Mnemonic::to_seed("edge talent poet tortoise trumpet dose", "");
+        let master_key = ExtSecretKey::derive_master(seed).unwrap();
+        let used = vec![address(&master_key, "m/44'/429'/0'/0/5")];
+        let accounts = discover_accounts(&master_key, 5, |a| used.contains(a)).unwrap();
+        assert!(accounts.is_empty());
+    }
+}
@@ -115,6 +115,17 @@ impl ChildIndex {
         }
     }
 
+    /// Create from 32-bit representation (highest bit is set for hard derivation and clear for
+    /// normal derivation)
+    pub fn from_bits(bits: u32) -> Self {
+        let index = bits & !(1 << 31);
+        if bits & (1 << 31) == 0 {
+            ChildIndex::Normal(ChildIndexNormal(index))
+        } else {
+            ChildIndex::Hardened(ChildIndexHardened(index))
+        }
+    }
+
     /// Returns a new instance of the `ChildIndex` with the index incremented
     pub fn next(&self) -> Result<Self, ChildIndexError> {
         match self {
@@ -310,6 +321,14 @@ mod tests {
         assert_eq!(expected, new_path.to_string());
     }
 
+    #[test]
+    fn test_child_index_bits_roundtrip() {
+        for s in ["0", "1'", "2147483647", "2147483647'"] {
+            let index: ChildIndex = s.parse().unwrap();
+            assert_eq!(ChildIndex::from_bits(index.to_bits()), index);
+        }
+    }
+
     // Test derivation_path.next() returns error if empty (doesn't panic)
     #[test]
     fn test_derivation_path_next_returns_err_if_emtpy() {
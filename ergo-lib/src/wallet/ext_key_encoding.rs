@@ -0,0 +1,297 @@
+//! Base58Check serialization of extended keys (xpub/xprv) according to BIP-32
+//! <https://github.com/bitcoin/bips/blob/master/bip-0032.mediawiki#serialization-format>
+
+use std::convert::TryFrom;
+use std::convert::TryInto;
+
+use ergotree_ir::chain::address::NetworkPrefix;
+use ripemd::Ripemd160;
+use sha2::Digest;
+use sha2::Sha256;
+use thiserror::Error;
+use zeroize::Zeroize;
+use zeroize::Zeroizing;
+
+use super::derivation_path::ChildIndex;
+use super::derivation_path::DerivationPath;
+
+/// Length of the serialized extended key (without checksum)
+/// version(4) ++ depth(1) ++ parent fingerprint(4) ++ child number(4) ++ chain code(32) ++ key(33)
+const PAYLOAD_LEN: usize = 78;
+/// Length of the double SHA-256 checksum appended to the payload
+const CHECKSUM_LEN: usize = 4;
+
+// The version bytes are the ones BIP-32 defines for Bitcoin
+// (<https://github.com/bitcoin/bips/blob/master/bip-0032.mediawiki#serialization-format>),
+// i.e. this is the Bitcoin `xpub`/`xprv` and `tpub`/`tprv` encoding of the keys. Ergo has no
+// version bytes of its own registered (SLIP-132), so the network only tells apart the mainnet
+// and testnet keys and does not guarantee that the key was exported for Ergo rather than for
+// another BIP-32 chain.
+
+/// Mainnet extended public key version bytes (Bitcoin BIP-32 `xpub`)
+pub const MAINNET_PUBLIC_VERSION: [u8; 4] = [0x04, 0x88, 0xB2, 0x1E];
+/// Mainnet extended secret key version bytes (Bitcoin BIP-32 `xprv`)
+pub const MAINNET_SECRET_VERSION: [u8; 4] = [0x04, 0x88, 0xAD, 0xE4];
+/// Testnet extended public key version bytes (Bitcoin BIP-32 `tpub`)
+pub const TESTNET_PUBLIC_VERSION: [u8; 4] = [0x04, 0x35, 0x87, 0xCF];
+/// Testnet extended secret key version bytes (Bitcoin BIP-32 `tprv`)
+pub const TESTNET_SECRET_VERSION: [u8; 4] = [0x04, 0x35, 0x83, 0x94];
+
+/// Key fingerprint (first 4 bytes of RIPEMD-160 of SHA-256 of the compressed public key)
+pub type Fingerprint = [u8; 4];
+
+/// Fingerprint of the key with the given compressed public key bytes
+pub(crate) fn fingerprint(pub_key_bytes: &[u8]) -> Fingerprint {
+    let hash = Ripemd160::digest(Sha256::digest(pub_key_bytes));
+    let mut res = [0u8; 4];
+    res.copy_from_slice(&hash[..4]);
+    res
+}
+
+/// Kind of the encoded extended key
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum ExtKeyKind {
+    /// Extended public key
+    Public,
+    /// Extended secret key
+    Secret,
+}
+
+/// Extended key encoding errors
+#[derive(Error, PartialEq, Eq, Debug, Clone)]
+pub enum ExtKeyEncodingError {
+    /// Base58 decoding error
+    #[error("Base58 decoding error: {0}")]
+    Base58DecodingError(String),
+    /// Invalid size of the decoded byte array
+    #[error("invalid size of the decoded byte array: {0}")]
+    InvalidSize(usize),
+    /// Checksum does not match
+    #[error("invalid checksum")]
+    InvalidChecksum,
+    /// Version bytes are not known
+    #[error("unknown version bytes: {0:?}")]
+    UnknownVersion([u8; 4]),
+    /// Encoded key is of a different kind (public instead of secret or vice versa)
+    #[error("expected {expected:?} key, got {actual:?}")]
+    KeyKindMismatch {
+        /// Expected kind
+        expected: ExtKeyKind,
+        /// Actual encoded kind
+        actual: ExtKeyKind,
+    },
+    /// Encoded depth or child number does not match the provided derivation path
+    #[error("derivation path mismatch: {0}")]
+    DerivationPathMismatch(String),
+    /// Derivation path is too deep to be encoded (depth does not fit in a byte)
+    #[error("derivation path depth {0} exceeds the maximum of 255")]
+    DepthTooLarge(usize),
+    /// Encoded key bytes are invalid
+    #[error("invalid key: {0}")]
+    InvalidKey(String),
+}
+
+impl From<bs58::decode::Error> for ExtKeyEncodingError {
+    fn from(err: bs58::decode::Error) -> Self {
+        ExtKeyEncodingError::Base58DecodingError(err.to_string())
+    }
+}
+
+/// Decoded fields of a serialized extended key.
+/// The key and chain code are zeroed on drop and not shown in `Debug` output
+#[derive(PartialEq, Eq)]
+pub(crate) struct ExtKeyPayload {
+    pub network: NetworkPrefix,
+    pub kind: ExtKeyKind,
+    pub depth: u8,
+    pub parent_fingerprint: Fingerprint,
+    pub child_number: u32,
+    pub chain_code: [u8; 32],
+    /// compressed public key or `0x00 ++ secret key bytes`
+    pub key: [u8; 33],
+}
+
+impl std::fmt::Debug for ExtKeyPayload {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ExtKeyPayload")
+            .field("network", &self.network)
+            .field("kind", &self.kind)
+            .field("depth", &self.depth)
+            .field("child_number", &self.child_number)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Drop for ExtKeyPayload {
+    fn drop(&mut self) {
+        self.chain_code.zeroize();
+        self.key.zeroize();
+    }
+}
+
+impl ExtKeyPayload {
+    /// Create payload for the key with the given derivation path and parent key fingerprint
+    /// (zeros for the master key)
+    pub fn new(
+        network: NetworkPrefix,
+        kind: ExtKeyKind,
+        derivation_path: &DerivationPath,
+        parent_fingerprint: Fingerprint,
+        chain_code: [u8; 32],
+        key: [u8; 33],
+    ) -> Result<Self, ExtKeyEncodingError> {
+        let depth = derivation_path.depth();
+        Ok(ExtKeyPayload {
+            network,
+            kind,
+            depth: u8::try_from(depth).map_err(|_| ExtKeyEncodingError::DepthTooLarge(depth))?,
+            parent_fingerprint,
+            child_number: derivation_path
+                .0
+                .last()
+                .map(ChildIndex::to_bits)
+                .unwrap_or(0),
+            chain_code,
+            key,
+        })
+    }
+
+    /// Check that encoded depth and child number are consistent with the given derivation path
+    pub fn check_path(&self, derivation_path: &DerivationPath) -> Result<(), ExtKeyEncodingError> {
+        let expected = ExtKeyPayload::new(
+            self.network,
+            self.kind,
+            derivation_path,
+            self.parent_fingerprint,
+            self.chain_code,
+            self.key,
+        )?;
+        if expected.depth == self.depth && expected.child_number == self.child_number {
+            Ok(())
+        } else {
+            Err(ExtKeyEncodingError::DerivationPathMismatch(format!(
+                "encoded depth {} and child number {} do not match path {}",
+                self.depth, self.child_number, derivation_path
+            )))
+        }
+    }
+
+    /// Encode as Base58Check string. Both the serialized bytes and the string hold the key, so
+    /// they are zeroed on drop
+    pub fn to_base58(&self) -> Zeroizing<String> {
+        let mut bytes = Zeroizing::new(Vec::with_capacity(PAYLOAD_LEN + CHECKSUM_LEN));
+        bytes.extend_from_slice(&version_bytes(self.network, self.kind));
+        bytes.push(self.depth);
+        bytes.extend_from_slice(&self.parent_fingerprint);
+        bytes.extend_from_slice(&self.child_number.to_be_bytes());
+        bytes.extend_from_slice(&self.chain_code);
+        bytes.extend_from_slice(&self.key);
+        let checksum = calc_checksum(&bytes);
+        bytes.extend_from_slice(&checksum);
+        Zeroizing::new(bs58::encode(bytes.as_slice()).into_string())
+    }
+
+    /// Decode from Base58Check string checking that the encoded key is of the expected kind
+    #[allow(clippy::unwrap_used)]
+    pub fn from_base58(s: &str, expected_kind: ExtKeyKind) -> Result<Self, ExtKeyEncodingError> {
+        let bytes = Zeroizing::new(bs58::decode(s).into_vec()?);
+        if bytes.len() != PAYLOAD_LEN + CHECKSUM_LEN {
+            return Err(ExtKeyEncodingError::InvalidSize(bytes.len()));
+        }
+        let (payload, checksum) = bytes.split_at(PAYLOAD_LEN);
+        if checksum != calc_checksum(payload) {
+            return Err(ExtKeyEncodingError::InvalidChecksum);
+        }
+        // unwraps are safe since the payload length is checked above
+        let (network, kind) = parse_version(payload[..4].try_into().unwrap())?;
+        if kind != expected_kind {
+            return Err(ExtKeyEncodingError::KeyKindMismatch {
+                expected: expected_kind,
+                actual: kind,
+            });
+        }
+        Ok(ExtKeyPayload {
+            network,
+            kind,
+            depth: payload[4],
+            parent_fingerprint: payload[5..9].try_into().unwrap(),
+            child_number: u32::from_be_bytes(payload[9..13].try_into().unwrap()),
+            chain_code: payload[13..45].try_into().unwrap(),
+            key: payload[45..].try_into().unwrap(),
+        })
+    }
+}
+
+fn version_bytes(network: NetworkPrefix, kind: ExtKeyKind) -> [u8; 4] {
+    match (network, kind) {
+        (NetworkPrefix::Mainnet, ExtKeyKind::Public) => MAINNET_PUBLIC_VERSION,
+        (NetworkPrefix::Mainnet, ExtKeyKind::Secret) => MAINNET_SECRET_VERSION,
+        (NetworkPrefix::Testnet, ExtKeyKind::Public) => TESTNET_PUBLIC_VERSION,
+        (NetworkPrefix::Testnet, ExtKeyKind::Secret) => TESTNET_SECRET_VERSION,
+    }
+}
+
+fn parse_version(version: [u8; 4]) -> Result<(NetworkPrefix, ExtKeyKind), ExtKeyEncodingError> {
+    match version {
+        MAINNET_PUBLIC_VERSION => Ok((NetworkPrefix::Mainnet, ExtKeyKind::Public)),
+        MAINNET_SECRET_VERSION => Ok((NetworkPrefix::Mainnet, ExtKeyKind::Secret)),
+        TESTNET_PUBLIC_VERSION => Ok((NetworkPrefix::Testnet, ExtKeyKind::Public)),
+        TESTNET_SECRET_VERSION => Ok((NetworkPrefix::Testnet, ExtKeyKind::Secret)),
+        v => Err(ExtKeyEncodingError::UnknownVersion(v)),
+    }
+}
+
+/// First 4 bytes of double SHA-256
+fn calc_checksum(bytes: &[u8]) -> [u8; CHECKSUM_LEN] {
+    let hash = Sha256::digest(Sha256::digest(bytes));
+    let mut res = [0u8; CHECKSUM_LEN];
+    res.copy_from_slice(&hash[..CHECKSUM_LEN]);
+    res
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::super::derivation_path::ChildIndexNormal;
+    use super::*;
+
+    #[test]
+    fn invalid_checksum() {
+        // BIP-32 test vector 1 master xpub with the last character changed
+        let s = "xpub661MyMwAqRbcFtXgS5sYJABqqG9YLmC4Q1Rdap9gSE8NqtwybGhePY2gZ29ESFjqJoCu1Rupje8YtGqsefD265TMg7usUDFdp6W1EGMcet7";
+        assert_eq!(
+            ExtKeyPayload::from_base58(s, ExtKeyKind::Public),
+            Err(ExtKeyEncodingError::InvalidChecksum)
+        );
+    }
+
+    #[test]
+    fn depth_too_large() {
+        let path = DerivationPath(
+            vec![ChildIndex::Normal(ChildIndexNormal::normal(0).unwrap()); 300].into_boxed_slice(),
+        );
+        assert_eq!(
+            ExtKeyPayload::new(
+                NetworkPrefix::Mainnet,
+                ExtKeyKind::Public,
+                &path,
+                [0; 4],
+                [0; 32],
+                [0; 33]
+            ),
+            Err(ExtKeyEncodingError::DepthTooLarge(300))
+        );
+    }
+
+    #[test]
+    fn kind_mismatch() {
+        let s = "xpub661MyMwAqRbcFtXgS5sYJABqqG9YLmC4Q1Rdap9gSE8NqtwybGhePY2gZ29ESFjqJoCu1Rupje8YtGqsefD265TMg7usUDFdp6W1EGMcet8";
+        assert_eq!(
+            ExtKeyPayload::from_base58(s, ExtKeyKind::Secret),
+            Err(ExtKeyEncodingError::KeyKindMismatch {
+                expected: ExtKeyKind::Secret,
+                actual: ExtKeyKind::Public
+            })
+        );
+    }
+}
@@ -4,6 +4,7 @@ use std::convert::TryInto;
 use ergo_chain_types::EcPoint;
 use ergotree_interpreter::sigma_protocol::private_input::DlogProverInput;
 use ergotree_ir::chain::address::Address;
+use ergotree_ir::chain::address::NetworkPrefix;
 use ergotree_ir::serialization::SigmaParsingError;
 use ergotree_ir::serialization::SigmaSerializable;
 use hmac::{Hmac, Mac};
@@ -13,6 +14,11 @@ use thiserror::Error;
 use super::derivation_path::ChildIndex;
 use super::derivation_path::ChildIndexNormal;
 use super::derivation_path::DerivationPath;
+use super::ext_key_encoding::fingerprint;
+use super::ext_key_encoding::ExtKeyEncodingError;
+use super::ext_key_encoding::ExtKeyKind;
+use super::ext_key_encoding::ExtKeyPayload;
+use super::ext_key_encoding::Fingerprint;
 
 /// Public key (serialized EcPoint) bytes
 pub type PubKeyBytes = [u8; EcPoint::GROUP_SIZE];
@@ -23,15 +29,27 @@ type HmacSha512 = Hmac<Sha512>;
 
 /// Extended public key
 /// implemented according to BIP-32
-#[derive(PartialEq, Eq, Debug, Clone)]
+#[derive(Debug, Clone)]
 pub struct ExtPubKey {
     /// Parsed public key (EcPoint)
     pub public_key: EcPoint,
     chain_code: ChainCode,
     /// Derivation path for this extended public key
     pub derivation_path: DerivationPath,
+    pub(super) parent_fingerprint: Fingerprint,
 }
 
+impl PartialEq for ExtPubKey {
+    fn eq(&self, other: &Self) -> bool {
+        // the parent fingerprint is informational (unknown for the keys created with `new`)
+        self.public_key == other.public_key
+            && self.chain_code == other.chain_code
+            && self.derivation_path == other.derivation_path
+    }
+}
+
+impl Eq for ExtPubKey {}
+
 /// Extended secret key errors
 #[derive(Error, PartialEq, Eq, Debug, Clone)]
 pub enum ExtPubKeyError {
@@ -42,7 +60,8 @@ pub enum ExtPubKeyError {
 
 impl ExtPubKey {
     /// Create ExtPubKey from public key bytes (from SEC1 compressed), chain code and derivation
-    /// path
+    /// path. The parent key is not known, so the parent fingerprint is set to zeros (as for the
+    /// master key)
     pub fn new(
         public_key_bytes: PubKeyBytes,
         chain_code: ChainCode,
@@ -53,6 +72,7 @@ impl ExtPubKey {
             public_key,
             chain_code,
             derivation_path,
+            parent_fingerprint: [0; 4],
         })
     }
 
@@ -74,6 +94,16 @@ impl ExtPubKey {
         self.chain_code
     }
 
+    /// Fingerprint of the parent key (zeros for the master key or if the parent is not known)
+    pub fn parent_fingerprint(&self) -> Fingerprint {
+        self.parent_fingerprint
+    }
+
+    /// Fingerprint of this key (first 4 bytes of HASH160 of the public key)
+    pub fn fingerprint(&self) -> Fingerprint {
+        fingerprint(&self.pub_key_bytes())
+    }
+
     /// Soft derivation of the child public key with a given index
     #[allow(clippy::unwrap_used)]
     pub fn child(&self, index: ChildIndexNormal) -> Self {
@@ -96,6 +126,7 @@ impl ExtPubKey {
                     public_key: child_pub_key,
                     chain_code,
                     derivation_path: self.derivation_path.extend(index.into()),
+                    parent_fingerprint: self.fingerprint(),
                 }
             }
         } else {
@@ -128,6 +159,36 @@ impl ExtPubKey {
             )))
         }
     }
+
+    /// Encode as BIP-32 Base58Check string (`xpub...` for mainnet, `tpub...` for testnet).
+    /// Fails if the derivation path is deeper than 255 levels.
+    pub fn to_base58(&self, network: NetworkPrefix) -> Result<String, ExtKeyEncodingError> {
+        ExtKeyPayload::new(
+            network,
+            ExtKeyKind::Public,
+            &self.derivation_path,
+            self.parent_fingerprint,
+            self.chain_code,
+            self.pub_key_bytes(),
+        )
+        .map(|payload| payload.to_base58().to_string())
+    }
+
+    /// Parse BIP-32 Base58Check encoded extended public key.
+    /// Since the encoding only holds the depth and the last child index, the full derivation path
+    /// of the key has to be provided and is checked against the encoded depth and child index.
+    /// Returns the network the key was encoded for along with the key.
+    pub fn from_base58(
+        s: &str,
+        derivation_path: DerivationPath,
+    ) -> Result<(NetworkPrefix, Self), ExtKeyEncodingError> {
+        let payload = ExtKeyPayload::from_base58(s, ExtKeyKind::Public)?;
+        payload.check_path(&derivation_path)?;
+        let mut ext_pub_key = ExtPubKey::new(payload.key, payload.chain_code, derivation_path)
+            .map_err(|e| ExtKeyEncodingError::InvalidKey(e.to_string()))?;
+        ext_pub_key.parent_fingerprint = payload.parent_fingerprint;
+        Ok((payload.network, ext_pub_key))
+    }
 }
 
 impl From<ExtPubKey> for Address {
@@ -247,6 +308,38 @@ mod tests {
         assert_eq!(child.pub_key_bytes(), expected_child_pub_key_bytes);
     }
 
+    #[test]
+    fn bip32_test_vector1_master_xpub() {
+        // from https://github.com/bitcoin/bips/blob/master/bip-0032.mediawiki#test-vector-1
+        let xpub = "xpub661MyMwAqRbcFtXgS5sYJABqqG9YLmC4Q1Rdap9gSE8NqtwybGhePY2gZ29ESFjqJoCu1Rupje8YtGqsefD265TMg7usUDFdp6W1EGMcet8";
+        let (network, ext_pub_key) =
+            ExtPubKey::from_base58(xpub, DerivationPath::master_path()).unwrap();
+        assert_eq!(network, NetworkPrefix::Mainnet);
+        assert_eq!(
+            base16::encode_lower(&ext_pub_key.pub_key_bytes()),
+            "0339a36013301597daef41fbe593a02cc513d0b55527ec2df1050e2e8ff49c85c2"
+        );
+        assert_eq!(ext_pub_key.to_base58(NetworkPrefix::Mainnet).unwrap(), xpub);
+    }
+
+    #[test]
+    fn base58_roundtrip() {
+        let seed = Mnemonic::to_seed("edge talent poet tortoise trumpet dose", "");
+        let path = DerivationPath::new(ChildIndexHardened::from_31_bit(1).unwrap(), vec![]);
+        let ext_pub_key = ExtSecretKey::derive_master(seed)
+            .unwrap()
+            .derive(path.clone())
+            .unwrap()
+            .public_key()
+            .unwrap();
+        let encoded = ext_pub_key.to_base58(NetworkPrefix::Testnet).unwrap();
+        assert!(encoded.starts_with("tpub"));
+        let (network, decoded) = ExtPubKey::from_base58(&encoded, path).unwrap();
+        assert_eq!(network, NetworkPrefix::Testnet);
+        assert_eq!(decoded, ext_pub_key);
+        assert!(ExtPubKey::from_base58(&encoded, DerivationPath::master_path()).is_err());
+    }
+
     #[test]
     fn ergo_node_key_tree_derivation_from_seed() {
         // Tests against the following ergo node test vector:
@@ -3,20 +3,25 @@ use std::convert::TryInto;
 
 use super::{
     derivation_path::{ChildIndex, ChildIndexError, DerivationPath},
+    ext_key_encoding::{fingerprint, ExtKeyEncodingError, ExtKeyKind, ExtKeyPayload, Fingerprint},
     ext_pub_key::ExtPubKey,
     mnemonic::MnemonicSeed,
     secret_key::SecretKey,
 };
 use crate::ArrLength;
-use ergotree_interpreter::sigma_protocol::{private_input::DlogProverInput, wscalar::Wscalar};
+use ergotree_interpreter::sigma_protocol::private_input::DlogProverInput;
 use ergotree_ir::{
+    chain::address::NetworkPrefix,
     serialization::{SigmaParsingError, SigmaSerializable, SigmaSerializationError},
     sigma_protocol::sigma_boolean::ProveDlog,
 };
 use hmac::{Hmac, Mac};
 
 use sha2::Sha512;
+use subtle::ConstantTimeEq;
 use thiserror::Error;
+use zeroize::Zeroize;
+use zeroize::Zeroizing;
 
 /// Private key (serialized Scalar) bytes
 pub type SecretKeyBytes = [u8; 32];
@@ -27,12 +32,14 @@ type HmacSha512 = Hmac<Sha512>;
 
 /// Extended secret key
 /// implemented according to BIP-32
-#[derive(PartialEq, Eq, Debug, Clone)]
+/// The secret key and chain code are zeroed on drop, not shown in `Debug` output, compared in
+/// constant time and are not `Clone` (see [`ExtSecretKey::clone_secret`])
 pub struct ExtSecretKey {
     /// The secret key
     private_input: DlogProverInput,
     chain_code: ChainCode,
     derivation_path: DerivationPath,
+    parent_fingerprint: Fingerprint,
 }
 
 /// Extended secret key errors
@@ -56,10 +63,38 @@ pub enum ExtSecretKeyError {
     IncompatibleDerivation(String),
 }
 
+impl PartialEq for ExtSecretKey {
+    fn eq(&self, other: &Self) -> bool {
+        // the parent fingerprint is informational (unknown for the keys created with `new`)
+        self.private_input == other.private_input
+            && bool::from(self.chain_code.ct_eq(&other.chain_code))
+            && self.derivation_path == other.derivation_path
+    }
+}
+
+impl Eq for ExtSecretKey {}
+
+impl std::fmt::Debug for ExtSecretKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // to avoid leaking it in error messages, logs, etc.
+        f.debug_struct("ExtSecretKey")
+            .field("derivation_path", &self.derivation_path.to_string())
+            .finish_non_exhaustive()
+    }
+}
+
+impl Drop for ExtSecretKey {
+    fn drop(&mut self) {
+        // private_input is zeroed by its own Drop impl
+        self.chain_code.zeroize();
+    }
+}
+
 impl ExtSecretKey {
     const BITCOIN_SEED: &'static [u8; 12] = b"Bitcoin seed";
 
-    /// Create a new extended secret key instance
+    /// Create a new extended secret key instance.
+    /// The parent key is not known, so the parent fingerprint is set to zeros (as for the master key)
     pub fn new(
         secret_key_bytes: SecretKeyBytes,
         chain_code: ChainCode,
@@ -71,6 +106,7 @@ impl ExtSecretKey {
             private_input,
             chain_code,
             derivation_path,
+            parent_fingerprint: [0; 4],
         })
     }
 
@@ -81,12 +117,38 @@ impl ExtSecretKey {
 
     /// Returns secret key
     pub fn secret_key(&self) -> SecretKey {
-        self.private_input.clone().into()
+        self.private_input.clone_secret().into()
+    }
+
+    /// Copy of the key (`Clone` is not implemented to make the copies of the secret explicit)
+    pub fn clone_secret(&self) -> ExtSecretKey {
+        ExtSecretKey {
+            private_input: self.private_input.clone_secret(),
+            chain_code: self.chain_code,
+            derivation_path: self.derivation_path.clone(),
+            parent_fingerprint: self.parent_fingerprint,
+        }
     }
 
-    /// Byte representation of the underlying scalar
-    pub fn secret_key_bytes(&self) -> SecretKeyBytes {
-        self.private_input.to_bytes()
+    /// Chain code of the `ExtSecretKey`
+    pub fn chain_code(&self) -> ChainCode {
+        self.chain_code
+    }
+
+    /// Fingerprint of the parent key (zeros for the master key or if the parent is not known)
+    pub fn parent_fingerprint(&self) -> Fingerprint {
+        self.parent_fingerprint
+    }
+
+    /// Fingerprint of this key (first 4 bytes of HASH160 of the public key)
+    pub fn fingerprint(&self) -> Result<Fingerprint, ExtSecretKeyError> {
+        Ok(fingerprint(&self.public_image_bytes()?))
+    }
+
+    /// Byte representation of the underlying scalar.
+    /// Exports the secret, the returned copy is zeroed on drop
+    pub fn expose_secret_bytes(&self) -> Zeroizing<SecretKeyBytes> {
+        self.private_input.expose_secret_bytes()
     }
 
     /// Public image associated with the private input
@@ -102,12 +164,14 @@ impl ExtSecretKey {
     /// The extended public key associated with this secret key
     pub fn public_key(&self) -> Result<ExtPubKey, ExtSecretKeyError> {
         #[allow(clippy::unwrap_used)]
-        Ok(ExtPubKey::new(
+        let mut ext_pub_key = ExtPubKey::new(
             // unwrap is safe as it is used on an Infallible result type
             self.public_image_bytes()?.try_into().unwrap(),
             self.chain_code,
             self.derivation_path.clone(),
-        )?)
+        )?;
+        ext_pub_key.parent_fingerprint = self.parent_fingerprint;
+        Ok(ext_pub_key)
     }
 
     /// Derive a child extended secret key using the provided index
@@ -118,7 +182,7 @@ impl ExtSecretKey {
         match index {
             ChildIndex::Hardened(_) => {
                 mac.update(&[0u8]);
-                mac.update(&self.secret_key_bytes());
+                mac.update(self.expose_secret_bytes().as_ref());
             }
             ChildIndex::Normal(_) => mac.update(&self.public_image_bytes()?),
         }
@@ -129,13 +193,7 @@ impl ExtSecretKey {
         if let Some(dlog_prover) = DlogProverInput::from_bytes(&secret_key_bytes) {
             // parse256(IL) + kpar (mod n).
             // via https://github.com/bitcoin/bips/blob/master/bip-0032.mediawiki#child-key-derivation-ckd-functions
-            let child_secret_key: DlogProverInput = Wscalar::from(
-                dlog_prover
-                    .w
-                    .as_scalar_ref()
-                    .add(self.private_input.w.as_scalar_ref()),
-            )
-            .into();
+            let child_secret_key = dlog_prover.add(&self.private_input);
             if child_secret_key.is_zero() {
                 // ki == 0 case of:
                 // > In case parse256(IL) ≥ n or ki = 0, the resulting key is invalid, and one
@@ -145,11 +203,12 @@ impl ExtSecretKey {
             } else {
                 let mut chain_code = [0; ChainCode::LEN];
                 chain_code.copy_from_slice(&mac_bytes[32..]);
-                ExtSecretKey::new(
-                    child_secret_key.to_bytes(),
+                Ok(ExtSecretKey {
+                    private_input: child_secret_key,
                     chain_code,
-                    self.derivation_path.extend(index),
-                )
+                    derivation_path: self.derivation_path.extend(index),
+                    parent_fingerprint: self.fingerprint()?,
+                })
             }
         } else {
             // not in range [0, modulus), thus repeat with next index value (BIP-32)
@@ -172,7 +231,7 @@ impl ExtSecretKey {
         if up_path.depth() >= self.derivation_path.depth() && is_matching_path {
             up_path.0[self.derivation_path.depth()..]
                 .iter()
-                .try_fold(self.clone(), |parent, i| parent.child(*i))
+                .try_fold(self.clone_secret(), |parent, i| parent.child(*i))
         } else {
             Err(ExtSecretKeyError::IncompatibleDerivation(format!(
                 "{}, {}",
@@ -186,14 +245,58 @@ impl ExtSecretKey {
         // Unwrap is safe, we are using a valid static length slice
         #[allow(clippy::unwrap_used)]
         let mut mac = HmacSha512::new_from_slice(ExtSecretKey::BITCOIN_SEED).unwrap();
-        mac.update(&seed);
+        mac.update(seed.expose_secret_bytes().as_ref());
         let hash = mac.finalize().into_bytes();
-        let mut secret_key_bytes = [0; SecretKeyBytes::LEN];
+        let mut secret_key_bytes = Zeroizing::new([0; SecretKeyBytes::LEN]);
         secret_key_bytes.copy_from_slice(&hash[..32]);
         let mut chain_code = [0; ChainCode::LEN];
         chain_code.copy_from_slice(&hash[32..]);
 
-        ExtSecretKey::new(secret_key_bytes, chain_code, DerivationPath::master_path())
+        ExtSecretKey::new(*secret_key_bytes, chain_code, DerivationPath::master_path())
+    }
+
+    /// Encode as BIP-32 Base58Check string (`xprv...` for mainnet, `tprv...` for testnet).
+    /// Fails if the derivation path is deeper than 255 levels.
+    /// The returned string holds the secret key and is zeroed on drop.
+    pub fn to_base58(
+        &self,
+        network: NetworkPrefix,
+    ) -> Result<Zeroizing<String>, ExtKeyEncodingError> {
+        let mut key = Zeroizing::new([0u8; 33]);
+        key[1..].copy_from_slice(self.expose_secret_bytes().as_ref());
+        ExtKeyPayload::new(
+            network,
+            ExtKeyKind::Secret,
+            &self.derivation_path,
+            self.parent_fingerprint,
+            self.chain_code,
+            *key,
+        )
+        .map(|payload| payload.to_base58())
+    }
+
+    /// Parse BIP-32 Base58Check encoded extended secret key.
+    /// Since the encoding only holds the depth and the last child index, the full derivation path
+    /// of the key has to be provided and is checked against the encoded depth and child index.
+    /// Returns the network the key was encoded for along with the key.
+    pub fn from_base58(
+        s: &str,
+        derivation_path: DerivationPath,
+    ) -> Result<(NetworkPrefix, Self), ExtKeyEncodingError> {
+        let payload = ExtKeyPayload::from_base58(s, ExtKeyKind::Secret)?;
+        payload.check_path(&derivation_path)?;
+        if payload.key[0] != 0 {
+            return Err(ExtKeyEncodingError::InvalidKey(
+                "secret key must be prefixed with 0x00".to_string(),
+            ));
+        }
+        let mut secret_key_bytes = Zeroizing::new([0u8; SecretKeyBytes::LEN]);
+        secret_key_bytes.copy_from_slice(&payload.key[1..]);
+        let mut ext_secret_key =
+            ExtSecretKey::new(*secret_key_bytes, payload.chain_code, derivation_path)
+                .map_err(|e| ExtKeyEncodingError::InvalidKey(e.to_string()))?;
+        ext_secret_key.parent_fingerprint = payload.parent_fingerprint;
+        Ok((payload.network, ext_secret_key))
     }
 }
 
@@ -272,7 +375,7 @@ mod tests {
 
         for v in vectors {
             ext_secret_key = ext_secret_key.child(v.next_index).unwrap();
-            assert_eq!(ext_secret_key.secret_key_bytes(), v.expected_secret_key);
+            assert_eq!(*ext_secret_key.expose_secret_bytes(), v.expected_secret_key);
         }
     }
 
@@ -320,10 +423,92 @@ mod tests {
 
         for v in vectors {
             ext_secret_key = ext_secret_key.child(v.next_index).unwrap();
-            assert_eq!(ext_secret_key.secret_key_bytes(), v.expected_secret_key);
+            assert_eq!(*ext_secret_key.expose_secret_bytes(), v.expected_secret_key);
         }
     }
 
+    #[test]
+    fn bip32_test_vector1_master_xprv() {
+        // from https://github.com/bitcoin/bips/blob/master/bip-0032.mediawiki#test-vector-1
+        let xprv = "xprv9s21ZrQH143K3QTDL4LXw2F7HEK3wJUD2nW2nRk4stbPy6cq3jPPqjiChkVvvNKmPGJxWUtg6LnF5kejMRNNU3TGtRBeJgk33yuGBxrMPHi";
+        let (network, ext_secret_key) =
+            ExtSecretKey::from_base58(xprv, DerivationPath::master_path()).unwrap();
+        assert_eq!(network, NetworkPrefix::Mainnet);
+        assert_eq!(
+            base16::encode_lower(&ext_secret_key.expose_secret_bytes()),
+            "e8f32e723decf4051aefac8e2c93c9c5b214313817cdb01a1494b917c8436b35"
+        );
+        assert_eq!(
+            ext_secret_key
+                .to_base58(NetworkPrefix::Mainnet)
+                .unwrap()
+                .as_str(),
+            xprv
+        );
+        assert_eq!(
+            ext_secret_key
+                .public_key()
+                .unwrap()
+                .to_base58(NetworkPrefix::Mainnet)
+                .unwrap(),
+            "xpub661MyMwAqRbcFtXgS5sYJABqqG9YLmC4Q1Rdap9gSE8NqtwybGhePY2gZ29ESFjqJoCu1Rupje8YtGqsefD265TMg7usUDFdp6W1EGMcet8"
+        );
+    }
+
+    #[test]
+    fn bip32_test_vector1_child_parent_fingerprint() {
+        // from https://github.com/bitcoin/bips/blob/master/bip-0032.mediawiki#test-vector-1
+        // Chain m/0H
+        let xprv = "xprv9s21ZrQH143K3QTDL4LXw2F7HEK3wJUD2nW2nRk4stbPy6cq3jPPqjiChkVvvNKmPGJxWUtg6LnF5kejMRNNU3TGtRBeJgk33yuGBxrMPHi";
+        let (_, master) = ExtSecretKey::from_base58(xprv, DerivationPath::master_path()).unwrap();
+        assert_eq!(master.parent_fingerprint(), [0; 4]);
+        assert_eq!(
+            base16::encode_lower(&master.fingerprint().unwrap()),
+            "3442193e"
+        );
+        let child = master.child("0'".parse().unwrap()).unwrap();
+        assert_eq!(child.parent_fingerprint(), master.fingerprint().unwrap());
+        let child_xprv = "xprv9uHRZZhk6KAJC1avXpDAp4MDc3sQKNxDiPvvkX8Br5ngLNv1TxvUxt4cV1rGL5hj6KCesnDYUhd7oWgT11eZG7XnxHrnYeSvkzY7d2bhkJ7";
+        assert_eq!(
+            child.to_base58(NetworkPrefix::Mainnet).unwrap().as_str(),
+            child_xprv
+        );
+        assert_eq!(
+            child
+                .public_key()
+                .unwrap()
+                .to_base58(NetworkPrefix::Mainnet)
+                .unwrap(),
+            "xpub68Gmy5EdvgibQVfPdqkBBCHxA5htiqg55crXYuXoQRKfDBFA1WEjWgP6LHhwBZeNK1VTsfTFUHCdrfp1bgwQ9xv5ski8PX9rL2dZXvgGDnw"
+        );
+        let (_, decoded) = ExtSecretKey::from_base58(child_xprv, "m/0'".parse().unwrap()).unwrap();
+        assert_eq!(decoded.parent_fingerprint(), master.fingerprint().unwrap());
+    }
+
+    #[test]
+    fn debug_is_redacted() {
+        let seed = Mnemonic::to_seed("edge talent poet tortoise trumpet dose", "");
+        let ext_secret_key = ExtSecretKey::derive_master(seed).unwrap();
+        let debug_str = format!("{:?}", ext_secret_key);
+        assert_eq!(debug_str, "ExtSecretKey { derivation_path: \"m/\", .. }");
+    }
+
+    #[test]
+    fn base58_roundtrip() {
+        let seed = Mnemonic::to_seed("edge talent poet tortoise trumpet dose", "");
+        let path: DerivationPath = "m/44'/429'/0'/0/1".parse().unwrap();
+        let ext_secret_key = ExtSecretKey::derive_master(seed)
+            .unwrap()
+            .derive(path.clone())
+            .unwrap();
+        let encoded = ext_secret_key.to_base58(NetworkPrefix::Mainnet).unwrap();
+        assert!(encoded.starts_with("xprv"));
+        let (network, decoded) = ExtSecretKey::from_base58(&encoded, path).unwrap();
+        assert_eq!(network, NetworkPrefix::Mainnet);
+        assert_eq!(decoded, ext_secret_key);
+        assert!(ExtSecretKey::from_base58(&encoded, "m/44'/429'/0'/0/2".parse().unwrap()).is_err());
+    }
+
     #[test]
     fn ergo_node_key_tree_derivation_from_seed() {
         // Tests against the following ergo node test vector:
@@ -347,13 +532,14 @@ mod tests {
         ];
 
         let mut ext_secret_key = ExtSecretKey::derive_master(seed).unwrap();
-        let ext_secret_key_b58 = bs58::encode(ext_secret_key.secret_key_bytes()).into_string();
+        let ext_secret_key_b58 = bs58::encode(ext_secret_key.expose_secret_bytes()).into_string();
 
         assert_eq!(expected_root, ext_secret_key_b58);
 
         for (expected_key, idx) in cases {
             ext_secret_key = ext_secret_key.child(idx).unwrap();
-            let ext_secret_key_b58 = bs58::encode(ext_secret_key.secret_key_bytes()).into_string();
+            let ext_secret_key_b58 =
+                bs58::encode(ext_secret_key.expose_secret_bytes()).into_string();
 
             assert_eq!(expected_key, ext_secret_key_b58);
         }
@@ -375,7 +561,7 @@ mod tests {
 
         for (expected_key, path) in cases {
             let derived = root.derive(path.parse().unwrap()).unwrap();
-            let ext_secret_key_b58 = bs58::encode(derived.secret_key_bytes()).into_string();
+            let ext_secret_key_b58 = bs58::encode(derived.expose_secret_bytes()).into_string();
 
             assert_eq!(expected_key, ext_secret_key_b58);
         }
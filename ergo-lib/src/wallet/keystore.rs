@@ -0,0 +1,477 @@
+//! Encrypted secret storage compatible with the reference node's `JsonSecretStorage` format
+//! <https://github.com/ergoplatform/ergo/blob/master/ergo-wallet/src/main/scala/org/ergoplatform/wallet/secrets/JsonSecretStorage.scala>
+//!
+//! The secret is encrypted with AES-256-GCM using a key derived from the password with PBKDF2.
+//! The reference node stores the mnemonic seed, keystores created from an [`ExtSecretKey`] use a
+//! different plaintext layout and can only be opened by this library.
+
+use std::convert::TryFrom;
+use std::convert::TryInto;
+
+use aes_gcm::aead::Aead;
+use aes_gcm::aead::KeyInit;
+use aes_gcm::Aes256Gcm;
+use aes_gcm::Nonce;
+use hmac::Hmac;
+use pbkdf2::pbkdf2;
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use thiserror::Error;
+use zeroize::Zeroize;
+
+use super::derivation_path::ChildIndex;
+use super::derivation_path::DerivationPath;
+use super::ext_secret_key::ChainCode;
+use super::ext_secret_key::ExtSecretKey;
+use super::ext_secret_key::ExtSecretKeyError;
+use super::ext_secret_key::SecretKeyBytes;
+use super::mnemonic::Mnemonic;
+use super::mnemonic::MnemonicSeed;
+use crate::ArrLength;
+
+/// Key derivation parameters
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Clone)]
+pub struct CipherParams {
+    /// Pseudo-random function used in PBKDF2 (only `HmacSHA256` is supported)
+    pub prf: String,
+    /// Number of PBKDF2 iterations
+    pub c: u32,
+    /// Derived key length in bits (only 256 is supported)
+    #[serde(rename = "dkLen")]
+    pub dk_len: u32,
+}
+
+impl CipherParams {
+    /// The only supported PBKDF2 pseudo-random function
+    pub const PRF: &'static str = "HmacSHA256";
+    /// The only supported derived key length (AES-256)
+    pub const DK_LEN: u32 = 256;
+    /// Number of PBKDF2 iterations used by the reference node
+    pub const DEFAULT_ITERATIONS: u32 = 128000;
+
+    /// Parameters with the given number of PBKDF2 iterations
+    pub fn with_iterations(c: u32) -> Self {
+        CipherParams {
+            prf: CipherParams::PRF.to_string(),
+            c,
+            dk_len: CipherParams::DK_LEN,
+        }
+    }
+
+    fn check_supported(&self) -> Result<(), KeystoreError> {
+        if self.prf != CipherParams::PRF || self.dk_len != CipherParams::DK_LEN {
+            Err(KeystoreError::UnsupportedCipherParams(format!(
+                "prf: {}, dkLen: {}",
+                self.prf, self.dk_len
+            )))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl Default for CipherParams {
+    fn default() -> Self {
+        CipherParams::with_iterations(CipherParams::DEFAULT_ITERATIONS)
+    }
+}
+
+/// Keystore errors
+#[derive(Error, PartialEq, Eq, Debug, Clone)]
+pub enum KeystoreError {
+    /// Decryption failed, most likely due to a wrong password
+    #[error("decryption failed (wrong password?)")]
+    DecryptionFailed,
+    /// Encryption failed
+    #[error("encryption failed")]
+    EncryptionFailed,
+    /// Key derivation parameters are not supported
+    #[error("unsupported cipher params: {0}")]
+    UnsupportedCipherParams(String),
+    /// Keystore uses the pre-1627 (incorrect) BIP-32 key derivation of the reference node
+    /// (see <https://github.com/ergoplatform/ergo/issues/1627>), which is not supported
+    #[error("pre-1627 key derivation is not supported")]
+    UnsupportedPre1627KeyDerivation,
+    /// Decrypted secret is malformed
+    #[error("invalid secret: {0}")]
+    InvalidSecret(String),
+    /// ExtSecretKey error
+    #[error("ExtSecretKeyError: {0}")]
+    ExtSecretKeyError(#[from] ExtSecretKeyError),
+    /// Ciphertext is longer than any stored secret
+    #[error("ciphertext of {0} bytes exceeds the maximum of {max}", max = Keystore::MAX_CIPHER_TEXT_LEN)]
+    CipherTextTooLong(usize),
+    /// Invalid Base16 encoding of the keystore fields
+    #[error("Base16 decoding error: {0}")]
+    Base16DecodingError(String),
+}
+
+impl From<base16::DecodeError> for KeystoreError {
+    fn from(e: base16::DecodeError) -> Self {
+        KeystoreError::Base16DecodingError(e.to_string())
+    }
+}
+
+/// Secret stored in the keystore
+#[derive(PartialEq, Eq, Debug)]
+pub enum KeystoreSecret {
+    /// Mnemonic seed (the format of the reference node)
+    Seed(MnemonicSeed),
+    /// Extended secret key
+    ExtSecretKey(ExtSecretKey),
+}
+
+impl KeystoreSecret {
+    /// Master (or stored) extended secret key
+    pub fn ext_secret_key(&self) -> Result<ExtSecretKey, KeystoreError> {
+        match self {
+            KeystoreSecret::Seed(seed) => Ok(ExtSecretKey::derive_master(MnemonicSeed::from(
+                *seed.expose_secret_bytes(),
+            ))?),
+            KeystoreSecret::ExtSecretKey(key) => Ok(key.clone_secret()),
+        }
+    }
+
+    /// Plaintext bytes: 64 bytes of the seed, or
+    /// secret key(32) ++ chain code(32) ++ path length(1) ++ path indices(4 each, big-endian)
+    fn to_bytes(&self) -> Vec<u8> {
+        match self {
+            KeystoreSecret::Seed(seed) => seed.expose_secret_bytes().to_vec(),
+            KeystoreSecret::ExtSecretKey(key) => {
+                let mut bytes = key.expose_secret_bytes().to_vec();
+                bytes.extend_from_slice(&key.chain_code());
+                bytes.extend_from_slice(&key.path().ledger_bytes());
+                bytes
+            }
+        }
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Result<Self, KeystoreError> {
+        if bytes.len() == MnemonicSeed::LEN {
+            return Ok(KeystoreSecret::Seed(
+                MnemonicSeed::try_from(bytes)
+                    .map_err(|e| KeystoreError::InvalidSecret(e.to_string()))?,
+            ));
+        }
+        let path_offset = SecretKeyBytes::LEN + ChainCode::LEN;
+        let path_len = *bytes
+            .get(path_offset)
+            .ok_or_else(|| KeystoreError::InvalidSecret("unexpected end of input".to_string()))?
+            as usize;
+        let path_bytes = &bytes[path_offset + 1..];
+        if path_bytes.len() != path_len * 4 {
+            return Err(KeystoreError::InvalidSecret(format!(
+                "invalid derivation path length: {}",
+                path_len
+            )));
+        }
+        #[allow(clippy::unwrap_used)]
+        let path: DerivationPath = path_bytes
+            .chunks(4)
+            // unwrap is safe since the chunk length is checked above
+            .map(|c| ChildIndex::from_bits(u32::from_be_bytes(c.try_into().unwrap())))
+            .collect::<Vec<_>>()
+            .into_boxed_slice()
+            .into();
+        let mut secret_key_bytes = [0u8; SecretKeyBytes::LEN];
+        secret_key_bytes.copy_from_slice(&bytes[..SecretKeyBytes::LEN]);
+        let mut chain_code = [0u8; ChainCode::LEN];
+        chain_code.copy_from_slice(&bytes[SecretKeyBytes::LEN..path_offset]);
+        let key = ExtSecretKey::new(secret_key_bytes, chain_code, path);
+        secret_key_bytes.zeroize();
+        chain_code.zeroize();
+        Ok(KeystoreSecret::ExtSecretKey(key?))
+    }
+}
+
+/// Encrypted secret storage
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Clone)]
+#[serde(try_from = "KeystoreJson", into = "KeystoreJson")]
+pub struct Keystore {
+    cipher_text: Vec<u8>,
+    salt: Vec<u8>,
+    iv: Vec<u8>,
+    auth_tag: Vec<u8>,
+    cipher_params: CipherParams,
+    use_pre1627_key_derivation: bool,
+}
+
+impl Keystore {
+    const SALT_LEN: usize = 32;
+    const IV_LEN: usize = 12;
+    const AUTH_TAG_LEN: usize = 16;
+    /// Ciphertext length of the longest secret (extended secret key with a 255 levels deep path),
+    /// the ciphertext has the length of the plaintext
+    const MAX_CIPHER_TEXT_LEN: usize = SecretKeyBytes::LEN + ChainCode::LEN + 1 + 255 * 4;
+
+    /// Encrypt the seed of the given mnemonic (reference node format) with default cipher params
+    pub fn from_mnemonic(
+        mnemonic_phrase: &str,
+        mnemonic_pass: &str,
+        password: &str,
+    ) -> Result<Keystore, KeystoreError> {
+        Keystore::encrypt(
+            &KeystoreSecret::Seed(Mnemonic::to_seed(mnemonic_phrase, mnemonic_pass)),
+            password,
+            CipherParams::default(),
+        )
+    }
+
+    /// Encrypt the extended secret key with default cipher params
+    pub fn from_ext_secret_key(
+        ext_secret_key: &ExtSecretKey,
+        password: &str,
+    ) -> Result<Keystore, KeystoreError> {
+        Keystore::encrypt(
+            &KeystoreSecret::ExtSecretKey(ext_secret_key.clone_secret()),
+            password,
+            CipherParams::default(),
+        )
+    }
+
+    /// Encrypt the secret with the given password and cipher params
+    pub fn encrypt(
+        secret: &KeystoreSecret,
+        password: &str,
+        cipher_params: CipherParams,
+    ) -> Result<Keystore, KeystoreError> {
+        cipher_params.check_supported()?;
+        let mut rng = rand::thread_rng();
+        let mut salt = vec![0u8; Keystore::SALT_LEN];
+        rng.fill_bytes(&mut salt);
+        let mut iv = vec![0u8; Keystore::IV_LEN];
+        rng.fill_bytes(&mut iv);
+        let cipher = Keystore::cipher(password, &salt, &cipher_params)?;
+        let mut plaintext = secret.to_bytes();
+        let res = cipher.encrypt(Nonce::from_slice(&iv), plaintext.as_slice());
+        plaintext.zeroize();
+        let mut encrypted = res.map_err(|_| KeystoreError::EncryptionFailed)?;
+        // The reference node splits the output of Java's AES/GCM (ciphertext ++ tag) at the
+        // tag length and stores the first part as "authTag"
+        let cipher_text = encrypted.split_off(Keystore::AUTH_TAG_LEN);
+        Ok(Keystore {
+            cipher_text,
+            salt,
+            iv,
+            auth_tag: encrypted,
+            cipher_params,
+            use_pre1627_key_derivation: false,
+        })
+    }
+
+    /// Decrypt the stored secret
+    pub fn decrypt(&self, password: &str) -> Result<KeystoreSecret, KeystoreError> {
+        if self.use_pre1627_key_derivation {
+            return Err(KeystoreError::UnsupportedPre1627KeyDerivation);
+        }
+        if self.iv.len() != Keystore::IV_LEN {
+            return Err(KeystoreError::UnsupportedCipherParams(format!(
+                "iv length: {}",
+                self.iv.len()
+            )));
+        }
+        let cipher = Keystore::cipher(password, &self.salt, &self.cipher_params)?;
+        let mut encrypted = self.auth_tag.clone();
+        encrypted.extend_from_slice(&self.cipher_text);
+        let mut plaintext = cipher
+            .decrypt(Nonce::from_slice(&self.iv), encrypted.as_slice())
+            .map_err(|_| KeystoreError::DecryptionFailed)?;
+        let res = KeystoreSecret::from_bytes(&plaintext);
+        plaintext.zeroize();
+        res
+    }
+
+    /// Decrypt the stored secret and return the master (or stored) extended secret key
+    pub fn ext_secret_key(&self, password: &str) -> Result<ExtSecretKey, KeystoreError> {
+        self.decrypt(password)?.ext_secret_key()
+    }
+
+    /// Re-encrypt the stored secret with a new password (and fresh salt and iv)
+    pub fn change_password(
+        &self,
+        old_password: &str,
+        new_password: &str,
+    ) -> Result<Keystore, KeystoreError> {
+        let secret = self.decrypt(old_password)?;
+        Keystore::encrypt(&secret, new_password, self.cipher_params.clone())
+    }
+
+    /// Key derivation parameters
+    pub fn cipher_params(&self) -> &CipherParams {
+        &self.cipher_params
+    }
+
+    fn cipher(
+        password: &str,
+        salt: &[u8],
+        cipher_params: &CipherParams,
+    ) -> Result<Aes256Gcm, KeystoreError> {
+        cipher_params.check_supported()?;
+        let mut key = [0u8; (CipherParams::DK_LEN / 8) as usize];
+        pbkdf2::<Hmac<Sha256>>(password.as_bytes(), salt, cipher_params.c, &mut key);
+        let cipher = Aes256Gcm::new_from_slice(&key)
+            .map_err(|e| KeystoreError::UnsupportedCipherParams(e.to_string()));
+        key.zeroize();
+        cipher
+    }
+}
+
+/// JSON representation of the keystore (as in the reference node)
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Clone)]
+struct KeystoreJson {
+    #[serde(rename = "cipherText")]
+    cipher_text: String,
+    salt: String,
+    iv: String,
+    #[serde(rename = "authTag")]
+    auth_tag: String,
+    #[serde(rename = "cipherParams")]
+    cipher_params: CipherParams,
+    #[serde(rename = "usePre1627KeyDerivation", default)]
+    use_pre1627_key_derivation: bool,
+}
+
+impl From<Keystore> for KeystoreJson {
+    fn from(k: Keystore) -> Self {
+        KeystoreJson {
+            cipher_text: base16::encode_lower(&k.cipher_text),
+            salt: base16::encode_lower(&k.salt),
+            iv: base16::encode_lower(&k.iv),
+            auth_tag: base16::encode_lower(&k.auth_tag),
+            cipher_params: k.cipher_params,
+            use_pre1627_key_derivation: k.use_pre1627_key_derivation,
+        }
+    }
+}
+
+impl TryFrom<KeystoreJson> for Keystore {
+    type Error = KeystoreError;
+
+    fn try_from(kj: KeystoreJson) -> Result<Self, Self::Error> {
+        // checked before decoding since the JSON is untrusted
+        let cipher_text_len = kj.cipher_text.len() / 2;
+        if cipher_text_len > Keystore::MAX_CIPHER_TEXT_LEN {
+            return Err(KeystoreError::CipherTextTooLong(cipher_text_len));
+        }
+        Ok(Keystore {
+            cipher_text: base16::decode(&kj.cipher_text)?,
+            salt: base16::decode(&kj.salt)?,
+            iv: base16::decode(&kj.iv)?,
+            auth_tag: base16::decode(&kj.auth_tag)?,
+            cipher_params: kj.cipher_params,
+            use_pre1627_key_derivation: kj.use_pre1627_key_derivation,
+        })
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    const MNEMONIC: &str = "edge talent poet tortoise trumpet dose";
+
+    fn test_params() -> CipherParams {
+        CipherParams::with_iterations(1000)
+    }
+
+    #[test]
+    fn decrypt_golden() {
+        // encrypted with the reference node's algorithm (Java AES/GCM output split at 16 bytes)
+        let json = r#"{
+  "cipherText": "88b4abd5d11079fb25392c4e44dabdde34d46eaa5ca213f3194c8e6ebfb6a9f0e00b88980d64aa7106bce373a500043730020513ebeb2c64748436cfbdf9f25f",
+  "salt": "000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f",
+  "iv": "000102030405060708090a0b",
+  "authTag": "d96d7246a42668853304a40eb605d0f1",
+  "cipherParams": {
+    "prf": "HmacSHA256",
+    "c": 1000,
+    "dkLen": 256
+  },
+  "usePre1627KeyDerivation": false
+}"#;
+        let keystore: Keystore = serde_json::from_str(json).unwrap();
+        assert_eq!(
+            keystore.decrypt("correct horse battery staple").unwrap(),
+            KeystoreSecret::Seed(Mnemonic::to_seed(MNEMONIC, ""))
+        );
+        assert_eq!(serde_json::to_string_pretty(&keystore).unwrap(), json);
+    }
+
+    #[test]
+    fn cipher_text_too_long() {
+        let cipher_text = "00".repeat(Keystore::MAX_CIPHER_TEXT_LEN + 1);
+        let json = format!(
+            r#"{{"cipherText":"{}","salt":"00","iv":"00","authTag":"00","cipherParams":{{"prf":"HmacSHA256","c":1000,"dkLen":256}}}}"#,
+            cipher_text
+        );
+        let err = serde_json::from_str::<Keystore>(&json).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            KeystoreError::CipherTextTooLong(Keystore::MAX_CIPHER_TEXT_LEN + 1).to_string()
+        );
+    }
+
+    #[test]
+    fn seed_roundtrip() {
+        let secret = KeystoreSecret::Seed(Mnemonic::to_seed(MNEMONIC, ""));
+        let keystore = Keystore::encrypt(&secret, "pass", test_params()).unwrap();
+        let json = serde_json::to_string(&keystore).unwrap();
+        let parsed: Keystore = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.decrypt("pass").unwrap(), secret);
+        assert_eq!(
+            parsed.ext_secret_key("pass").unwrap(),
+            ExtSecretKey::derive_master(Mnemonic::to_seed(MNEMONIC, "")).unwrap()
+        );
+    }
+
+    #[test]
+    fn ext_secret_key_roundtrip() {
+        let key = ExtSecretKey::derive_master(Mnemonic::to_seed(MNEMONIC, ""))
+            .unwrap()
+            .derive("m/44'/429'/0'/0/1".parse().unwrap())
+            .unwrap();
+        let secret = KeystoreSecret::ExtSecretKey(key.clone_secret());
+        let keystore = Keystore::encrypt(&secret, "pass", test_params()).unwrap();
+        assert_eq!(keystore.ext_secret_key("pass").unwrap(), key);
+    }
+
+    #[test]
+    fn wrong_password() {
+        let secret = KeystoreSecret::Seed(Mnemonic::to_seed(MNEMONIC, ""));
+        let keystore = Keystore::encrypt(&secret, "pass", test_params()).unwrap();
+        assert_eq!(
+            keystore.decrypt("wrong"),
+            Err(KeystoreError::DecryptionFailed)
+        );
+    }
+
+    #[test]
+    fn change_password() {
+        let secret = KeystoreSecret::Seed(Mnemonic::to_seed(MNEMONIC, ""));
+        let keystore = Keystore::encrypt(&secret, "old", test_params()).unwrap();
+        let changed = keystore.change_password("old", "new").unwrap();
+        assert_eq!(changed.cipher_params(), &test_params());
+        assert_eq!(changed.decrypt("new").unwrap(), secret);
+        assert_eq!(changed.decrypt("old"), Err(KeystoreError::DecryptionFailed));
+        assert_eq!(
+            keystore.change_password("wrong", "new"),
+            Err(KeystoreError::DecryptionFailed)
+        );
+    }
+
+    #[test]
+    fn unsupported_params() {
+        let secret = KeystoreSecret::Seed(Mnemonic::to_seed(MNEMONIC, ""));
+        let params = CipherParams {
+            prf: "HmacSHA512".to_string(),
+            c: 1000,
+            dk_len: 256,
+        };
+        assert!(matches!(
+            Keystore::encrypt(&secret, "pass", params),
+            Err(KeystoreError::UnsupportedCipherParams(_))
+        ));
+    }
+}
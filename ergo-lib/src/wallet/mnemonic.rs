@@ -1,16 +1,72 @@
 //! Mnemonic operations according to BIP32/BIP39
 
+use std::array::TryFromSliceError;
+use std::convert::TryFrom;
+use std::convert::TryInto;
+use std::fmt::Formatter;
+
 use hmac::Hmac;
 use pbkdf2::pbkdf2;
 use sha2::Sha512;
 extern crate unicode_normalization;
+use subtle::ConstantTimeEq;
 use unicode_normalization::UnicodeNormalization;
+use zeroize::Zeroize;
+use zeroize::Zeroizing;
 
 /// Length of mnemonic seed in bytes
 const SHA512_OUTPUT_LEN: usize = 512 / 8;
 
 /// Mnemonic seed
-pub type MnemonicSeed = [u8; SHA512_OUTPUT_LEN];
+/// The seed bytes are zeroed on drop, not shown in `Debug` output, compared in constant time and
+/// are not `Clone`
+pub struct MnemonicSeed([u8; SHA512_OUTPUT_LEN]);
+
+impl MnemonicSeed {
+    /// Length of mnemonic seed in bytes
+    pub const LEN: usize = SHA512_OUTPUT_LEN;
+
+    /// Seed bytes.
+    /// This is the only way to get the secret bytes out, the returned copy is zeroed on drop
+    pub fn expose_secret_bytes(&self) -> Zeroizing<[u8; SHA512_OUTPUT_LEN]> {
+        Zeroizing::new(self.0)
+    }
+}
+
+impl PartialEq for MnemonicSeed {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.ct_eq(&other.0).into()
+    }
+}
+
+impl Eq for MnemonicSeed {}
+
+impl From<[u8; SHA512_OUTPUT_LEN]> for MnemonicSeed {
+    fn from(bytes: [u8; SHA512_OUTPUT_LEN]) -> Self {
+        MnemonicSeed(bytes)
+    }
+}
+
+impl TryFrom<&[u8]> for MnemonicSeed {
+    type Error = TryFromSliceError;
+
+    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+        Ok(MnemonicSeed(bytes.try_into()?))
+    }
+}
+
+impl std::fmt::Debug for MnemonicSeed {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        // to avoid leaking it in error messages, logs, etc.
+        f.write_str("MnemonicSeed:***")
+    }
+}
+
+impl Drop for MnemonicSeed {
+    fn drop(&mut self) {
+        self.0.zeroize()
+    }
+}
 
 /// Mnemonic type
 #[derive(PartialEq, Eq, Debug, Clone)]
@@ -22,16 +78,17 @@ impl Mnemonic {
     /// Convert a mnemonic phrase into a mnemonic seed
     /// mnemonic_pass is optional and is used to salt the seed
     pub fn to_seed(mnemonic_phrase: &str, mnemonic_pass: &str) -> MnemonicSeed {
-        let mut seed: MnemonicSeed = [0u8; SHA512_OUTPUT_LEN];
-        let normalized_phrase = mnemonic_phrase.nfkd().collect::<String>();
-        let normalized_pass = mnemonic_pass.nfkd().collect::<String>();
+        let mut seed = MnemonicSeed([0u8; SHA512_OUTPUT_LEN]);
+        let mut normalized_phrase = mnemonic_phrase.nfkd().collect::<String>();
+        let mut salt = format!("mnemonic{}", mnemonic_pass.nfkd().collect::<String>());
         pbkdf2::<Hmac<Sha512>>(
             normalized_phrase.as_bytes(),
-            format!("mnemonic{}", normalized_pass).as_bytes(),
+            salt.as_bytes(),
             Mnemonic::PBKDF2_ITERATIONS,
-            &mut seed,
+            &mut seed.0,
         );
-
+        normalized_phrase.zeroize();
+        salt.zeroize();
         seed
     }
 }
@@ -45,7 +102,7 @@ mod tests {
     fn test_mnemonic_to_seed() {
         let mnemonic = "change me do not use me change me do not use me";
         let seed = Mnemonic::to_seed(mnemonic, "");
-        let encoded_seed = base16::encode_lower(&seed);
+        let encoded_seed = base16::encode_lower(&seed.expose_secret_bytes());
         let expected = "c5b2537b52b27b903b34c423783ced17c489e4385ec6d49d6a19a7f892ecd3917db36675de36bcbe3b8dbc6f803877f4155bdf83482ca5f0fc4282a61ac842a3";
 
         assert_eq!(encoded_seed, expected);
@@ -55,9 +112,17 @@ mod tests {
     fn test_mnemonic_to_seed_with_pass() {
         let mnemonic = "change me do not use me change me do not use me";
         let seed = Mnemonic::to_seed(mnemonic, "password123");
-        let encoded_seed = base16::encode_lower(&seed);
+        let encoded_seed = base16::encode_lower(&seed.expose_secret_bytes());
         let expected = "dfe3088b88e2eb8588482e8c56d9cde497c4e1f63fd29b480cbb0ed0227331d51301cfc2d461acce642868ecb618a37b4fd75d48dc6189674c55fbafd807d69c";
 
         assert_eq!(encoded_seed, expected);
     }
+
+    #[test]
+    fn test_mnemonic_seed_debug_is_redacted() {
+        let seed = Mnemonic::to_seed("change me do not use me change me do not use me", "");
+        let encoded_seed = base16::encode_lower(&seed.expose_secret_bytes());
+        let debug_str = format!("{:?}", seed);
+        assert!(!debug_str.contains(&encoded_seed[..8]));
+    }
 }
@@ -564,7 +564,7 @@ mod tests {
         let mnemonic = MnemonicGenerator::new(lang.parse().unwrap(), strength);
 
         assert_eq!(
-            base16::encode_lower(&Mnemonic::to_seed(sentence, pass)),
+            base16::encode_lower(&Mnemonic::to_seed(sentence, pass).expose_secret_bytes()),
             seed
         );
         assert_eq!(
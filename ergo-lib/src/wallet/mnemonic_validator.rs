@@ -0,0 +1,264 @@
+//! Mnemonic phrase validation according to BIP-39
+//! <https://github.com/bitcoin/bips/blob/master/bip-0039.mediawiki>
+
+use std::collections::HashMap;
+
+use bitvec::prelude::*;
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+use unicode_normalization::UnicodeNormalization;
+
+use super::mnemonic::Mnemonic;
+use super::mnemonic::MnemonicSeed;
+use super::mnemonic_generator::Language;
+use super::mnemonic_generator::MnemonicGenerator;
+use super::mnemonic_generator::WordList;
+
+/// Number of bits encoded by each word
+const BITS_GROUP_SIZE: usize = 11;
+
+/// Mnemonic validation errors
+#[derive(Error, PartialEq, Eq, Debug, Clone)]
+pub enum MnemonicValidationError {
+    /// Number of words is not one of [`MnemonicGenerator::ALLOWED_SENTENCE_LENS`]
+    #[error("invalid number of words: {0}")]
+    InvalidWordCount(usize),
+    /// Word is not in the wordlist
+    #[error("unknown word #{index} '{word}', did you mean one of {suggestions:?}?")]
+    UnknownWord {
+        /// Position of the word in the phrase (starting from 0)
+        index: usize,
+        /// The word as it was entered
+        word: String,
+        /// Closest words from the wordlist
+        suggestions: Vec<String>,
+    },
+    /// Checksum encoded in the last word does not match the entropy
+    #[error("invalid checksum")]
+    InvalidChecksum,
+}
+
+/// Mnemonic validator type
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct MnemonicValidator {
+    lang: Language,
+}
+
+impl MnemonicValidator {
+    /// Max edit distance for a wordlist word to be suggested instead of an unknown word
+    const MAX_SUGGESTION_DISTANCE: usize = 2;
+    /// Max number of suggestions for an unknown word
+    const MAX_SUGGESTIONS: usize = 5;
+
+    /// Create new MnemonicValidator instance for the given language
+    pub fn new(lang: Language) -> Self {
+        Self { lang }
+    }
+
+    /// Check that the phrase has an allowed number of words, every word is in the wordlist and
+    /// the checksum is valid
+    pub fn validate(&self, mnemonic_phrase: &str) -> Result<(), MnemonicValidationError> {
+        self.to_entrophy(mnemonic_phrase).map(|_| ())
+    }
+
+    /// Validate the phrase and convert it into a mnemonic seed (see [`Mnemonic::to_seed`])
+    pub fn to_seed(
+        &self,
+        mnemonic_phrase: &str,
+        mnemonic_pass: &str,
+    ) -> Result<MnemonicSeed, MnemonicValidationError> {
+        self.validate(mnemonic_phrase)?;
+        Ok(Mnemonic::to_seed(mnemonic_phrase, mnemonic_pass))
+    }
+
+    /// Validate the phrase and convert it back into the entrophy it was generated from
+    /// (inverse of [`MnemonicGenerator::from_entrophy`])
+    pub fn to_entrophy(&self, mnemonic_phrase: &str) -> Result<Vec<u8>, MnemonicValidationError> {
+        let phrase_words = mnemonic_phrase.split_whitespace().collect::<Vec<_>>();
+        if !MnemonicGenerator::ALLOWED_SENTENCE_LENS.contains(&phrase_words.len()) {
+            return Err(MnemonicValidationError::InvalidWordCount(
+                phrase_words.len(),
+            ));
+        }
+        let word_indices = self.word_indices();
+        let mut entrophy_with_checksum =
+            BitVec::<u8, Msb0>::with_capacity(phrase_words.len() * BITS_GROUP_SIZE);
+        for (index, word) in phrase_words.iter().enumerate() {
+            let normalized = word.nfkd().collect::<String>();
+            let word_idx = word_indices.get(&normalized).ok_or_else(|| {
+                MnemonicValidationError::UnknownWord {
+                    index,
+                    word: word.to_string(),
+                    suggestions: self
+                        .suggestions(word)
+                        .into_iter()
+                        .map(String::from)
+                        .collect(),
+                }
+            })?;
+            for bit in (0..BITS_GROUP_SIZE).rev() {
+                entrophy_with_checksum.push((word_idx >> bit) & 1 == 1);
+            }
+        }
+        // every 32 bits of entrophy are followed by one bit of checksum
+        let checksum_len = entrophy_with_checksum.len() / 33;
+        let entrophy_len = entrophy_with_checksum.len() - checksum_len;
+        let (entrophy_bits, checksum_bits) = entrophy_with_checksum.split_at(entrophy_len);
+        let entrophy = entrophy_bits.to_bitvec().into_vec();
+        let hash = BitVec::<u8, Msb0>::from_vec(Sha256::digest(&entrophy).to_vec());
+        if hash[..checksum_len] == *checksum_bits {
+            Ok(entrophy)
+        } else {
+            Err(MnemonicValidationError::InvalidChecksum)
+        }
+    }
+
+    /// Words from the wordlist closest to the given (possibly mistyped) word,
+    /// ordered by edit distance
+    pub fn suggestions(&self, word: &str) -> Vec<&'static str> {
+        let normalized = word.nfkd().collect::<String>();
+        let mut candidates = WordList(self.lang)
+            .words()
+            .into_iter()
+            .map(|w| (edit_distance(&normalized, &w.nfkd().collect::<String>()), w))
+            .filter(|(distance, _)| *distance <= MnemonicValidator::MAX_SUGGESTION_DISTANCE)
+            .collect::<Vec<_>>();
+        candidates.sort_by_key(|(distance, _)| *distance);
+        candidates
+            .into_iter()
+            .take(MnemonicValidator::MAX_SUGGESTIONS)
+            .map(|(_, w)| w)
+            .collect()
+    }
+
+    fn word_indices(&self) -> HashMap<String, usize> {
+        WordList(self.lang)
+            .words()
+            .into_iter()
+            .enumerate()
+            .map(|(idx, w)| (w.nfkd().collect::<String>(), idx))
+            .collect()
+    }
+}
+
+/// Levenshtein distance between two strings (in chars)
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b_chars = b.chars().collect::<Vec<_>>();
+    let mut prev_row = (0..=b_chars.len()).collect::<Vec<_>>();
+    for (i, a_char) in a.chars().enumerate() {
+        let mut row = vec![i + 1; b_chars.len() + 1];
+        for (j, b_char) in b_chars.iter().enumerate() {
+            let substitution_cost = if a_char == *b_char { 0 } else { 1 };
+            row[j + 1] = (prev_row[j] + substitution_cost)
+                .min(prev_row[j + 1] + 1)
+                .min(row[j] + 1);
+        }
+        prev_row = row;
+    }
+    prev_row[b_chars.len()]
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::panic)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_valid_phrases() {
+        let validator = MnemonicValidator::new(Language::English);
+        // from https://github.com/trezor/python-mnemonic/blob/master/vectors.json
+        let cases = vec![
+            (
+                "00000000000000000000000000000000",
+                "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about",
+            ),
+            (
+                "7f7f7f7f7f7f7f7f7f7f7f7f7f7f7f7f",
+                "legal winner thank year wave sausage worth useful legal winner thank yellow",
+            ),
+            (
+                "f585c11aec520db57dd353c69554b21a89b20fb0650966fa0a9d6f74fd989d8f",
+                "void come effort suffer camp survey warrior heavy shoot primary clutch crush open amazing screen patrol group space point ten exist slush involve unfold",
+            ),
+        ];
+        for (entrophy, phrase) in cases {
+            assert_eq!(
+                base16::encode_lower(&validator.to_entrophy(phrase).unwrap()),
+                entrophy
+            );
+        }
+    }
+
+    #[test]
+    fn test_japanese_phrase() {
+        // from https://github.com/bip32JP/bip32JP.github.io/blob/master/test_JP_BIP39.json
+        let phrase = "あいこくしん　あいこくしん　あいこくしん　あいこくしん　あいこくしん　あいこくしん　あいこくしん　あいこくしん　あいこくしん　あいこくしん　あいこくしん　あおぞら";
+        let entrophy = MnemonicValidator::new(Language::Japanese)
+            .to_entrophy(phrase)
+            .unwrap();
+        assert_eq!(entrophy, vec![0u8; 16]);
+    }
+
+    #[test]
+    fn test_generated_roundtrip() {
+        let generator = MnemonicGenerator::new(Language::English, 256);
+        let entrophy =
+            base16::decode("68a79eaca2324873eacc50cb9c6eca8cc68ea5d936f98787c60c7ebc74e6ce7c")
+                .unwrap();
+        let phrase = generator.from_entrophy(entrophy.clone()).unwrap();
+        let validator = MnemonicValidator::new(Language::English);
+        assert_eq!(validator.to_entrophy(&phrase).unwrap(), entrophy);
+        assert_eq!(
+            validator.to_seed(&phrase, "").unwrap(),
+            Mnemonic::to_seed(&phrase, "")
+        );
+    }
+
+    #[test]
+    fn test_invalid_checksum() {
+        let phrase = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon";
+        assert_eq!(
+            MnemonicValidator::new(Language::English).validate(phrase),
+            Err(MnemonicValidationError::InvalidChecksum)
+        );
+    }
+
+    #[test]
+    fn test_invalid_word_count() {
+        let phrase =
+            "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+        assert_eq!(
+            MnemonicValidator::new(Language::English).validate(phrase),
+            Err(MnemonicValidationError::InvalidWordCount(11))
+        );
+    }
+
+    #[test]
+    fn test_unknown_word_suggestions() {
+        let phrase = "legal winner thank year wave sausage worth usefull legal winner thank yellow";
+        let err = MnemonicValidator::new(Language::English)
+            .validate(phrase)
+            .unwrap_err();
+        match err {
+            MnemonicValidationError::UnknownWord {
+                index,
+                word,
+                suggestions,
+            } => {
+                assert_eq!(index, 7);
+                assert_eq!(word, "usefull");
+                assert_eq!(suggestions[0], "useful");
+            }
+            e @ (MnemonicValidationError::InvalidWordCount(_)
+            | MnemonicValidationError::InvalidChecksum) => panic!("unexpected error: {:?}", e),
+        }
+    }
+
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(edit_distance("abandon", "abandon"), 0);
+        assert_eq!(edit_distance("abandn", "abandon"), 1);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        assert_eq!(edit_distance("", "abc"), 3);
+    }
+}
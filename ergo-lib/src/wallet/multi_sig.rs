@@ -1,4 +1,9 @@
 //! multi sig prove crate::chain::ergo_state_context::ErgoStateContext;
+
+pub mod partial_proof;
+pub mod secret_commitments;
+pub mod session;
+
 use crate::chain::ergo_state_context::ErgoStateContext;
 use crate::chain::transaction::unsigned::UnsignedTransaction;
 use crate::chain::transaction::Transaction;
@@ -24,7 +29,7 @@ use ergotree_interpreter::sigma_protocol::sig_serializer::parse_sig_compute_chal
 use ergotree_interpreter::sigma_protocol::unchecked_tree::UncheckedTree;
 use ergotree_interpreter::sigma_protocol::verifier::compute_commitments;
 use std::collections::HashMap;
-use std::rc::Rc;
+use std::sync::Arc;
 
 use super::tx_context::TransactionContextError;
 
@@ -127,6 +132,44 @@ impl TransactionHintsBag {
         let hints_bag: HintsBag = HintsBag { hints };
         hints_bag
     }
+
+    /// Hints that can be shared with other signers, i.e. without own commitments which hold
+    /// the secret randomness
+    pub fn without_own_commitments(&self) -> TransactionHintsBag {
+        let strip = |hints: &HashMap<usize, HintsBag>| {
+            hints
+                .iter()
+                .map(|(idx, bag)| {
+                    let hints = bag
+                        .hints
+                        .iter()
+                        .filter(|hint| {
+                            !matches!(hint, Hint::CommitmentHint(CommitmentHint::OwnCommitment(_)))
+                        })
+                        .cloned()
+                        .collect();
+                    (*idx, HintsBag { hints })
+                })
+                .collect()
+        };
+        TransactionHintsBag {
+            secret_hints: strip(&self.secret_hints),
+            public_hints: strip(&self.public_hints),
+        }
+    }
+
+    /// Indices of the inputs having hints
+    pub(crate) fn input_indices(&self) -> Vec<usize> {
+        let mut indices: Vec<usize> = self
+            .secret_hints
+            .keys()
+            .chain(self.public_hints.keys())
+            .copied()
+            .collect();
+        indices.sort_unstable();
+        indices.dedup();
+        indices
+    }
 }
 
 /// A method which is extracting partial proofs of secret knowledge for particular secrets with their
@@ -250,7 +293,7 @@ pub fn generate_commitments(
         let input_box = tx_context
             .get_input_box(&input.box_id)
             .ok_or(TransactionContextError::InputBoxNotFound(i))?;
-        let ctx = Rc::new(make_context(state_context, &tx_context, i)?);
+        let ctx = Arc::new(make_context(state_context, &tx_context, i)?);
         let tree = input_box.ergo_tree.clone();
         let exp = tree
             .proposition()
@@ -278,7 +321,7 @@ pub fn extract_hints(
         let input_box = tx_ctx
             .get_input_box(&input.box_id)
             .ok_or(TransactionContextError::InputBoxNotFound(i))?;
-        let ctx = Rc::new(make_context(state_context, tx_ctx, i)?);
+        let ctx = Arc::new(make_context(state_context, tx_ctx, i)?);
         let tree = input_box.ergo_tree.clone();
         let exp = tree
             .proposition()
@@ -383,6 +426,36 @@ pub fn generate_commitments_for(
     bag
 }
 
+/// Proof-of-knowledge leaves (public images) of the sigma tree with their positions
+pub(crate) fn proof_of_knowledge_leaves(
+    sigma_tree: &SigmaBoolean,
+) -> Vec<(NodePosition, SigmaBoolean)> {
+    fn traverse_node(
+        sb: &SigmaBoolean,
+        position: NodePosition,
+        leaves: &mut Vec<(NodePosition, SigmaBoolean)>,
+    ) {
+        match sb {
+            SigmaBoolean::SigmaConjecture(sc) => {
+                let children = match sc {
+                    SigmaConjecture::Cand(c_and) => &c_and.items,
+                    SigmaConjecture::Cor(cor) => &cor.items,
+                    SigmaConjecture::Cthreshold(c_threshold) => &c_threshold.children,
+                };
+                children
+                    .iter()
+                    .enumerate()
+                    .for_each(|(i, x)| traverse_node(x, position.child(i), leaves));
+            }
+            SigmaBoolean::ProofOfKnowledge(_) => leaves.push((position, sb.clone())),
+            SigmaBoolean::TrivialProp(_) => (),
+        }
+    }
+    let mut leaves = Vec::new();
+    traverse_node(sigma_tree, NodePosition::crypto_tree_prefix(), &mut leaves);
+    leaves
+}
+
 #[allow(clippy::unwrap_used)]
 #[cfg(test)]
 mod tests {
@@ -414,7 +487,7 @@ mod tests {
     use ergotree_ir::types::stype::SType;
     use sigma_test_util::force_any_val;
     use std::convert::{TryFrom, TryInto};
-    use std::rc::Rc;
+    use std::sync::Arc;
 
     #[test]
     fn extract_hint() {
@@ -466,7 +539,7 @@ mod tests {
         let bytes_m = Base16DecodedBytes::try_from("100208cd03c847c306a2f9a8087b4ae63261cc5acea9034000ba8d033b0fb033247e8aade908cd02f4b05f44eb9703db7fcf9c94b89566787a7188c7e48964821d485d9ef2f9e4c4ea0273007301").unwrap();
         let tree_m: ErgoTree = ErgoTree::sigma_parse_bytes(&bytes_m.0).unwrap();
 
-        let contx = Rc::new(force_any_val::<Context>());
+        let contx = Arc::new(force_any_val::<Context>());
         let exp = tree_m.proposition().unwrap();
         let reduction_result = reduce_to_crypto(&exp, &Env::empty(), contx).unwrap();
         let sigma_tree = reduction_result.sigma_prop;
@@ -656,7 +729,7 @@ mod tests {
 
     #[test]
     fn multi_sig_2() {
-        let ctx = Rc::new(force_any_val::<Context>());
+        let ctx = Arc::new(force_any_val::<Context>());
 
         let secret1 = DlogProverInput::random();
         let secret2 = DlogProverInput::random();
@@ -740,7 +813,7 @@ mod tests {
 
     #[test]
     fn multi_sig_and_3() {
-        let ctx = Rc::new(force_any_val::<Context>());
+        let ctx = Arc::new(force_any_val::<Context>());
 
         let secret1 = DlogProverInput::random();
         let secret2 = DlogProverInput::random();
@@ -904,7 +977,7 @@ mod tests {
 
     #[test]
     fn multi_dlog_dht() {
-        let ctx = Rc::new(force_any_val::<Context>());
+        let ctx = Arc::new(force_any_val::<Context>());
 
         let secret_alice = DlogProverInput::random();
         let secret_bob = DlogProverInput::random();
@@ -922,10 +995,12 @@ mod tests {
             secrets: vec![PrivateInput::DlogProverInput(secret_bob)],
         };
         let _prover_c = TestProver {
-            secrets: vec![PrivateInput::DhTupleProverInput(secret_carol.clone())],
+            secrets: vec![PrivateInput::DhTupleProverInput(
+                secret_carol.clone_secret(),
+            )],
         };
         let _prover_d = TestProver {
-            secrets: vec![PrivateInput::DhTupleProverInput(secret_dave.clone())],
+            secrets: vec![PrivateInput::DhTupleProverInput(secret_dave.clone_secret())],
         };
         let first_expr: Expr = SigmaOr::new(vec![
             Expr::Const(pk_alice.clone().into()),
@@ -1027,7 +1102,7 @@ mod tests {
     #[test]
     fn multi_sig_atleast_2_out_of_3() {
         // from https://github.com/ScorexFoundation/sigmastate-interpreter/blob/78dd1e715038c2f95c518fb56977c6591b76e20c/sc/src/test/scala/sigmastate/utxo/DistributedSigSpecification.scala#L124
-        let ctx = Rc::new(force_any_val::<Context>());
+        let ctx = Arc::new(force_any_val::<Context>());
 
         let alice_secret = DlogProverInput::random();
         let bob_secret = DlogProverInput::random();
@@ -1146,7 +1221,7 @@ mod tests {
     fn multi_sig_atleast_3_out_of_4() {
         // from https://github.com/ScorexFoundation/sigmastate-interpreter/blob/78dd1e715038c2f95c518fb56977c6591b76e20c/sc/src/test/scala/sigmastate/utxo/DistributedSigSpecification.scala#L160-L205
 
-        let ctx = Rc::new(force_any_val::<Context>());
+        let ctx = Arc::new(force_any_val::<Context>());
 
         let alice_secret = DlogProverInput::random();
         let bob_secret = DlogProverInput::random();
@@ -1320,7 +1395,7 @@ mod tests {
     fn multi_sig_atleast_7_out_of_10_i692() {
         // based on
         // https://github.com/ScorexFoundation/sigmastate-interpreter/blob/78dd1e715038c2f95c518fb56977c6591b76e20c/sc/src/test/scala/sigmastate/utxo/DistributedSigSpecification.scala#L299-L389
-        let ctx = Rc::new(force_any_val::<Context>());
+        let ctx = Arc::new(force_any_val::<Context>());
 
         let sk1 = DlogProverInput::random();
         let pk1 = sk1.public_image();
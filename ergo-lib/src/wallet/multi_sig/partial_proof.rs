@@ -0,0 +1,228 @@
+//! Verification of partial proofs sent by co-signers
+
+use ergotree_interpreter::sigma_protocol::dht_protocol::interactive_prover as dht_interactive_prover;
+use ergotree_interpreter::sigma_protocol::dht_protocol::FirstDhTupleProverMessage;
+use ergotree_interpreter::sigma_protocol::dlog_protocol::interactive_prover as dlog_interactive_prover;
+use ergotree_interpreter::sigma_protocol::dlog_protocol::FirstDlogProverMessage;
+use ergotree_interpreter::sigma_protocol::prover::hint::HintsBag;
+use ergotree_interpreter::sigma_protocol::prover::hint::RealCommitment;
+use ergotree_interpreter::sigma_protocol::prover::hint::RealSecretProof;
+use ergotree_interpreter::sigma_protocol::unchecked_tree::UncheckedLeaf;
+use ergotree_interpreter::sigma_protocol::unchecked_tree::UncheckedTree;
+use ergotree_interpreter::sigma_protocol::unproven_tree::NodePosition;
+use ergotree_interpreter::sigma_protocol::FirstProverMessage;
+use ergotree_ir::sigma_protocol::sigma_boolean::SigmaBoolean;
+use thiserror::Error;
+
+use super::proof_of_knowledge_leaves;
+
+/// Partial proof verification errors. Each variant holds the public image of the co-signer
+/// that sent the proof.
+#[derive(Error, PartialEq, Eq, Debug, Clone)]
+pub enum PartialProofError {
+    /// Public image is not in the sigma tree at the given position
+    #[error("{image:?} is not in the sigma tree at position {position:?}")]
+    ImageNotInTree {
+        /// Public image of the proof
+        image: SigmaBoolean,
+        /// Position of the proof
+        position: NodePosition,
+    },
+    /// No real commitment for the public image at the given position
+    #[error("no commitment of {image:?} at position {position:?}")]
+    CommitmentNotFound {
+        /// Public image of the proof
+        image: SigmaBoolean,
+        /// Position of the proof
+        position: NodePosition,
+    },
+    /// Proof is not a leaf proving the public image with the hint's challenge
+    #[error("malformed proof of {image:?} at position {position:?}")]
+    MalformedProof {
+        /// Public image of the proof
+        image: SigmaBoolean,
+        /// Position of the proof
+        position: NodePosition,
+    },
+    /// Response does not match the commitment and the challenge
+    #[error("invalid proof of {image:?} at position {position:?}")]
+    InvalidResponse {
+        /// Public image of the proof
+        image: SigmaBoolean,
+        /// Position of the proof
+        position: NodePosition,
+    },
+}
+
+impl PartialProofError {
+    /// Public image of the co-signer that sent the faulty proof
+    pub fn image(&self) -> &SigmaBoolean {
+        match self {
+            PartialProofError::ImageNotInTree { image, .. }
+            | PartialProofError::CommitmentNotFound { image, .. }
+            | PartialProofError::MalformedProof { image, .. }
+            | PartialProofError::InvalidResponse { image, .. } => image,
+        }
+    }
+}
+
+/// Check that the proof is a valid proof of knowledge of the secret for the public image at
+/// its position in the sigma tree, i.e. that the response matches the commitment shared by the
+/// co-signer in the commitment round and the challenge.
+pub fn verify_real_secret_proof(
+    sigma_tree: &SigmaBoolean,
+    proof: &RealSecretProof,
+    commitment: &RealCommitment,
+) -> Result<(), PartialProofError> {
+    let image = proof.image.clone();
+    let position = proof.position.clone();
+    if !proof_of_knowledge_leaves(sigma_tree).contains(&(position.clone(), image.clone())) {
+        return Err(PartialProofError::ImageNotInTree { image, position });
+    }
+    if commitment.image != image || commitment.position != position {
+        return Err(PartialProofError::CommitmentNotFound { image, position });
+    }
+    let computed: FirstProverMessage = match &proof.unchecked_tree {
+        UncheckedTree::UncheckedLeaf(UncheckedLeaf::UncheckedSchnorr(us))
+            if SigmaBoolean::from(us.proposition.clone()) == image
+                && us.challenge == proof.challenge =>
+        {
+            FirstDlogProverMessage::from(dlog_interactive_prover::compute_commitment(
+                &us.proposition,
+                &us.challenge,
+                &us.second_message,
+            ))
+            .into()
+        }
+        UncheckedTree::UncheckedLeaf(UncheckedLeaf::UncheckedDhTuple(dh))
+            if SigmaBoolean::from(dh.proposition.clone()) == image
+                && dh.challenge == proof.challenge =>
+        {
+            let (a, b) = dht_interactive_prover::compute_commitment(
+                &dh.proposition,
+                &dh.challenge,
+                &dh.second_message,
+            );
+            FirstDhTupleProverMessage::new(a, b).into()
+        }
+        UncheckedTree::UncheckedLeaf(_) | UncheckedTree::UncheckedConjecture(_) => {
+            return Err(PartialProofError::MalformedProof { image, position })
+        }
+    };
+    if computed == commitment.commitment {
+        Ok(())
+    } else {
+        Err(PartialProofError::InvalidResponse { image, position })
+    }
+}
+
+/// Verify every real proof in `proofs` against the real commitment at the same position in
+/// `commitments` (see [`verify_real_secret_proof`])
+pub fn verify_real_secret_proofs(
+    sigma_tree: &SigmaBoolean,
+    commitments: &HintsBag,
+    proofs: &HintsBag,
+) -> Result<(), PartialProofError> {
+    let real_commitments = commitments.real_commitments();
+    proofs.real_proofs().iter().try_for_each(|proof| {
+        let commitment = real_commitments
+            .iter()
+            .find(|c| c.position == proof.position && c.image == proof.image)
+            .ok_or_else(|| PartialProofError::CommitmentNotFound {
+                image: proof.image.clone(),
+                position: proof.position.clone(),
+            })?;
+        verify_real_secret_proof(sigma_tree, proof, commitment)
+    })
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use crate::ergotree_interpreter::sigma_protocol::private_input::DlogProverInput;
+    use crate::ergotree_interpreter::sigma_protocol::prover::Prover;
+    use crate::ergotree_interpreter::sigma_protocol::prover::TestProver;
+    use crate::ergotree_ir::sigma_protocol::sigma_boolean::cand::Cand;
+    use crate::wallet::multi_sig::bag_for_multi_sig;
+    use crate::wallet::multi_sig::generate_commitments_for;
+    use std::convert::TryInto;
+
+    /// Alice's partial proof of `alice && bob` with commitments of both
+    fn alice_partial_proof() -> (SigmaBoolean, HintsBag, HintsBag) {
+        let alice_secret = DlogProverInput::random();
+        let alice_pk: SigmaBoolean = alice_secret.public_image().into();
+        let bob_pk: SigmaBoolean = DlogProverInput::random().public_image().into();
+        let sigma_tree =
+            Cand::normalized(vec![alice_pk.clone(), bob_pk.clone()].try_into().unwrap());
+        let alice_hints = generate_commitments_for(&sigma_tree, &[alice_pk.clone()]);
+        let bob_hints = generate_commitments_for(&sigma_tree, &[bob_pk]);
+        let mut bag = HintsBag::from_bags(vec![alice_hints.clone(), bob_hints.clone()]);
+        bag.hints.retain(|h| {
+            !bob_hints
+                .own_commitments()
+                .iter()
+                .any(|c| h == &c.clone().into())
+        });
+        let alice_prover = TestProver {
+            secrets: vec![alice_secret.into()],
+        };
+        let proof = alice_prover
+            .generate_proof(sigma_tree.clone(), &[0u8; 100], &bag)
+            .unwrap();
+        let proofs = bag_for_multi_sig(&sigma_tree, &[alice_pk], &[], proof.as_ref()).unwrap();
+        let commitments = HintsBag {
+            hints: alice_hints
+                .real_commitments()
+                .into_iter()
+                .map(|c| c.into())
+                .collect(),
+        };
+        (sigma_tree, commitments, proofs)
+    }
+
+    #[test]
+    fn valid_partial_proof() {
+        let (sigma_tree, commitments, proofs) = alice_partial_proof();
+        assert_eq!(proofs.real_proofs().len(), 1);
+        assert_eq!(
+            verify_real_secret_proofs(&sigma_tree, &commitments, &proofs),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn proof_for_other_commitment() {
+        let (sigma_tree, _, proofs) = alice_partial_proof();
+        let proof = proofs.real_proofs().first().unwrap().clone();
+        // commitment of another signing round
+        let other_commitment = generate_commitments_for(&sigma_tree, &[proof.image.clone()])
+            .real_commitments()
+            .first()
+            .unwrap()
+            .clone();
+        assert_eq!(
+            verify_real_secret_proof(&sigma_tree, &proof, &other_commitment),
+            Err(PartialProofError::InvalidResponse {
+                image: proof.image.clone(),
+                position: proof.position.clone(),
+            })
+        );
+    }
+
+    #[test]
+    fn proof_at_wrong_position() {
+        let (sigma_tree, commitments, proofs) = alice_partial_proof();
+        let mut proof = proofs.real_proofs().first().unwrap().clone();
+        proof.position = proof.position.child(0);
+        let err = verify_real_secret_proofs(
+            &sigma_tree,
+            &commitments,
+            &HintsBag {
+                hints: vec![proof.clone().into()],
+            },
+        )
+        .unwrap_err();
+        assert_eq!(err.image(), &proof.image);
+    }
+}
@@ -0,0 +1,255 @@
+//! Two-phase signing with the secret randomness of the commitments kept apart from the public
+//! commitments
+//!
+//! [`crate::wallet::Wallet::generate_commitments`] returns the secret randomness
+//! ([`OwnCommitment`]) in the same [`TransactionHintsBag`] as the public commitments, and
+//! publishing the randomness leaks the secret key. [`SecretCommitments`] holds the randomness in a
+//! handle that can't be cloned or serialized, exports only the public commitments (see
+//! [`SecretCommitments::public_commitments`]) and is consumed by signing
+//! (see [`crate::wallet::Wallet::sign_reduced_transaction_with_commitments`]), so the same
+//! randomness can't be used for two signatures.
+
+use std::fmt::Debug;
+use std::fmt::Formatter;
+
+use ergotree_interpreter::sigma_protocol::prover::hint::CommitmentHint;
+use ergotree_interpreter::sigma_protocol::prover::hint::Hint;
+use ergotree_interpreter::sigma_protocol::prover::hint::HintsBag;
+use ergotree_ir::chain::tx_id::TxId;
+use zeroize::Zeroize;
+
+use super::TransactionHintsBag;
+
+/// Commitments generated by the wallet for a transaction, holding the secret randomness.
+/// Consumed by signing.
+pub struct SecretCommitments {
+    tx_id: TxId,
+    hints: TransactionHintsBag,
+}
+
+impl SecretCommitments {
+    pub(crate) fn new(tx_id: TxId, hints: TransactionHintsBag) -> Self {
+        SecretCommitments { tx_id, hints }
+    }
+
+    /// Id of the transaction the commitments were generated for
+    pub fn tx_id(&self) -> TxId {
+        self.tx_id
+    }
+
+    /// Commitments to share with the co-signers (without the secret randomness)
+    pub fn public_commitments(&self) -> TransactionHintsBag {
+        self.hints.without_own_commitments()
+    }
+
+    /// Hints to sign with: own commitments along with the hints received from the co-signers
+    /// (or the coordinator). Received hints for the positions of the own commitments are dropped,
+    /// so the proofs of the wallet's keys are always computed with its own commitments.
+    pub(crate) fn into_signing_hints(
+        mut self,
+        co_signers_hints: &TransactionHintsBag,
+    ) -> TransactionHintsBag {
+        let own_hints = std::mem::replace(&mut self.hints, TransactionHintsBag::empty());
+        let mut indices = own_hints.input_indices();
+        indices.extend(co_signers_hints.input_indices());
+        indices.sort_unstable();
+        indices.dedup();
+        let mut res = TransactionHintsBag::empty();
+        for idx in indices {
+            let own_commitments = own_hints.all_hints_for_input(idx).own_commitments();
+            let mut hints: Vec<Hint> = co_signers_hints
+                .all_hints_for_input(idx)
+                .hints
+                .into_iter()
+                .filter(|hint| match hint {
+                    Hint::CommitmentHint(CommitmentHint::OwnCommitment(_)) => false,
+                    Hint::CommitmentHint(c) => !own_commitments
+                        .iter()
+                        .any(|own| &own.position == c.position()),
+                    Hint::SecretProven(_) => true,
+                })
+                .collect();
+            hints.extend(own_commitments.into_iter().map(Hint::from));
+            res.add_hints_for_input(idx, HintsBag { hints });
+        }
+        res
+    }
+}
+
+impl Debug for SecretCommitments {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        // to avoid leaking it in error messages, logs, etc.
+        write!(f, "SecretCommitments({:?}, ***)", self.tx_id)
+    }
+}
+
+impl Drop for SecretCommitments {
+    fn drop(&mut self) {
+        zeroize_own_commitments(&mut self.hints);
+    }
+}
+
+/// Overwrite the secret randomness of own commitments in the hints
+pub(crate) fn zeroize_own_commitments(hints: &mut TransactionHintsBag) {
+    hints
+        .secret_hints
+        .values_mut()
+        .chain(hints.public_hints.values_mut())
+        .flat_map(|bag| bag.hints.iter_mut())
+        .for_each(|hint| {
+            if let Hint::CommitmentHint(CommitmentHint::OwnCommitment(c)) = hint {
+                c.secret_randomness.zeroize();
+            }
+        });
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use crate::chain::ergo_box::box_builder::ErgoBoxCandidateBuilder;
+    use crate::chain::ergo_state_context::ErgoStateContext;
+    use crate::chain::transaction::reduced::reduce_tx;
+    use crate::chain::transaction::reduced::ReducedTransaction;
+    use crate::chain::transaction::unsigned::UnsignedTransaction;
+    use crate::chain::transaction::UnsignedInput;
+    use crate::ergotree_interpreter::sigma_protocol::private_input::PrivateInput;
+    use crate::ergotree_interpreter::sigma_protocol::verifier::verify_signature;
+    use crate::ergotree_ir::chain::ergo_box::box_value::BoxValue;
+    use crate::ergotree_ir::chain::ergo_box::ErgoBox;
+    use crate::ergotree_ir::chain::ergo_box::NonMandatoryRegisters;
+    use crate::ergotree_ir::ergo_tree::ErgoTree;
+    use crate::ergotree_ir::mir::expr::Expr;
+    use crate::ergotree_ir::mir::sigma_and::SigmaAnd;
+    use crate::ergotree_ir::sigma_protocol::sigma_boolean::SigmaBoolean;
+    use crate::ergotree_ir::sigma_protocol::sigma_boolean::SigmaProp;
+    use crate::wallet::multi_sig::session::SigningSession;
+    use crate::wallet::secret_key::SecretKey;
+    use crate::wallet::signing::TransactionContext;
+    use crate::wallet::Wallet;
+    use sigma_test_util::force_any_val;
+    use std::convert::TryFrom;
+
+    /// Transaction spending a box guarded by `alice && bob`
+    fn reduced_2_of_2_tx(alice: &SigmaBoolean, bob: &SigmaBoolean) -> ReducedTransaction {
+        let expr: Expr = SigmaAnd::new(vec![
+            Expr::Const(SigmaProp::new(alice.clone()).into()),
+            Expr::Const(SigmaProp::new(bob.clone()).into()),
+        ])
+        .unwrap()
+        .into();
+        let tree = ErgoTree::try_from(expr).unwrap();
+        let input_box = ErgoBox::new(
+            BoxValue::SAFE_USER_MIN,
+            tree.clone(),
+            None,
+            NonMandatoryRegisters::empty(),
+            0,
+            TxId::zero(),
+            0,
+        )
+        .unwrap();
+        let candidate = ErgoBoxCandidateBuilder::new(BoxValue::SAFE_USER_MIN, tree, 0)
+            .build()
+            .unwrap();
+        let tx = UnsignedTransaction::new_from_vec(
+            vec![UnsignedInput::from(input_box.clone())],
+            vec![],
+            vec![candidate],
+        )
+        .unwrap();
+        let tx_context: TransactionContext<UnsignedTransaction> =
+            TransactionContext::new(tx, vec![input_box], vec![]).unwrap();
+        reduce_tx(tx_context, &force_any_val::<ErgoStateContext>()).unwrap()
+    }
+
+    fn wallet_and_key() -> (Wallet, SigmaBoolean) {
+        let secret = SecretKey::random_dlog();
+        let key = PrivateInput::from(secret.clone_secret()).public_image();
+        (Wallet::from_secrets(vec![secret]), key)
+    }
+
+    #[test]
+    fn public_commitments_have_no_secrets() {
+        let (alice, alice_key) = wallet_and_key();
+        let (_, bob_key) = wallet_and_key();
+        let reduced_tx = reduced_2_of_2_tx(&alice_key, &bob_key);
+        let commitments = alice
+            .generate_secret_commitments_for_reduced_transaction(reduced_tx.clone())
+            .unwrap();
+        assert_eq!(commitments.tx_id(), reduced_tx.unsigned_tx.id());
+        let public = commitments.public_commitments().all_hints_for_input(0);
+        assert!(public.own_commitments().is_empty());
+        assert_eq!(public.real_commitments().len(), 1);
+        assert_eq!(
+            format!("{:?}", commitments),
+            format!("SecretCommitments({:?}, ***)", reduced_tx.unsigned_tx.id())
+        );
+    }
+
+    #[test]
+    fn two_phase_signing_session() {
+        let (alice, alice_key) = wallet_and_key();
+        let (bob, bob_key) = wallet_and_key();
+        let reduced_tx = reduced_2_of_2_tx(&alice_key, &bob_key);
+        let mut session =
+            SigningSession::new(reduced_tx.clone(), vec![alice_key.clone(), bob_key.clone()])
+                .unwrap();
+        let alice_commitments = alice
+            .generate_secret_commitments_for_reduced_transaction(reduced_tx.clone())
+            .unwrap();
+        let bob_commitments = bob
+            .generate_secret_commitments_for_reduced_transaction(reduced_tx.clone())
+            .unwrap();
+        session
+            .add_commitments(&alice_key, &alice_commitments.public_commitments())
+            .unwrap();
+        session
+            .add_commitments(&bob_key, &bob_commitments.public_commitments())
+            .unwrap();
+        for (wallet, key, commitments) in [
+            (alice, alice_key, alice_commitments),
+            (bob, bob_key, bob_commitments),
+        ] {
+            // the coordinator's hints are used as is, own commitments come from the handle
+            let tx = wallet
+                .sign_reduced_transaction_with_commitments(
+                    reduced_tx.clone(),
+                    commitments,
+                    session.hints(),
+                )
+                .unwrap();
+            session.add_partial_signature(&key, &tx).unwrap();
+        }
+        let tx = session.transaction().unwrap();
+        let input = reduced_tx.reduced_inputs().first().clone();
+        assert!(verify_signature(
+            input.reduction_result.sigma_prop,
+            &tx.bytes_to_sign().unwrap(),
+            tx.inputs.first().spending_proof.proof.as_ref()
+        )
+        .unwrap());
+    }
+
+    #[test]
+    fn rejects_commitments_for_other_tx() {
+        let (alice, alice_key) = wallet_and_key();
+        let (_, bob_key) = wallet_and_key();
+        let (_, carol_key) = wallet_and_key();
+        let reduced_tx = reduced_2_of_2_tx(&alice_key, &bob_key);
+        let other_tx = reduced_2_of_2_tx(&alice_key, &carol_key);
+        let commitments = alice
+            .generate_secret_commitments_for_reduced_transaction(other_tx.clone())
+            .unwrap();
+        let res = alice.sign_reduced_transaction_with_commitments(
+            reduced_tx.clone(),
+            commitments,
+            &TransactionHintsBag::empty(),
+        );
+        assert!(matches!(
+            res,
+            Err(crate::wallet::WalletError::CommitmentsTxMismatch { expected, actual })
+                if expected == reduced_tx.unsigned_tx.id() && actual == other_tx.unsigned_tx.id()
+        ));
+    }
+}
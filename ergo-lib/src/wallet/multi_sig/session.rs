@@ -0,0 +1,717 @@
+//! Multi-party signing session for a reduced transaction
+//!
+//! Signing with several co-signers takes two rounds:
+//! 1. Every signer generates commitments for its keys (see
+//! [`crate::wallet::Wallet::generate_commitments_for_reduced_transaction`]) and shares them
+//! (without own commitments, see [`TransactionHintsBag::without_own_commitments`]) via
+//! [`SigningSession::add_commitments`]. To keep the secret randomness out of the shared hints
+//! altogether use [`super::secret_commitments::SecretCommitments`] and sign with
+//! [`crate::wallet::Wallet::sign_reduced_transaction_with_commitments`] and [`SigningSession::hints`].
+//! 2. Signers sign one after another using the hints from [`SigningSession::hints_for_signer`]
+//! and submit the partially signed transaction via [`SigningSession::add_partial_signature`].
+//! Branches of the keys that do not take part in the signing are simulated by the first signer
+//! and reused by the rest. The transaction signed by the last signer is the final one.
+//!
+//! The session state can be serialized and passed between the parties over any transport.
+
+use ergotree_interpreter::sigma_protocol::prover::hint::CommitmentHint;
+use ergotree_interpreter::sigma_protocol::prover::hint::Hint;
+use ergotree_interpreter::sigma_protocol::prover::hint::HintsBag;
+use ergotree_interpreter::sigma_protocol::prover::hint::SecretProven;
+use ergotree_interpreter::sigma_protocol::sig_serializer::SigParsingError;
+use ergotree_interpreter::sigma_protocol::unproven_tree::NodePosition;
+use ergotree_interpreter::sigma_protocol::verifier::verify_signature;
+use ergotree_interpreter::sigma_protocol::FirstProverMessage;
+use ergotree_ir::serialization::SigmaSerializationError;
+use ergotree_ir::sigma_protocol::sigma_boolean::SigmaBoolean;
+use ergotree_ir::sigma_protocol::sigma_boolean::SigmaProofOfKnowledgeTree;
+use thiserror::Error;
+
+use crate::chain::transaction::reduced::ReducedTransaction;
+use crate::chain::transaction::Transaction;
+
+use super::bag_for_multi_sig;
+use super::partial_proof::verify_real_secret_proofs;
+use super::partial_proof::PartialProofError;
+use super::proof_of_knowledge_leaves;
+use super::TransactionHintsBag;
+
+/// Phase of the signing session
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum SessionPhase {
+    /// Waiting for commitments from the signers
+    Commitment,
+    /// Waiting for partial signatures from the signers
+    Signing,
+    /// Transaction is signed by all the signers
+    Complete,
+}
+
+/// Signing session errors
+#[derive(Error, PartialEq, Debug, Clone)]
+pub enum SigningSessionError {
+    /// No signers given
+    #[error("no signers")]
+    NoSigners,
+    /// Signer is given more than once
+    #[error("duplicate signer {0:?}")]
+    DuplicateSigner(SigmaBoolean),
+    /// Signer is not a signer of this session
+    #[error("unknown signer {0:?}")]
+    UnknownSigner(SigmaBoolean),
+    /// Operation is not allowed in the current phase
+    #[error("expected {expected:?} phase, got {actual:?}")]
+    UnexpectedPhase {
+        /// Phase the operation is allowed in
+        expected: SessionPhase,
+        /// Current phase
+        actual: SessionPhase,
+    },
+    /// Signer has already sent commitments
+    #[error("signer {0:?} has already committed")]
+    AlreadyCommitted(SigmaBoolean),
+    /// Signer has already sent a partial signature
+    #[error("signer {0:?} has already signed")]
+    AlreadySigned(SigmaBoolean),
+    /// Own commitment holding the secret randomness was shared
+    #[error("own commitment with secret randomness for input {0} must not be shared")]
+    SecretHintLeak(usize),
+    /// Hint does not belong to the signer or does not match the input's sigma tree
+    #[error("invalid hint for input {input}: {reason}")]
+    InvalidHint {
+        /// Input index
+        input: usize,
+        /// Reason
+        reason: String,
+    },
+    /// Expected hint is missing
+    #[error("missing hint for input {input} at position {position:?}")]
+    MissingHint {
+        /// Input index
+        input: usize,
+        /// Position of the signer's key in the input's sigma tree
+        position: NodePosition,
+    },
+    /// Transaction is not the one being signed in the session
+    #[error("transaction does not match the session's transaction")]
+    TransactionMismatch,
+    /// Proof of the final transaction is invalid
+    #[error("invalid proof for input {0}")]
+    InvalidProof(usize),
+    /// Partial proof of a co-signer does not match its commitment
+    #[error("invalid partial proof for input {input}: {error}")]
+    InvalidPartialProof {
+        /// Input index
+        input: usize,
+        /// Verification error, identifying the co-signer
+        error: PartialProofError,
+    },
+    /// Failed to parse a partial signature
+    #[error("SigParsingError: {0}")]
+    SigParsingError(#[from] SigParsingError),
+    /// Failed to serialize the transaction
+    #[error("SigmaSerializationError: {0}")]
+    SigmaSerializationError(#[from] SigmaSerializationError),
+}
+
+/// State of an N-of-M signing round for a reduced transaction
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "json",
+    serde(
+        try_from = "crate::chain::json::multi_sig::SigningSessionJson",
+        into = "crate::chain::json::multi_sig::SigningSessionJson"
+    )
+)]
+#[derive(PartialEq, Debug, Clone)]
+pub struct SigningSession {
+    pub(crate) reduced_tx: ReducedTransaction,
+    pub(crate) signers: Vec<SigmaBoolean>,
+    pub(crate) committed: Vec<SigmaBoolean>,
+    pub(crate) signed: Vec<SigmaBoolean>,
+    /// commitments, real proofs of the signers and simulated proofs of the rest of the keys
+    pub(crate) hints: TransactionHintsBag,
+    pub(crate) transaction: Option<Transaction>,
+}
+
+impl SigningSession {
+    /// Start a session for the given signers (public keys that are going to sign).
+    /// Signers should be the minimal set of keys satisfying the inputs' propositions, the rest
+    /// of the keys are simulated.
+    pub fn new(
+        reduced_tx: ReducedTransaction,
+        signers: Vec<SigmaBoolean>,
+    ) -> Result<Self, SigningSessionError> {
+        if signers.is_empty() {
+            return Err(SigningSessionError::NoSigners);
+        }
+        let participants = participants(&reduced_tx);
+        for (i, signer) in signers.iter().enumerate() {
+            if signers[..i].contains(signer) {
+                return Err(SigningSessionError::DuplicateSigner(signer.clone()));
+            }
+            if !participants.contains(signer) {
+                return Err(SigningSessionError::UnknownSigner(signer.clone()));
+            }
+        }
+        Ok(SigningSession {
+            reduced_tx,
+            signers,
+            committed: Vec::new(),
+            signed: Vec::new(),
+            hints: TransactionHintsBag::empty(),
+            transaction: None,
+        })
+    }
+
+    /// Transaction being signed
+    pub fn reduced_tx(&self) -> &ReducedTransaction {
+        &self.reduced_tx
+    }
+
+    /// Public keys that are going to sign
+    pub fn signers(&self) -> &[SigmaBoolean] {
+        &self.signers
+    }
+
+    /// All public keys in the inputs' propositions
+    pub fn participants(&self) -> Vec<SigmaBoolean> {
+        participants(&self.reduced_tx)
+    }
+
+    /// Public keys which branches are simulated (do not take part in the signing)
+    pub fn simulated(&self) -> Vec<SigmaBoolean> {
+        self.participants()
+            .into_iter()
+            .filter(|p| !self.signers.contains(p))
+            .collect()
+    }
+
+    /// Current phase
+    pub fn phase(&self) -> SessionPhase {
+        if self.committed.len() < self.signers.len() {
+            SessionPhase::Commitment
+        } else if self.transaction.is_none() {
+            SessionPhase::Signing
+        } else {
+            SessionPhase::Complete
+        }
+    }
+
+    /// Signers that have not sent commitments yet
+    pub fn pending_commitments(&self) -> Vec<SigmaBoolean> {
+        self.signers
+            .iter()
+            .filter(|s| !self.committed.contains(s))
+            .cloned()
+            .collect()
+    }
+
+    /// Signers that have not sent partial signatures yet
+    pub fn pending_signatures(&self) -> Vec<SigmaBoolean> {
+        self.signers
+            .iter()
+            .filter(|s| !self.signed.contains(s))
+            .cloned()
+            .collect()
+    }
+
+    /// Hints collected so far (commitments, real proofs of the signers and simulated proofs)
+    pub fn hints(&self) -> &TransactionHintsBag {
+        &self.hints
+    }
+
+    /// Transaction signed by all the signers (when the session is complete)
+    pub fn transaction(&self) -> Option<&Transaction> {
+        self.transaction.as_ref()
+    }
+
+    /// Add commitments of the signer. Only real commitments for the signer's key at its
+    /// positions in the inputs' propositions are accepted, and there must be one for every position.
+    pub fn add_commitments(
+        &mut self,
+        signer: &SigmaBoolean,
+        commitments: &TransactionHintsBag,
+    ) -> Result<(), SigningSessionError> {
+        self.check_phase(SessionPhase::Commitment)?;
+        self.check_signer(signer)?;
+        if self.committed.contains(signer) {
+            return Err(SigningSessionError::AlreadyCommitted(signer.clone()));
+        }
+        let inputs = self.reduced_tx.reduced_inputs();
+        if let Some(idx) = commitments
+            .input_indices()
+            .into_iter()
+            .find(|idx| *idx >= inputs.len())
+        {
+            return Err(SigningSessionError::InvalidHint {
+                input: idx,
+                reason: "no such input".to_string(),
+            });
+        }
+        let mut accepted = Vec::new();
+        for (idx, input) in inputs.iter().enumerate() {
+            let positions = signer_positions(&input.reduction_result.sigma_prop, signer);
+            let bag = commitments.all_hints_for_input(idx);
+            for hint in &bag.hints {
+                match hint {
+                    Hint::CommitmentHint(CommitmentHint::RealCommitment(c)) => {
+                        if &c.image != signer || !positions.contains(&c.position) {
+                            return Err(SigningSessionError::InvalidHint {
+                                input: idx,
+                                reason: format!(
+                                    "commitment at {:?} is not for the signer's key",
+                                    c.position
+                                ),
+                            });
+                        }
+                        if !is_commitment_for(&c.commitment, signer) {
+                            return Err(SigningSessionError::InvalidHint {
+                                input: idx,
+                                reason: "commitment type does not match the key".to_string(),
+                            });
+                        }
+                    }
+                    Hint::CommitmentHint(CommitmentHint::OwnCommitment(_)) => {
+                        return Err(SigningSessionError::SecretHintLeak(idx))
+                    }
+                    Hint::CommitmentHint(CommitmentHint::SimulatedCommitment(_))
+                    | Hint::SecretProven(_) => {
+                        return Err(SigningSessionError::InvalidHint {
+                            input: idx,
+                            reason: "only real commitments are expected".to_string(),
+                        })
+                    }
+                }
+            }
+            let real_commitments = bag.real_commitments();
+            for position in positions {
+                if !real_commitments.iter().any(|c| c.position == position) {
+                    return Err(SigningSessionError::MissingHint {
+                        input: idx,
+                        position,
+                    });
+                }
+            }
+            accepted.push((idx, bag));
+        }
+        for (idx, bag) in accepted {
+            self.hints.add_hints_for_input(idx, bag);
+        }
+        self.committed.push(signer.clone());
+        Ok(())
+    }
+
+    /// Hints for the signer to sign the transaction with
+    /// (`Wallet::sign_reduced_transaction(reduced_tx, Some(&hints))`).
+    /// Consists of the signer's own commitments (taken from `own_hints` generated in the
+    /// commitment round), commitments of the other signers, proofs of the signers that have already
+    /// signed and simulated proofs.
+    pub fn hints_for_signer(
+        &self,
+        signer: &SigmaBoolean,
+        own_hints: &TransactionHintsBag,
+    ) -> Result<TransactionHintsBag, SigningSessionError> {
+        self.check_phase(SessionPhase::Signing)?;
+        self.check_signer(signer)?;
+        if self.signed.contains(signer) {
+            return Err(SigningSessionError::AlreadySigned(signer.clone()));
+        }
+        let mut res = TransactionHintsBag::empty();
+        for (idx, input) in self.reduced_tx.reduced_inputs().iter().enumerate() {
+            let own_commitments = own_hints.all_hints_for_input(idx).own_commitments();
+            let session_hints = self.hints.all_hints_for_input(idx);
+            let mut hints = Vec::new();
+            for position in signer_positions(&input.reduction_result.sigma_prop, signer) {
+                let committed = session_hints
+                    .real_commitments()
+                    .into_iter()
+                    .find(|c| c.position == position);
+                let own = own_commitments
+                    .iter()
+                    .find(|c| c.position == position && &c.image == signer);
+                match (committed, own) {
+                    (Some(committed), Some(own)) if committed.commitment == own.commitment => {
+                        hints.push(own.clone().into())
+                    }
+                    (_, Some(_)) => {
+                        return Err(SigningSessionError::InvalidHint {
+                            input: idx,
+                            reason: format!(
+                                "own commitment at {:?} does not match the shared one",
+                                position
+                            ),
+                        })
+                    }
+                    (_, None) => {
+                        return Err(SigningSessionError::MissingHint {
+                            input: idx,
+                            position,
+                        })
+                    }
+                }
+            }
+            hints.extend(session_hints.hints.into_iter().filter(|hint| {
+                !matches!(hint, Hint::CommitmentHint(CommitmentHint::RealCommitment(c)) if &c.image == signer)
+            }));
+            res.add_hints_for_input(idx, HintsBag { hints });
+        }
+        Ok(res)
+    }
+
+    /// Add the transaction signed by the signer with the hints from
+    /// [`SigningSession::hints_for_signer`].
+    /// The first signature fixes the simulated branches. The signature of the last signer must
+    /// be a valid proof for every input and completes the session.
+    pub fn add_partial_signature(
+        &mut self,
+        signer: &SigmaBoolean,
+        tx: &Transaction,
+    ) -> Result<(), SigningSessionError> {
+        self.check_phase(SessionPhase::Signing)?;
+        self.check_signer(signer)?;
+        if self.signed.contains(signer) {
+            return Err(SigningSessionError::AlreadySigned(signer.clone()));
+        }
+        if tx.id() != self.reduced_tx.unsigned_tx.id() {
+            return Err(SigningSessionError::TransactionMismatch);
+        }
+        let is_first = self.signed.is_empty();
+        let is_last = self.signed.len() + 1 == self.signers.len();
+        let simulated = self.simulated();
+        let message = tx.bytes_to_sign()?;
+        let mut extracted = Vec::new();
+        for (idx, (input, reduced_input)) in tx
+            .inputs
+            .iter()
+            .zip(self.reduced_tx.reduced_inputs().iter())
+            .enumerate()
+        {
+            let sigma_prop = &reduced_input.reduction_result.sigma_prop;
+            let proof: &[u8] = input.spending_proof.proof.as_ref();
+            if is_last {
+                if !verify_signature(sigma_prop.clone(), &message, proof).unwrap_or(false) {
+                    return Err(SigningSessionError::InvalidProof(idx));
+                }
+                continue;
+            }
+            let positions = signer_positions(sigma_prop, signer);
+            if positions.is_empty() && !is_first {
+                continue;
+            }
+            let bag = bag_for_multi_sig(sigma_prop, &[signer.clone()], &simulated, proof)?;
+            let mut hints: Vec<Hint> = Vec::new();
+            for position in positions {
+                let real_proof = bag
+                    .real_proofs()
+                    .into_iter()
+                    .find(|p| p.position == position)
+                    .ok_or_else(|| SigningSessionError::MissingHint {
+                        input: idx,
+                        position: position.clone(),
+                    })?;
+                hints.push(real_proof.into());
+            }
+            let real_proofs = HintsBag {
+                hints: hints.clone(),
+            };
+            verify_real_secret_proofs(
+                sigma_prop,
+                &self.hints.all_hints_for_input(idx),
+                &real_proofs,
+            )
+            .map_err(|error| SigningSessionError::InvalidPartialProof { input: idx, error })?;
+            if is_first {
+                // simulated branches are chosen by the first signer
+                hints.extend(bag.hints.into_iter().filter(|hint| {
+                    matches!(
+                        hint,
+                        Hint::SecretProven(SecretProven::SimulatedSecretProof(_))
+                            | Hint::CommitmentHint(CommitmentHint::SimulatedCommitment(_))
+                    )
+                }));
+            }
+            extracted.push((idx, HintsBag { hints }));
+        }
+        for (idx, bag) in extracted {
+            self.hints.add_hints_for_input(idx, bag);
+        }
+        self.signed.push(signer.clone());
+        if is_last {
+            self.transaction = Some(tx.clone());
+        }
+        Ok(())
+    }
+
+    fn check_phase(&self, expected: SessionPhase) -> Result<(), SigningSessionError> {
+        let actual = self.phase();
+        if actual == expected {
+            Ok(())
+        } else {
+            Err(SigningSessionError::UnexpectedPhase { expected, actual })
+        }
+    }
+
+    fn check_signer(&self, signer: &SigmaBoolean) -> Result<(), SigningSessionError> {
+        if self.signers.contains(signer) {
+            Ok(())
+        } else {
+            Err(SigningSessionError::UnknownSigner(signer.clone()))
+        }
+    }
+}
+
+/// Distinct public keys of all the inputs' propositions
+fn participants(reduced_tx: &ReducedTransaction) -> Vec<SigmaBoolean> {
+    let mut res: Vec<SigmaBoolean> = Vec::new();
+    for input in reduced_tx.reduced_inputs().iter() {
+        for (_, leaf) in proof_of_knowledge_leaves(&input.reduction_result.sigma_prop) {
+            if !res.contains(&leaf) {
+                res.push(leaf);
+            }
+        }
+    }
+    res
+}
+
+/// Positions of the signer's key in the sigma tree
+fn signer_positions(sigma_tree: &SigmaBoolean, signer: &SigmaBoolean) -> Vec<NodePosition> {
+    proof_of_knowledge_leaves(sigma_tree)
+        .into_iter()
+        .filter(|(_, leaf)| leaf == signer)
+        .map(|(position, _)| position)
+        .collect()
+}
+
+fn is_commitment_for(commitment: &FirstProverMessage, image: &SigmaBoolean) -> bool {
+    matches!(
+        (commitment, image),
+        (
+            FirstProverMessage::FirstDlogProverMessage(_),
+            SigmaBoolean::ProofOfKnowledge(SigmaProofOfKnowledgeTree::ProveDlog(_))
+        ) | (
+            FirstProverMessage::FirstDhtProverMessage(_),
+            SigmaBoolean::ProofOfKnowledge(SigmaProofOfKnowledgeTree::ProveDhTuple(_))
+        )
+    )
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::panic)]
+mod tests {
+    use super::*;
+    use crate::chain::ergo_box::box_builder::ErgoBoxCandidateBuilder;
+    use crate::chain::ergo_state_context::ErgoStateContext;
+    use crate::chain::transaction::reduced::reduce_tx;
+    use crate::chain::transaction::unsigned::UnsignedTransaction;
+    use crate::chain::transaction::UnsignedInput;
+    use crate::ergotree_interpreter::sigma_protocol::private_input::PrivateInput;
+    use crate::ergotree_ir::chain::ergo_box::box_value::BoxValue;
+    use crate::ergotree_ir::chain::ergo_box::ErgoBox;
+    use crate::ergotree_ir::chain::ergo_box::NonMandatoryRegisters;
+    use crate::ergotree_ir::chain::tx_id::TxId;
+    use crate::ergotree_ir::ergo_tree::ErgoTree;
+    use crate::ergotree_ir::mir::atleast::Atleast;
+    use crate::ergotree_ir::mir::constant::Constant;
+    use crate::ergotree_ir::mir::constant::Literal;
+    use crate::ergotree_ir::mir::expr::Expr;
+    use crate::ergotree_ir::mir::value::CollKind;
+    use crate::ergotree_ir::sigma_protocol::sigma_boolean::SigmaProp;
+    use crate::ergotree_ir::types::stype::SType;
+    use crate::wallet::secret_key::SecretKey;
+    use crate::wallet::signing::TransactionContext;
+    use crate::wallet::Wallet;
+    use sigma_test_util::force_any_val;
+    use std::convert::TryFrom;
+
+    fn threshold_tree(k: i32, keys: &[SigmaBoolean]) -> ErgoTree {
+        let items = keys
+            .iter()
+            .map(|k| SigmaProp::new(k.clone()).into())
+            .collect();
+        let input = Constant {
+            tpe: SType::SColl(SType::SSigmaProp.into()),
+            v: Literal::Coll(CollKind::from_vec(SType::SSigmaProp, items).unwrap()),
+        }
+        .into();
+        let expr: Expr = Atleast::new(Expr::Const(k.into()), input).unwrap().into();
+        ErgoTree::try_from(expr).unwrap()
+    }
+
+    /// Transaction spending two boxes guarded by `atLeast(k, keys)`
+    fn reduced_threshold_tx(k: i32, keys: &[SigmaBoolean]) -> ReducedTransaction {
+        let boxes: Vec<ErgoBox> = (0..2)
+            .map(|idx| {
+                ErgoBox::new(
+                    BoxValue::SAFE_USER_MIN,
+                    threshold_tree(k, keys),
+                    None,
+                    NonMandatoryRegisters::empty(),
+                    0,
+                    TxId::zero(),
+                    idx,
+                )
+                .unwrap()
+            })
+            .collect();
+        let inputs = boxes.iter().cloned().map(UnsignedInput::from).collect();
+        let candidate =
+            ErgoBoxCandidateBuilder::new(BoxValue::SAFE_USER_MIN, threshold_tree(k, keys), 0)
+                .build()
+                .unwrap();
+        let tx = UnsignedTransaction::new_from_vec(inputs, vec![], vec![candidate]).unwrap();
+        let tx_context = TransactionContext::new(tx, boxes, vec![]).unwrap();
+        reduce_tx(tx_context, &force_any_val::<ErgoStateContext>()).unwrap()
+    }
+
+    fn secret_and_key() -> (SecretKey, SigmaBoolean) {
+        let secret = SecretKey::random_dlog();
+        let key = PrivateInput::from(secret.clone_secret()).public_image();
+        (secret, key)
+    }
+
+    #[test]
+    fn three_of_five() {
+        let (secrets, keys): (Vec<SecretKey>, Vec<SigmaBoolean>) =
+            (0..5).map(|_| secret_and_key()).unzip();
+        let reduced_tx = reduced_threshold_tx(3, &keys);
+        let signer_keys = vec![keys[4].clone(), keys[0].clone(), keys[2].clone()];
+        let mut session = SigningSession::new(reduced_tx.clone(), signer_keys.clone()).unwrap();
+        assert_eq!(session.simulated(), vec![keys[1].clone(), keys[3].clone()]);
+
+        let wallets: Vec<Wallet> = [4, 0, 2]
+            .iter()
+            .map(|i| Wallet::from_secrets(vec![secrets[*i].clone_secret()]))
+            .collect();
+        let own_hints: Vec<TransactionHintsBag> = wallets
+            .iter()
+            .map(|w| {
+                w.generate_commitments_for_reduced_transaction(reduced_tx.clone())
+                    .unwrap()
+            })
+            .collect();
+        for (key, hints) in signer_keys.iter().zip(own_hints.iter()) {
+            assert_eq!(session.phase(), SessionPhase::Commitment);
+            session
+                .add_commitments(key, &hints.without_own_commitments())
+                .unwrap();
+        }
+        assert!(session.pending_commitments().is_empty());
+
+        for ((key, wallet), own) in signer_keys.iter().zip(wallets.iter()).zip(own_hints.iter()) {
+            assert_eq!(session.phase(), SessionPhase::Signing);
+            // session state is passed to the next signer
+            #[cfg(feature = "json")]
+            {
+                let json = serde_json::to_string(&session).unwrap();
+                session = serde_json::from_str(&json).unwrap();
+            }
+            let hints = session.hints_for_signer(key, own).unwrap();
+            let tx = wallet
+                .sign_reduced_transaction(reduced_tx.clone(), Some(&hints))
+                .unwrap();
+            session.add_partial_signature(key, &tx).unwrap();
+        }
+        assert_eq!(session.phase(), SessionPhase::Complete);
+        let tx = session.transaction().unwrap();
+        let message = tx.bytes_to_sign().unwrap();
+        for (input, reduced_input) in tx.inputs.iter().zip(reduced_tx.reduced_inputs().iter()) {
+            assert!(verify_signature(
+                reduced_input.reduction_result.sigma_prop.clone(),
+                &message,
+                input.spending_proof.proof.as_ref()
+            )
+            .unwrap());
+        }
+    }
+
+    #[test]
+    fn rejects_shared_own_commitments() {
+        let (secret, key) = secret_and_key();
+        let (_, other_key) = secret_and_key();
+        let reduced_tx = reduced_threshold_tx(1, &[key.clone(), other_key]);
+        let mut session = SigningSession::new(reduced_tx.clone(), vec![key.clone()]).unwrap();
+        let own_hints = Wallet::from_secrets(vec![secret])
+            .generate_commitments_for_reduced_transaction(reduced_tx)
+            .unwrap();
+        assert_eq!(
+            session.add_commitments(&key, &own_hints),
+            Err(SigningSessionError::SecretHintLeak(0))
+        );
+    }
+
+    #[test]
+    fn rejects_out_of_order_rounds() {
+        let (secret, key) = secret_and_key();
+        let (_, other_key) = secret_and_key();
+        let reduced_tx = reduced_threshold_tx(2, &[key.clone(), other_key.clone()]);
+        let mut session =
+            SigningSession::new(reduced_tx.clone(), vec![key.clone(), other_key.clone()]).unwrap();
+        let own_hints = Wallet::from_secrets(vec![secret])
+            .generate_commitments_for_reduced_transaction(reduced_tx.clone())
+            .unwrap();
+        session
+            .add_commitments(&key, &own_hints.without_own_commitments())
+            .unwrap();
+        assert_eq!(
+            session.add_commitments(&key, &own_hints.without_own_commitments()),
+            Err(SigningSessionError::AlreadyCommitted(key.clone()))
+        );
+        assert_eq!(
+            session.hints_for_signer(&key, &own_hints),
+            Err(SigningSessionError::UnexpectedPhase {
+                expected: SessionPhase::Signing,
+                actual: SessionPhase::Commitment
+            })
+        );
+        // commitments of one signer can not be passed as the other's
+        assert!(matches!(
+            session.add_commitments(&other_key, &own_hints.without_own_commitments()),
+            Err(SigningSessionError::InvalidHint { input: 0, .. })
+        ));
+    }
+
+    #[test]
+    fn rejects_partial_signature_for_other_commitment() {
+        let (secret, key) = secret_and_key();
+        let (other_secret, other_key) = secret_and_key();
+        let reduced_tx = reduced_threshold_tx(2, &[key.clone(), other_key.clone()]);
+        let mut session =
+            SigningSession::new(reduced_tx.clone(), vec![key.clone(), other_key.clone()]).unwrap();
+        let wallet = Wallet::from_secrets(vec![secret]);
+        for (k, w) in [
+            (&key, &wallet),
+            (&other_key, &Wallet::from_secrets(vec![other_secret])),
+        ] {
+            let hints = w
+                .generate_commitments_for_reduced_transaction(reduced_tx.clone())
+                .unwrap();
+            session
+                .add_commitments(k, &hints.without_own_commitments())
+                .unwrap();
+        }
+        // without own commitments the signer generates fresh randomness and the proof does not
+        // match the commitment shared in the first round
+        let tx = wallet
+            .sign_reduced_transaction(reduced_tx, Some(session.hints()))
+            .unwrap();
+        match session.add_partial_signature(&key, &tx) {
+            Err(SigningSessionError::InvalidPartialProof { input: 0, error }) => {
+                assert_eq!(error.image(), &key)
+            }
+            res => panic!("unexpected result: {:?}", res),
+        }
+        assert_eq!(session.pending_signatures(), vec![key, other_key]);
+    }
+
+    #[test]
+    fn rejects_unknown_signer() {
+        let (_, key) = secret_and_key();
+        let (_, other_key) = secret_and_key();
+        let reduced_tx = reduced_threshold_tx(1, &[key]);
+        assert_eq!(
+            SigningSession::new(reduced_tx, vec![other_key.clone()]),
+            Err(SigningSessionError::UnknownSigner(other_key))
+        );
+    }
+}
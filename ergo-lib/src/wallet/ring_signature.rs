@@ -0,0 +1,168 @@
+//! Ring signatures: proofs that one of the keys of a set signed a message without revealing which
+//! one (a signature for `COR` of the keys, see [`crate::wallet::Wallet::sign_message`])
+
+use std::convert::TryInto;
+
+use ergo_chain_types::EcPoint;
+use ergotree_interpreter::sigma_protocol::verifier::verify_signature;
+use ergotree_interpreter::sigma_protocol::verifier::VerifierError;
+use ergotree_ir::serialization::sigma_byte_reader::SigmaByteRead;
+use ergotree_ir::serialization::sigma_byte_writer::SigmaByteWrite;
+use ergotree_ir::serialization::SigmaParsingError;
+use ergotree_ir::serialization::SigmaSerializable;
+use ergotree_ir::serialization::SigmaSerializeResult;
+use ergotree_ir::sigma_protocol::sigma_boolean::cor::Cor;
+use ergotree_ir::sigma_protocol::sigma_boolean::ProveDlog;
+use ergotree_ir::sigma_protocol::sigma_boolean::SigmaBoolean;
+use thiserror::Error;
+
+/// Key ring errors
+#[derive(Error, PartialEq, Eq, Debug, Clone)]
+pub enum KeyRingError {
+    /// Ring has no keys or more than [`KeyRing::MAX_SIZE`] keys
+    #[error("invalid number of keys {0}, expected 1..=255")]
+    InvalidSize(usize),
+    /// Key is in the ring more than once
+    #[error("duplicate key {0:?}")]
+    DuplicateKey(ProveDlog),
+}
+
+/// Set of public keys, one of which signs a message
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct KeyRing {
+    keys: Vec<ProveDlog>,
+}
+
+impl KeyRing {
+    /// Maximum number of keys in a ring (maximum number of `COR` children)
+    pub const MAX_SIZE: usize = 255;
+
+    /// Create a ring of the given keys
+    pub fn new(keys: Vec<ProveDlog>) -> Result<Self, KeyRingError> {
+        if keys.is_empty() || keys.len() > Self::MAX_SIZE {
+            return Err(KeyRingError::InvalidSize(keys.len()));
+        }
+        if let Some((idx, _)) = keys
+            .iter()
+            .enumerate()
+            .find(|(idx, k)| keys[..*idx].contains(k))
+        {
+            return Err(KeyRingError::DuplicateKey(keys[idx].clone()));
+        }
+        Ok(KeyRing { keys })
+    }
+
+    /// Add a key to the ring
+    pub fn add(&mut self, key: ProveDlog) -> Result<(), KeyRingError> {
+        if self.keys.contains(&key) {
+            return Err(KeyRingError::DuplicateKey(key));
+        }
+        if self.keys.len() == Self::MAX_SIZE {
+            return Err(KeyRingError::InvalidSize(self.keys.len() + 1));
+        }
+        self.keys.push(key);
+        Ok(())
+    }
+
+    /// Keys of the ring
+    pub fn keys(&self) -> &[ProveDlog] {
+        &self.keys
+    }
+
+    /// Proposition signed by a ring signature (`COR` of the keys)
+    pub fn proposition(&self) -> SigmaBoolean {
+        let items: Vec<SigmaBoolean> = self.keys.iter().cloned().map(Into::into).collect();
+        match items.clone().try_into() {
+            Ok(items) => Cor::normalized(items),
+            // single key
+            Err(_) => items
+                .into_iter()
+                .next()
+                .unwrap_or(SigmaBoolean::TrivialProp(false)),
+        }
+    }
+}
+
+/// Compact encoding: number of keys followed by the compressed keys (33 bytes each), i.e. without
+/// the op codes of `SigmaBoolean` serialization
+impl SigmaSerializable for KeyRing {
+    fn sigma_serialize<W: SigmaByteWrite>(&self, w: &mut W) -> SigmaSerializeResult {
+        // ring size is at most 255
+        w.put_u8(self.keys.len() as u8)?;
+        self.keys.iter().try_for_each(|k| k.h.sigma_serialize(w))
+    }
+
+    fn sigma_parse<R: SigmaByteRead>(r: &mut R) -> Result<Self, SigmaParsingError> {
+        let count = r.get_u8()?;
+        let keys = (0..count)
+            .map(|_| EcPoint::sigma_parse(r).map(ProveDlog::new))
+            .collect::<Result<Vec<_>, _>>()?;
+        KeyRing::new(keys).map_err(|e| SigmaParsingError::Misc(e.to_string()))
+    }
+}
+
+/// Verify that the signature was produced by one of the ring's keys for the message
+pub fn verify_ring_signature(
+    ring: &KeyRing,
+    message: &[u8],
+    signature: &[u8],
+) -> Result<bool, VerifierError> {
+    verify_signature(ring.proposition(), message, signature)
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use crate::ergotree_interpreter::sigma_protocol::private_input::DlogProverInput;
+    use crate::wallet::secret_key::SecretKey;
+    use crate::wallet::Wallet;
+    use ergotree_ir::serialization::sigma_serialize_roundtrip;
+
+    #[test]
+    fn sign_and_verify() {
+        let secrets: Vec<DlogProverInput> = (0..5).map(|_| DlogProverInput::random()).collect();
+        let ring =
+            KeyRing::new(secrets.iter().map(DlogProverInput::public_image).collect()).unwrap();
+        let signer =
+            Wallet::from_secrets(vec![SecretKey::DlogSecretKey(secrets[3].clone_secret())]);
+        let message = b"vote: yes";
+        let signature = signer.sign_ring_message(&ring, message).unwrap();
+        assert!(verify_ring_signature(&ring, message, &signature).unwrap());
+        assert!(!verify_ring_signature(&ring, b"vote: no", &signature).unwrap());
+
+        let outsider = Wallet::from_secrets(vec![SecretKey::random_dlog()]);
+        assert!(outsider.sign_ring_message(&ring, message).is_err());
+    }
+
+    #[test]
+    fn single_key_ring() {
+        let secret = DlogProverInput::random();
+        let ring = KeyRing::new(vec![secret.public_image()]).unwrap();
+        assert_eq!(ring.proposition(), secret.public_image().into());
+    }
+
+    #[test]
+    fn invalid_rings() {
+        let key = DlogProverInput::random().public_image();
+        assert_eq!(KeyRing::new(vec![]), Err(KeyRingError::InvalidSize(0)));
+        assert_eq!(
+            KeyRing::new(vec![key.clone(), key.clone()]),
+            Err(KeyRingError::DuplicateKey(key.clone()))
+        );
+        let mut ring = KeyRing::new(vec![key.clone()]).unwrap();
+        assert_eq!(ring.add(key.clone()), Err(KeyRingError::DuplicateKey(key)));
+    }
+
+    #[test]
+    fn compact_serialization() {
+        let ring = KeyRing::new(
+            (0..3)
+                .map(|_| DlogProverInput::random().public_image())
+                .collect(),
+        )
+        .unwrap();
+        assert_eq!(sigma_serialize_roundtrip(&ring), ring);
+        assert_eq!(ring.sigma_serialize_bytes().unwrap().len(), 1 + 3 * 33);
+    }
+}
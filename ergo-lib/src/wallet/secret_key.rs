@@ -11,11 +11,16 @@ use ergotree_ir::mir::constant::Constant;
 use ergotree_ir::mir::expr::Expr;
 use ergotree_ir::serialization::SigmaSerializable;
 use thiserror::Error;
+use zeroize::Zeroizing;
+
+#[cfg(feature = "json")]
+use crate::chain::json::secret_key::SecretKeyJson;
 
 /// Types of secrets
-#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
-#[cfg_attr(feature = "json", serde(untagged))]
-#[derive(PartialEq, Eq, Debug, Clone, From)]
+/// Secrets are zeroed on drop, not shown in `Debug` output, compared in constant time and are not
+/// `Clone` (see [`SecretKey::clone_secret`]). They are not serializable either, use
+/// [`SecretKey::expose_secret_bytes`] or [`SecretKey::expose_secret_json`] to export them
+#[derive(PartialEq, Eq, Debug, From)]
 pub enum SecretKey {
     /// Secret exponent of a group element, i.e. secret w such as h = g^^w, where g is group generator,
     /// h is a public key.
@@ -80,11 +85,34 @@ impl SecretKey {
         }
     }
 
-    /// Encode from a serialized key
-    pub fn to_bytes(&self) -> Vec<u8> {
+    /// Encode from a serialized key.
+    /// Exports the secret, the returned copy is zeroed on drop
+    pub fn expose_secret_bytes(&self) -> Zeroizing<Vec<u8>> {
+        Zeroizing::new(match self {
+            SecretKey::DlogSecretKey(dlog) => dlog.expose_secret_bytes().to_vec(),
+            SecretKey::DhtSecretKey(dht) => dht.expose_secret_bytes().to_vec(),
+        })
+    }
+
+    /// Encode to JSON (Dlog as base16-encoded bytes, DHT in node REST API format).
+    /// Exports the secret, the returned string is zeroed on drop
+    #[cfg(feature = "json")]
+    pub fn expose_secret_json(&self) -> Result<Zeroizing<String>, serde_json::Error> {
+        let json = Zeroizing::new(SecretKeyJson::from(self));
+        serde_json::to_string(&*json).map(Zeroizing::new)
+    }
+
+    /// Parse from JSON (Dlog as base16-encoded bytes, DHT in node REST API format)
+    #[cfg(feature = "json")]
+    pub fn from_json(json: &str) -> Result<SecretKey, serde_json::Error> {
+        serde_json::from_str::<SecretKeyJson>(json).map(SecretKey::from)
+    }
+
+    /// Copy of the secret (`Clone` is not implemented to make the copies of the secret explicit)
+    pub fn clone_secret(&self) -> SecretKey {
         match self {
-            SecretKey::DlogSecretKey(dlog) => dlog.to_bytes().to_vec(),
-            SecretKey::DhtSecretKey(dht) => dht.to_bytes().to_vec(),
+            SecretKey::DlogSecretKey(dlog) => dlog.clone_secret().into(),
+            SecretKey::DhtSecretKey(dht) => dht.clone_secret().into(),
         }
     }
 
@@ -142,18 +170,28 @@ mod tests {
     fn dlog_roundtrip() {
         let sk = SecretKey::random_dlog();
         let sk_copy1 =
-            SecretKey::dlog_from_bytes(&sk.to_bytes().as_slice().try_into().unwrap()).unwrap();
-        let sk_copy2 = SecretKey::from_bytes(sk.to_bytes().as_slice()).unwrap();
+            SecretKey::dlog_from_bytes(&sk.expose_secret_bytes().as_slice().try_into().unwrap())
+                .unwrap();
+        let sk_copy2 = SecretKey::from_bytes(sk.expose_secret_bytes().as_slice()).unwrap();
         assert_eq!(sk, sk_copy1);
         assert_eq!(sk, sk_copy2);
     }
 
+    #[test]
+    fn debug_is_redacted() {
+        let sk = SecretKey::random_dlog();
+        assert_eq!(format!("{:?}", sk), r#"DlogSecretKey("DLOGPI:***")"#);
+        let sk = SecretKey::random_dht();
+        assert_eq!(format!("{:?}", sk), r#"DhtSecretKey("DHTPI:***")"#);
+    }
+
     #[test]
     fn dht_roundtrip() {
         let sk = SecretKey::random_dht();
         let sk_copy1 =
-            SecretKey::dht_from_bytes(&sk.to_bytes().as_slice().try_into().unwrap()).unwrap();
-        let sk_copy2 = SecretKey::from_bytes(sk.to_bytes().as_slice()).unwrap();
+            SecretKey::dht_from_bytes(&sk.expose_secret_bytes().as_slice().try_into().unwrap())
+                .unwrap();
+        let sk_copy2 = SecretKey::from_bytes(sk.expose_secret_bytes().as_slice()).unwrap();
         assert_eq!(sk, sk_copy1);
         assert_eq!(sk, sk_copy2);
     }
@@ -169,43 +207,32 @@ mod json_tests {
     #[test]
     fn json_dlog_roundtrip() {
         let sk = SecretKey::random_dlog();
-        let sk_json = serde_json::to_string(&sk).unwrap();
-        //dbg!(&sk_json);
-        let sk_copy: SecretKey = serde_json::from_str(&sk_json).unwrap();
+        let sk_json = sk.expose_secret_json().unwrap();
+        let sk_copy = SecretKey::from_json(&sk_json).unwrap();
         assert_eq!(sk, sk_copy);
     }
 
     #[test]
     fn json_dht_roundtrip() {
         let sk = SecretKey::random_dht();
-        let sk_json = serde_json::to_string(&sk).unwrap();
-        //dbg!(&sk_json);
-        let sk_copy: SecretKey = serde_json::from_str(&sk_json).unwrap();
+        let sk_json = sk.expose_secret_json().unwrap();
+        let sk_copy = SecretKey::from_json(&sk_json).unwrap();
         assert_eq!(sk, sk_copy);
     }
 
     #[test]
     fn json_dht_golden() {
-        let sk_json = r#"{
-  "secret": "b2a93a9a37b4656c7abf4e259b9c066cd8bf4e02449d5956aaf453a73764bfeb",
-  "g": "0279be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798",
-  "h": "0288a812f57b66b4c68fd9e097c79a6e2847013fa4112a43c45cd41c9ba8c79b69",
-  "u": "0381fff110959bc06d99c5580c462c0196d8434bc5d470fb10a160019276e648c2",
-  "v": "02c6626ad387bb6b2eccc2fdd238c97ee4a81bfded401843bc8bb71f1cc7269924"
-}"#;
-        let sk: SecretKey = serde_json::from_str(sk_json).unwrap();
+        let sk_json = r#"{"secret":"b2a93a9a37b4656c7abf4e259b9c066cd8bf4e02449d5956aaf453a73764bfeb","g":"0279be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798","h":"0288a812f57b66b4c68fd9e097c79a6e2847013fa4112a43c45cd41c9ba8c79b69","u":"0381fff110959bc06d99c5580c462c0196d8434bc5d470fb10a160019276e648c2","v":"02c6626ad387bb6b2eccc2fdd238c97ee4a81bfded401843bc8bb71f1cc7269924"}"#;
+        let sk = SecretKey::from_json(sk_json).unwrap();
         assert!(matches!(sk, SecretKey::DhtSecretKey(_)));
-        let sk_json_copy = serde_json::to_string_pretty(&sk).unwrap();
-        assert_eq!(sk_json, sk_json_copy);
+        assert_eq!(sk.expose_secret_json().unwrap().as_str(), sk_json);
     }
 
     #[test]
     fn json_dlog_golden() {
         let sk_json = r#""0cd81ce156fed4017520e561e9c492222027751ed0dd71b5a9b3a61da68b5850""#;
-        //dbg!(&sk_json);
-        let sk: SecretKey = serde_json::from_str(sk_json).unwrap();
+        let sk = SecretKey::from_json(sk_json).unwrap();
         assert!(matches!(sk, SecretKey::DlogSecretKey(_)));
-        let sk_json_copy = serde_json::to_string_pretty(&sk).unwrap();
-        assert_eq!(sk_json, sk_json_copy);
+        assert_eq!(sk.expose_secret_json().unwrap().as_str(), sk_json);
     }
 }
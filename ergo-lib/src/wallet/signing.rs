@@ -11,7 +11,6 @@ use ergotree_interpreter::sigma_protocol::sig_serializer::SigParsingError;
 use ergotree_ir::chain::ergo_box::ErgoBox;
 use ergotree_ir::serialization::SigmaSerializationError;
 use ergotree_ir::sigma_protocol::sigma_boolean::SigmaBoolean;
-use std::rc::Rc;
 use std::sync::Arc;
 
 use crate::ergotree_ir::chain::ergo_box::BoxId;
@@ -255,7 +254,7 @@ pub fn sign_tx_input(
     let input_box = tx_context
         .get_input_box(&unsigned_input.box_id)
         .ok_or(TransactionContextError::InputBoxNotFound(input_idx))?;
-    let ctx = Rc::new(make_context(state_context, tx_context, input_idx)?);
+    let ctx = Arc::new(make_context(state_context, tx_context, input_idx)?);
     let mut hints_bag = HintsBag::empty();
     if let Some(bag) = tx_hints {
         hints_bag = bag.all_hints_for_input(input_idx);
@@ -276,8 +275,12 @@ pub fn sign_tx_input(
 #[allow(clippy::unwrap_used, clippy::panic)]
 mod tests {
     use super::*;
+    use ergotree_interpreter::sigma_protocol::musig::MusigKeyAgg;
+    use ergotree_interpreter::sigma_protocol::musig::MusigSecretNonce;
+    use ergotree_interpreter::sigma_protocol::musig::MusigSession;
     use ergotree_interpreter::sigma_protocol::private_input::DlogProverInput;
     use ergotree_interpreter::sigma_protocol::private_input::PrivateInput;
+    use ergotree_interpreter::sigma_protocol::prover::external_signer::MockExternalSigner;
     use ergotree_interpreter::sigma_protocol::prover::ContextExtension;
     use ergotree_interpreter::sigma_protocol::prover::TestProver;
     use ergotree_interpreter::sigma_protocol::verifier::verify_signature;
@@ -297,6 +300,7 @@ mod tests {
     use sigma_test_util::force_any_val;
 
     use crate::chain::transaction::reduced::reduce_tx;
+    use crate::chain::transaction::verify_txs_input_proofs;
     use crate::chain::transaction::DataInput;
     use crate::chain::{
         ergo_box::box_builder::ErgoBoxCandidateBuilder, transaction::UnsignedInput,
@@ -308,7 +312,7 @@ mod tests {
     use ergotree_ir::mir::expr::Expr;
     use std::convert::TryFrom;
     use std::convert::TryInto;
-    use std::rc::Rc;
+    use std::sync::Arc;
 
     fn verify_tx_proofs(
         tx: &Transaction,
@@ -324,7 +328,7 @@ mod tests {
             let res = verifier.verify(
                 &b.ergo_tree,
                 &Env::empty(),
-                Rc::new(force_any_val::<Context>()),
+                Arc::new(force_any_val::<Context>()),
                 input.spending_proof.proof.clone(),
                 &message,
             )?;
@@ -349,8 +353,8 @@ mod tests {
                              TxId::zero(),
                              0).unwrap()
             }).collect();
-            let prover = Rc::new(TestProver {
-                secrets: secrets.clone().into_iter().map(PrivateInput::DlogProverInput).collect(),
+            let prover = Arc::new(TestProver {
+                secrets: secrets.iter().map(|s| PrivateInput::DlogProverInput(s.clone_secret())).collect(),
             });
             let inputs: Vec<UnsignedInput> = boxes_to_spend.clone().into_iter().map(UnsignedInput::from).collect();
             // boxes_to_spend are in the different order to test inputs <-> boxes_to_spend association in the
@@ -369,6 +373,25 @@ mod tests {
             let reduced_tx = reduce_tx(tx_context, &force_any_val::<ErgoStateContext>()).unwrap();
             let signed_reduced_tx = sign_reduced_transaction(prover.as_ref(), reduced_tx,None).unwrap();
             prop_assert!(verify_tx_proofs(&signed_reduced_tx, &boxes_to_spend).unwrap());
+            let signed_tx_contexts: Vec<TransactionContext<Transaction>> = vec![signed_tx, signed_reduced_tx]
+                .into_iter()
+                .map(|tx| TransactionContext::new(tx, boxes_to_spend.clone(), vec![]).unwrap())
+                .collect();
+            let state_context = force_any_val::<ErgoStateContext>();
+            let batch_res: Vec<Vec<bool>> = verify_txs_input_proofs(&signed_tx_contexts, &state_context)
+                .into_iter()
+                .map(|tx_res| tx_res.into_iter().map(|r| r.unwrap()).collect())
+                .collect();
+            #[cfg(feature = "parallel")]
+            prop_assert_eq!(
+                crate::chain::transaction::par_verify_txs_input_proofs(&signed_tx_contexts, &state_context)
+                    .into_iter()
+                    .map(|tx_res| tx_res.into_iter().map(|r| r.unwrap()).collect::<Vec<bool>>())
+                    .collect::<Vec<_>>(),
+                batch_res.clone()
+            );
+            prop_assert_eq!(batch_res.len(), 2);
+            prop_assert!(batch_res.into_iter().all(|tx_res| tx_res.len() == secrets.len() && tx_res.into_iter().all(|r| r)));
         }
     }
 
@@ -561,15 +584,15 @@ mod tests {
         let ver_res = verifier.verify(
             &ergo_tree,
             &Env::empty(),
-            Rc::new(force_any_val::<Context>()),
+            Arc::new(force_any_val::<Context>()),
             tx.inputs.get(1).unwrap().spending_proof.proof.clone(),
             message.as_slice(),
         );
         assert!(ver_res.unwrap().result);
     }
 
-    #[test]
-    fn test_multi_sig_issue_597() {
+    /// Secrets and reduced multi-sig transaction from <https://github.com/ergoplatform/sigma-rust/issues/597>
+    fn issue_597_secrets_and_reduced_tx() -> (Vec<SecretKey>, ReducedTransaction) {
         let secrets: Vec<SecretKey> = [
             "00eda6c0e9fc808d4cf050fc4e98705372b9f0786a6b63aa4013d1a20539b104",
             "cc2e48e5e53059e0d68866eff97a6037cb39945ea9f09f40fcec82d12cd8cb8b",
@@ -584,7 +607,75 @@ mod tests {
         })
         .collect();
         let reduced = ReducedTransaction::sigma_parse_bytes(&base16::decode("ce04022f4cd0df4db787875b3a071e098b72ba4923bd2460e08184b34359563febe04700005e8269c8e2b975a43dc6e74a9c5b10b273313c6d32c1dd40c171fc0a8852ca0100000001a6ac381e6fa99929fd1477b3ba9499790a775e91d4c14c5aa86e9a118dfac8530480ade204100504000400040004000402d804d601b2a5730000d602e4c6a7041ad603e4c6a70510d604ad7202d901040ecdee7204ea02d19683020193c27201c2a7938cb2db63087201730100018cb2db6308a773020001eb02ea02d19683020193e4c67201041a720293e4c672010510720398b27203730300720498b272037304007204d18b0f010001021a04210302e57ca7ebf8cfa1802d4bc79a455008307a936b4f50f0629d9bef484fdd5189210399f5724bbc4d08c6e146d61449c05a3e0546868b1d4f83411f325187d5ca4f8521024e06e6c6073e13a03fa4629882a69108cd60e0a9fbb2e0fcc898ce68a7051b6621027a069cc972fc7816539a316ba1cfc0164656d63dd1873ee407670b0e8195f3bd100206088094ebdc030008cd0314368e16c9c99c5a6e20dda917aeb826b3a908becff543b3a36b38e6b3355ff5d18b0f0000c0843d1005040004000e36100204a00b08cd0279be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798ea02d192a39a8cc7a701730073011001020402d19683030193a38cc7b2a57300000193c2b2a57301007473027303830108cdeeac93b1a57304d18b0f0000c0af87c3210008cd0314368e16c9c99c5a6e20dda917aeb826b3a908becff543b3a36b38e6b3355ff5d18b0f00009702980304cd0302e57ca7ebf8cfa1802d4bc79a455008307a936b4f50f0629d9bef484fdd5189cd0399f5724bbc4d08c6e146d61449c05a3e0546868b1d4f83411f325187d5ca4f85cd024e06e6c6073e13a03fa4629882a69108cd60e0a9fbb2e0fcc898ce68a7051b66cd027a069cc972fc7816539a316ba1cfc0164656d63dd1873ee407670b0e8195f3bd9604cd0302e57ca7ebf8cfa1802d4bc79a455008307a936b4f50f0629d9bef484fdd5189cd0399f5724bbc4d08c6e146d61449c05a3e0546868b1d4f83411f325187d5ca4f85cd024e06e6c6073e13a03fa4629882a69108cd60e0a9fbb2e0fcc898ce68a7051b66cd027a069cc972fc7816539a316ba1cfc0164656d63dd1873ee407670b0e8195f3bdf39b03d3cb9e02d073").unwrap()).unwrap();
+        (secrets, reduced)
+    }
+
+    #[test]
+    fn test_multi_sig_issue_597() {
+        let (secrets, reduced) = issue_597_secrets_and_reduced_tx();
         let prover = Wallet::from_secrets(secrets);
         assert!(prover.sign_reduced_transaction(reduced, None).is_ok());
     }
+
+    #[test]
+    fn test_sign_reduced_tx_with_external_signer() {
+        let (mut secrets, reduced) = issue_597_secrets_and_reduced_tx();
+        // half of the secrets are held by the device
+        let device = MockExternalSigner::new(
+            secrets
+                .split_off(2)
+                .into_iter()
+                .map(PrivateInput::from)
+                .collect(),
+        );
+        let wallet = Wallet::from_external_signers(secrets, vec![Box::new(device)]);
+        assert!(wallet.sign_reduced_transaction(reduced, None).is_ok());
+    }
+
+    #[test]
+    fn test_sign_message_with_deterministic_nonces() {
+        let secret = SecretKey::random_dlog();
+        let sigma_tree = PrivateInput::from(secret.clone_secret()).public_image();
+        let wallet = Wallet::from_secrets_with_deterministic_nonces(vec![secret]);
+        let message = b"reproducible";
+        let signature = wallet.sign_message(sigma_tree.clone(), message).unwrap();
+        assert_eq!(
+            wallet.sign_message(sigma_tree.clone(), message).unwrap(),
+            signature
+        );
+        assert!(verify_signature(sigma_tree, message, &signature).unwrap());
+    }
+
+    #[test]
+    fn test_sign_message_with_musig() {
+        let secrets: Vec<DlogProverInput> = (0..3).map(|_| DlogProverInput::random()).collect();
+        let keys: Vec<_> = secrets.iter().map(DlogProverInput::public_image).collect();
+        let wallets: Vec<Wallet> = secrets
+            .into_iter()
+            .map(|s| Wallet::from_secrets(vec![SecretKey::DlogSecretKey(s)]))
+            .collect();
+        let message = vec![0u8; 100];
+        // round 1: nonces
+        let (secret_nonces, public_nonces): (Vec<_>, Vec<_>) =
+            keys.iter().map(|_| MusigSecretNonce::generate()).unzip();
+        let key_agg = MusigKeyAgg::new(keys.clone()).unwrap();
+        let session = MusigSession::new(
+            key_agg,
+            keys.iter().cloned().zip(public_nonces).collect(),
+            &message,
+        )
+        .unwrap();
+        // round 2: partial signatures
+        let partials: Vec<_> = wallets
+            .iter()
+            .zip(secret_nonces)
+            .map(|(w, nonce)| w.musig_partial_sign(&session, nonce).unwrap())
+            .collect();
+        let aggregated_key: SigmaBoolean = session.aggregated_key().clone().into();
+        let signer = session.into_signer(&partials).unwrap();
+        let signature = Wallet::from_external_signers(vec![], vec![Box::new(signer)])
+            .sign_message(aggregated_key.clone(), &message)
+            .unwrap();
+        assert!(verify_signature(aggregated_key, &message, &signature).unwrap());
+    }
 }
@@ -31,8 +31,8 @@ impl Root {
         }
     }
 
-    pub fn children(&self) -> impl Iterator<Item = Expr> {
-        self.0.children().filter_map(Expr::cast)
+    pub fn statements(&self) -> impl Iterator<Item = Stmt> {
+        self.0.children().filter_map(Stmt::cast)
     }
 
     pub fn span(&self) -> TextRange {
@@ -40,6 +40,90 @@ impl Root {
     }
 }
 
+#[derive(Debug)]
+pub enum Stmt {
+    ValDef(ValDef),
+    Expr(Expr),
+}
+
+impl Stmt {
+    pub fn cast(node: SyntaxNode) -> Option<Self> {
+        if node.kind() == SyntaxKind::VariableDef {
+            Some(Self::ValDef(ValDef(node)))
+        } else {
+            Expr::cast(node).map(Self::Expr)
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct ValDef(SyntaxNode);
+
+impl ValDef {
+    pub fn name(&self) -> Result<SyntaxToken, AstError> {
+        first_ident_token(&self.0)
+    }
+
+    pub fn tpe(&self) -> Option<TypeRef> {
+        self.0.children().find_map(TypeRef::cast)
+    }
+
+    pub fn rhs(&self) -> Result<Expr, AstError> {
+        self.0.children().find_map(Expr::cast).ok_or_else(|| {
+            AstError::new(
+                format!("Cannot find rhs in {:?}", self.0.children()),
+                self.span(),
+            )
+        })
+    }
+
+    pub fn span(&self) -> TextRange {
+        self.0.text_range()
+    }
+}
+
+#[derive(Debug)]
+pub struct TypeRef(SyntaxNode);
+
+impl TypeRef {
+    pub fn cast(node: SyntaxNode) -> Option<Self> {
+        if node.kind() == SyntaxKind::TypeRef {
+            Some(Self(node))
+        } else {
+            None
+        }
+    }
+
+    pub fn name(&self) -> Result<SyntaxToken, AstError> {
+        first_ident_token(&self.0)
+    }
+
+    pub fn text(&self) -> String {
+        self.0.text().to_string().trim().to_string()
+    }
+
+    /// Type arguments, e.g. `Byte` in `Coll[Byte]`
+    pub fn args(&self) -> impl Iterator<Item = TypeRef> {
+        self.0.children().filter_map(TypeRef::cast)
+    }
+
+    pub fn span(&self) -> TextRange {
+        self.0.text_range()
+    }
+}
+
+fn first_ident_token(node: &SyntaxNode) -> Result<SyntaxToken, AstError> {
+    node.children_with_tokens()
+        .filter_map(SyntaxElement::into_token)
+        .find(|token| token.kind() == SyntaxKind::Ident)
+        .ok_or_else(|| {
+            AstError::new(
+                format!("Cannot find name in: {:?}", node),
+                node.text_range(),
+            )
+        })
+}
+
 #[derive(Debug)]
 pub struct Ident(SyntaxNode);
 
@@ -63,8 +147,13 @@ pub enum Expr {
     Ident(Ident),
     BinaryExpr(BinaryExpr),
     Literal(Literal),
-    // ParenExpr(ParenExpr),
-    // UnaryExpr(UnaryExpr),
+    ParenExpr(ParenExpr),
+    BlockExpr(BlockExpr),
+    LambdaExpr(LambdaExpr),
+    CallExpr(CallExpr),
+    UnaryExpr(UnaryExpr),
+    IfExpr(IfExpr),
+    SelectExpr(SelectExpr),
 }
 
 impl Expr {
@@ -74,20 +163,35 @@ impl Expr {
             SyntaxKind::InfixExpr => Self::BinaryExpr(BinaryExpr(node)),
             SyntaxKind::IntNumber => Self::Literal(Literal(node)),
             SyntaxKind::LongNumber => Self::Literal(Literal(node)),
-            // SyntaxKind::ParenExpr => Self::ParenExpr(ParenExpr(node)),
-            // SyntaxKind::PrefixExpr => Self::UnaryExpr(UnaryExpr(node)),
+            SyntaxKind::BoolLiteral => Self::Literal(Literal(node)),
+            SyntaxKind::StringLiteral => Self::Literal(Literal(node)),
+            SyntaxKind::ParenExpr => Self::ParenExpr(ParenExpr(node)),
+            SyntaxKind::BlockExpr => Self::BlockExpr(BlockExpr(node)),
+            SyntaxKind::LambdaExpr => Self::LambdaExpr(LambdaExpr(node)),
+            SyntaxKind::CallExpr => Self::CallExpr(CallExpr(node)),
+            SyntaxKind::PrefixExpr => Self::UnaryExpr(UnaryExpr(node)),
+            SyntaxKind::IfExpr => Self::IfExpr(IfExpr(node)),
+            SyntaxKind::SelectExpr => Self::SelectExpr(SelectExpr(node)),
             _ => return None,
         };
 
         Some(result)
     }
 
-    // pub fn span(&self) -> TextRange {
-    //     match self {
-    //         Expr::Ident(node) => node.0.text_range(),
-    //         _ => todo!(),
-    //     }
-    // }
+    pub fn span(&self) -> TextRange {
+        match self {
+            Expr::Ident(e) => e.0.text_range(),
+            Expr::BinaryExpr(e) => e.0.text_range(),
+            Expr::Literal(e) => e.0.text_range(),
+            Expr::ParenExpr(e) => e.0.text_range(),
+            Expr::BlockExpr(e) => e.0.text_range(),
+            Expr::LambdaExpr(e) => e.0.text_range(),
+            Expr::CallExpr(e) => e.0.text_range(),
+            Expr::UnaryExpr(e) => e.0.text_range(),
+            Expr::IfExpr(e) => e.0.text_range(),
+            Expr::SelectExpr(e) => e.0.text_range(),
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -124,10 +228,21 @@ impl BinaryExpr {
                 matches!(
                     token.kind(),
                     SyntaxKind::Plus
+                        | SyntaxKind::PlusPlus
                         | SyntaxKind::Minus
                         | SyntaxKind::Star
                         | SyntaxKind::Slash
-                        | SyntaxKind::And,
+                        | SyntaxKind::EqualsEquals
+                        | SyntaxKind::NotEquals
+                        | SyntaxKind::Less
+                        | SyntaxKind::LessEquals
+                        | SyntaxKind::Greater
+                        | SyntaxKind::GreaterEquals
+                        | SyntaxKind::And
+                        | SyntaxKind::Or
+                        | SyntaxKind::Caret
+                        | SyntaxKind::Amp
+                        | SyntaxKind::Pipe,
                 )
             })
             .ok_or_else(|| {
@@ -147,6 +262,8 @@ impl BinaryExpr {
 pub enum LiteralValue {
     Int(i32),
     Long(i64),
+    Boolean(bool),
+    Str(String),
 }
 
 #[derive(Debug)]
@@ -155,7 +272,13 @@ pub struct Literal(SyntaxNode);
 impl Literal {
     pub fn parse(&self) -> Result<LiteralValue, AstError> {
         let text = self.0.first_token().unwrap().text().to_string();
-        if text.ends_with('L') {
+        if self.0.kind() == SyntaxKind::BoolLiteral {
+            text.parse().ok().map(LiteralValue::Boolean)
+        } else if self.0.kind() == SyntaxKind::StringLiteral {
+            text.strip_prefix('"')
+                .and_then(|t| t.strip_suffix('"'))
+                .map(|t| LiteralValue::Str(t.to_string()))
+        } else if text.ends_with('L') {
             text.strip_suffix('L')
                 .unwrap()
                 .parse()
@@ -177,27 +300,185 @@ impl Literal {
     }
 }
 
-// #[derive(Debug)]
-// pub struct ParenExpr(SyntaxNode);
+#[derive(Debug)]
+pub struct ParenExpr(SyntaxNode);
 
-// impl ParenExpr {
-//     pub fn expr(&self) -> Option<Expr> {
-//         self.0.children().find_map(Expr::cast)
-//     }
-// }
+impl ParenExpr {
+    pub fn expr(&self) -> Result<Expr, AstError> {
+        self.0.children().find_map(Expr::cast).ok_or_else(|| {
+            AstError::new(
+                format!("Cannot find expr in {:?}", self.0),
+                self.0.text_range(),
+            )
+        })
+    }
+}
 
-// #[derive(Debug)]
-// pub struct UnaryExpr(SyntaxNode);
+#[derive(Debug)]
+pub struct BlockExpr(SyntaxNode);
 
-// impl UnaryExpr {
-//     pub fn expr(&self) -> Option<Expr> {
-//         self.0.children().find_map(Expr::cast)
-//     }
+impl BlockExpr {
+    pub fn statements(&self) -> impl Iterator<Item = Stmt> {
+        self.0.children().filter_map(Stmt::cast)
+    }
 
-//     pub fn op(&self) -> Option<SyntaxToken> {
-//         self.0
-//             .children_with_tokens()
-//             .filter_map(SyntaxElement::into_token)
-//             .find(|token| token.kind() == SyntaxKind::Minus)
-//     }
-// }
+    pub fn span(&self) -> TextRange {
+        self.0.text_range()
+    }
+}
+
+#[derive(Debug)]
+pub struct LambdaExpr(SyntaxNode);
+
+impl LambdaExpr {
+    pub fn args(&self) -> impl Iterator<Item = LambdaArg> {
+        self.0
+            .children()
+            .filter(|node| node.kind() == SyntaxKind::LambdaArg)
+            .map(LambdaArg)
+    }
+
+    pub fn body(&self) -> Result<Expr, AstError> {
+        self.0.children().find_map(Expr::cast).ok_or_else(|| {
+            AstError::new(
+                format!("Cannot find lambda body in {:?}", self.0),
+                self.span(),
+            )
+        })
+    }
+
+    pub fn span(&self) -> TextRange {
+        self.0.text_range()
+    }
+}
+
+#[derive(Debug)]
+pub struct LambdaArg(SyntaxNode);
+
+impl LambdaArg {
+    pub fn name(&self) -> Result<SyntaxToken, AstError> {
+        first_ident_token(&self.0)
+    }
+
+    pub fn tpe(&self) -> Result<TypeRef, AstError> {
+        self.0.children().find_map(TypeRef::cast).ok_or_else(|| {
+            AstError::new(
+                format!("Cannot find type of the argument in {:?}", self.0),
+                self.span(),
+            )
+        })
+    }
+
+    pub fn span(&self) -> TextRange {
+        self.0.text_range()
+    }
+}
+
+#[derive(Debug)]
+pub struct CallExpr(SyntaxNode);
+
+impl CallExpr {
+    pub fn func(&self) -> Result<Expr, AstError> {
+        self.0.children().find_map(Expr::cast).ok_or_else(|| {
+            AstError::new(format!("Cannot find function in {:?}", self.0), self.span())
+        })
+    }
+
+    pub fn args(&self) -> Vec<Expr> {
+        self.0
+            .children()
+            .find(|node| node.kind() == SyntaxKind::ArgList)
+            .map(|args| args.children().filter_map(Expr::cast).collect())
+            .unwrap_or_default()
+    }
+
+    pub fn span(&self) -> TextRange {
+        self.0.text_range()
+    }
+}
+
+#[derive(Debug)]
+pub struct UnaryExpr(SyntaxNode);
+
+impl UnaryExpr {
+    pub fn expr(&self) -> Result<Expr, AstError> {
+        self.0.children().find_map(Expr::cast).ok_or_else(|| {
+            AstError::new(format!("Cannot find operand in {:?}", self.0), self.span())
+        })
+    }
+
+    pub fn op(&self) -> Result<SyntaxToken, AstError> {
+        self.0
+            .children_with_tokens()
+            .filter_map(SyntaxElement::into_token)
+            .find(|token| {
+                matches!(
+                    token.kind(),
+                    SyntaxKind::Minus | SyntaxKind::Bang | SyntaxKind::Tilde
+                )
+            })
+            .ok_or_else(|| {
+                AstError::new(format!("Cannot find unary op in {:?}", self.0), self.span())
+            })
+    }
+
+    pub fn span(&self) -> TextRange {
+        self.0.text_range()
+    }
+}
+
+#[derive(Debug)]
+pub struct IfExpr(SyntaxNode);
+
+impl IfExpr {
+    pub fn condition(&self) -> Result<Expr, AstError> {
+        self.nth_expr(0, "condition")
+    }
+
+    pub fn then_branch(&self) -> Result<Expr, AstError> {
+        self.nth_expr(1, "then branch")
+    }
+
+    pub fn else_branch(&self) -> Result<Expr, AstError> {
+        self.nth_expr(2, "else branch")
+    }
+
+    fn nth_expr(&self, n: usize, what: &str) -> Result<Expr, AstError> {
+        self.0
+            .children()
+            .filter_map(Expr::cast)
+            .nth(n)
+            .ok_or_else(|| {
+                AstError::new(format!("Cannot find {} in {:?}", what, self.0), self.span())
+            })
+    }
+
+    pub fn span(&self) -> TextRange {
+        self.0.text_range()
+    }
+}
+
+#[derive(Debug)]
+pub struct SelectExpr(SyntaxNode);
+
+impl SelectExpr {
+    pub fn obj(&self) -> Result<Expr, AstError> {
+        self.0.children().find_map(Expr::cast).ok_or_else(|| {
+            AstError::new(format!("Cannot find object in {:?}", self.0), self.span())
+        })
+    }
+
+    /// Name of the field/method
+    pub fn field(&self) -> Result<SyntaxToken, AstError> {
+        first_ident_token(&self.0)
+    }
+
+    /// Type arguments, e.g. `Long` in `SELF.R4[Long]`
+    pub fn tpe_args(&self) -> impl Iterator<Item = TypeRef> {
+        self.0.children().filter_map(TypeRef::cast)
+    }
+
+    pub fn span(&self) -> TextRange {
+        self.0.text_range()
+    }
+}
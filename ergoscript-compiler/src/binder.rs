@@ -1,10 +1,37 @@
+use ergotree_ir::chain::address::Address;
+use ergotree_ir::chain::address::AddressEncoder;
+use ergotree_ir::mir::constant::Constant;
+use ergotree_ir::mir::constant::ConstantPlaceholder;
+use ergotree_ir::types::stype::SType;
 use rowan::TextRange;
+use rowan::TextSize;
+use std::cell::RefCell;
+use std::convert::Infallible;
 
+use crate::diagnostic::Diagnostic;
+use crate::diagnostic::DiagnosticCode;
 use crate::error::pretty_error_desc;
 use crate::hir;
+use crate::hir::Apply;
+use crate::hir::Binary;
+use crate::hir::BinaryOp;
+use crate::hir::BindingId;
+use crate::hir::Block;
+use crate::hir::Collection;
 use crate::hir::Expr;
 use crate::hir::ExprKind;
 use crate::hir::GlobalVars;
+use crate::hir::If;
+use crate::hir::Lambda;
+use crate::hir::LambdaArg;
+use crate::hir::Literal;
+use crate::hir::MethodCall;
+use crate::hir::PredefFunc;
+use crate::hir::Select;
+use crate::hir::Spanned;
+use crate::hir::Unary;
+use crate::hir::Val;
+use crate::hir::ValUse;
 use crate::script_env::ScriptEnv;
 
 #[derive(Debug, PartialEq, Eq)]
@@ -21,41 +48,440 @@ impl BinderError {
     pub fn pretty_desc(&self, source: &str) -> String {
         pretty_error_desc(source, self.span, &self.msg)
     }
+
+    pub fn diagnostic(&self) -> Diagnostic {
+        Diagnostic::new(
+            DiagnosticCode::BinderError,
+            self.msg.clone(),
+            Some(self.span),
+        )
+    }
 }
 
 pub struct Binder {
     env: ScriptEnv,
+    /// Substitute the environment values with constant placeholders (instead of constants)
+    placeholders: bool,
 }
 
 impl Binder {
     pub fn new(env: ScriptEnv) -> Self {
-        Binder { env }
+        Binder {
+            env,
+            placeholders: false,
+        }
+    }
+
+    /// Environment values are substituted as constant placeholders, with their index in the
+    /// environment as the constant index
+    pub fn with_placeholders(self) -> Self {
+        Binder {
+            placeholders: true,
+            ..self
+        }
+    }
+
+    /// Resolves the identifiers, reporting all the errors (ordered by position)
+    pub fn bind(&self, expr: Expr) -> Result<Expr, Vec<BinderError>> {
+        let (bound, mut errors) = self.bind_all(expr);
+        if errors.is_empty() {
+            Ok(bound)
+        } else {
+            errors.sort_by_key(|e| e.span.start());
+            Err(errors)
+        }
+    }
+
+    /// Binds what can be bound ignoring the errors, the identifiers that can't be resolved are
+    /// left as is (for the IDE, to analyze a source with errors)
+    pub fn bind_partial(&self, expr: Expr) -> Expr {
+        self.bind_all(expr).0
+    }
+
+    fn bind_all(&self, expr: Expr) -> (Expr, Vec<BinderError>) {
+        let mut errors = Vec::new();
+        check_is_proven(&expr, true, &mut errors);
+        let mut scope = Scope::default();
+        let scoped = bind_scoped(expr, &mut scope);
+        errors.append(&mut scope.errors);
+        let rewrite_errors = RefCell::new(Vec::new());
+        let rewritten = rewrite(scoped, &self.env, self.placeholders, &rewrite_errors);
+        errors.extend(rewrite_errors.into_inner());
+        check_unresolved(&rewritten, &scope.out_of_scope, &mut errors);
+        (rewritten, errors)
+    }
+}
+
+/// Values (`val` and lambda arguments) visible at the current point
+#[derive(Default)]
+struct Scope {
+    /// Innermost last
+    visible: Vec<(String, BindingId)>,
+    /// Names of the values that went out of scope
+    out_of_scope: Vec<String>,
+    /// Errors found so far (binding goes on after an error)
+    errors: Vec<BinderError>,
+    next_id: u32,
+}
+
+impl Scope {
+    fn define(&mut self, name: &str) -> BindingId {
+        let id = BindingId(self.next_id);
+        self.next_id += 1;
+        self.visible.push((name.to_string(), id));
+        id
+    }
+
+    /// Drops the values defined after the first `outer_len` ones (at the end of a block/lambda)
+    fn leave(&mut self, outer_len: usize) {
+        let inner = self.visible.split_off(outer_len);
+        self.out_of_scope
+            .extend(inner.into_iter().map(|(name, _)| name));
     }
 
-    pub fn bind(&self, expr: Expr) -> Result<Expr, BinderError> {
-        rewrite(expr, &self.env)
+    fn lookup(&self, name: &str) -> Option<BindingId> {
+        self.visible
+            .iter()
+            .rev()
+            .find(|(n, _)| n == name)
+            .map(|(_, id)| *id)
+    }
+
+    /// Checks that the names are not defined twice in the same block/lambda
+    fn check_unique<'a>(&mut self, names: impl Iterator<Item = &'a Spanned<String>>) {
+        let mut seen: Vec<&str> = Vec::new();
+        for name in names {
+            if seen.contains(&name.node.as_str()) {
+                self.errors.push(BinderError::new(
+                    format!("{} is already defined", name.node),
+                    name.span,
+                ));
+            }
+            seen.push(&name.node);
+        }
     }
 }
 
-fn rewrite(expr: Expr, env: &ScriptEnv) -> Result<Expr, BinderError> {
-    hir::rewrite(expr, |e| {
+/// Resolves identifiers referring to `val`s and lambda arguments (assigning ids to them)
+fn bind_scoped(expr: Expr, scope: &mut Scope) -> Expr {
+    let kind = match expr.kind {
+        ExprKind::Ident(ident) => match scope.lookup(&ident) {
+            Some(id) => ValUse { name: ident, id }.into(),
+            None => ExprKind::Ident(ident),
+        },
+        ExprKind::Binary(binary) => Binary {
+            op: binary.op,
+            lhs: Box::new(bind_scoped(*binary.lhs, scope)),
+            rhs: Box::new(bind_scoped(*binary.rhs, scope)),
+        }
+        .into(),
+        ExprKind::Unary(unary) => Unary {
+            op: unary.op,
+            expr: Box::new(bind_scoped(*unary.expr, scope)),
+        }
+        .into(),
+        ExprKind::If(if_expr) => If {
+            condition: Box::new(bind_scoped(*if_expr.condition, scope)),
+            then_branch: Box::new(bind_scoped(*if_expr.then_branch, scope)),
+            else_branch: Box::new(bind_scoped(*if_expr.else_branch, scope)),
+        }
+        .into(),
+        ExprKind::Block(block) => {
+            scope.check_unique(block.bindings.iter().map(|val| &val.name));
+            let outer_len = scope.visible.len();
+            let mut bindings = Vec::with_capacity(block.bindings.len());
+            for val in block.bindings {
+                // the value is not visible in its own definition
+                let rhs = bind_scoped(*val.rhs, scope);
+                let id = scope.define(&val.name.node);
+                bindings.push(Val {
+                    id: Some(id),
+                    rhs: Box::new(rhs),
+                    ..val
+                });
+            }
+            let body = bind_scoped(*block.body, scope);
+            scope.leave(outer_len);
+            Block {
+                bindings,
+                body: Box::new(body),
+            }
+            .into()
+        }
+        ExprKind::Lambda(lambda) => {
+            scope.check_unique(lambda.args.iter().map(|arg| &arg.name));
+            let outer_len = scope.visible.len();
+            let args = lambda
+                .args
+                .into_iter()
+                .map(|arg| LambdaArg {
+                    id: Some(scope.define(&arg.name.node)),
+                    ..arg
+                })
+                .collect();
+            let body = bind_scoped(*lambda.body, scope);
+            scope.leave(outer_len);
+            Lambda {
+                args,
+                body: Box::new(body),
+            }
+            .into()
+        }
+        ExprKind::Apply(apply) => Apply {
+            func: Box::new(bind_scoped(*apply.func, scope)),
+            args: apply
+                .args
+                .into_iter()
+                .map(|arg| bind_scoped(arg, scope))
+                .collect(),
+        }
+        .into(),
+        ExprKind::Collection(coll) => Collection {
+            items: coll
+                .items
+                .into_iter()
+                .map(|item| bind_scoped(item, scope))
+                .collect(),
+        }
+        .into(),
+        ExprKind::Select(select) => Select {
+            obj: Box::new(bind_scoped(*select.obj, scope)),
+            ..select
+        }
+        .into(),
+        ExprKind::MethodCall(call) => MethodCall {
+            obj: Box::new(bind_scoped(*call.obj, scope)),
+            method: call.method,
+            args: call
+                .args
+                .into_iter()
+                .map(|arg| bind_scoped(arg, scope))
+                .collect(),
+        }
+        .into(),
+        kind @ (ExprKind::GlobalVars(_)
+        | ExprKind::PredefFunc(_)
+        | ExprKind::Literal(_)
+        | ExprKind::Constant(_)
+        | ExprKind::ConstantPlaceholder(_)
+        | ExprKind::ValUse(_)) => kind,
+    };
+    Expr { kind, ..expr }
+}
+
+fn rewrite(
+    expr: Expr,
+    env: &ScriptEnv,
+    placeholders: bool,
+    errors: &RefCell<Vec<BinderError>>,
+) -> Expr {
+    let res: Result<Expr, Infallible> = hir::rewrite(expr, |e| {
         Ok(match &e.kind {
             ExprKind::Ident(ident) => match env.get(ident) {
-                Some(_) => todo!(),
-                None => match ident.as_ref() {
-                    "HEIGHT" => {
-                        let v = GlobalVars::Height;
-                        let tpe = v.tpe();
-                        Some(Expr {
-                            kind: v.into(),
-                            span: e.span,
-                            tpe: tpe.into(),
-                        })
+                Some(value) => {
+                    let tpe = value.tpe.clone();
+                    let kind = match env.index_of(ident) {
+                        Some(id) if placeholders => ConstantPlaceholder {
+                            id: id as u32,
+                            tpe: tpe.clone(),
+                        }
+                        .into(),
+                        _ => value.clone().into(),
+                    };
+                    Some(Expr {
+                        kind,
+                        span: e.span,
+                        tpe: tpe.into(),
+                    })
+                }
+                None => predefined(ident, e.span),
+            },
+            ExprKind::Apply(Apply { func, args }) => match &func.kind {
+                ExprKind::Ident(ident) if ident == "Coll" => Some(Expr {
+                    kind: Collection {
+                        items: args
+                            .iter()
+                            .map(|arg| rewrite(arg.clone(), env, placeholders, errors))
+                            .collect(),
                     }
-                    _ => None,
-                },
+                    .into(),
+                    ..e.clone()
+                }),
+                ExprKind::Ident(ident) if ident == "PK" => report(pk(args, e.span), errors),
+                ExprKind::Ident(ident) if ident == "fromBase16" => {
+                    report(from_base16(args, e.span), errors)
+                }
+                _ => None,
             },
             _ => None,
         })
-    })
+    });
+    match res {
+        Ok(expr) => expr,
+        Err(never) => match never {},
+    }
+}
+
+/// The rewritten node, or `None` (the node is left as is) with the error recorded
+fn report(res: Result<Expr, BinderError>, errors: &RefCell<Vec<BinderError>>) -> Option<Expr> {
+    match res {
+        Ok(expr) => Some(expr),
+        Err(e) => {
+            errors.borrow_mut().push(e);
+            None
+        }
+    }
+}
+
+/// `SigmaProp.isProven` is only allowed in the top-level proposition: as the whole proposition,
+/// an operand of its `&&`/`||` or the result of its block
+fn check_is_proven(expr: &Expr, top_level: bool, errors: &mut Vec<BinderError>) {
+    match &expr.kind {
+        ExprKind::Select(select) if select.field.node == "isProven" => {
+            if !top_level {
+                errors.push(BinderError::new(
+                    "isProven is only allowed in the top-level proposition".to_string(),
+                    select.field.span,
+                ));
+            }
+            check_is_proven(&select.obj, false, errors)
+        }
+        ExprKind::Binary(binary) if matches!(binary.op.node, BinaryOp::And | BinaryOp::Or) => {
+            check_is_proven(&binary.lhs, top_level, errors);
+            check_is_proven(&binary.rhs, top_level, errors)
+        }
+        ExprKind::Block(block) => {
+            for val in &block.bindings {
+                check_is_proven(&val.rhs, false, errors);
+            }
+            check_is_proven(&block.body, top_level, errors)
+        }
+        _ => {
+            for child in expr.children() {
+                check_is_proven(child, false, errors)
+            }
+        }
+    }
+}
+
+/// Reports the identifiers left unresolved after [`rewrite`]: the ones referring to a value
+/// defined in a block or lambda not enclosing the reference are out of scope, the rest are not
+/// defined
+fn check_unresolved(expr: &Expr, out_of_scope: &[String], errors: &mut Vec<BinderError>) {
+    match &expr.kind {
+        ExprKind::Ident(ident) => {
+            let msg = if out_of_scope.contains(ident) {
+                format!("{} is out of scope here", ident)
+            } else {
+                format!("{} is not defined", ident)
+            };
+            errors.push(BinderError::new(
+                msg,
+                TextRange::at(expr.span.start(), TextSize::of(ident.as_str())),
+            ))
+        }
+        // the invalid `PK`/`fromBase16` calls left as is are already reported
+        ExprKind::Apply(Apply { func, args }) if matches!(&func.kind, ExprKind::Ident(ident) if ident == "PK" || ident == "fromBase16") => {
+            for arg in args {
+                check_unresolved(arg, out_of_scope, errors)
+            }
+        }
+        _ => {
+            for child in expr.children() {
+                check_unresolved(child, out_of_scope, errors)
+            }
+        }
+    }
+}
+
+/// Predefined value (`HEIGHT`, `SELF`, `dataInputs`, etc.)
+fn predefined(name: &str, span: TextRange) -> Option<Expr> {
+    match GlobalVars::from_name(name) {
+        Some(v) => {
+            let tpe = v.tpe();
+            Some(Expr {
+                kind: v.into(),
+                span,
+                tpe: tpe.into(),
+            })
+        }
+        // shortcut for `CONTEXT.dataInputs`
+        None if name == "dataInputs" => Some(Expr {
+            kind: Select {
+                obj: Box::new(predefined("CONTEXT", span)?),
+                field: Spanned {
+                    node: name.to_string(),
+                    span,
+                },
+                tpe_args: vec![],
+            }
+            .into(),
+            span,
+            tpe: None,
+        }),
+        None => PredefFunc::from_name(name).map(|func| Expr {
+            tpe: Some(func.tpe().into()),
+            kind: func.into(),
+            span,
+        }),
+    }
+}
+
+/// `PK("address")`, the public key of the P2PK address
+/// (the network prefix is not checked, the key is the same in every network)
+fn pk(args: &[Expr], span: TextRange) -> Result<Expr, BinderError> {
+    let address = match args {
+        [Expr {
+            kind: ExprKind::Literal(Literal::Str(address)),
+            ..
+        }] => address,
+        _ => {
+            return Err(BinderError::new(
+                "Expected an address string, e.g. PK(\"9f...\")".to_string(),
+                span,
+            ))
+        }
+    };
+    match AddressEncoder::unchecked_parse_address_from_str(address) {
+        Ok(Address::P2Pk(pk)) => Ok(Expr {
+            kind: Constant::from(pk).into(),
+            span,
+            tpe: Some(SType::SSigmaProp),
+        }),
+        Ok(_) => Err(BinderError::new(
+            format!("Expected a P2PK address, got {}", address),
+            span,
+        )),
+        Err(e) => Err(BinderError::new(
+            format!("Invalid address {}: {}", address, e),
+            span,
+        )),
+    }
+}
+
+/// `fromBase16("...")`, the decoded bytes as the `Coll[Byte]` constant
+fn from_base16(args: &[Expr], span: TextRange) -> Result<Expr, BinderError> {
+    let hex = match args {
+        [Expr {
+            kind: ExprKind::Literal(Literal::Str(hex)),
+            ..
+        }] => hex,
+        _ => {
+            return Err(BinderError::new(
+                "Expected a Base16 string, e.g. fromBase16(\"0a1b\")".to_string(),
+                span,
+            ))
+        }
+    };
+    match base16::decode(hex.as_bytes()) {
+        Ok(bytes) => Ok(Expr {
+            kind: Constant::from(bytes).into(),
+            span,
+            tpe: Some(SType::SColl(SType::SByte.into())),
+        }),
+        Err(e) => Err(BinderError::new(
+            format!("Invalid Base16 string {}: {}", hex, e),
+            span,
+        )),
+    }
 }
@@ -4,8 +4,14 @@ use super::binder::BinderError;
 use super::hir::HirLoweringError;
 use crate::ast;
 use crate::binder::Binder;
+use crate::diagnostic::Diagnostic;
+use crate::diagnostic::DiagnosticCode;
 use crate::hir;
+use crate::lint::lint;
 use crate::mir;
+use crate::optimizer;
+use crate::optimizer::Optimizations;
+use crate::optimizer::OptimizerError;
 use crate::parser::parse_error::ParseError;
 use crate::script_env::ScriptEnv;
 use crate::type_infer::assign_type;
@@ -16,6 +22,7 @@ extern crate derive_more;
 use derive_more::From;
 use ergotree_ir::ergo_tree::ErgoTree;
 use ergotree_ir::ergo_tree::ErgoTreeError;
+use ergotree_ir::ergo_tree::ErgoTreeHeader;
 use ergotree_ir::type_check::TypeCheckError;
 use mir::lower::MirLoweringError;
 
@@ -26,49 +33,166 @@ pub enum CompileError {
     ParseError(Vec<ParseError>),
     /// Error on AST to HIR lowering
     HirLoweringError(HirLoweringError),
-    /// Error on binder pass
-    BinderError(BinderError),
-    /// Error on type inference pass
-    TypeInferenceError(TypeInferenceError),
-    /// Error on HIT to MIR lowering
-    MirLoweringError(MirLoweringError),
-    /// Error on type checking
+    /// Errors on binder pass
+    BinderError(Vec<BinderError>),
+    /// Errors on type inference pass
+    TypeInferenceError(Vec<TypeInferenceError>),
+    /// Errors on HIT to MIR lowering
+    MirLoweringError(Vec<MirLoweringError>),
+    /// Error on type checking (MIR has no source spans)
     TypeCheckError(TypeCheckError),
     /// ErgoTree error
     ErgoTreeError(ErgoTreeError),
+    /// Error on MIR optimisation
+    OptimizerError(OptimizerError),
 }
 
 impl CompileError {
-    /// Pretty formatted error with CST/AST/IR, etc.
+    /// Pretty formatted error with CST/AST/IR, etc. (one error per line for multiple errors)
     pub fn pretty_desc(&self, source: &str) -> String {
         match self {
             CompileError::ParseError(errors) => {
-                errors.iter().map(|e| e.pretty_desc(source)).collect()
+                join_lines(errors.iter().map(|e| e.pretty_desc(source)))
             }
             CompileError::HirLoweringError(e) => e.pretty_desc(source),
-            CompileError::BinderError(e) => e.pretty_desc(source),
-            CompileError::TypeInferenceError(e) => e.pretty_desc(source),
-            CompileError::MirLoweringError(e) => e.pretty_desc(source),
+            CompileError::BinderError(errors) => {
+                join_lines(errors.iter().map(|e| e.pretty_desc(source)))
+            }
+            CompileError::TypeInferenceError(errors) => {
+                join_lines(errors.iter().map(|e| e.pretty_desc(source)))
+            }
+            CompileError::MirLoweringError(errors) => {
+                join_lines(errors.iter().map(|e| e.pretty_desc(source)))
+            }
             CompileError::TypeCheckError(e) => e.pretty_desc(),
             CompileError::ErgoTreeError(e) => format!("{:?}", e),
+            CompileError::OptimizerError(e) => e.pretty_desc(),
+        }
+    }
+
+    /// Errors as [`Diagnostic`]s (with the source spans, if known)
+    pub fn diagnostics(&self) -> Vec<Diagnostic> {
+        match self {
+            CompileError::ParseError(errors) => errors.iter().map(|e| e.diagnostic()).collect(),
+            CompileError::HirLoweringError(e) => vec![e.diagnostic()],
+            CompileError::BinderError(errors) => errors.iter().map(|e| e.diagnostic()).collect(),
+            CompileError::TypeInferenceError(errors) => {
+                errors.iter().map(|e| e.diagnostic()).collect()
+            }
+            CompileError::MirLoweringError(errors) => {
+                errors.iter().map(|e| e.diagnostic()).collect()
+            }
+            CompileError::TypeCheckError(e) => vec![Diagnostic::new(
+                DiagnosticCode::TypeCheckError,
+                e.pretty_desc(),
+                None,
+            )],
+            CompileError::ErgoTreeError(e) => vec![Diagnostic::new(
+                DiagnosticCode::ErgoTreeError,
+                format!("{:?}", e),
+                None,
+            )],
+            CompileError::OptimizerError(e) => vec![e.diagnostic()],
         }
     }
 }
 
+fn join_lines(descs: impl Iterator<Item = String>) -> String {
+    descs.collect::<Vec<String>>().join("\n")
+}
+
 /// Compiles given source code to [`ergotree_ir::mir::expr::Expr`], or returns an error
 pub fn compile_expr(
     source: &str,
     env: ScriptEnv,
 ) -> Result<ergotree_ir::mir::expr::Expr, CompileError> {
+    compile_expr_with(source, Binder::new(env))
+}
+
+/// Compiles given source code to [`ErgoTree`] with constant segregation, or returns an error.
+/// The environment values are the first constants of the tree (in the order of
+/// [`ScriptEnv::insert`]), so the tree can be reused as a template with other values
+/// (see [`ErgoTree::with_constant`]).
+pub fn compile_segregated(source: &str, env: ScriptEnv) -> Result<ErgoTree, CompileError> {
+    let constants = env.values();
+    let expr = compile_expr_with(source, Binder::new(env).with_placeholders())?;
+    Ok(ErgoTree::with_segregated_constants(
+        ErgoTreeHeader::v0(true),
+        &expr,
+        constants,
+    )?)
+}
+
+/// Compiles given source code to [`ErgoTree`] and runs the given optimisation passes over it
+pub fn compile_optimized(
+    source: &str,
+    env: ScriptEnv,
+    optimizations: &Optimizations,
+) -> Result<ErgoTree, CompileError> {
+    let expr = compile_expr_with(source, Binder::new(env))?;
+    Ok(optimize(expr, optimizations)?.try_into()?)
+}
+
+/// [`compile_segregated`] with the given optimisation passes
+pub fn compile_segregated_optimized(
+    source: &str,
+    env: ScriptEnv,
+    optimizations: &Optimizations,
+) -> Result<ErgoTree, CompileError> {
+    let constants = env.values();
+    let expr = compile_expr_with(source, Binder::new(env).with_placeholders())?;
+    Ok(ErgoTree::with_segregated_constants(
+        ErgoTreeHeader::v0(true),
+        &optimize(expr, optimizations)?,
+        constants,
+    )?)
+}
+
+/// Compiles given source code to [`ErgoTree`], collecting all errors and warnings. Each pass
+/// reports all its errors, the passes after the first failed one are not run.
+pub fn diagnostics(source: &str, env: ScriptEnv) -> Vec<Diagnostic> {
+    let typed = match compile_typed_hir(source, Binder::new(env)) {
+        Ok(typed) => typed,
+        Err(e) => return e.diagnostics(),
+    };
+    let warnings = lint(&typed);
+    let res: Result<ErgoTree, CompileError> =
+        lower_typed_hir(typed).and_then(|expr| Ok(expr.try_into()?));
+    let mut diagnostics = res.err().map(|e| e.diagnostics()).unwrap_or_default();
+    diagnostics.extend(warnings);
+    diagnostics
+}
+
+fn compile_expr_with(
+    source: &str,
+    binder: Binder,
+) -> Result<ergotree_ir::mir::expr::Expr, CompileError> {
+    let typed = compile_typed_hir(source, binder)?;
+    lower_typed_hir(typed)
+}
+
+pub(crate) fn compile_typed_hir(source: &str, binder: Binder) -> Result<hir::Expr, CompileError> {
     let hir = compile_hir(source)?;
-    let binder = Binder::new(env);
     let bind = binder.bind(hir)?;
-    let typed = assign_type(bind)?;
+    Ok(assign_type(bind)?)
+}
+
+fn lower_typed_hir(typed: hir::Expr) -> Result<ergotree_ir::mir::expr::Expr, CompileError> {
     let mir = mir::lower::lower(typed)?;
     let res = ergotree_ir::type_check::type_check(mir)?;
     Ok(res)
 }
 
+fn optimize(
+    expr: ergotree_ir::mir::expr::Expr,
+    optimizations: &Optimizations,
+) -> Result<ergotree_ir::mir::expr::Expr, CompileError> {
+    let tpe = expr.tpe();
+    let optimized = optimizer::optimize(expr, optimizations)?;
+    optimizer::validate(&optimized, &tpe)?;
+    Ok(optimized)
+}
+
 /// Compiles given source code to [`ErgoTree`], or returns an error
 pub fn compile(source: &str, env: ScriptEnv) -> Result<ErgoTree, CompileError> {
     let expr = compile_expr(source, env)?;
@@ -88,7 +212,12 @@ pub(crate) fn compile_hir(source: &str) -> Result<hir::Expr, CompileError> {
 
 #[cfg(test)]
 fn check(input: &str, expected_tree: expect_test::Expect) {
-    let res = compile_expr(input, ScriptEnv::new());
+    check_env(input, ScriptEnv::new(), expected_tree)
+}
+
+#[cfg(test)]
+fn check_env(input: &str, env: ScriptEnv, expected_tree: expect_test::Expect) {
+    let res = compile_expr(input, env);
 
     let expected_out = res
         .map(|tree| tree.debug_tree())
@@ -99,6 +228,19 @@ fn check(input: &str, expected_tree: expect_test::Expect) {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use ergotree_ir::chain::address::Address;
+    use ergotree_ir::chain::address::AddressEncoder;
+    use ergotree_ir::mir::bin_op::ArithOp;
+    use ergotree_ir::mir::bin_op::BinOp;
+    use ergotree_ir::mir::bin_op::BinOpKind;
+    use ergotree_ir::mir::constant::Constant;
+    use ergotree_ir::mir::downcast::Downcast;
+    use ergotree_ir::mir::expr::Expr;
+    use ergotree_ir::mir::global_vars::GlobalVars;
+    use ergotree_ir::pretty_printer::print_expr;
+    use ergotree_ir::serialization::SigmaSerializable;
+    use ergotree_ir::sigma_protocol::sigma_boolean::ProveDlog;
+    use ergotree_ir::types::stype::SType;
     use expect_test::expect;
 
     #[test]
@@ -115,12 +257,1141 @@ mod tests {
     #[test]
     fn test_parser_error() {
         check(
-            "HSB.HEIGHT",
+            "HSB#HEIGHT",
+            expect![[r#"
+                error: expected ‘(’, ‘.’, ‘val’, number, number, string, identifier, ‘true’, ‘false’, ‘-’, ‘!’, ‘~’, ‘if’, ‘(’ or ‘{’, but found an unrecognized token
+                line: 1
+                HSB#HEIGHT
+                   ^"#]],
+        );
+    }
+
+    #[test]
+    fn test_top_level_vals() {
+        check(
+            "val a = 1L\nval b = 2L\na + b",
+            expect![[r#"
+                BlockValue(
+                    BlockValue {
+                        items: [
+                            ValDef(
+                                ValDef {
+                                    id: ValId(
+                                        0,
+                                    ),
+                                    rhs: Const(
+                                        "1: SLong",
+                                    ),
+                                },
+                            ),
+                            ValDef(
+                                ValDef {
+                                    id: ValId(
+                                        1,
+                                    ),
+                                    rhs: Const(
+                                        "2: SLong",
+                                    ),
+                                },
+                            ),
+                        ],
+                        result: BinOp(
+                            BinOp {
+                                kind: Arith(
+                                    Plus,
+                                ),
+                                left: ValUse(
+                                    ValUse {
+                                        val_id: ValId(
+                                            0,
+                                        ),
+                                        tpe: SLong,
+                                    },
+                                ),
+                                right: ValUse(
+                                    ValUse {
+                                        val_id: ValId(
+                                            1,
+                                        ),
+                                        tpe: SLong,
+                                    },
+                                ),
+                            },
+                        ),
+                    },
+                )"#]],
+        );
+    }
+
+    #[test]
+    fn test_shadowing() {
+        check(
+            "{ val x = 1; val f = (x: Long) => x; f(2L) }",
+            expect![[r#"
+                BlockValue(
+                    BlockValue {
+                        items: [
+                            ValDef(
+                                ValDef {
+                                    id: ValId(
+                                        0,
+                                    ),
+                                    rhs: Const(
+                                        "1: SInt",
+                                    ),
+                                },
+                            ),
+                            ValDef(
+                                ValDef {
+                                    id: ValId(
+                                        2,
+                                    ),
+                                    rhs: FuncValue(
+                                        FuncValue {
+                                            args: [
+                                                FuncArg {
+                                                    idx: ValId(
+                                                        1,
+                                                    ),
+                                                    tpe: SLong,
+                                                },
+                                            ],
+                                            body: ValUse(
+                                                ValUse {
+                                                    val_id: ValId(
+                                                        1,
+                                                    ),
+                                                    tpe: SLong,
+                                                },
+                                            ),
+                                            tpe: SFunc(
+                                                SFunc {
+                                                    t_dom: [
+                                                        SLong,
+                                                    ],
+                                                    t_range: SLong,
+                                                    tpe_params: [],
+                                                },
+                                            ),
+                                        },
+                                    ),
+                                },
+                            ),
+                        ],
+                        result: Apply(
+                            Apply {
+                                func: ValUse(
+                                    ValUse {
+                                        val_id: ValId(
+                                            2,
+                                        ),
+                                        tpe: SFunc(
+                                            SFunc {
+                                                t_dom: [
+                                                    SLong,
+                                                ],
+                                                t_range: SLong,
+                                                tpe_params: [],
+                                            },
+                                        ),
+                                    },
+                                ),
+                                args: [
+                                    Const(
+                                        "2: SLong",
+                                    ),
+                                ],
+                                tpe: SLong,
+                            },
+                        ),
+                    },
+                )"#]],
+        );
+    }
+
+    #[test]
+    fn test_val_out_of_scope() {
+        check(
+            "{ val y = { val x = 1; x }; x }",
+            expect![[r#"
+                x is out of scope here
+                line: 1
+                { val y = { val x = 1; x }; x }
+                                            ^"#]],
+        );
+    }
+
+    #[test]
+    fn test_undefined_ident() {
+        check(
+            "{ val x = 1; x > y }",
+            expect![[r#"
+                y is not defined
+                line: 1
+                { val x = 1; x > y }
+                                 ^"#]],
+        );
+    }
+
+    #[test]
+    fn test_multiple_binder_errors() {
+        check(
+            "{ val x = 1; x > y + z }",
+            expect![[r#"
+            y is not defined
+            line: 1
+            { val x = 1; x > y + z }
+                             ^
+            z is not defined
+            line: 1
+            { val x = 1; x > y + z }
+                                 ^"#]],
+        );
+    }
+
+    #[test]
+    fn test_lambda_arg_out_of_scope() {
+        check(
+            "{ val f = { (a: Int) => a + 1 }; f(1) > a }",
+            expect![[r#"
+                a is out of scope here
+                line: 1
+                { val f = { (a: Int) => a + 1 }; f(1) > a }
+                                                        ^"#]],
+        );
+    }
+
+    #[test]
+    fn test_env_value_named_as_out_of_scope_val() {
+        let tree = compile_expr(
+            "{ val y = { val deadline = 1; deadline }; HEIGHT > deadline + y }",
+            test_env(),
+        );
+        assert!(tree.is_ok());
+    }
+
+    #[test]
+    fn test_apply_type_mismatch() {
+        check(
+            "{ val f = (x: Long) => x; f(1) }",
+            expect![[r#"
+                Expected arguments of types [SLong], got [SInt]
+                line: 1
+                { val f = (x: Long) => x; f(1) }
+                                          ^^^^^"#]],
+        );
+    }
+
+    #[test]
+    fn test_duplicate_val() {
+        check(
+            "{ val x = 1; val x = 2; x }",
+            expect![[r#"
+                x is already defined
+                line: 1
+                { val x = 1; val x = 2; x }
+                                 ^"#]],
+        );
+    }
+
+    #[test]
+    fn test_height_condition() {
+        check(
+            "HEIGHT > 100 && !(HEIGHT == 200)",
+            expect![[r#"
+            BinOp(
+                BinOp {
+                    kind: Logical(
+                        And,
+                    ),
+                    left: BinOp(
+                        BinOp {
+                            kind: Relation(
+                                Gt,
+                            ),
+                            left: GlobalVars(
+                                Height,
+                            ),
+                            right: Const(
+                                "100: SInt",
+                            ),
+                        },
+                    ),
+                    right: LogicalNot(
+                        LogicalNot {
+                            input: BinOp(
+                                BinOp {
+                                    kind: Relation(
+                                        Eq,
+                                    ),
+                                    left: GlobalVars(
+                                        Height,
+                                    ),
+                                    right: Const(
+                                        "200: SInt",
+                                    ),
+                                },
+                            ),
+                        },
+                    ),
+                },
+            )"#]],
+        );
+    }
+
+    #[test]
+    fn test_operand_type_mismatch() {
+        check(
+            "HEIGHT > 100L",
+            expect![[r#"
+                Type mismatch: operands of Gt have types SInt and SLong
+                line: 1
+                HEIGHT > 100L
+                         ^^^^"#]],
+        );
+    }
+
+    #[test]
+    fn test_if_branch_type_mismatch() {
+        check(
+            "if (HEIGHT > 100) 1 else 2L",
+            expect![[r#"
+                Type mismatch: branches have types SInt and SLong
+                line: 1
+                if (HEIGHT > 100) 1 else 2L
+                                         ^^"#]],
+        );
+    }
+
+    #[test]
+    fn test_if_condition_type() {
+        check(
+            "if (HEIGHT) 1 else 2",
+            expect![[r#"
+                Expected Boolean condition, got SInt
+                line: 1
+                if (HEIGHT) 1 else 2
+                    ^^^^^^"#]],
+        );
+    }
+
+    #[test]
+    fn test_box_registers() {
+        check(
+            "SELF.R4[Int].isDefined && SELF.R5[Coll[Byte]].get == minerPubKey",
+            expect![[r#"
+                BinOp(
+                    BinOp {
+                        kind: Logical(
+                            And,
+                        ),
+                        left: OptionIsDefined(
+                            OptionIsDefined {
+                                input: ExtractRegisterAs(
+                                    ExtractRegisterAs {
+                                        input: GlobalVars(
+                                            SelfBox,
+                                        ),
+                                        register_id: 4,
+                                        elem_tpe: SInt,
+                                    },
+                                ),
+                            },
+                        ),
+                        right: BinOp(
+                            BinOp {
+                                kind: Relation(
+                                    Eq,
+                                ),
+                                left: OptionGet(
+                                    OptionGet {
+                                        input: ExtractRegisterAs(
+                                            ExtractRegisterAs {
+                                                input: GlobalVars(
+                                                    SelfBox,
+                                                ),
+                                                register_id: 5,
+                                                elem_tpe: SColl(
+                                                    SByte,
+                                                ),
+                                            },
+                                        ),
+                                        elem_tpe: SColl(
+                                            SByte,
+                                        ),
+                                    },
+                                ),
+                                right: GlobalVars(
+                                    MinerPubKey,
+                                ),
+                            },
+                        ),
+                    },
+                )"#]],
+        );
+    }
+
+    #[test]
+    fn test_unknown_method() {
+        check(
+            "SELF.amount",
+            expect![[r#"
+                Unknown method amount of type SBox
+                line: 1
+                SELF.amount
+                     ^^^^^^"#]],
+        );
+    }
+
+    #[test]
+    fn test_register_without_type() {
+        check(
+            "SELF.R4.get",
+            expect![[r#"
+                Expected the type of R4, e.g. R4[Int]
+                line: 1
+                SELF.R4.get
+                     ^^"#]],
+        );
+    }
+
+    #[test]
+    fn test_coll_items_type_mismatch() {
+        check(
+            "Coll(1, 2L)",
+            expect![[r#"
+                Type mismatch: collection items have types SInt and SLong
+                line: 1
+                Coll(1, 2L)
+                        ^^"#]],
+        );
+    }
+
+    #[test]
+    fn test_coll_method_args_mismatch() {
+        check(
+            "{ OUTPUTS.map((x: Int) => x) }",
+            expect![[r#"
+                Expected arguments of types [SFunc(SFunc { t_dom: [SBox], t_range: STypeVar("OV"), tpe_params: [] })], got [SFunc(SFunc { t_dom: [SInt], t_range: SInt, tpe_params: [] })]
+                line: 1
+                { OUTPUTS.map((x: Int) => x) }
+                  ^^^^^^^^^^^^^^^^^^^^^^^^^^^"#]],
+        );
+    }
+
+    #[test]
+    fn test_sum_of_outputs() {
+        check(
+            "OUTPUTS.fold(0L, (acc: Long, b: Box) => acc + b.value) >= 100L",
+            expect![[r#"
+                BinOp(
+                    BinOp {
+                        kind: Relation(
+                            Ge,
+                        ),
+                        left: Fold(
+                            Fold {
+                                input: GlobalVars(
+                                    Outputs,
+                                ),
+                                zero: Const(
+                                    "0: SLong",
+                                ),
+                                fold_op: FuncValue(
+                                    FuncValue {
+                                        args: [
+                                            FuncArg {
+                                                idx: ValId(
+                                                    0,
+                                                ),
+                                                tpe: STuple(
+                                                    [
+                                                        SLong,
+                                                        SBox,
+                                                    ],
+                                                ),
+                                            },
+                                        ],
+                                        body: BlockValue(
+                                            BlockValue {
+                                                items: [
+                                                    ValDef(
+                                                        ValDef {
+                                                            id: ValId(
+                                                                1,
+                                                            ),
+                                                            rhs: SelectField(
+                                                                SelectField {
+                                                                    input: ValUse(
+                                                                        ValUse {
+                                                                            val_id: ValId(
+                                                                                0,
+                                                                            ),
+                                                                            tpe: STuple(
+                                                                                [
+                                                                                    SLong,
+                                                                                    SBox,
+                                                                                ],
+                                                                            ),
+                                                                        },
+                                                                    ),
+                                                                    field_index: TupleFieldIndex(
+                                                                        1,
+                                                                    ),
+                                                                    field_tpe: SLong,
+                                                                },
+                                                            ),
+                                                        },
+                                                    ),
+                                                    ValDef(
+                                                        ValDef {
+                                                            id: ValId(
+                                                                2,
+                                                            ),
+                                                            rhs: SelectField(
+                                                                SelectField {
+                                                                    input: ValUse(
+                                                                        ValUse {
+                                                                            val_id: ValId(
+                                                                                0,
+                                                                            ),
+                                                                            tpe: STuple(
+                                                                                [
+                                                                                    SLong,
+                                                                                    SBox,
+                                                                                ],
+                                                                            ),
+                                                                        },
+                                                                    ),
+                                                                    field_index: TupleFieldIndex(
+                                                                        2,
+                                                                    ),
+                                                                    field_tpe: SBox,
+                                                                },
+                                                            ),
+                                                        },
+                                                    ),
+                                                ],
+                                                result: BinOp(
+                                                    BinOp {
+                                                        kind: Arith(
+                                                            Plus,
+                                                        ),
+                                                        left: ValUse(
+                                                            ValUse {
+                                                                val_id: ValId(
+                                                                    1,
+                                                                ),
+                                                                tpe: SLong,
+                                                            },
+                                                        ),
+                                                        right: ExtractAmount(
+                                                            ExtractAmount {
+                                                                input: ValUse(
+                                                                    ValUse {
+                                                                        val_id: ValId(
+                                                                            2,
+                                                                        ),
+                                                                        tpe: SBox,
+                                                                    },
+                                                                ),
+                                                            },
+                                                        ),
+                                                    },
+                                                ),
+                                            },
+                                        ),
+                                        tpe: SFunc(
+                                            SFunc {
+                                                t_dom: [
+                                                    STuple(
+                                                        [
+                                                            SLong,
+                                                            SBox,
+                                                        ],
+                                                    ),
+                                                ],
+                                                t_range: SLong,
+                                                tpe_params: [],
+                                            },
+                                        ),
+                                    },
+                                ),
+                            },
+                        ),
+                        right: Const(
+                            "100: SLong",
+                        ),
+                    },
+                )"#]],
+        );
+    }
+
+    #[test]
+    fn test_pk() {
+        check(
+            "PK(\"9hzP24a2q8KLPVCUk7gdMDXYc7vinmGuxmLp5KU7k9UwptgYBYV\").isProven && HEIGHT > 100",
+            expect![[r#"
+                SigmaAnd(
+                    SigmaAnd {
+                        items: BoundedVec {
+                            inner: [
+                                Const(
+                                    "SigmaProp(ProofOfKnowledge(ProveDlog(ProveDlog { h: EC:03c91e9783f89b7bf6e5b65cf866180c34b6c2476d8dd442882b4ef0f717d2caba }))): SSigmaProp",
+                                ),
+                                BoolToSigmaProp(
+                                    BoolToSigmaProp {
+                                        input: BinOp(
+                                            BinOp {
+                                                kind: Relation(
+                                                    Gt,
+                                                ),
+                                                left: GlobalVars(
+                                                    Height,
+                                                ),
+                                                right: Const(
+                                                    "100: SInt",
+                                                ),
+                                            },
+                                        ),
+                                    },
+                                ),
+                            ],
+                        },
+                    },
+                )"#]],
+        );
+    }
+
+    #[test]
+    fn test_is_proven_in_block_body() {
+        let pk = "PK(\"9hzP24a2q8KLPVCUk7gdMDXYc7vinmGuxmLp5KU7k9UwptgYBYV\")";
+        assert_eq!(
+            compile_expr(
+                &format!("{{ val h = HEIGHT; h > 100 || {}.isProven }}", pk),
+                ScriptEnv::new()
+            ),
+            compile_expr(
+                &format!("{{ val h = HEIGHT; h > 100 || {} }}", pk),
+                ScriptEnv::new()
+            ),
+        );
+    }
+
+    #[test]
+    fn test_is_proven_not_top_level() {
+        check(
+            "{ val p = PK(\"9hzP24a2q8KLPVCUk7gdMDXYc7vinmGuxmLp5KU7k9UwptgYBYV\").isProven; p }",
+            expect![[r#"
+                isProven is only allowed in the top-level proposition
+                line: 1
+                { val p = PK("9hzP24a2q8KLPVCUk7gdMDXYc7vinmGuxmLp5KU7k9UwptgYBYV").isProven; p }
+                                                                                    ^^^^^^^^"#]],
+        );
+        check(
+            "if (PK(\"9hzP24a2q8KLPVCUk7gdMDXYc7vinmGuxmLp5KU7k9UwptgYBYV\").isProven) HEIGHT > 1 else HEIGHT > 2",
+            expect![[r#"
+                isProven is only allowed in the top-level proposition
+                line: 1
+                if (PK("9hzP24a2q8KLPVCUk7gdMDXYc7vinmGuxmLp5KU7k9UwptgYBYV").isProven) HEIGHT > 1 else HEIGHT > 2
+                                                                              ^^^^^^^^"#]],
+        );
+    }
+
+    #[test]
+    fn test_pk_invalid_address() {
+        check(
+            "{ PK(\"9hzP24\") }",
+            expect![[r#"
+                Invalid address 9hzP24: Invalid size of the decoded byte array
+                line: 1
+                { PK("9hzP24") }
+                  ^^^^^^^^^^^^^"#]],
+        );
+    }
+
+    #[test]
+    fn test_from_base16() {
+        check(
+            "fromBase16(\"0a1b\").size == 2",
+            expect![[r#"
+                BinOp(
+                    BinOp {
+                        kind: Relation(
+                            Eq,
+                        ),
+                        left: SizeOf(
+                            SizeOf {
+                                input: Const(
+                                    "\"0a1b\": SColl(SByte)",
+                                ),
+                            },
+                        ),
+                        right: Const(
+                            "2: SInt",
+                        ),
+                    },
+                )"#]],
+        );
+        check(
+            "{ fromBase16(\"0z\") }",
+            expect![[r#"
+                Invalid Base16 string 0z: Invalid byte `b'z'`, at index 1.
+                line: 1
+                { fromBase16("0z") }
+                  ^^^^^^^^^^^^^^^^^"#]],
+        );
+    }
+
+    #[test]
+    fn test_predef_func_args_mismatch() {
+        check(
+            "{ sigmaProp(1) }",
+            expect![[r#"
+                Expected arguments of types [SBoolean], got [SInt]
+                line: 1
+                { sigmaProp(1) }
+                  ^^^^^^^^^^^^^"#]],
+        );
+    }
+
+    #[test]
+    fn test_compile_pk_tree() {
+        let address = "9hzP24a2q8KLPVCUk7gdMDXYc7vinmGuxmLp5KU7k9UwptgYBYV";
+        let expected = AddressEncoder::unchecked_parse_address_from_str(address)
+            .unwrap()
+            .script()
+            .unwrap();
+        let tree = compile(&format!("PK(\"{}\")", address), ScriptEnv::new()).unwrap();
+        assert_eq!(tree, expected);
+    }
+
+    fn test_pk_value() -> ProveDlog {
+        match AddressEncoder::unchecked_parse_address_from_str(
+            "9hzP24a2q8KLPVCUk7gdMDXYc7vinmGuxmLp5KU7k9UwptgYBYV",
+        )
+        .unwrap()
+        {
+            Address::P2Pk(pk) => pk,
+            _ => unreachable!(),
+        }
+    }
+
+    fn test_env() -> ScriptEnv {
+        let pk = test_pk_value();
+        let mut env = ScriptEnv::new();
+        env.insert("deadline", 100i32.into());
+        env.insert("owner", pk.clone().into());
+        env.insert("hash", vec![1u8, 2, 3].into());
+        env.insert("point", (*pk.h).into());
+        env
+    }
+
+    #[test]
+    fn test_env_constants() {
+        check_env(
+            "{ HEIGHT > deadline && blake2b256(SELF.propositionBytes) == hash && owner && proveDlog(point) }",
+            test_env(),
+            expect![[r#"
+                SigmaAnd(
+                    SigmaAnd {
+                        items: BoundedVec {
+                            inner: [
+                                SigmaAnd(
+                                    SigmaAnd {
+                                        items: BoundedVec {
+                                            inner: [
+                                                BoolToSigmaProp(
+                                                    BoolToSigmaProp {
+                                                        input: BinOp(
+                                                            BinOp {
+                                                                kind: Logical(
+                                                                    And,
+                                                                ),
+                                                                left: BinOp(
+                                                                    BinOp {
+                                                                        kind: Relation(
+                                                                            Gt,
+                                                                        ),
+                                                                        left: GlobalVars(
+                                                                            Height,
+                                                                        ),
+                                                                        right: Const(
+                                                                            "100: SInt",
+                                                                        ),
+                                                                    },
+                                                                ),
+                                                                right: BinOp(
+                                                                    BinOp {
+                                                                        kind: Relation(
+                                                                            Eq,
+                                                                        ),
+                                                                        left: CalcBlake2b256(
+                                                                            CalcBlake2b256 {
+                                                                                input: ExtractScriptBytes(
+                                                                                    ExtractScriptBytes {
+                                                                                        input: GlobalVars(
+                                                                                            SelfBox,
+                                                                                        ),
+                                                                                    },
+                                                                                ),
+                                                                            },
+                                                                        ),
+                                                                        right: Const(
+                                                                            "\"010203\": SColl(SByte)",
+                                                                        ),
+                                                                    },
+                                                                ),
+                                                            },
+                                                        ),
+                                                    },
+                                                ),
+                                                Const(
+                                                    "SigmaProp(ProofOfKnowledge(ProveDlog(ProveDlog { h: EC:03c91e9783f89b7bf6e5b65cf866180c34b6c2476d8dd442882b4ef0f717d2caba }))): SSigmaProp",
+                                                ),
+                                            ],
+                                        },
+                                    },
+                                ),
+                                CreateProveDlog(
+                                    CreateProveDlog {
+                                        input: Const(
+                                            "EC:03c91e9783f89b7bf6e5b65cf866180c34b6c2476d8dd442882b4ef0f717d2caba: SGroupElement",
+                                        ),
+                                    },
+                                ),
+                            ],
+                        },
+                    },
+                )"#]],
+        );
+    }
+
+    #[test]
+    fn test_env_value_replaced() {
+        let mut env = test_env();
+        env.insert("deadline", 200i32.into());
+        assert_eq!(env.index_of("deadline"), Some(0));
+        assert_eq!(env.get("deadline"), Some(&200i32.into()));
+    }
+
+    #[test]
+    fn test_compile_segregated() {
+        let source = "{ HEIGHT > deadline + 1 && owner }";
+        let tree = compile_segregated(source, test_env()).unwrap();
+        let owner: Constant = test_pk_value().into();
+        assert_eq!(
+            tree.get_constants().unwrap(),
+            vec![
+                100i32.into(),
+                owner,
+                vec![1u8, 2, 3].into(),
+                (*test_pk_value().h).into(),
+                1i32.into(),
+            ]
+        );
+        // the same template with another value
+        let mut env = test_env();
+        env.insert("deadline", 200i32.into());
+        let other = compile_segregated(source, env.clone()).unwrap();
+        assert_eq!(
+            other.template_bytes().unwrap(),
+            tree.template_bytes().unwrap()
+        );
+        let tree = tree.with_constant(0, 200i32.into()).unwrap();
+        assert_eq!(tree, other);
+        assert_eq!(
+            tree.proposition().unwrap(),
+            compile_expr(source, env).unwrap()
+        );
+    }
+
+    fn check_diagnostics(input: &str, expected: expect_test::Expect) {
+        let desc = diagnostics(input, ScriptEnv::new())
+            .iter()
+            .map(|d| d.pretty_desc(input))
+            .collect::<Vec<String>>()
+            .join("\n");
+        expected.assert_eq(&desc);
+    }
+
+    #[test]
+    fn test_diagnostics_parse_errors() {
+        check_diagnostics(
+            "{ val a = ; val b = 2 + ; a }",
+            expect![[r#"
+            error[E0001]: expected number, number, string, identifier, ‘true’, ‘false’, ‘-’, ‘!’, ‘~’, ‘if’, ‘(’ or ‘{’, but found ‘;’
+            line: 1
+            { val a = ; val b = 2 + ; a }
+                      ^
+            error[E0001]: expected number, number, string, identifier, ‘true’, ‘false’, ‘-’, ‘!’, ‘~’, ‘if’, ‘(’ or ‘{’, but found ‘;’
+            line: 1
+            { val a = ; val b = 2 + ; a }
+                                    ^"#]],
+        );
+    }
+
+    #[test]
+    fn test_diagnostics_type_error() {
+        check_diagnostics(
+            "{ val a = 1\n a > 1L }",
+            expect![[r#"
+            error[E0004]: Type mismatch: operands of Gt have types SInt and SLong
+            line: 2
+             a > 1L }
+                 ^^^"#]],
+        );
+    }
+
+    #[test]
+    fn test_diagnostics_type_errors() {
+        check_diagnostics(
+            "{ val a = HEIGHT > 1L\n val b = HEIGHT + true\n a && b }",
+            expect![[r#"
+                error[E0004]: Type mismatch: operands of Gt have types SInt and SLong
+                line: 1
+                { val a = HEIGHT > 1L
+                                   ^^
+                error[E0004]: Type mismatch: operands of Plus have types SInt and SBoolean
+                line: 2
+                 val b = HEIGHT + true
+                                  ^^^^"#]],
+        );
+    }
+
+    #[test]
+    fn test_diagnostics_type_errors_in_one_expression() {
+        check_diagnostics(
+            "(HEIGHT + true == 1 + 1L) && (if (1) true else 1) && Coll(1, true, 2L).size > 0",
+            expect![[r#"
+                error[E0004]: Type mismatch: operands of Plus have types SInt and SBoolean
+                line: 1
+                (HEIGHT + true == 1 + 1L) && (if (1) true else 1) && Coll(1, true, 2L).size > 0
+                          ^^^^^
+                error[E0004]: Type mismatch: operands of Plus have types SInt and SLong
+                line: 1
+                (HEIGHT + true == 1 + 1L) && (if (1) true else 1) && Coll(1, true, 2L).size > 0
+                                      ^^
+                error[E0004]: Expected Boolean condition, got SInt
+                line: 1
+                (HEIGHT + true == 1 + 1L) && (if (1) true else 1) && Coll(1, true, 2L).size > 0
+                                                  ^
+                error[E0004]: Type mismatch: branches have types SBoolean and SInt
+                line: 1
+                (HEIGHT + true == 1 + 1L) && (if (1) true else 1) && Coll(1, true, 2L).size > 0
+                                                               ^
+                error[E0004]: Type mismatch: collection items have types SInt and SBoolean
+                line: 1
+                (HEIGHT + true == 1 + 1L) && (if (1) true else 1) && Coll(1, true, 2L).size > 0
+                                                                             ^^^^
+                error[E0004]: Type mismatch: collection items have types SInt and SLong
+                line: 1
+                (HEIGHT + true == 1 + 1L) && (if (1) true else 1) && Coll(1, true, 2L).size > 0
+                                                                                   ^^"#]],
+        );
+    }
+
+    #[test]
+    fn test_diagnostics_binder_errors() {
+        check_diagnostics(
+            "{ val x = 1; val x = 2; PK(\"abc\") }",
+            expect![[r#"
+                error[E0003]: x is already defined
+                line: 1
+                { val x = 1; val x = 2; PK("abc") }
+                                 ^
+                error[E0003]: Invalid address abc: Invalid size of the decoded byte array
+                line: 1
+                { val x = 1; val x = 2; PK("abc") }
+                                        ^^^^^^^^^^"#]],
+        );
+    }
+
+    #[test]
+    fn test_diagnostics_uses_of_failed_val_with_declared_type() {
+        check_diagnostics(
+            "{ val a: Int = true\n val b = a > 1L\n b }",
+            expect![[r#"
+                error[E0004]: Type mismatch: a is declared as SInt, but has type SBoolean
+                line: 1
+                { val a: Int = true
+                               ^^^^
+                error[E0004]: Type mismatch: operands of Gt have types SInt and SLong
+                line: 2
+                 val b = a > 1L
+                             ^^"#]],
+        );
+    }
+
+    #[test]
+    fn test_diagnostics_warnings() {
+        check_diagnostics(
+            "{ val unused = 1; if (true) HEIGHT > 1 else HEIGHT > 2 }",
             expect![[r#"
-                error: expected ‘+’, ‘-’, ‘*’, ‘/’, ‘val’, number, number, identifier, ‘-’ or ‘(’, but found an unrecognized token
+                warning[W0001]: Unused val unused
+                line: 1
+                { val unused = 1; if (true) HEIGHT > 1 else HEIGHT > 2 }
+                      ^^^^^^
+                warning[W0002]: Condition is always true
+                line: 1
+                { val unused = 1; if (true) HEIGHT > 1 else HEIGHT > 2 }
+                                      ^^^^
+                warning[W0003]: Unreachable branch
                 line: 1
-                HSB.HEIGHT
-                  ^^"#]],
+                { val unused = 1; if (true) HEIGHT > 1 else HEIGHT > 2 }
+                                                            ^^^^^^^^^^^"#]],
+        );
+    }
+
+    #[test]
+    fn test_diagnostics_fold_args_are_not_unused() {
+        check_diagnostics(
+            "OUTPUTS.fold(0L, (acc: Long, b: Box) => acc + 1L) > 1L",
+            expect![[r#""#]],
+        );
+    }
+
+    /// Decompiles the tree and checks that the decompiled source compiles back to an expression
+    /// with the same source (constants like `GroupElement` are compiled from the expressions
+    /// producing them, so the expression itself may differ)
+    fn check_decompile(tree: ErgoTree, expected: expect_test::Expect) {
+        let decompiled = tree.to_ergoscript().unwrap();
+        expected.assert_eq(&decompiled);
+        let recompiled = compile_expr(&decompiled, ScriptEnv::new()).unwrap();
+        assert_eq!(print_expr(&recompiled, &[]).unwrap(), decompiled);
+    }
+
+    #[test]
+    fn test_decompile() {
+        check_decompile(
+            compile(
+                "HEIGHT > 100 && !(HEIGHT == 200 || HEIGHT < 1)",
+                ScriptEnv::new(),
+            )
+            .unwrap(),
+            expect!["HEIGHT > 100 && !(HEIGHT == 200 || HEIGHT < 1)"],
+        );
+    }
+
+    #[test]
+    fn test_decompile_vals_and_lambdas() {
+        check_decompile(
+            compile(
+                "{ val f = (x: Long) => x + 1L; val y = f(SELF.value); sigmaProp(OUTPUTS.fold(0L, (acc: Long, b: Box) => acc + b.value) >= y) }",
+                ScriptEnv::new(),
+            )
+            .unwrap(),
+            expect![[r#"
+                {
+                  val v1 = (v0: Long) => v0 + 1L
+                  val v2 = v1(SELF.value)
+                  sigmaProp(OUTPUTS.fold(0L, (v4: Long, v5: Box) => v4 + v5.value) >= v2)
+                }"#]],
         );
     }
+
+    #[test]
+    fn test_decompile_segregated_constants() {
+        check_decompile(
+            compile_segregated(
+                "{ HEIGHT > deadline && blake2b256(SELF.propositionBytes) == hash && owner && proveDlog(point) }",
+                test_env(),
+            )
+            .unwrap(),
+            expect![[r#"sigmaProp(HEIGHT > 100 && blake2b256(SELF.propositionBytes) == fromBase16("010203")) && proveDlog(decodePoint(fromBase16("03c91e9783f89b7bf6e5b65cf866180c34b6c2476d8dd442882b4ef0f717d2caba"))) && proveDlog(decodePoint(fromBase16("03c91e9783f89b7bf6e5b65cf866180c34b6c2476d8dd442882b4ef0f717d2caba")))"#]],
+        );
+    }
+
+    #[test]
+    fn test_decompile_unsupported_syntax() {
+        // printed in the syntax of the reference compiler, which is not supported here (see
+        // `ergotree_ir::pretty_printer`)
+        let height = || Expr::from(GlobalVars::Height);
+        let bin_op = |kind: BinOpKind, right: i32| -> Expr {
+            BinOp {
+                kind,
+                left: height().into(),
+                right: Box::new(Constant::from(right).into()),
+            }
+            .into()
+        };
+        let exprs = vec![
+            bin_op(ArithOp::Modulo.into(), 2),
+            bin_op(ArithOp::Max.into(), 10),
+            bin_op(ArithOp::Min.into(), 10),
+            Downcast::new(height(), SType::SShort).unwrap().into(),
+            Constant::from(5i8).into(),
+        ];
+        let decompiled: Vec<String> = exprs
+            .iter()
+            .map(|expr| print_expr(expr, &[]).unwrap())
+            .collect();
+        expect![[r#"
+            HEIGHT % 2
+            max(HEIGHT, 10)
+            min(HEIGHT, 10)
+            HEIGHT.toShort
+            5.toByte"#]]
+        .assert_eq(&decompiled.join("\n"));
+        for source in &decompiled {
+            assert!(
+                compile_expr(source, ScriptEnv::new()).is_err(),
+                "{}",
+                source
+            );
+        }
+    }
+
+    #[test]
+    fn test_compile_optimized() {
+        let source = "{ val limit = 10L * 1000L; val unused = SELF.R5[Int].get; val f = (x: Long) => x + 1L; sigmaProp(true) && sigmaProp(OUTPUTS(0).R4[Long].get > f(limit) && OUTPUTS(0).R4[Long].get < limit * 2L && !(HEIGHT <= 100)) }";
+        let optimized =
+            compile_optimized(source, ScriptEnv::new(), &Optimizations::default()).unwrap();
+        expect![[r#"
+            {
+              val v1 = OUTPUTS(0).R4[Long].get
+              val v0 = 10000L
+              sigmaProp(v1 > v0 + 1L && v1 < v0 * 2L && HEIGHT > 100)
+            }"#]]
+        .assert_eq(&optimized.to_ergoscript().unwrap());
+        let bytes = optimized.sigma_serialize_bytes().unwrap();
+        assert_eq!(ErgoTree::sigma_parse_bytes(&bytes).unwrap(), optimized);
+        let unoptimized = compile(source, ScriptEnv::new()).unwrap();
+        assert!(bytes.len() < unoptimized.sigma_serialize_bytes().unwrap().len());
+    }
+
+    #[test]
+    fn test_compile_optimized_switched_passes() {
+        let source = "{ val unused = SELF.R5[Int].get; sigmaProp(HEIGHT > 10 * 10) }";
+        assert_eq!(
+            compile_optimized(source, ScriptEnv::new(), &Optimizations::none()),
+            compile(source, ScriptEnv::new())
+        );
+        let folding_only = Optimizations {
+            constant_folding: true,
+            ..Optimizations::none()
+        };
+        expect![[r#"
+            {
+              val v0 = SELF.R5[Int].get
+              sigmaProp(HEIGHT > 100)
+            }"#]]
+        .assert_eq(
+            &compile_optimized(source, ScriptEnv::new(), &folding_only)
+                .unwrap()
+                .to_ergoscript()
+                .unwrap(),
+        );
+    }
+
+    #[test]
+    fn test_compile_segregated_optimized() {
+        let tree = compile_segregated_optimized(
+            "{ HEIGHT > deadline + 10 && owner }",
+            test_env(),
+            &Optimizations::default(),
+        )
+        .unwrap();
+        expect![[r#"sigmaProp(HEIGHT > 100 + 10) && proveDlog(decodePoint(fromBase16("03c91e9783f89b7bf6e5b65cf866180c34b6c2476d8dd442882b4ef0f717d2caba")))"#]].assert_eq(&tree.to_ergoscript().unwrap());
+    }
 }
@@ -0,0 +1,122 @@
+//! Compiler errors and warnings in a machine-readable form (for editors, CI, etc.)
+
+use std::fmt;
+
+use rowan::TextRange;
+
+use crate::error::pretty_error_desc;
+
+/// Severity of a [`Diagnostic`]
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Severity {
+    /// The source cannot be compiled
+    Error,
+    /// The source compiles, but is likely to contain a mistake
+    Warning,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Error => write!(f, "error"),
+            Severity::Warning => write!(f, "warning"),
+        }
+    }
+}
+
+/// Kind of a [`Diagnostic`]
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum DiagnosticCode {
+    /// Syntax error
+    ParseError,
+    /// Error on AST to HIR lowering
+    HirLoweringError,
+    /// Error on binder pass
+    BinderError,
+    /// Error on type inference pass
+    TypeInferenceError,
+    /// Error on HIR to MIR lowering
+    MirLoweringError,
+    /// Error on type checking
+    TypeCheckError,
+    /// ErgoTree error
+    ErgoTreeError,
+    /// Error on MIR optimisation
+    OptimizerError,
+    /// `val` is never used
+    UnusedVal,
+    /// `if` condition is always `true` or always `false`
+    ConstantCondition,
+    /// `if` branch is never evaluated
+    UnreachableBranch,
+}
+
+impl DiagnosticCode {
+    /// Stable identifier of the code (`E0001`, `W0001`, etc.)
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DiagnosticCode::ParseError => "E0001",
+            DiagnosticCode::HirLoweringError => "E0002",
+            DiagnosticCode::BinderError => "E0003",
+            DiagnosticCode::TypeInferenceError => "E0004",
+            DiagnosticCode::MirLoweringError => "E0005",
+            DiagnosticCode::TypeCheckError => "E0006",
+            DiagnosticCode::ErgoTreeError => "E0007",
+            DiagnosticCode::OptimizerError => "E0008",
+            DiagnosticCode::UnusedVal => "W0001",
+            DiagnosticCode::ConstantCondition => "W0002",
+            DiagnosticCode::UnreachableBranch => "W0003",
+        }
+    }
+
+    /// Severity of the diagnostics with this code
+    pub fn severity(&self) -> Severity {
+        match self {
+            DiagnosticCode::UnusedVal
+            | DiagnosticCode::ConstantCondition
+            | DiagnosticCode::UnreachableBranch => Severity::Warning,
+            _ => Severity::Error,
+        }
+    }
+}
+
+impl fmt::Display for DiagnosticCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// Compiler error or warning
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Diagnostic {
+    /// Kind
+    pub code: DiagnosticCode,
+    /// Severity (defined by the code)
+    pub severity: Severity,
+    /// Human-readable description
+    pub message: String,
+    /// Source span (`None` for errors found after the source positions are lost, e.g. on type
+    /// checking)
+    pub span: Option<TextRange>,
+}
+
+impl Diagnostic {
+    /// Diagnostic with the severity of the given code
+    pub fn new(code: DiagnosticCode, message: String, span: Option<TextRange>) -> Self {
+        Diagnostic {
+            code,
+            severity: code.severity(),
+            message,
+            span,
+        }
+    }
+
+    /// Pretty formatted diagnostic with the highlighted span of the source
+    pub fn pretty_desc(&self, source: &str) -> String {
+        let msg = format!("{}[{}]: {}", self.severity, self.code, self.message);
+        match self.span {
+            Some(span) => pretty_error_desc(source, span, &msg),
+            None => msg,
+        }
+    }
+}
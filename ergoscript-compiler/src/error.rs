@@ -3,21 +3,83 @@ use rowan::TextRange;
 
 pub fn pretty_error_desc(source: &str, span: TextRange, error_msg: &str) -> String {
     let line_col_lookup = LineColLookup::new(source);
-    let start_zero_based: usize = usize::from(span.start()) - 1;
-    let end_zero_based: usize = usize::from(span.end()) - 1;
-    let (line_start, col_start) = line_col_lookup.get(start_zero_based);
-    let (line_end, col_end) = line_col_lookup.get(end_zero_based);
-    if line_end != line_start {
-        return "Multiline error spans are not yet supported".to_string();
-    }
-    let source_line = source.lines().nth(line_start - 1).unwrap();
-    let highlight = format!("{0:^>span$}", "^", span = col_end - col_start + 1);
+    let start: usize = usize::from(span.start()).min(source.len());
+    // the last character of the span (the first one for an empty span)
+    let last: usize = usize::from(span.end()).max(start + 1) - 1;
+    let (line_start, col_start) = line_col_lookup.get(start);
+    let (line_end, col_end) = line_col_lookup.get(last.min(source.len()));
+    let source_line = source.lines().nth(line_start - 1).unwrap_or("");
+    // multiline spans are highlighted up to the end of the first line
+    let col_end = if line_end != line_start {
+        source_line.len().max(col_start)
+    } else {
+        col_end
+    };
+    let highlight = "^".repeat(col_end - col_start + 1);
     format!(
         "{0}\nline: {1}\n{2}\n{3:>ident$}",
         error_msg,
         line_start,
         source_line,
         highlight,
-        ident = col_start + 1,
+        ident = col_start - 1 + highlight.len(),
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use expect_test::expect;
+
+    #[test]
+    fn highlight_span() {
+        let desc = pretty_error_desc("HEIGHT > 100L", TextRange::new(0.into(), 6.into()), "err");
+        expect![[r#"
+            err
+            line: 1
+            HEIGHT > 100L
+            ^^^^^^"#]]
+        .assert_eq(&desc);
+    }
+
+    #[test]
+    fn highlight_span_on_second_line() {
+        let desc = pretty_error_desc(
+            "val a = 1\na + true",
+            TextRange::new(14.into(), 18.into()),
+            "err",
+        );
+        expect![[r#"
+            err
+            line: 2
+            a + true
+                ^^^^"#]]
+        .assert_eq(&desc);
+    }
+
+    #[test]
+    fn highlight_empty_span_at_end() {
+        let desc = pretty_error_desc("HEIGHT >", TextRange::new(8.into(), 8.into()), "err");
+        expect![[r#"
+            err
+            line: 1
+            HEIGHT >
+                    ^"#]]
+        .assert_eq(&desc);
+    }
+
+    #[test]
+    fn highlight_multiline_span() {
+        let desc = pretty_error_desc(
+            "{ val a = 1\na }",
+            TextRange::new(2.into(), 13.into()),
+            "err",
+        );
+        expect![[r#"
+            err
+            line: 1
+            { val a = 1
+              ^^^^^^^^^"#]]
+        .assert_eq(&desc);
+    }
+}
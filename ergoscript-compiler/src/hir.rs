@@ -1,13 +1,22 @@
 //! High-level Intermediate Representation
 //! Refered as frontend representation in sigmastate
 
+mod method;
+mod predef_func;
 mod rewrite;
 
+use ergotree_ir::mir::constant::Constant;
+use ergotree_ir::mir::constant::ConstantPlaceholder;
+use ergotree_ir::types::stuple::STuple;
 use ergotree_ir::types::stype::SType;
+pub use method::Method;
+pub use predef_func::PredefFunc;
 pub use rewrite::rewrite;
 
 use super::ast;
 use crate::ast::AstError;
+use crate::diagnostic::Diagnostic;
+use crate::diagnostic::DiagnosticCode;
 use crate::error::pretty_error_desc;
 use crate::syntax::SyntaxKind;
 use text_size::TextRange;
@@ -16,17 +25,84 @@ extern crate derive_more;
 use derive_more::From;
 
 pub fn lower(ast: ast::Root) -> Result<Expr, HirLoweringError> {
-    let exprs: Vec<ast::Expr> = ast.children().collect();
-    if exprs.len() > 1 {
-        return Err(HirLoweringError::new(
-            format!("More than one root expr found: {:?}", exprs),
-            ast.span(),
-        ));
+    let stmts: Vec<ast::Stmt> = ast.statements().collect();
+    if stmts.is_empty() {
+        return Err(
+            AstError::new(format!("Cannot parse empty root: {:?}", ast), ast.span()).into(),
+        );
     }
-    let first_expr = exprs
-        .first()
-        .ok_or_else(|| AstError::new(format!("Cannot parse empty root: {:?}", ast), ast.span()))?;
-    Expr::lower(first_expr)
+    lower_block(stmts, ast.span())
+}
+
+/// Statements of a block (or the root): `val` definitions followed by the result expression
+fn lower_block(mut stmts: Vec<ast::Stmt>, span: TextRange) -> Result<Expr, HirLoweringError> {
+    let body = match stmts.pop() {
+        Some(ast::Stmt::Expr(e)) => Expr::lower(&e)?,
+        Some(ast::Stmt::ValDef(v)) => {
+            return Err(HirLoweringError::new(
+                "Block should end with an expression, found a val definition".to_string(),
+                v.span(),
+            ))
+        }
+        None => return Err(HirLoweringError::new("Empty block".to_string(), span)),
+    };
+    let bindings = stmts
+        .iter()
+        .map(|stmt| match stmt {
+            ast::Stmt::ValDef(v) => Val::lower(v),
+            ast::Stmt::Expr(e) => Err(HirLoweringError::new(
+                "Only val definitions are expected before the result expression".to_string(),
+                e.span(),
+            )),
+        })
+        .collect::<Result<Vec<Val>, _>>()?;
+    if bindings.is_empty() {
+        return Ok(body);
+    }
+    Ok(Expr {
+        kind: Block {
+            bindings,
+            body: Box::new(body),
+        }
+        .into(),
+        span,
+        tpe: None,
+    })
+}
+
+/// Type from its name and type arguments
+fn lower_type(ast: &ast::TypeRef) -> Result<SType, HirLoweringError> {
+    let name = ast.name()?;
+    let args = ast
+        .args()
+        .map(|arg| lower_type(&arg))
+        .collect::<Result<Vec<SType>, _>>()?;
+    let tpe = match (name.text(), args.as_slice()) {
+        ("Unit", []) => SType::SUnit,
+        ("Any", []) => SType::SAny,
+        ("Boolean", []) => SType::SBoolean,
+        ("Byte", []) => SType::SByte,
+        ("Short", []) => SType::SShort,
+        ("Int", []) => SType::SInt,
+        ("Long", []) => SType::SLong,
+        ("BigInt", []) => SType::SBigInt,
+        ("GroupElement", []) => SType::SGroupElement,
+        ("SigmaProp", []) => SType::SSigmaProp,
+        ("Box", []) => SType::SBox,
+        ("AvlTree", []) => SType::SAvlTree,
+        ("Context", []) => SType::SContext,
+        ("Header", []) => SType::SHeader,
+        ("PreHeader", []) => SType::SPreHeader,
+        ("Coll", [elem]) => SType::SColl(elem.clone().into()),
+        ("Option", [elem]) => SType::SOption(elem.clone().into()),
+        _ => {
+            return Err(HirLoweringError::new(
+                format!("Unknown type: {}", ast.text()),
+                ast.span(),
+            ))
+        }
+    };
+    Ok(tpe)
 }
 
 #[derive(Debug, PartialEq, Clone)]
@@ -50,6 +126,14 @@ impl HirLoweringError {
     pub fn pretty_desc(&self, source: &str) -> String {
         pretty_error_desc(source, self.span, &self.msg)
     }
+
+    pub fn diagnostic(&self) -> Diagnostic {
+        Diagnostic::new(
+            DiagnosticCode::HirLoweringError,
+            self.msg.clone(),
+            Some(self.span),
+        )
+    }
 }
 
 impl From<AstError> for HirLoweringError {
@@ -59,10 +143,45 @@ impl From<AstError> for HirLoweringError {
 }
 
 impl Expr {
+    /// Direct subexpressions
+    pub fn children(&self) -> Vec<&Expr> {
+        match &self.kind {
+            ExprKind::Binary(binary) => vec![&binary.lhs, &binary.rhs],
+            ExprKind::Unary(unary) => vec![&unary.expr],
+            ExprKind::If(if_expr) => vec![
+                &if_expr.condition,
+                &if_expr.then_branch,
+                &if_expr.else_branch,
+            ],
+            ExprKind::Block(block) => block
+                .bindings
+                .iter()
+                .map(|val| val.rhs.as_ref())
+                .chain(std::iter::once(block.body.as_ref()))
+                .collect(),
+            ExprKind::Lambda(lambda) => vec![&lambda.body],
+            ExprKind::Apply(apply) => std::iter::once(apply.func.as_ref())
+                .chain(apply.args.iter())
+                .collect(),
+            ExprKind::Collection(coll) => coll.items.iter().collect(),
+            ExprKind::Select(select) => vec![&select.obj],
+            ExprKind::MethodCall(call) => std::iter::once(call.obj.as_ref())
+                .chain(call.args.iter())
+                .collect(),
+            ExprKind::Ident(_)
+            | ExprKind::GlobalVars(_)
+            | ExprKind::PredefFunc(_)
+            | ExprKind::Literal(_)
+            | ExprKind::Constant(_)
+            | ExprKind::ConstantPlaceholder(_)
+            | ExprKind::ValUse(_) => vec![],
+        }
+    }
+
     pub fn lower(expr: &ast::Expr) -> Result<Expr, HirLoweringError> {
         match expr {
             ast::Expr::BinaryExpr(ast) => Ok(Expr {
-                kind: Binary::lower(ast)?.into(),
+                kind: Binary::lower(ast)?,
                 span: ast.span(),
                 tpe: None,
             }),
@@ -87,9 +206,47 @@ impl Expr {
                         span: ast.span(),
                         tpe: Some(SType::SLong),
                     },
+                    ast::LiteralValue::Boolean(v) => Expr {
+                        kind: Literal::Boolean(v).into(),
+                        span: ast.span(),
+                        tpe: Some(SType::SBoolean),
+                    },
+                    // only as an argument of `PK`, so no type
+                    ast::LiteralValue::Str(v) => Expr {
+                        kind: Literal::Str(v).into(),
+                        span: ast.span(),
+                        tpe: None,
+                    },
                 };
                 Ok(expr)
             }
+            ast::Expr::ParenExpr(ast) => Expr::lower(&ast.expr()?),
+            ast::Expr::BlockExpr(ast) => lower_block(ast.statements().collect(), ast.span()),
+            ast::Expr::LambdaExpr(ast) => Ok(Expr {
+                kind: Lambda::lower(ast)?.into(),
+                span: ast.span(),
+                tpe: None,
+            }),
+            ast::Expr::CallExpr(ast) => Ok(Expr {
+                kind: Apply::lower(ast)?.into(),
+                span: ast.span(),
+                tpe: None,
+            }),
+            ast::Expr::UnaryExpr(ast) => Ok(Expr {
+                kind: Unary::lower(ast)?.into(),
+                span: ast.span(),
+                tpe: None,
+            }),
+            ast::Expr::IfExpr(ast) => Ok(Expr {
+                kind: If::lower(ast)?.into(),
+                span: ast.span(),
+                tpe: None,
+            }),
+            ast::Expr::SelectExpr(ast) => Ok(Expr {
+                kind: Select::lower(ast)?.into(),
+                span: ast.span(),
+                tpe: None,
+            }),
         }
     }
 
@@ -114,13 +271,27 @@ pub struct Binary {
 }
 
 impl Binary {
-    fn lower(ast: &ast::BinaryExpr) -> Result<Binary, HirLoweringError> {
+    fn lower(ast: &ast::BinaryExpr) -> Result<ExprKind, HirLoweringError> {
         let syntax_token = ast.op()?;
+        if syntax_token.kind() == SyntaxKind::PlusPlus {
+            return Ok(Binary::lower_append(ast, syntax_token.text_range())?.into());
+        }
         let op = match syntax_token.kind() {
             SyntaxKind::Plus => BinaryOp::Plus,
             SyntaxKind::Minus => BinaryOp::Minus,
             SyntaxKind::Star => BinaryOp::Multiply,
             SyntaxKind::Slash => BinaryOp::Divide,
+            SyntaxKind::EqualsEquals => BinaryOp::Eq,
+            SyntaxKind::NotEquals => BinaryOp::NEq,
+            SyntaxKind::Less => BinaryOp::Lt,
+            SyntaxKind::LessEquals => BinaryOp::Le,
+            SyntaxKind::Greater => BinaryOp::Gt,
+            SyntaxKind::GreaterEquals => BinaryOp::Ge,
+            SyntaxKind::And => BinaryOp::And,
+            SyntaxKind::Or => BinaryOp::Or,
+            SyntaxKind::Caret => BinaryOp::Xor,
+            SyntaxKind::Amp => BinaryOp::BitAnd,
+            SyntaxKind::Pipe => BinaryOp::BitOr,
             _ => {
                 return Err(HirLoweringError::new(
                     format!("unknown binary operator: {:?}", ast.op()),
@@ -139,23 +310,322 @@ impl Binary {
             },
             lhs: Box::new(lhs?),
             rhs: Box::new(rhs?),
+        }
+        .into())
+    }
+
+    /// `xs ++ ys` is the method call `xs.++(ys)`
+    fn lower_append(ast: &ast::BinaryExpr, op_span: TextRange) -> Result<Apply, HirLoweringError> {
+        let lhs = Expr::lower(&ast.lhs()?)?;
+        let rhs = Expr::lower(&ast.rhs()?)?;
+        let select_span = TextRange::new(lhs.span.start(), op_span.end());
+        Ok(Apply {
+            func: Box::new(Expr {
+                kind: Select {
+                    obj: Box::new(lhs),
+                    field: Spanned {
+                        node: "++".to_string(),
+                        span: op_span,
+                    },
+                    tpe_args: vec![],
+                }
+                .into(),
+                span: select_span,
+                tpe: None,
+            }),
+            args: vec![rhs],
+        })
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Unary {
+    pub op: Spanned<UnaryOp>,
+    pub expr: Box<Expr>,
+}
+
+impl Unary {
+    fn lower(ast: &ast::UnaryExpr) -> Result<Unary, HirLoweringError> {
+        let syntax_token = ast.op()?;
+        let op = match syntax_token.kind() {
+            SyntaxKind::Minus => UnaryOp::Neg,
+            SyntaxKind::Bang => UnaryOp::Not,
+            SyntaxKind::Tilde => UnaryOp::BitInversion,
+            _ => {
+                return Err(HirLoweringError::new(
+                    format!("unknown unary operator: {:?}", syntax_token),
+                    syntax_token.text_range(),
+                ))
+            }
+        };
+        Ok(Unary {
+            op: Spanned {
+                node: op,
+                span: syntax_token.text_range(),
+            },
+            expr: Box::new(Expr::lower(&ast.expr()?)?),
+        })
+    }
+}
+
+/// `if (condition) then_branch else else_branch`
+#[derive(Debug, PartialEq, Clone)]
+pub struct If {
+    pub condition: Box<Expr>,
+    pub then_branch: Box<Expr>,
+    pub else_branch: Box<Expr>,
+}
+
+impl If {
+    fn lower(ast: &ast::IfExpr) -> Result<If, HirLoweringError> {
+        Ok(If {
+            condition: Box::new(Expr::lower(&ast.condition()?)?),
+            then_branch: Box::new(Expr::lower(&ast.then_branch()?)?),
+            else_branch: Box::new(Expr::lower(&ast.else_branch()?)?),
         })
     }
 }
 
+/// Id of a value (`val` or lambda argument), unique in the script, assigned by the binder
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub struct BindingId(pub u32);
+
+/// Value definition (`val x = ...`)
+#[derive(Debug, PartialEq, Clone)]
+pub struct Val {
+    pub name: Spanned<String>,
+    /// `None` until assigned by the binder
+    pub id: Option<BindingId>,
+    /// Explicitly declared type (`val x: Int = ...`)
+    pub declared_tpe: Option<SType>,
+    pub rhs: Box<Expr>,
+}
+
+impl Val {
+    fn lower(ast: &ast::ValDef) -> Result<Val, HirLoweringError> {
+        let name = ast.name()?;
+        let declared_tpe = ast.tpe().map(|t| lower_type(&t)).transpose()?;
+        Ok(Val {
+            name: Spanned {
+                node: name.text().to_string(),
+                span: name.text_range(),
+            },
+            id: None,
+            declared_tpe,
+            rhs: Box::new(Expr::lower(&ast.rhs()?)?),
+        })
+    }
+}
+
+/// Block of `val` definitions with the resulting expression
+#[derive(Debug, PartialEq, Clone)]
+pub struct Block {
+    pub bindings: Vec<Val>,
+    pub body: Box<Expr>,
+}
+
+/// Lambda argument
+#[derive(Debug, PartialEq, Clone)]
+pub struct LambdaArg {
+    pub name: Spanned<String>,
+    /// `None` until assigned by the binder
+    pub id: Option<BindingId>,
+    pub tpe: SType,
+}
+
+/// Lambda (`(x: Int) => x + 1`)
+#[derive(Debug, PartialEq, Clone)]
+pub struct Lambda {
+    pub args: Vec<LambdaArg>,
+    pub body: Box<Expr>,
+}
+
+impl Lambda {
+    /// Name of the argument of a `fold` op lambda (see [`Lambda::with_pair_arg`])
+    pub const PAIR_ARG: &'static str = "$pair";
+
+    fn lower(ast: &ast::LambdaExpr) -> Result<Lambda, HirLoweringError> {
+        let args = ast
+            .args()
+            .map(|arg| {
+                let name = arg.name()?;
+                Ok(LambdaArg {
+                    name: Spanned {
+                        node: name.text().to_string(),
+                        span: name.text_range(),
+                    },
+                    id: None,
+                    tpe: lower_type(&arg.tpe()?)?,
+                })
+            })
+            .collect::<Result<Vec<LambdaArg>, HirLoweringError>>()?;
+        Ok(Lambda {
+            args,
+            body: Box::new(Expr::lower(&ast.body()?)?),
+        })
+    }
+
+    /// Whether the lambda is produced by [`Lambda::with_pair_arg`] (its body starts with the
+    /// vals of the original arguments)
+    pub fn has_pair_arg(&self) -> bool {
+        matches!(self.args.as_slice(), [arg] if arg.name.node == Self::PAIR_ARG)
+    }
+
+    /// `(acc: A, x: B) => body` as `(arg: (A, B)) => { val acc = arg._1; val x = arg._2; body }`
+    /// since `fold` expects a function of a single (pair) argument
+    fn with_pair_arg(self, span: TextRange) -> Lambda {
+        let pair_tpe = SType::STuple(STuple::pair(
+            self.args[0].tpe.clone(),
+            self.args[1].tpe.clone(),
+        ));
+        let bindings = self
+            .args
+            .into_iter()
+            .zip(["_1", "_2"])
+            .map(|(arg, field)| Val {
+                name: arg.name.clone(),
+                id: None,
+                declared_tpe: None,
+                rhs: Box::new(Expr {
+                    kind: Select {
+                        obj: Box::new(Expr {
+                            kind: ExprKind::Ident(Self::PAIR_ARG.to_string()),
+                            span: arg.name.span,
+                            tpe: None,
+                        }),
+                        field: Spanned {
+                            node: field.to_string(),
+                            span: arg.name.span,
+                        },
+                        tpe_args: vec![],
+                    }
+                    .into(),
+                    span: arg.name.span,
+                    tpe: None,
+                }),
+            })
+            .collect();
+        Lambda {
+            args: vec![LambdaArg {
+                name: Spanned {
+                    node: Self::PAIR_ARG.to_string(),
+                    span,
+                },
+                id: None,
+                tpe: pair_tpe,
+            }],
+            body: Box::new(Expr {
+                kind: Block {
+                    bindings,
+                    body: self.body,
+                }
+                .into(),
+                span,
+                tpe: None,
+            }),
+        }
+    }
+}
+
+/// Function application (`f(x)`)
+#[derive(Debug, PartialEq, Clone)]
+pub struct Apply {
+    pub func: Box<Expr>,
+    pub args: Vec<Expr>,
+}
+
+impl Apply {
+    fn lower(ast: &ast::CallExpr) -> Result<Apply, HirLoweringError> {
+        let func = Expr::lower(&ast.func()?)?;
+        let mut args = ast
+            .args()
+            .iter()
+            .map(Expr::lower)
+            .collect::<Result<Vec<Expr>, _>>()?;
+        if let (ExprKind::Select(select), [_, fold_op]) = (&func.kind, args.as_mut_slice()) {
+            match &fold_op.kind {
+                ExprKind::Lambda(lambda)
+                    if select.field.node == "fold" && lambda.args.len() == 2 =>
+                {
+                    fold_op.kind = lambda.clone().with_pair_arg(fold_op.span).into();
+                }
+                _ => (),
+            }
+        }
+        Ok(Apply {
+            func: Box::new(func),
+            args,
+        })
+    }
+}
+
+/// Collection literal (`Coll(1, 2, 3)`), produced by the binder
+#[derive(Debug, PartialEq, Clone)]
+pub struct Collection {
+    pub items: Vec<Expr>,
+}
+
+/// Field/method selection (`SELF.value`, `SELF.R4[Long]`), resolved by the type inference
+#[derive(Debug, PartialEq, Clone)]
+pub struct Select {
+    pub obj: Box<Expr>,
+    pub field: Spanned<String>,
+    pub tpe_args: Vec<SType>,
+}
+
+impl Select {
+    fn lower(ast: &ast::SelectExpr) -> Result<Select, HirLoweringError> {
+        let field = ast.field()?;
+        let tpe_args = ast
+            .tpe_args()
+            .map(|arg| lower_type(&arg))
+            .collect::<Result<Vec<SType>, _>>()?;
+        Ok(Select {
+            obj: Box::new(Expr::lower(&ast.obj()?)?),
+            field: Spanned {
+                node: field.text().to_string(),
+                span: field.text_range(),
+            },
+            tpe_args,
+        })
+    }
+}
+
+/// Call of a predefined method or property (a resolved `Select`)
+#[derive(Debug, PartialEq, Clone)]
+pub struct MethodCall {
+    pub obj: Box<Expr>,
+    pub method: Method,
+    pub args: Vec<Expr>,
+}
+
+/// Reference to a value (`val` or lambda argument) resolved by the binder
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct ValUse {
+    pub name: String,
+    pub id: BindingId,
+}
+
 #[derive(Debug, PartialEq, From, Clone)]
 pub enum ExprKind {
     Ident(String),
     Binary(Binary),
+    Unary(Unary),
+    If(If),
     GlobalVars(GlobalVars),
+    PredefFunc(PredefFunc),
     Literal(Literal),
-    // ...
-    // Block
-    // ValNode
-    // Select
-    // ApplyTypes
-    // MethodCallLike
-    // Lambda
+    /// Value computed by the compiler (e.g. `PK("...")`)
+    Constant(Constant),
+    /// Environment value segregated as the constant with the given index
+    ConstantPlaceholder(ConstantPlaceholder),
+    Block(Block),
+    ValUse(ValUse),
+    Lambda(Lambda),
+    Apply(Apply),
+    Collection(Collection),
+    Select(Select),
+    MethodCall(MethodCall),
 }
 
 #[derive(Debug, PartialEq, Eq, Clone)]
@@ -164,23 +634,73 @@ pub enum BinaryOp {
     Minus,
     Multiply,
     Divide,
+    Eq,
+    NEq,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    And,
+    Or,
+    /// Logical XOR for `Boolean` operands, bitwise XOR for numeric operands
+    Xor,
+    BitAnd,
+    BitOr,
 }
 
-// #[derive(Debug, PartialEq, Clone)]
-// pub enum UnaryOp {
-//     Neg,
-// }
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum UnaryOp {
+    Neg,
+    Not,
+    BitInversion,
+}
 
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub enum GlobalVars {
     Height,
+    SelfBox,
+    Inputs,
+    Outputs,
+    Context,
+    MinerPubKey,
+    GroupGenerator,
 }
 
 impl GlobalVars {
+    /// Names of the predefined global variables
+    pub const NAMES: [&'static str; 7] = [
+        "HEIGHT",
+        "SELF",
+        "INPUTS",
+        "OUTPUTS",
+        "CONTEXT",
+        "minerPubKey",
+        "groupGenerator",
+    ];
+
+    /// Predefined global variable with the given name
+    pub fn from_name(name: &str) -> Option<GlobalVars> {
+        match name {
+            "HEIGHT" => Some(GlobalVars::Height),
+            "SELF" => Some(GlobalVars::SelfBox),
+            "INPUTS" => Some(GlobalVars::Inputs),
+            "OUTPUTS" => Some(GlobalVars::Outputs),
+            "CONTEXT" => Some(GlobalVars::Context),
+            "minerPubKey" => Some(GlobalVars::MinerPubKey),
+            "groupGenerator" => Some(GlobalVars::GroupGenerator),
+            _ => None,
+        }
+    }
+
     /// Type
     pub fn tpe(&self) -> SType {
         match self {
             GlobalVars::Height => SType::SInt,
+            GlobalVars::SelfBox => SType::SBox,
+            GlobalVars::Inputs | GlobalVars::Outputs => SType::SColl(SType::SBox.into()),
+            GlobalVars::Context => SType::SContext,
+            GlobalVars::MinerPubKey => SType::SColl(SType::SByte.into()),
+            GlobalVars::GroupGenerator => SType::SGroupElement,
         }
     }
 }
@@ -189,6 +709,8 @@ impl GlobalVars {
 pub enum Literal {
     Int(i32),
     Long(i64),
+    Boolean(bool),
+    Str(String),
 }
 
 #[cfg(test)]
@@ -0,0 +1,278 @@
+use ergotree_ir::types::sfunc::SFunc;
+use ergotree_ir::types::smethod::SMethod;
+use ergotree_ir::types::stuple::STuple;
+use ergotree_ir::types::stype::SType;
+use ergotree_ir::types::stype_companion::STypeCompanion;
+use ergotree_ir::types::stype_param::STypeVar;
+use ergotree_ir::types::type_unify::unify_one;
+use std::collections::HashMap;
+
+/// Predefined method or property (`SELF.value`, `SELF.R4[Long]`, `opt.getOrElse(0)`, etc.)
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum Method {
+    /// `Box.value`
+    BoxValue,
+    /// `Box.propositionBytes`
+    BoxPropositionBytes,
+    /// `Box.bytes`
+    BoxBytes,
+    /// `Box.bytesWithoutRef`
+    BoxBytesWithoutRef,
+    /// `Box.id`
+    BoxId,
+    /// `Box.creationInfo`
+    BoxCreationInfo,
+    /// `Box.R0[T]` .. `Box.R9[T]`
+    BoxRegister { id: i8, tpe: SType },
+    /// `Option.get`
+    OptionGet,
+    /// `Option.isDefined`
+    OptionIsDefined,
+    /// `Option.getOrElse(default)`
+    OptionGetOrElse,
+    /// `SigmaProp.propBytes`
+    SigmaPropBytes,
+    /// `SigmaProp.isProven`, `Boolean`. Only allowed in the top-level proposition (as the whole
+    /// proposition or an operand of its `&&`/`||`), where it is lowered to the proposition itself
+    /// (`prop.isProven && cond` gives the same tree as `prop && cond`)
+    SigmaPropIsProven,
+    /// `Coll.apply(index)`, i.e. `xs(i)`
+    CollByIndex,
+    /// `Coll.size`
+    CollSize,
+    /// `Coll.slice(from, until)`
+    CollSlice,
+    /// `Coll.++(other)`, i.e. `xs ++ ys`
+    CollAppend,
+    /// `Coll.map(f)`
+    CollMap,
+    /// `Coll.filter(p)`
+    CollFilter,
+    /// `Coll.exists(p)`
+    CollExists,
+    /// `Coll.forall(p)`
+    CollForAll,
+    /// `Coll.fold(zero, op)`
+    CollFold,
+    /// `Tuple._1`, `Tuple._2`, etc. (1-based index)
+    TupleField(u8),
+    /// Property without a dedicated MIR node (e.g. `Box.tokens`, `CONTEXT.dataInputs`)
+    Property(SMethod),
+    /// Method without a dedicated MIR node (e.g. `Coll.zip`, `Coll.flatMap`)
+    Call(SMethod),
+}
+
+impl Method {
+    /// Finds the method `name` of an object of type `obj_tpe`, returns it along with its
+    /// signature (argument types and result type). The signature may contain type variables
+    /// (e.g. the result element type of `map`) to be unified with the argument types.
+    pub fn resolve(
+        obj_tpe: &SType,
+        name: &str,
+        tpe_args: &[SType],
+    ) -> Result<(Method, SFunc), String> {
+        if let (SType::SBox, Some(id)) = (obj_tpe, register_id(name)) {
+            return match tpe_args {
+                [tpe] => Ok((
+                    Method::BoxRegister {
+                        id,
+                        tpe: tpe.clone(),
+                    },
+                    SFunc::new(vec![], SType::SOption(tpe.clone().into())),
+                )),
+                _ => Err(format!("Expected the type of {0}, e.g. {0}[Int]", name)),
+            };
+        }
+        if let (SType::STuple(tuple), Some(index)) = (obj_tpe, tuple_field_index(name)) {
+            return match tuple.items.get(index as usize - 1) {
+                Some(tpe) => Ok((Method::TupleField(index), SFunc::new(vec![], tpe.clone()))),
+                None => Err(format!("Unknown field {} of type {:?}", name, obj_tpe)),
+            };
+        }
+        if !tpe_args.is_empty() {
+            return Err(format!("Unexpected type arguments for {}", name));
+        }
+        let coll_byte = || SType::SColl(SType::SByte.into());
+        let ov: SType = STypeVar::ov().into();
+        let predicate =
+            |elem: &SType| SType::SFunc(SFunc::new(vec![elem.clone()], SType::SBoolean));
+        let (method, args, tpe) = match (obj_tpe, name) {
+            (SType::SBox, "value") => (Method::BoxValue, vec![], SType::SLong),
+            (SType::SBox, "propositionBytes") => (Method::BoxPropositionBytes, vec![], coll_byte()),
+            (SType::SBox, "bytes") => (Method::BoxBytes, vec![], coll_byte()),
+            (SType::SBox, "bytesWithoutRef") => (Method::BoxBytesWithoutRef, vec![], coll_byte()),
+            (SType::SBox, "id") => (Method::BoxId, vec![], coll_byte()),
+            (SType::SBox, "creationInfo") => (
+                Method::BoxCreationInfo,
+                vec![],
+                SType::STuple(STuple::pair(SType::SInt, coll_byte())),
+            ),
+            (SType::SSigmaProp, "propBytes") => (Method::SigmaPropBytes, vec![], coll_byte()),
+            (SType::SSigmaProp, "isProven") => (Method::SigmaPropIsProven, vec![], SType::SBoolean),
+            (SType::SOption(elem), "get") => (Method::OptionGet, vec![], *elem.clone()),
+            (SType::SOption(_), "isDefined") => (Method::OptionIsDefined, vec![], SType::SBoolean),
+            (SType::SOption(elem), "getOrElse") => {
+                (Method::OptionGetOrElse, vec![*elem.clone()], *elem.clone())
+            }
+            (SType::SColl(elem), "apply") => {
+                (Method::CollByIndex, vec![SType::SInt], *elem.clone())
+            }
+            (SType::SColl(_), "size") => (Method::CollSize, vec![], SType::SInt),
+            (SType::SColl(_), "slice") => (
+                Method::CollSlice,
+                vec![SType::SInt, SType::SInt],
+                obj_tpe.clone(),
+            ),
+            (SType::SColl(_), "++") => (Method::CollAppend, vec![obj_tpe.clone()], obj_tpe.clone()),
+            (SType::SColl(elem), "map") => (
+                Method::CollMap,
+                vec![SFunc::new(vec![*elem.clone()], ov.clone()).into()],
+                SType::SColl(ov.into()),
+            ),
+            (SType::SColl(elem), "filter") => {
+                (Method::CollFilter, vec![predicate(elem)], obj_tpe.clone())
+            }
+            (SType::SColl(elem), "exists") => {
+                (Method::CollExists, vec![predicate(elem)], SType::SBoolean)
+            }
+            (SType::SColl(elem), "forall") => {
+                (Method::CollForAll, vec![predicate(elem)], SType::SBoolean)
+            }
+            (SType::SColl(elem), "fold") => (
+                Method::CollFold,
+                vec![
+                    ov.clone(),
+                    SFunc::new(
+                        vec![STuple::pair(ov.clone(), *elem.clone()).into()],
+                        ov.clone(),
+                    )
+                    .into(),
+                ],
+                ov,
+            ),
+            _ => match property(obj_tpe, name) {
+                Some(method) => {
+                    let tpe = *method.tpe().t_range.clone();
+                    (Method::Property(method), vec![], tpe)
+                }
+                None => match coll_method(obj_tpe, name) {
+                    Some(method) => {
+                        let args = method.tpe().t_dom[1..].to_vec();
+                        let tpe = *method.tpe().t_range.clone();
+                        if args.is_empty() {
+                            (Method::Property(method), args, tpe)
+                        } else {
+                            (Method::Call(method), args, tpe)
+                        }
+                    }
+                    None => return Err(format!("Unknown method {} of type {:?}", name, obj_tpe)),
+                },
+            },
+        };
+        Ok((method, SFunc::new(args, tpe)))
+    }
+
+    /// Names of the methods and properties of an object of type `obj_tpe`
+    pub fn names(obj_tpe: &SType) -> Vec<String> {
+        const DEDICATED: [&str; 18] = [
+            "value",
+            "propositionBytes",
+            "bytes",
+            "bytesWithoutRef",
+            "id",
+            "creationInfo",
+            "propBytes",
+            "isProven",
+            "get",
+            "isDefined",
+            "getOrElse",
+            "size",
+            "slice",
+            "map",
+            "filter",
+            "exists",
+            "forall",
+            "fold",
+        ];
+        let companion = match obj_tpe {
+            SType::SBox => Some(STypeCompanion::Box),
+            SType::SContext => Some(STypeCompanion::Context),
+            SType::SGroupElement => Some(STypeCompanion::GroupElem),
+            SType::SHeader => Some(STypeCompanion::Header),
+            SType::SPreHeader => Some(STypeCompanion::PreHeader),
+            SType::SAvlTree => Some(STypeCompanion::AvlTree),
+            SType::SColl(_) => Some(STypeCompanion::Coll),
+            _ => None,
+        };
+        let mut candidates: Vec<String> = DEDICATED.iter().map(|n| n.to_string()).collect();
+        if let Some(companion) = companion {
+            candidates.extend(companion.methods().iter().map(|m| m.name().to_string()));
+        }
+        if let SType::STuple(tuple) = obj_tpe {
+            candidates.extend((1..=tuple.items.len()).map(|i| format!("_{}", i)));
+        }
+        let mut names: Vec<String> = Vec::new();
+        if *obj_tpe == SType::SBox {
+            names.extend((0..=9).map(|i| format!("R{}", i)));
+        }
+        for name in candidates {
+            if !names.contains(&name) && Method::resolve(obj_tpe, &name, &[]).is_ok() {
+                names.push(name);
+            }
+        }
+        names
+    }
+
+    /// Substitutes the type variables left in the signature of a generic method
+    pub fn with_subst(self, subst: &HashMap<STypeVar, SType>) -> Method {
+        match self {
+            Method::Call(method) => Method::Call(method.with_concrete_types(subst)),
+            method => method,
+        }
+    }
+}
+
+/// `0` for `R0`, .., `9` for `R9`
+fn register_id(name: &str) -> Option<i8> {
+    match name.as_bytes() {
+        [b'R', d @ b'0'..=b'9'] => Some((d - b'0') as i8),
+        _ => None,
+    }
+}
+
+/// `1` for `_1`, `2` for `_2`, etc.
+fn tuple_field_index(name: &str) -> Option<u8> {
+    name.strip_prefix('_')
+        .and_then(|index| index.parse::<u8>().ok())
+        .filter(|index| *index >= 1)
+}
+
+/// Method of `Coll` specialized for the element type of `obj_tpe`
+fn coll_method(obj_tpe: &SType, name: &str) -> Option<SMethod> {
+    if !matches!(obj_tpe, SType::SColl(_)) {
+        return None;
+    }
+    let method = STypeCompanion::Coll
+        .methods()
+        .into_iter()
+        .find(|m| m.name() == name)?;
+    let subst = unify_one(method.tpe().t_dom.first()?, obj_tpe).ok()?;
+    Some(method.with_concrete_types(&subst))
+}
+
+/// Property (method without arguments) of a predefined type
+fn property(obj_tpe: &SType, name: &str) -> Option<SMethod> {
+    let companion = match obj_tpe {
+        SType::SBox => STypeCompanion::Box,
+        SType::SContext => STypeCompanion::Context,
+        SType::SGroupElement => STypeCompanion::GroupElem,
+        SType::SHeader => STypeCompanion::Header,
+        SType::SPreHeader => STypeCompanion::PreHeader,
+        SType::SAvlTree => STypeCompanion::AvlTree,
+        _ => return None,
+    };
+    companion
+        .methods()
+        .into_iter()
+        .find(|m| m.name() == name && m.tpe().t_dom == vec![obj_tpe.clone()])
+}
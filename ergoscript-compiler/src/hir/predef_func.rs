@@ -0,0 +1,84 @@
+use ergotree_ir::types::sfunc::SFunc;
+use ergotree_ir::types::stype::SType;
+
+/// Predefined global function (`sigmaProp`, `proveDlog`, `blake2b256`, etc.)
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum PredefFunc {
+    /// `sigmaProp(condition)`
+    SigmaProp,
+    /// `proveDlog(pk)`
+    ProveDlog,
+    /// `proveDHTuple(g, h, u, v)`
+    ProveDhTuple,
+    /// `atLeast(bound, props)`
+    AtLeast,
+    /// `allOf(conditions)`
+    AllOf,
+    /// `anyOf(conditions)`
+    AnyOf,
+    /// `blake2b256(bytes)`
+    Blake2b256,
+    /// `sha256(bytes)`
+    Sha256,
+    /// `decodePoint(bytes)`
+    DecodePoint,
+}
+
+impl PredefFunc {
+    /// Names of the predefined functions
+    pub const NAMES: [&'static str; 9] = [
+        "sigmaProp",
+        "proveDlog",
+        "proveDHTuple",
+        "atLeast",
+        "allOf",
+        "anyOf",
+        "blake2b256",
+        "sha256",
+        "decodePoint",
+    ];
+
+    /// Predefined function with the given name
+    pub fn from_name(name: &str) -> Option<PredefFunc> {
+        match name {
+            "sigmaProp" => Some(PredefFunc::SigmaProp),
+            "proveDlog" => Some(PredefFunc::ProveDlog),
+            "proveDHTuple" => Some(PredefFunc::ProveDhTuple),
+            "atLeast" => Some(PredefFunc::AtLeast),
+            "allOf" => Some(PredefFunc::AllOf),
+            "anyOf" => Some(PredefFunc::AnyOf),
+            "blake2b256" => Some(PredefFunc::Blake2b256),
+            "sha256" => Some(PredefFunc::Sha256),
+            "decodePoint" => Some(PredefFunc::DecodePoint),
+            _ => None,
+        }
+    }
+
+    /// Type
+    pub fn tpe(&self) -> SFunc {
+        let coll_byte = || SType::SColl(SType::SByte.into());
+        let coll_bool = || SType::SColl(SType::SBoolean.into());
+        match self {
+            PredefFunc::SigmaProp => SFunc::new(vec![SType::SBoolean], SType::SSigmaProp),
+            PredefFunc::ProveDlog => SFunc::new(vec![SType::SGroupElement], SType::SSigmaProp),
+            PredefFunc::ProveDhTuple => SFunc::new(
+                vec![
+                    SType::SGroupElement,
+                    SType::SGroupElement,
+                    SType::SGroupElement,
+                    SType::SGroupElement,
+                ],
+                SType::SSigmaProp,
+            ),
+            PredefFunc::AtLeast => SFunc::new(
+                vec![SType::SInt, SType::SColl(SType::SSigmaProp.into())],
+                SType::SSigmaProp,
+            ),
+            PredefFunc::AllOf | PredefFunc::AnyOf => SFunc::new(vec![coll_bool()], SType::SBoolean),
+            PredefFunc::Blake2b256 | PredefFunc::Sha256 => {
+                SFunc::new(vec![coll_byte()], coll_byte())
+            }
+            PredefFunc::DecodePoint => SFunc::new(vec![coll_byte()], SType::SGroupElement),
+        }
+    }
+}
@@ -1,29 +1,106 @@
+use super::Apply;
 use super::Binary;
+use super::Block;
+use super::Collection;
 use super::Expr;
 use super::ExprKind;
+use super::If;
+use super::Lambda;
+use super::MethodCall;
+use super::Select;
+use super::Unary;
+use super::Val;
 
 // pub fn hir_rewrite_safe<F: Fn(&Expr) -> Option<Expr>>(e: Expr, f: F) -> Expr {
 //     let f_wrap = |e| Result::<Option<Expr>, BinderError>::Ok(f(e));
 //     hir_rewrite(e, f_wrap).unwrap()
 // }
 
+/// Replaces every node for which `f` returns `Some` (top-down, the replacement is not visited)
 pub fn rewrite<E, F: Fn(&Expr) -> Result<Option<Expr>, E>>(e: Expr, f: F) -> Result<Expr, E> {
-    let e = f(&e)?.unwrap_or(e);
-    Ok(match &e.kind {
-        ExprKind::Binary(binary) => match (f(&binary.lhs)?, f(&binary.rhs)?) {
-            (None, None) => e,
-            (l, r) => Expr {
-                kind: Binary {
-                    op: binary.op.clone(),
-                    lhs: Box::new(l.unwrap_or(*binary.lhs.clone())),
-                    rhs: Box::new(r.unwrap_or(*binary.rhs.clone())),
-                }
-                .into(),
-                ..e
-            },
-        },
-        ExprKind::Ident(_) => f(&e)?.unwrap_or(e), // TODO: duplicate call to f?
-        ExprKind::GlobalVars(_) => f(&e)?.unwrap_or(e),
-        ExprKind::Literal(_) => f(&e)?.unwrap_or(e),
-    })
+    rewrite_with(e, &f)
+}
+
+fn rewrite_with<E, F: Fn(&Expr) -> Result<Option<Expr>, E>>(e: Expr, f: &F) -> Result<Expr, E> {
+    if let Some(replaced) = f(&e)? {
+        return Ok(replaced);
+    }
+    let kind = match e.kind {
+        ExprKind::Binary(binary) => Binary {
+            op: binary.op,
+            lhs: Box::new(rewrite_with(*binary.lhs, f)?),
+            rhs: Box::new(rewrite_with(*binary.rhs, f)?),
+        }
+        .into(),
+        ExprKind::Unary(unary) => Unary {
+            op: unary.op,
+            expr: Box::new(rewrite_with(*unary.expr, f)?),
+        }
+        .into(),
+        ExprKind::If(if_expr) => If {
+            condition: Box::new(rewrite_with(*if_expr.condition, f)?),
+            then_branch: Box::new(rewrite_with(*if_expr.then_branch, f)?),
+            else_branch: Box::new(rewrite_with(*if_expr.else_branch, f)?),
+        }
+        .into(),
+        ExprKind::Block(block) => Block {
+            bindings: block
+                .bindings
+                .into_iter()
+                .map(|val| {
+                    Ok(Val {
+                        rhs: Box::new(rewrite_with(*val.rhs, f)?),
+                        ..val
+                    })
+                })
+                .collect::<Result<Vec<Val>, E>>()?,
+            body: Box::new(rewrite_with(*block.body, f)?),
+        }
+        .into(),
+        ExprKind::Lambda(lambda) => Lambda {
+            args: lambda.args,
+            body: Box::new(rewrite_with(*lambda.body, f)?),
+        }
+        .into(),
+        ExprKind::Apply(apply) => Apply {
+            func: Box::new(rewrite_with(*apply.func, f)?),
+            args: apply
+                .args
+                .into_iter()
+                .map(|arg| rewrite_with(arg, f))
+                .collect::<Result<Vec<Expr>, E>>()?,
+        }
+        .into(),
+        ExprKind::Collection(coll) => Collection {
+            items: coll
+                .items
+                .into_iter()
+                .map(|item| rewrite_with(item, f))
+                .collect::<Result<Vec<Expr>, E>>()?,
+        }
+        .into(),
+        ExprKind::Select(select) => Select {
+            obj: Box::new(rewrite_with(*select.obj, f)?),
+            ..select
+        }
+        .into(),
+        ExprKind::MethodCall(call) => MethodCall {
+            obj: Box::new(rewrite_with(*call.obj, f)?),
+            method: call.method,
+            args: call
+                .args
+                .into_iter()
+                .map(|arg| rewrite_with(arg, f))
+                .collect::<Result<Vec<Expr>, E>>()?,
+        }
+        .into(),
+        kind @ (ExprKind::Ident(_)
+        | ExprKind::GlobalVars(_)
+        | ExprKind::PredefFunc(_)
+        | ExprKind::Literal(_)
+        | ExprKind::Constant(_)
+        | ExprKind::ConstantPlaceholder(_)
+        | ExprKind::ValUse(_)) => kind,
+    };
+    Ok(Expr { kind, ..e })
 }
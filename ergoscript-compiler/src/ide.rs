@@ -0,0 +1,221 @@
+//! Source code analysis for editors (hover, go-to-definition, completion and formatting)
+
+mod completion;
+mod format;
+
+pub use completion::completions;
+pub use completion::Completion;
+pub use completion::CompletionKind;
+pub use format::format;
+
+use ergotree_ir::pretty_printer::print_type;
+use ergotree_ir::types::stype::SType;
+use rowan::TextRange;
+use rowan::TextSize;
+
+use crate::binder::Binder;
+use crate::compiler::compile_hir;
+use crate::hir::BindingId;
+use crate::hir::Expr;
+use crate::hir::ExprKind;
+use crate::hir::Lambda;
+use crate::script_env::ScriptEnv;
+use crate::type_infer::assign_partial_type;
+
+/// Inferred type of the innermost typed expression (or `val`/lambda argument name) at `offset`,
+/// along with its span. The source may have binder and type errors, in which case only the
+/// expressions that type check have a type. `None` if there is no such expression or the source
+/// does not parse.
+pub fn type_at(source: &str, env: ScriptEnv, offset: TextSize) -> Option<(TextRange, SType)> {
+    let hir = compile_hir(source).ok()?;
+    let typed = assign_partial_type(Binder::new(env).bind_partial(hir));
+    let mut found = None;
+    find_type(&typed, offset, &mut found);
+    found
+}
+
+/// Span of the name of the definition (`val` or lambda argument) of the value referenced at
+/// `offset`
+pub fn definition_at(source: &str, offset: TextSize) -> Option<TextRange> {
+    let hir = compile_hir(source).ok()?;
+    let bound = Binder::new(ScriptEnv::new()).bind_partial(hir);
+    let id = find_val_use(&bound, offset)?;
+    find_definition(&bound, id)
+}
+
+/// Type in the ErgoScript syntax (`Coll[Byte]`, `(Int, Long)`, etc.)
+pub fn type_name(tpe: &SType) -> String {
+    print_type(tpe)
+}
+
+/// Visits the expressions containing `offset` from the outermost, so the innermost one is left
+/// in `found`
+fn find_type(expr: &Expr, offset: TextSize, found: &mut Option<(TextRange, SType)>) {
+    if !expr.span.contains(offset) {
+        return;
+    }
+    if let Some(tpe) = &expr.tpe {
+        *found = Some((expr.span, tpe.clone()));
+    }
+    match &expr.kind {
+        ExprKind::Block(block) => {
+            for val in &block.bindings {
+                let tpe = val.declared_tpe.as_ref().or(val.rhs.tpe.as_ref());
+                if let (true, Some(tpe)) = (val.name.span.contains(offset), tpe) {
+                    *found = Some((val.name.span, tpe.clone()));
+                }
+            }
+        }
+        ExprKind::Lambda(lambda) => {
+            for arg in &lambda.args {
+                if arg.name.span.contains(offset) {
+                    *found = Some((arg.name.span, arg.tpe.clone()));
+                }
+            }
+        }
+        _ => (),
+    }
+    for child in expr.children() {
+        find_type(child, offset, found);
+    }
+}
+
+fn find_val_use(expr: &Expr, offset: TextSize) -> Option<BindingId> {
+    if !expr.span.contains(offset) {
+        return None;
+    }
+    match &expr.kind {
+        // the argument of a `fold` op is not in the source
+        ExprKind::ValUse(val_use) if val_use.name != Lambda::PAIR_ARG => Some(val_use.id),
+        _ => expr
+            .children()
+            .into_iter()
+            .find_map(|child| find_val_use(child, offset)),
+    }
+}
+
+fn find_definition(expr: &Expr, id: BindingId) -> Option<TextRange> {
+    let defined = match &expr.kind {
+        ExprKind::Block(block) => block
+            .bindings
+            .iter()
+            .find(|val| val.id == Some(id))
+            .map(|val| val.name.span),
+        ExprKind::Lambda(lambda) => lambda
+            .args
+            .iter()
+            .find(|arg| arg.id == Some(id))
+            .map(|arg| arg.name.span),
+        _ => None,
+    };
+    defined.or_else(|| {
+        expr.children()
+            .into_iter()
+            .find_map(|child| find_definition(child, id))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use expect_test::expect;
+
+    /// Source with the `$0` marker removed, and the offset of the marker
+    fn with_offset(marked: &str) -> (String, TextSize) {
+        let offset = marked.find("$0").unwrap();
+        (marked.replace("$0", ""), TextSize::from(offset as u32))
+    }
+
+    fn check_type(marked: &str, expected: expect_test::Expect) {
+        let (source, offset) = with_offset(marked);
+        let desc = match type_at(&source, ScriptEnv::new(), offset) {
+            Some((span, tpe)) => format!("{}: {}", &source[span], type_name(&tpe)),
+            None => "None".to_string(),
+        };
+        expected.assert_eq(&desc);
+    }
+
+    fn check_definition(marked: &str, expected: expect_test::Expect) {
+        let (source, offset) = with_offset(marked);
+        let desc = match definition_at(&source, offset) {
+            Some(span) => format!("{:?} {}", span, &source[span]),
+            None => "None".to_string(),
+        };
+        expected.assert_eq(&desc);
+    }
+
+    #[test]
+    fn type_of_global() {
+        check_type("HEIGHT > 1 && SE$0LF.value > 1L", expect!["SELF: Box"]);
+    }
+
+    #[test]
+    fn type_of_method_call() {
+        check_type(
+            "OUTPUTS.ma$0p((b: Box) => b.value)",
+            expect!["OUTPUTS.map((b: Box) => b.value): Coll[Long]"],
+        );
+    }
+
+    #[test]
+    fn type_of_val_name() {
+        check_type(
+            "{ val t$0okens = SELF.tokens; tokens.size > 0 }",
+            expect!["tokens: Coll[(Coll[Byte], Long)]"],
+        );
+    }
+
+    #[test]
+    fn type_of_fold_arg() {
+        check_type(
+            "OUTPUTS.fold(0L, (acc: Long, b: Box) => acc + $0b.value)",
+            expect!["b: Box"],
+        );
+    }
+
+    #[test]
+    fn type_of_ill_typed_source() {
+        check_type("HEIGHT$0 > 1L", expect!["HEIGHT : Int"]);
+        check_type("HEIGHT >$0 1L", expect!["None"]);
+        check_type(
+            "{ val a = HEIGHT > 1L; val b = SELF.val$0ue; b > 1L && a }",
+            expect!["SELF.value: Long"],
+        );
+        check_type(
+            "{ val a = HEIGHT > 1L; val b = SELF.value; $0b > 1L && a }",
+            expect!["b : Long"],
+        );
+    }
+
+    #[test]
+    fn type_with_binder_errors() {
+        check_type("{ val a = x; HE$0IGHT }", expect!["HEIGHT : Int"]);
+    }
+
+    #[test]
+    fn definition_of_val() {
+        check_definition("{ val a = 1\n val b = 2\n $0a + b }", expect!["6..7 a"]);
+    }
+
+    #[test]
+    fn definition_of_lambda_arg() {
+        check_definition(
+            "OUTPUTS.fold(0L, (acc: Long, b: Box) => acc + $0b.value)",
+            expect!["29..30 b"],
+        );
+    }
+
+    #[test]
+    fn definition_of_global() {
+        check_definition("HEI$0GHT", expect!["None"]);
+    }
+
+    #[test]
+    fn type_names() {
+        let tpe = SType::SFunc(ergotree_ir::types::sfunc::SFunc::new(
+            vec![SType::SColl(SType::SBox.into())],
+            SType::SOption(SType::SLong.into()),
+        ));
+        assert_eq!(type_name(&tpe), "(Coll[Box]) => Option[Long]");
+    }
+}
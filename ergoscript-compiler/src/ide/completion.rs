@@ -0,0 +1,218 @@
+use ergotree_ir::types::sfunc::SFunc;
+use ergotree_ir::types::stype::SType;
+use rowan::TextSize;
+
+use super::type_at;
+use super::type_name;
+use crate::hir::GlobalVars;
+use crate::hir::Method;
+use crate::hir::PredefFunc;
+use crate::script_env::ScriptEnv;
+
+/// Kind of a [`Completion`]
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum CompletionKind {
+    /// Predefined global variable (`HEIGHT`, `SELF`, etc.)
+    Global,
+    /// Predefined function (`sigmaProp`, `blake2b256`, etc.)
+    Function,
+    /// Method or property of an object (`value`, `tokens`, etc.)
+    Method,
+}
+
+/// Completion candidate
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Completion {
+    /// Name to insert
+    pub label: String,
+    /// Kind
+    pub kind: CompletionKind,
+    /// Type (signature for functions and methods)
+    pub detail: String,
+}
+
+/// Completion candidates at `offset`: methods and properties of the object when `offset` follows
+/// `obj.` (or `obj.prefix`), predefined global variables and functions otherwise
+pub fn completions(source: &str, offset: TextSize) -> Vec<Completion> {
+    let offset = usize::from(offset).min(source.len());
+    let before = &source[..offset];
+    let prefix_start = before
+        .trim_end_matches(|c: char| c.is_ascii_alphanumeric() || c == '_')
+        .len();
+    let prefix = &before[prefix_start..];
+    let candidates = match before[..prefix_start].strip_suffix('.') {
+        Some(obj) => members(obj, &source[offset..]),
+        None => globals(),
+    };
+    candidates
+        .into_iter()
+        .filter(|c| c.label.starts_with(prefix))
+        .collect()
+}
+
+fn globals() -> Vec<Completion> {
+    let global = |name: &str, tpe: SType| Completion {
+        label: name.to_string(),
+        kind: CompletionKind::Global,
+        detail: type_name(&tpe),
+    };
+    let function = |name: &str, tpe: SFunc| Completion {
+        label: name.to_string(),
+        kind: CompletionKind::Function,
+        detail: type_name(&tpe.into()),
+    };
+    let mut res: Vec<Completion> = GlobalVars::NAMES
+        .iter()
+        .filter_map(|name| Some(global(name, GlobalVars::from_name(name)?.tpe())))
+        .collect();
+    res.push(global("dataInputs", SType::SColl(SType::SBox.into())));
+    res.extend(
+        PredefFunc::NAMES
+            .iter()
+            .filter_map(|name| Some(function(name, PredefFunc::from_name(name)?.tpe()))),
+    );
+    res.push(Completion {
+        label: "PK".to_string(),
+        kind: CompletionKind::Function,
+        detail: "(String) => SigmaProp".to_string(),
+    });
+    res.push(Completion {
+        label: "Coll".to_string(),
+        kind: CompletionKind::Function,
+        detail: "(T, ..) => Coll[T]".to_string(),
+    });
+    res
+}
+
+/// Members of the object ending the source before the `.`, `rest` is the source after the
+/// incomplete member name
+fn members(obj: &str, rest: &str) -> Vec<Completion> {
+    let obj_end = obj.trim_end().len();
+    if obj_end == 0 {
+        return Vec::new();
+    }
+    // the object type in the source without the incomplete selection
+    let source = format!("{}{}", obj, rest);
+    let offset = TextSize::from(obj_end as u32 - 1);
+    let obj_tpe = match type_at(&source, ScriptEnv::new(), offset) {
+        Some((_, tpe)) => tpe,
+        None => return Vec::new(),
+    };
+    Method::names(&obj_tpe)
+        .into_iter()
+        .map(|name| {
+            let detail = match Method::resolve(&obj_tpe, &name, &[]) {
+                Ok((_, signature)) if signature.t_dom.is_empty() => type_name(&signature.t_range),
+                Ok((_, signature)) => type_name(&signature.into()),
+                // registers need the type of the value
+                Err(_) => "Option[T]".to_string(),
+            };
+            Completion {
+                label: name,
+                kind: CompletionKind::Method,
+                detail,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use expect_test::expect;
+
+    fn check(marked: &str, expected: expect_test::Expect) {
+        let offset = marked.find("$0").unwrap();
+        let source = marked.replace("$0", "");
+        let desc = completions(&source, TextSize::from(offset as u32))
+            .iter()
+            .map(|c| format!("{:?} {}: {}", c.kind, c.label, c.detail))
+            .collect::<Vec<String>>()
+            .join("\n");
+        expected.assert_eq(&desc);
+    }
+
+    #[test]
+    fn complete_globals() {
+        check(
+            "HEIGHT > 1 && $0",
+            expect![[r#"
+            Global HEIGHT: Int
+            Global SELF: Box
+            Global INPUTS: Coll[Box]
+            Global OUTPUTS: Coll[Box]
+            Global CONTEXT: Context
+            Global minerPubKey: Coll[Byte]
+            Global groupGenerator: GroupElement
+            Global dataInputs: Coll[Box]
+            Function sigmaProp: (Boolean) => SigmaProp
+            Function proveDlog: (GroupElement) => SigmaProp
+            Function proveDHTuple: (GroupElement, GroupElement, GroupElement, GroupElement) => SigmaProp
+            Function atLeast: (Int, Coll[SigmaProp]) => SigmaProp
+            Function allOf: (Coll[Boolean]) => Boolean
+            Function anyOf: (Coll[Boolean]) => Boolean
+            Function blake2b256: (Coll[Byte]) => Coll[Byte]
+            Function sha256: (Coll[Byte]) => Coll[Byte]
+            Function decodePoint: (Coll[Byte]) => GroupElement
+            Function PK: (String) => SigmaProp
+            Function Coll: (T, ..) => Coll[T]"#]],
+        );
+    }
+
+    #[test]
+    fn complete_globals_with_prefix() {
+        check(
+            "{ val x = bl$0 }",
+            expect!["Function blake2b256: (Coll[Byte]) => Coll[Byte]"],
+        );
+    }
+
+    #[test]
+    fn complete_box_members() {
+        check(
+            "SELF.$0",
+            expect![[r#"
+            Method R0: Option[T]
+            Method R1: Option[T]
+            Method R2: Option[T]
+            Method R3: Option[T]
+            Method R4: Option[T]
+            Method R5: Option[T]
+            Method R6: Option[T]
+            Method R7: Option[T]
+            Method R8: Option[T]
+            Method R9: Option[T]
+            Method value: Long
+            Method propositionBytes: Coll[Byte]
+            Method bytes: Coll[Byte]
+            Method bytesWithoutRef: Coll[Byte]
+            Method id: Coll[Byte]
+            Method creationInfo: (Int, Coll[Byte])
+            Method tokens: Coll[(Coll[Byte], Long)]"#]],
+        );
+    }
+
+    #[test]
+    fn complete_box_members_with_prefix() {
+        check(
+            "OUTPUTS(0).pro$0",
+            expect!["Method propositionBytes: Coll[Byte]"],
+        );
+    }
+
+    #[test]
+    fn complete_val_members() {
+        check(
+            "{ val b = SELF.tokens\n b.si$0 }",
+            expect!["Method size: Int"],
+        );
+    }
+
+    #[test]
+    fn complete_members_in_ill_typed_source() {
+        check(
+            "{ val a = HEIGHT > 1L\n val b = SELF.tokens\n b.si$0 }",
+            expect!["Method size: Int"],
+        );
+    }
+}
@@ -0,0 +1,172 @@
+use crate::lexer::Lexer;
+use crate::lexer::TokenKind;
+use crate::parser::parse;
+
+/// Indentation of a nesting level (block, arguments, etc.)
+const INDENT: &str = "  ";
+
+/// Source with normalized spacing and indentation (line breaks are kept, blank lines are
+/// collapsed), or `None` if the source has syntax errors
+pub fn format(source: &str) -> Option<String> {
+    if !parse(source).errors.is_empty() {
+        return None;
+    }
+    let mut out = String::with_capacity(source.len());
+    // indentation of the lines with the unclosed brackets, so the brackets opened on the same
+    // line add a single level
+    let mut openers: Vec<usize> = Vec::new();
+    let mut line_indent = 0;
+    let mut prev: Option<(TokenKind, bool)> = None;
+    let mut line_breaks = 0;
+    for token in Lexer::new(source) {
+        if token.kind == TokenKind::Whitespace {
+            line_breaks += token.text.matches('\n').count();
+            continue;
+        }
+        let prefix_op = is_prefix_op(token.kind, prev.map(|(kind, _)| kind));
+        match prev {
+            Some(_) if line_breaks > 0 => {
+                line_indent = match openers.last() {
+                    Some(indent) if is_closing(token.kind) => *indent,
+                    Some(indent) => indent + 1,
+                    None => 0,
+                };
+                out.push_str(if line_breaks > 1 { "\n\n" } else { "\n" });
+                out.push_str(&INDENT.repeat(line_indent));
+            }
+            Some((prev_kind, false)) if space_between(prev_kind, token.kind) => out.push(' '),
+            _ => (),
+        }
+        out.push_str(token.text);
+        if is_opening(token.kind) {
+            openers.push(line_indent);
+        } else if is_closing(token.kind) {
+            openers.pop();
+        }
+        prev = Some((token.kind, prefix_op));
+        line_breaks = 0;
+    }
+    if source.ends_with('\n') {
+        out.push('\n');
+    }
+    Some(out)
+}
+
+fn is_opening(kind: TokenKind) -> bool {
+    matches!(
+        kind,
+        TokenKind::LBrace | TokenKind::LParen | TokenKind::LBracket
+    )
+}
+
+fn is_closing(kind: TokenKind) -> bool {
+    matches!(
+        kind,
+        TokenKind::RBrace | TokenKind::RParen | TokenKind::RBracket
+    )
+}
+
+/// Whether the token ends an operand (so the next `-` is a binary operator and the next `(` is
+/// an application)
+fn ends_operand(kind: TokenKind) -> bool {
+    matches!(
+        kind,
+        TokenKind::Ident
+            | TokenKind::IntNumber
+            | TokenKind::LongNumber
+            | TokenKind::StringLiteral
+            | TokenKind::TrueKw
+            | TokenKind::FalseKw
+            | TokenKind::RParen
+            | TokenKind::RBracket
+            | TokenKind::RBrace
+    )
+}
+
+fn is_prefix_op(kind: TokenKind, prev: Option<TokenKind>) -> bool {
+    match kind {
+        TokenKind::Bang | TokenKind::Tilde => true,
+        TokenKind::Minus => !matches!(prev, Some(kind) if ends_operand(kind)),
+        _ => false,
+    }
+}
+
+fn space_between(prev: TokenKind, next: TokenKind) -> bool {
+    match (prev, next) {
+        (TokenKind::LParen | TokenKind::LBracket | TokenKind::Dot, _) => false,
+        (
+            _,
+            TokenKind::RParen
+            | TokenKind::RBracket
+            | TokenKind::Dot
+            | TokenKind::Comma
+            | TokenKind::Colon
+            | TokenKind::Semicolon
+            | TokenKind::LBracket,
+        ) => false,
+        (TokenKind::LBrace, TokenKind::RBrace) => false,
+        (prev, TokenKind::LParen) => !ends_operand(prev),
+        _ => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use expect_test::expect;
+
+    fn check(source: &str, expected: expect_test::Expect) {
+        expected.assert_eq(&format(source).unwrap_or_else(|| "None".to_string()));
+    }
+
+    #[test]
+    fn format_spacing() {
+        check(
+            "HEIGHT>100&&SELF.R4[Coll[Byte]].get==minerPubKey||-1<2",
+            expect!["HEIGHT > 100 && SELF.R4[Coll[Byte]].get == minerPubKey || -1 < 2"],
+        );
+    }
+
+    #[test]
+    fn format_block() {
+        check(
+            "{\nval a:Int=1;val f=(x:Int,y:Int)=>x+y\n\n\n    f(a,-a)>0 && !false\n}\n",
+            expect![[r#"
+                {
+                  val a: Int = 1; val f = (x: Int, y: Int) => x + y
+
+                  f(a, -a) > 0 && !false
+                }
+            "#]],
+        );
+    }
+
+    #[test]
+    fn format_nested_args() {
+        check(
+            "{ allOf(Coll(\nHEIGHT > 1,   // height\nOUTPUTS.size > 1\n)) &&\n{ val b = OUTPUTS(0)\n b.value > 1L\n  }\n}",
+            expect![[r#"
+                { allOf(Coll(
+                  HEIGHT > 1, // height
+                  OUTPUTS.size > 1
+                )) &&
+                  { val b = OUTPUTS(0)
+                    b.value > 1L
+                  }
+                }"#]],
+        );
+    }
+
+    #[test]
+    fn format_if_else() {
+        check(
+            "if(HEIGHT>1){sigmaProp(true)}else{ sigmaProp(false) }",
+            expect!["if (HEIGHT > 1) { sigmaProp(true) } else { sigmaProp(false) }"],
+        );
+    }
+
+    #[test]
+    fn do_not_format_syntax_errors() {
+        check("HEIGHT >", expect!["None"]);
+    }
+}
@@ -14,7 +14,19 @@ pub enum TokenKind {
     #[token("val")]
     ValKw,
 
-    #[regex("[A-Za-z][A-Za-z0-9]*")]
+    #[token("if")]
+    IfKw,
+
+    #[token("else")]
+    ElseKw,
+
+    #[token("true")]
+    TrueKw,
+
+    #[token("false")]
+    FalseKw,
+
+    #[regex("[A-Za-z_][A-Za-z0-9_]*")]
     Ident,
 
     #[regex("[0-9]+")]
@@ -23,9 +35,15 @@ pub enum TokenKind {
     #[regex("[0-9]+L")]
     LongNumber,
 
+    #[regex(r#""[^"\n]*""#)]
+    StringLiteral,
+
     #[token("+")]
     Plus,
 
+    #[token("++")]
+    PlusPlus,
+
     #[token("-")]
     Minus,
 
@@ -38,9 +56,60 @@ pub enum TokenKind {
     #[token("&&")]
     And,
 
+    #[token("||")]
+    Or,
+
+    #[token("&")]
+    Amp,
+
+    #[token("|")]
+    Pipe,
+
+    #[token("^")]
+    Caret,
+
+    #[token("!")]
+    Bang,
+
+    #[token("~")]
+    Tilde,
+
+    #[token("==")]
+    EqualsEquals,
+
+    #[token("!=")]
+    NotEquals,
+
+    #[token("<")]
+    Less,
+
+    #[token("<=")]
+    LessEquals,
+
+    #[token(">")]
+    Greater,
+
+    #[token(">=")]
+    GreaterEquals,
+
     #[token("=")]
     Equals,
 
+    #[token("=>")]
+    FatArrow,
+
+    #[token(":")]
+    Colon,
+
+    #[token(",")]
+    Comma,
+
+    #[token(".")]
+    Dot,
+
+    #[token(";")]
+    Semicolon,
+
     #[token("(")]
     LParen,
 
@@ -53,6 +122,12 @@ pub enum TokenKind {
     #[token("}")]
     RBrace,
 
+    #[token("[")]
+    LBracket,
+
+    #[token("]")]
+    RBracket,
+
     #[regex("//.*")]
     Comment,
 
@@ -72,19 +147,44 @@ impl fmt::Display for TokenKind {
             Self::Whitespace => "whitespace",
             Self::FnKw => "‘def’",
             Self::ValKw => "‘val’",
+            Self::IfKw => "‘if’",
+            Self::ElseKw => "‘else’",
+            Self::TrueKw => "‘true’",
+            Self::FalseKw => "‘false’",
             Self::Ident => "identifier",
             Self::IntNumber => "number",
             Self::LongNumber => "number",
+            Self::StringLiteral => "string",
             Self::Plus => "‘+’",
+            Self::PlusPlus => "‘++’",
             Self::Minus => "‘-’",
             Self::Star => "‘*’",
             Self::Slash => "‘/’",
             Self::And => "‘&&’",
+            Self::Or => "‘||’",
+            Self::Amp => "‘&’",
+            Self::Pipe => "‘|’",
+            Self::Caret => "‘^’",
+            Self::Bang => "‘!’",
+            Self::Tilde => "‘~’",
+            Self::EqualsEquals => "‘==’",
+            Self::NotEquals => "‘!=’",
+            Self::Less => "‘<’",
+            Self::LessEquals => "‘<=’",
+            Self::Greater => "‘>’",
+            Self::GreaterEquals => "‘>=’",
             Self::Equals => "‘=’",
+            Self::FatArrow => "‘=>’",
+            Self::Colon => "‘:’",
+            Self::Comma => "‘,’",
+            Self::Dot => "‘.’",
+            Self::Semicolon => "‘;’",
             Self::LParen => "‘(’",
             Self::RParen => "‘)’",
             Self::LBrace => "‘{’",
             Self::RBrace => "‘}’",
+            Self::LBracket => "‘[’",
+            Self::RBracket => "‘]’",
             Self::Comment => "comment",
             Self::Error => "an unrecognized token",
         })
@@ -119,6 +219,24 @@ mod tests {
         check("val", TokenKind::ValKw);
     }
 
+    #[test]
+    fn lex_if_else_keywords() {
+        check("if", TokenKind::IfKw);
+        check("else", TokenKind::ElseKw);
+    }
+
+    #[test]
+    fn lex_boolean_keywords() {
+        check("true", TokenKind::TrueKw);
+        check("false", TokenKind::FalseKw);
+    }
+
+    #[test]
+    fn lex_identifier_starting_with_keyword() {
+        check("iffy", TokenKind::Ident);
+        check("trueValue", TokenKind::Ident);
+    }
+
     #[test]
     fn lex_alphabetic_identifier() {
         check("abcd", TokenKind::Ident);
@@ -129,6 +247,12 @@ mod tests {
         check("ab123cde456", TokenKind::Ident);
     }
 
+    #[test]
+    fn lex_identifier_with_underscores() {
+        check("_1", TokenKind::Ident);
+        check("my_val", TokenKind::Ident);
+    }
+
     #[test]
     fn lex_mixed_case_identifier() {
         check("ABCdef", TokenKind::Ident);
@@ -144,11 +268,24 @@ mod tests {
         check("123456", TokenKind::IntNumber);
     }
 
+    #[test]
+    fn lex_string() {
+        check(
+            "\"9hzP24a2q8KLPVCUk7gdMDXYc7vinmGuxmLp5KU7k9UwptgYBYV\"",
+            TokenKind::StringLiteral,
+        );
+    }
+
     #[test]
     fn lex_plus() {
         check("+", TokenKind::Plus);
     }
 
+    #[test]
+    fn lex_plus_plus() {
+        check("++", TokenKind::PlusPlus);
+    }
+
     #[test]
     fn lex_minus() {
         check("-", TokenKind::Minus);
@@ -164,11 +301,61 @@ mod tests {
         check("/", TokenKind::Slash);
     }
 
+    #[test]
+    fn lex_logical_operators() {
+        check("&&", TokenKind::And);
+        check("||", TokenKind::Or);
+        check("^", TokenKind::Caret);
+        check("!", TokenKind::Bang);
+    }
+
+    #[test]
+    fn lex_bit_operators() {
+        check("&", TokenKind::Amp);
+        check("|", TokenKind::Pipe);
+        check("~", TokenKind::Tilde);
+    }
+
+    #[test]
+    fn lex_relational_operators() {
+        check("==", TokenKind::EqualsEquals);
+        check("!=", TokenKind::NotEquals);
+        check("<", TokenKind::Less);
+        check("<=", TokenKind::LessEquals);
+        check(">", TokenKind::Greater);
+        check(">=", TokenKind::GreaterEquals);
+    }
+
     #[test]
     fn lex_equals() {
         check("=", TokenKind::Equals);
     }
 
+    #[test]
+    fn lex_fat_arrow() {
+        check("=>", TokenKind::FatArrow);
+    }
+
+    #[test]
+    fn lex_colon() {
+        check(":", TokenKind::Colon);
+    }
+
+    #[test]
+    fn lex_comma() {
+        check(",", TokenKind::Comma);
+    }
+
+    #[test]
+    fn lex_dot() {
+        check(".", TokenKind::Dot);
+    }
+
+    #[test]
+    fn lex_semicolon() {
+        check(";", TokenKind::Semicolon);
+    }
+
     #[test]
     fn lex_left_parenthesis() {
         check("(", TokenKind::LParen);
@@ -189,6 +376,16 @@ mod tests {
         check("}", TokenKind::RBrace);
     }
 
+    #[test]
+    fn lex_left_bracket() {
+        check("[", TokenKind::LBracket);
+    }
+
+    #[test]
+    fn lex_right_bracket() {
+        check("]", TokenKind::RBracket);
+    }
+
     #[test]
     fn lex_comment() {
         check("// foo", TokenKind::Comment);
@@ -18,10 +18,14 @@ pub(crate) mod binder;
 pub(crate) mod error;
 pub(crate) mod hir;
 pub(crate) mod lexer;
+pub(crate) mod lint;
 pub(crate) mod mir;
 pub(crate) mod parser;
 pub(crate) mod syntax;
 pub(crate) mod type_infer;
 
 pub mod compiler;
+pub mod diagnostic;
+pub mod ide;
+pub mod optimizer;
 pub mod script_env;
@@ -0,0 +1,79 @@
+//! Warnings on the typed HIR
+
+use std::collections::HashSet;
+
+use crate::diagnostic::Diagnostic;
+use crate::diagnostic::DiagnosticCode;
+use crate::hir::BindingId;
+use crate::hir::Expr;
+use crate::hir::ExprKind;
+use crate::hir::Literal;
+use crate::hir::Val;
+
+/// Unused vals, constant `if` conditions and unreachable branches
+pub fn lint(expr: &Expr) -> Vec<Diagnostic> {
+    let mut used = HashSet::new();
+    collect_used(expr, &mut used);
+    let mut warnings = Vec::new();
+    check(expr, &used, &mut warnings);
+    warnings
+}
+
+fn collect_used(expr: &Expr, used: &mut HashSet<BindingId>) {
+    if let ExprKind::ValUse(val_use) = &expr.kind {
+        used.insert(val_use.id);
+    }
+    for child in expr.children() {
+        collect_used(child, used);
+    }
+}
+
+fn check(expr: &Expr, used: &HashSet<BindingId>, warnings: &mut Vec<Diagnostic>) {
+    match &expr.kind {
+        ExprKind::Block(block) => check_unused(&block.bindings, used, warnings),
+        ExprKind::If(if_expr) => {
+            if let ExprKind::Literal(Literal::Boolean(condition)) = if_expr.condition.kind {
+                warnings.push(Diagnostic::new(
+                    DiagnosticCode::ConstantCondition,
+                    format!("Condition is always {}", condition),
+                    Some(if_expr.condition.span),
+                ));
+                let unreachable = if condition {
+                    &if_expr.else_branch
+                } else {
+                    &if_expr.then_branch
+                };
+                warnings.push(Diagnostic::new(
+                    DiagnosticCode::UnreachableBranch,
+                    "Unreachable branch".to_string(),
+                    Some(unreachable.span),
+                ));
+            }
+        }
+        ExprKind::Lambda(lambda) if lambda.has_pair_arg() => {
+            // the vals are the arguments of the original lambda, so they are not checked
+            if matches!(lambda.body.kind, ExprKind::Block(_)) {
+                for child in lambda.body.children() {
+                    check(child, used, warnings);
+                }
+                return;
+            }
+        }
+        _ => (),
+    }
+    for child in expr.children() {
+        check(child, used, warnings);
+    }
+}
+
+fn check_unused(bindings: &[Val], used: &HashSet<BindingId>, warnings: &mut Vec<Diagnostic>) {
+    for val in bindings {
+        if matches!(val.id, Some(id) if !used.contains(&id)) {
+            warnings.push(Diagnostic::new(
+                DiagnosticCode::UnusedVal,
+                format!("Unused val {}", val.name.node),
+                Some(val.name.span),
+            ));
+        }
+    }
+}
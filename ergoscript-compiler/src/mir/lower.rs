@@ -1,12 +1,69 @@
+use ergotree_ir::mir::and::And;
+use ergotree_ir::mir::apply::Apply;
+use ergotree_ir::mir::atleast::Atleast;
 use ergotree_ir::mir::bin_op::ArithOp;
 use ergotree_ir::mir::bin_op::BinOp;
 use ergotree_ir::mir::bin_op::BinOpKind;
+use ergotree_ir::mir::bin_op::BitOp;
+use ergotree_ir::mir::bin_op::LogicalOp;
+use ergotree_ir::mir::bin_op::RelationOp;
+use ergotree_ir::mir::bit_inversion::BitInversion;
+use ergotree_ir::mir::block::BlockValue;
+use ergotree_ir::mir::bool_to_sigma::BoolToSigmaProp;
+use ergotree_ir::mir::calc_blake2b256::CalcBlake2b256;
+use ergotree_ir::mir::calc_sha256::CalcSha256;
+use ergotree_ir::mir::coll_append::Append;
+use ergotree_ir::mir::coll_by_index::ByIndex;
+use ergotree_ir::mir::coll_exists::Exists;
+use ergotree_ir::mir::coll_filter::Filter;
+use ergotree_ir::mir::coll_fold::Fold;
+use ergotree_ir::mir::coll_forall::ForAll;
+use ergotree_ir::mir::coll_map::Map;
+use ergotree_ir::mir::coll_size::SizeOf;
+use ergotree_ir::mir::coll_slice::Slice;
+use ergotree_ir::mir::collection::Collection;
 use ergotree_ir::mir::constant::Constant;
+use ergotree_ir::mir::create_prove_dh_tuple::CreateProveDhTuple;
+use ergotree_ir::mir::create_provedlog::CreateProveDlog;
+use ergotree_ir::mir::decode_point::DecodePoint;
 use ergotree_ir::mir::expr::Expr;
+use ergotree_ir::mir::expr::InvalidArgumentError;
+use ergotree_ir::mir::extract_amount::ExtractAmount;
+use ergotree_ir::mir::extract_bytes::ExtractBytes;
+use ergotree_ir::mir::extract_bytes_with_no_ref::ExtractBytesWithNoRef;
+use ergotree_ir::mir::extract_creation_info::ExtractCreationInfo;
+use ergotree_ir::mir::extract_id::ExtractId;
+use ergotree_ir::mir::extract_reg_as::ExtractRegisterAs;
+use ergotree_ir::mir::extract_script_bytes::ExtractScriptBytes;
+use ergotree_ir::mir::func_value::FuncArg;
+use ergotree_ir::mir::func_value::FuncValue;
 use ergotree_ir::mir::global_vars::GlobalVars;
+use ergotree_ir::mir::if_op::If;
+use ergotree_ir::mir::logical_not::LogicalNot;
+use ergotree_ir::mir::method_call::MethodCall;
+use ergotree_ir::mir::negation::Negation;
+use ergotree_ir::mir::option_get::OptionGet;
+use ergotree_ir::mir::option_get_or_else::OptionGetOrElse;
+use ergotree_ir::mir::option_is_defined::OptionIsDefined;
+use ergotree_ir::mir::or::Or;
+use ergotree_ir::mir::property_call::PropertyCall;
+use ergotree_ir::mir::select_field::SelectField;
+use ergotree_ir::mir::select_field::TupleFieldIndex;
+use ergotree_ir::mir::sigma_and::SigmaAnd;
+use ergotree_ir::mir::sigma_or::SigmaOr;
+use ergotree_ir::mir::sigma_prop_bytes::SigmaPropBytes;
+use ergotree_ir::mir::unary_op::OneArgOpTryBuild;
+use ergotree_ir::mir::val_def::ValDef;
+use ergotree_ir::mir::val_def::ValId;
+use ergotree_ir::mir::val_use::ValUse;
+use ergotree_ir::types::stype::SType;
 use hir::BinaryOp;
+use hir::Method;
+use hir::PredefFunc;
 use rowan::TextRange;
 
+use crate::diagnostic::Diagnostic;
+use crate::diagnostic::DiagnosticCode;
 use crate::error::pretty_error_desc;
 use crate::hir;
 
@@ -24,57 +81,391 @@ impl MirLoweringError {
     pub fn pretty_desc(&self, source: &str) -> String {
         pretty_error_desc(source, self.span, &self.msg)
     }
+
+    pub fn diagnostic(&self) -> Diagnostic {
+        Diagnostic::new(
+            DiagnosticCode::MirLoweringError,
+            self.msg.clone(),
+            Some(self.span),
+        )
+    }
 }
 
-pub fn lower(hir_expr: hir::Expr) -> Result<Expr, MirLoweringError> {
+/// Lowers the typed HIR, reporting the errors of all the independent subexpressions
+pub fn lower(hir_expr: hir::Expr) -> Result<Expr, Vec<MirLoweringError>> {
     let mir: Expr = match &hir_expr.kind {
         hir::ExprKind::GlobalVars(hir) => match hir {
             hir::GlobalVars::Height => GlobalVars::Height.into(),
+            hir::GlobalVars::SelfBox => GlobalVars::SelfBox.into(),
+            hir::GlobalVars::Inputs => GlobalVars::Inputs.into(),
+            hir::GlobalVars::Outputs => GlobalVars::Outputs.into(),
+            hir::GlobalVars::Context => Expr::Context,
+            hir::GlobalVars::MinerPubKey => GlobalVars::MinerPubKey.into(),
+            hir::GlobalVars::GroupGenerator => GlobalVars::GroupGenerator.into(),
         },
+        hir::ExprKind::PredefFunc(_) => {
+            return Err(vec![MirLoweringError::new(
+                format!(
+                    "MIR error: Predefined function is not applied {0:?}",
+                    hir_expr
+                ),
+                hir_expr.span,
+            )])
+        }
+        hir::ExprKind::Constant(c) => c.clone().into(),
+        hir::ExprKind::ConstantPlaceholder(c) => c.clone().into(),
         hir::ExprKind::Ident(_) => {
-            return Err(MirLoweringError::new(
+            return Err(vec![MirLoweringError::new(
                 format!("MIR error: Unresolved Ident {0:?}", hir_expr),
                 hir_expr.span,
-            ))
+            )])
+        }
+        hir::ExprKind::Select(_) => {
+            return Err(vec![MirLoweringError::new(
+                format!("MIR error: Unresolved Select {0:?}", hir_expr),
+                hir_expr.span,
+            )])
+        }
+        hir::ExprKind::MethodCall(hir) => {
+            let (obj, args) = join(lower(*hir.obj.clone()), lower_all(hir.args.iter().cloned()))?;
+            lower_method_call(obj, &hir.method, args).map_err(|e| {
+                vec![MirLoweringError::new(
+                    format!("MIR error: {}", e.0),
+                    hir_expr.span,
+                )]
+            })?
         }
         hir::ExprKind::Binary(hir) => {
-            let l = lower(*hir.lhs.clone())?;
-            let r = lower(*hir.rhs.clone())?;
+            let (l, r) = join(lower(*hir.lhs.clone()), lower(*hir.rhs.clone()))?;
+            // `isProven` is `Boolean` but is lowered to the proposition itself
+            let sigma_operand = l.tpe() == SType::SSigmaProp || r.tpe() == SType::SSigmaProp;
+            if hir_expr.tpe == Some(SType::SSigmaProp)
+                || (matches!(hir.op.node, BinaryOp::And | BinaryOp::Or) && sigma_operand)
+            {
+                return lower_sigma_op(&hir.op.node, l, r).map_err(|e| {
+                    vec![MirLoweringError::new(
+                        format!("MIR error: {}", e.0),
+                        hir_expr.span,
+                    )]
+                });
+            }
+            let kind = match hir.op.node {
+                BinaryOp::Xor if l.tpe().is_numeric() => BitOp::BitXor.into(),
+                ref op => op.clone().into(),
+            };
             BinOp {
-                kind: hir.op.node.clone().into(),
+                kind,
                 left: l.into(),
                 right: r.into(),
             }
             .into()
         }
+        hir::ExprKind::Unary(hir) => {
+            let input = lower(*hir.expr.clone())?;
+            match hir.op.node {
+                hir::UnaryOp::Neg => Negation::try_build(input).map(Expr::from),
+                hir::UnaryOp::Not => LogicalNot::try_build(input).map(Expr::from),
+                hir::UnaryOp::BitInversion => BitInversion::try_build(input).map(Expr::from),
+            }
+            .map_err(|e| {
+                vec![MirLoweringError::new(
+                    format!("MIR error: {}", e.0),
+                    hir_expr.span,
+                )]
+            })?
+        }
+        hir::ExprKind::If(hir) => {
+            let ((condition, true_branch), false_branch) = join(
+                join(
+                    lower(*hir.condition.clone()),
+                    lower(*hir.then_branch.clone()),
+                ),
+                lower(*hir.else_branch.clone()),
+            )?;
+            If {
+                condition: Box::new(condition),
+                true_branch: Box::new(true_branch),
+                false_branch: Box::new(false_branch),
+            }
+            .into()
+        }
         hir::ExprKind::Literal(hir) => {
             let constant: Constant = match *hir {
                 hir::Literal::Int(v) => v.into(),
                 hir::Literal::Long(v) => v.into(),
+                hir::Literal::Boolean(v) => v.into(),
+                hir::Literal::Str(_) => {
+                    return Err(vec![MirLoweringError::new(
+                        "MIR error: String literals are only supported as PK arguments".to_string(),
+                        hir_expr.span,
+                    )])
+                }
             };
             constant.into()
         }
+        hir::ExprKind::Block(hir) => {
+            let items = collect_all(hir.bindings.iter().map(|val| {
+                let (id, rhs) = join(val_id(val.id, val.name.span), lower(*val.rhs.clone()))?;
+                Ok(ValDef {
+                    id,
+                    rhs: Box::new(rhs),
+                }
+                .into())
+            }));
+            let (items, result) = join(items, lower(*hir.body.clone()))?;
+            BlockValue {
+                items,
+                result: Box::new(result),
+            }
+            .into()
+        }
+        hir::ExprKind::ValUse(hir) => {
+            let tpe = hir_expr.tpe.clone().ok_or_else(|| {
+                vec![MirLoweringError::new(
+                    format!("MIR error: missing tpe for HIR: {0:?}", hir_expr),
+                    hir_expr.span,
+                )]
+            })?;
+            ValUse {
+                val_id: ValId(hir.id.0),
+                tpe,
+            }
+            .into()
+        }
+        hir::ExprKind::Lambda(hir) => {
+            let args = collect_all(hir.args.iter().map(|arg| {
+                Ok(FuncArg {
+                    idx: val_id(arg.id, arg.name.span)?,
+                    tpe: arg.tpe.clone(),
+                })
+            }));
+            let (args, body) = join(args, lower(*hir.body.clone()))?;
+            FuncValue::new(args, body).into()
+        }
+        hir::ExprKind::Collection(hir) => {
+            let items = lower_all(hir.items.iter().cloned())?;
+            let elem_tpe = match &hir_expr.tpe {
+                Some(SType::SColl(elem_tpe)) => *elem_tpe.clone(),
+                tpe => {
+                    return Err(vec![MirLoweringError::new(
+                        format!("MIR error: expected collection type, got {:?}", tpe),
+                        hir_expr.span,
+                    )])
+                }
+            };
+            Collection::new(elem_tpe, items)
+                .map_err(|e| {
+                    vec![MirLoweringError::new(
+                        format!("MIR error: {}", e.0),
+                        hir_expr.span,
+                    )]
+                })?
+                .into()
+        }
+        hir::ExprKind::Apply(hir) => {
+            let args = lower_all(hir.args.iter().cloned());
+            match &hir.func.kind {
+                hir::ExprKind::PredefFunc(func) => lower_predef_func(func, args?),
+                _ => {
+                    let (func, args) = join(lower(*hir.func.clone()), args)?;
+                    Apply::new(func, args).map(Expr::from)
+                }
+            }
+            .map_err(|e| {
+                vec![MirLoweringError::new(
+                    format!("MIR error: {}", e.0),
+                    hir_expr.span,
+                )]
+            })?
+        }
     };
     let hir_tpe = hir_expr.tpe.clone().ok_or_else(|| {
-        MirLoweringError::new(
+        vec![MirLoweringError::new(
             format!("MIR error: missing tpe for HIR: {0:?}", hir_expr),
             hir_expr.span,
-        )
+        )]
     })?;
-    if mir.tpe() == hir_tpe {
+    // `isProven` is `Boolean` but is lowered to the proposition itself
+    let is_proven =
+        hir_tpe == SType::SBoolean && mir.tpe() == SType::SSigmaProp && has_is_proven(&hir_expr);
+    if mir.tpe() == hir_tpe || is_proven {
         Ok(mir)
     } else {
-        Err(MirLoweringError::new(
+        Err(vec![MirLoweringError::new(
             format!(
                 "MIR error: lowered MIR type != HIR type ({0:?} != {1:?})",
                 mir.tpe(),
                 hir_expr.tpe
             ),
             hir_expr.span,
-        ))
+        )])
+    }
+}
+
+/// Lowers all the expressions, reporting the errors of all of them
+fn lower_all(
+    hir_exprs: impl Iterator<Item = hir::Expr>,
+) -> Result<Vec<Expr>, Vec<MirLoweringError>> {
+    collect_all(hir_exprs.map(lower))
+}
+
+/// All the values, or the errors of all the failed ones
+fn collect_all<T>(
+    results: impl Iterator<Item = Result<T, Vec<MirLoweringError>>>,
+) -> Result<Vec<T>, Vec<MirLoweringError>> {
+    let mut values = Vec::new();
+    let mut errors = Vec::new();
+    for res in results {
+        match res {
+            Ok(v) => values.push(v),
+            Err(mut e) => errors.append(&mut e),
+        }
+    }
+    if errors.is_empty() {
+        Ok(values)
+    } else {
+        Err(errors)
+    }
+}
+
+/// Both values, or the errors of both
+fn join<A, B>(
+    a: Result<A, Vec<MirLoweringError>>,
+    b: Result<B, Vec<MirLoweringError>>,
+) -> Result<(A, B), Vec<MirLoweringError>> {
+    match (a, b) {
+        (Ok(a), Ok(b)) => Ok((a, b)),
+        (Err(mut a), Err(mut b)) => {
+            a.append(&mut b);
+            Err(a)
+        }
+        (Err(e), _) | (_, Err(e)) => Err(e),
+    }
+}
+
+fn lower_method_call(
+    obj: Expr,
+    method: &Method,
+    args: Vec<Expr>,
+) -> Result<Expr, InvalidArgumentError> {
+    Ok(match (method, args.as_slice()) {
+        (Method::BoxValue, []) => ExtractAmount::try_build(obj)?.into(),
+        (Method::BoxPropositionBytes, []) => ExtractScriptBytes::try_build(obj)?.into(),
+        (Method::BoxBytes, []) => ExtractBytes::try_build(obj)?.into(),
+        (Method::BoxBytesWithoutRef, []) => ExtractBytesWithNoRef::try_build(obj)?.into(),
+        (Method::BoxId, []) => ExtractId::try_build(obj)?.into(),
+        (Method::BoxCreationInfo, []) => ExtractCreationInfo::try_build(obj)?.into(),
+        (Method::BoxRegister { id, tpe }, []) => {
+            ExtractRegisterAs::new(obj, *id, SType::SOption(tpe.clone().into()))?.into()
+        }
+        (Method::SigmaPropBytes, []) => SigmaPropBytes::try_build(obj)?.into(),
+        // only allowed in the top-level proposition (checked by the binder)
+        (Method::SigmaPropIsProven, []) => obj,
+        (Method::OptionGet, []) => OptionGet::try_build(obj)?.into(),
+        (Method::OptionIsDefined, []) => OptionIsDefined::try_build(obj)?.into(),
+        (Method::OptionGetOrElse, [default]) => OptionGetOrElse::new(obj, default.clone())?.into(),
+        (Method::CollByIndex, [index]) => ByIndex::new(obj, index.clone(), None)?.into(),
+        (Method::CollSize, []) => SizeOf::try_build(obj)?.into(),
+        (Method::CollSlice, [from, until]) => Slice::new(obj, from.clone(), until.clone())?.into(),
+        (Method::CollAppend, [other]) => Append::new(obj, other.clone())?.into(),
+        (Method::CollMap, [mapper]) => Map::new(obj, mapper.clone())?.into(),
+        (Method::CollFilter, [condition]) => Filter::new(obj, condition.clone())?.into(),
+        (Method::CollExists, [condition]) => Exists::new(obj, condition.clone())?.into(),
+        (Method::CollForAll, [condition]) => ForAll::new(obj, condition.clone())?.into(),
+        (Method::CollFold, [zero, fold_op]) => {
+            Fold::new(obj, zero.clone(), fold_op.clone())?.into()
+        }
+        (Method::TupleField(index), []) => {
+            let field_index = TupleFieldIndex::try_from(*index).map_err(|_| {
+                InvalidArgumentError(format!("invalid tuple field index: {}", index))
+            })?;
+            SelectField::new(obj, field_index)?.into()
+        }
+        (Method::Property(method), []) => PropertyCall::new(obj, method.clone())?.into(),
+        (Method::Call(method), _) => MethodCall::new(obj, method.clone(), args)?.into(),
+        _ => {
+            return Err(InvalidArgumentError(format!(
+                "unexpected arguments {:?} for {:?}",
+                args, method
+            )))
+        }
+    })
+}
+
+fn lower_predef_func(func: &PredefFunc, args: Vec<Expr>) -> Result<Expr, InvalidArgumentError> {
+    Ok(match (func, args.as_slice()) {
+        (PredefFunc::SigmaProp, [condition]) => {
+            BoolToSigmaProp::try_build(condition.clone())?.into()
+        }
+        (PredefFunc::ProveDlog, [pk]) => CreateProveDlog::try_build(pk.clone())?.into(),
+        (PredefFunc::ProveDhTuple, [g, h, u, v]) => {
+            CreateProveDhTuple::new(g.clone(), h.clone(), u.clone(), v.clone())?.into()
+        }
+        (PredefFunc::AtLeast, [bound, props]) => Atleast::new(bound.clone(), props.clone())?.into(),
+        (PredefFunc::AllOf, [conditions]) => And {
+            input: Box::new(conditions.clone()),
+        }
+        .into(),
+        (PredefFunc::AnyOf, [conditions]) => Or {
+            input: Box::new(conditions.clone()),
+        }
+        .into(),
+        (PredefFunc::Blake2b256, [bytes]) => CalcBlake2b256::try_build(bytes.clone())?.into(),
+        (PredefFunc::Sha256, [bytes]) => CalcSha256::try_build(bytes.clone())?.into(),
+        (PredefFunc::DecodePoint, [bytes]) => DecodePoint::try_build(bytes.clone())?.into(),
+        _ => {
+            return Err(InvalidArgumentError(format!(
+                "unexpected arguments {:?} for {:?}",
+                args, func
+            )))
+        }
+    })
+}
+
+/// `true` if `isProven` is the expression itself, an operand of its `&&`/`||` or the result of its
+/// block, i.e. the expression is lowered to `SigmaProp`
+fn has_is_proven(expr: &hir::Expr) -> bool {
+    match &expr.kind {
+        hir::ExprKind::MethodCall(call) => call.method == Method::SigmaPropIsProven,
+        hir::ExprKind::Binary(binary) if matches!(binary.op.node, BinaryOp::And | BinaryOp::Or) => {
+            has_is_proven(&binary.lhs) || has_is_proven(&binary.rhs)
+        }
+        hir::ExprKind::Block(block) => has_is_proven(&block.body),
+        _ => false,
     }
 }
 
+/// `&&` and `||` with a `SigmaProp` operand, a `Boolean` operand is converted to `SigmaProp`
+fn lower_sigma_op(op: &BinaryOp, l: Expr, r: Expr) -> Result<Expr, InvalidArgumentError> {
+    let to_sigma_prop = |e: Expr| -> Result<Expr, InvalidArgumentError> {
+        if e.tpe() == SType::SBoolean {
+            Ok(BoolToSigmaProp::try_build(e)?.into())
+        } else {
+            Ok(e)
+        }
+    };
+    let items = vec![to_sigma_prop(l)?, to_sigma_prop(r)?];
+    Ok(match op {
+        BinaryOp::And => SigmaAnd::new(items)?.into(),
+        BinaryOp::Or => SigmaOr::new(items)?.into(),
+        _ => {
+            return Err(InvalidArgumentError(format!(
+                "unexpected SigmaProp operation {:?}",
+                op
+            )))
+        }
+    })
+}
+
+fn val_id(id: Option<hir::BindingId>, span: TextRange) -> Result<ValId, Vec<MirLoweringError>> {
+    id.map(|id| ValId(id.0)).ok_or_else(|| {
+        vec![MirLoweringError::new(
+            "MIR error: unbound value".to_string(),
+            span,
+        )]
+    })
+}
+
 impl From<hir::BinaryOp> for BinOpKind {
     fn from(op: hir::BinaryOp) -> Self {
         match op {
@@ -82,6 +473,18 @@ impl From<hir::BinaryOp> for BinOpKind {
             BinaryOp::Minus => ArithOp::Minus.into(),
             BinaryOp::Multiply => ArithOp::Multiply.into(),
             BinaryOp::Divide => ArithOp::Divide.into(),
+            BinaryOp::Eq => RelationOp::Eq.into(),
+            BinaryOp::NEq => RelationOp::NEq.into(),
+            BinaryOp::Lt => RelationOp::Lt.into(),
+            BinaryOp::Le => RelationOp::Le.into(),
+            BinaryOp::Gt => RelationOp::Gt.into(),
+            BinaryOp::Ge => RelationOp::Ge.into(),
+            BinaryOp::And => LogicalOp::And.into(),
+            BinaryOp::Or => LogicalOp::Or.into(),
+            // bitwise XOR for numeric operands is chosen in `lower`
+            BinaryOp::Xor => LogicalOp::Xor.into(),
+            BinaryOp::BitAnd => BitOp::BitAnd.into(),
+            BinaryOp::BitOr => BitOp::BitOr.into(),
         }
     }
 }
@@ -189,4 +592,1275 @@ mod tests {
             )"#]],
         );
     }
+
+    #[test]
+    fn block_with_vals() {
+        check(
+            "{ val x = HEIGHT; x + 1 }",
+            expect![[r#"
+                BlockValue(
+                    BlockValue {
+                        items: [
+                            ValDef(
+                                ValDef {
+                                    id: ValId(
+                                        0,
+                                    ),
+                                    rhs: GlobalVars(
+                                        Height,
+                                    ),
+                                },
+                            ),
+                        ],
+                        result: BinOp(
+                            BinOp {
+                                kind: Arith(
+                                    Plus,
+                                ),
+                                left: ValUse(
+                                    ValUse {
+                                        val_id: ValId(
+                                            0,
+                                        ),
+                                        tpe: SInt,
+                                    },
+                                ),
+                                right: Const(
+                                    "1: SInt",
+                                ),
+                            },
+                        ),
+                    },
+                )"#]],
+        );
+    }
+
+    #[test]
+    fn lambda_apply() {
+        check(
+            "{ val f = (x: Int) => x + 1; f(HEIGHT) }",
+            expect![[r#"
+                BlockValue(
+                    BlockValue {
+                        items: [
+                            ValDef(
+                                ValDef {
+                                    id: ValId(
+                                        1,
+                                    ),
+                                    rhs: FuncValue(
+                                        FuncValue {
+                                            args: [
+                                                FuncArg {
+                                                    idx: ValId(
+                                                        0,
+                                                    ),
+                                                    tpe: SInt,
+                                                },
+                                            ],
+                                            body: BinOp(
+                                                BinOp {
+                                                    kind: Arith(
+                                                        Plus,
+                                                    ),
+                                                    left: ValUse(
+                                                        ValUse {
+                                                            val_id: ValId(
+                                                                0,
+                                                            ),
+                                                            tpe: SInt,
+                                                        },
+                                                    ),
+                                                    right: Const(
+                                                        "1: SInt",
+                                                    ),
+                                                },
+                                            ),
+                                            tpe: SFunc(
+                                                SFunc {
+                                                    t_dom: [
+                                                        SInt,
+                                                    ],
+                                                    t_range: SInt,
+                                                    tpe_params: [],
+                                                },
+                                            ),
+                                        },
+                                    ),
+                                },
+                            ),
+                        ],
+                        result: Apply(
+                            Apply {
+                                func: ValUse(
+                                    ValUse {
+                                        val_id: ValId(
+                                            1,
+                                        ),
+                                        tpe: SFunc(
+                                            SFunc {
+                                                t_dom: [
+                                                    SInt,
+                                                ],
+                                                t_range: SInt,
+                                                tpe_params: [],
+                                            },
+                                        ),
+                                    },
+                                ),
+                                args: [
+                                    GlobalVars(
+                                        Height,
+                                    ),
+                                ],
+                                tpe: SInt,
+                            },
+                        ),
+                    },
+                )"#]],
+        );
+    }
+
+    #[test]
+    fn if_else() {
+        check(
+            "if (HEIGHT >= 10) HEIGHT else -HEIGHT",
+            expect![[r#"
+            If(
+                If {
+                    condition: BinOp(
+                        BinOp {
+                            kind: Relation(
+                                Ge,
+                            ),
+                            left: GlobalVars(
+                                Height,
+                            ),
+                            right: Const(
+                                "10: SInt",
+                            ),
+                        },
+                    ),
+                    true_branch: GlobalVars(
+                        Height,
+                    ),
+                    false_branch: Negation(
+                        Negation {
+                            input: GlobalVars(
+                                Height,
+                            ),
+                        },
+                    ),
+                },
+            )"#]],
+        );
+    }
+
+    #[test]
+    fn xor() {
+        check(
+            "(HEIGHT ^ 1) == 0 ^ true",
+            expect![[r#"
+            BinOp(
+                BinOp {
+                    kind: Logical(
+                        Xor,
+                    ),
+                    left: BinOp(
+                        BinOp {
+                            kind: Relation(
+                                Eq,
+                            ),
+                            left: BinOp(
+                                BinOp {
+                                    kind: Bit(
+                                        BitXor,
+                                    ),
+                                    left: GlobalVars(
+                                        Height,
+                                    ),
+                                    right: Const(
+                                        "1: SInt",
+                                    ),
+                                },
+                            ),
+                            right: Const(
+                                "0: SInt",
+                            ),
+                        },
+                    ),
+                    right: Const(
+                        "true: SBoolean",
+                    ),
+                },
+            )"#]],
+        );
+    }
+
+    #[test]
+    fn bit_ops() {
+        check(
+            "~HEIGHT & 255 | 1",
+            expect![[r#"
+            BinOp(
+                BinOp {
+                    kind: Bit(
+                        BitOr,
+                    ),
+                    left: BinOp(
+                        BinOp {
+                            kind: Bit(
+                                BitAnd,
+                            ),
+                            left: BitInversion(
+                                BitInversion {
+                                    input: GlobalVars(
+                                        Height,
+                                    ),
+                                },
+                            ),
+                            right: Const(
+                                "255: SInt",
+                            ),
+                        },
+                    ),
+                    right: Const(
+                        "1: SInt",
+                    ),
+                },
+            )"#]],
+        );
+    }
+
+    #[test]
+    fn box_properties() {
+        check(
+            "SELF.value > 0L && SELF.id == SELF.propositionBytes",
+            expect![[r#"
+                BinOp(
+                    BinOp {
+                        kind: Logical(
+                            And,
+                        ),
+                        left: BinOp(
+                            BinOp {
+                                kind: Relation(
+                                    Gt,
+                                ),
+                                left: ExtractAmount(
+                                    ExtractAmount {
+                                        input: GlobalVars(
+                                            SelfBox,
+                                        ),
+                                    },
+                                ),
+                                right: Const(
+                                    "0: SLong",
+                                ),
+                            },
+                        ),
+                        right: BinOp(
+                            BinOp {
+                                kind: Relation(
+                                    Eq,
+                                ),
+                                left: ExtractId(
+                                    ExtractId {
+                                        input: GlobalVars(
+                                            SelfBox,
+                                        ),
+                                    },
+                                ),
+                                right: ExtractScriptBytes(
+                                    ExtractScriptBytes {
+                                        input: GlobalVars(
+                                            SelfBox,
+                                        ),
+                                    },
+                                ),
+                            },
+                        ),
+                    },
+                )"#]],
+        );
+    }
+
+    #[test]
+    fn register_get_or_else() {
+        check(
+            "SELF.R4[Long].getOrElse(0L)",
+            expect![[r#"
+            OptionGetOrElse(
+                OptionGetOrElse {
+                    input: ExtractRegisterAs(
+                        ExtractRegisterAs {
+                            input: GlobalVars(
+                                SelfBox,
+                            ),
+                            register_id: 4,
+                            elem_tpe: SLong,
+                        },
+                    ),
+                    default: Const(
+                        "0: SLong",
+                    ),
+                    elem_tpe: SLong,
+                },
+            )"#]],
+        );
+    }
+
+    #[test]
+    fn context_properties() {
+        check(
+            "dataInputs",
+            expect![[r#"
+            ProperyCall(
+                PropertyCall {
+                    obj: Context,
+                    method: SMethod {
+                        obj_type: Context,
+                        method_raw: SMethodDesc {
+                            name: "dataInputs",
+                            method_id: MethodId(
+                                1,
+                            ),
+                            tpe: SFunc {
+                                t_dom: [
+                                    SContext,
+                                ],
+                                t_range: SColl(
+                                    SBox,
+                                ),
+                                tpe_params: [],
+                            },
+                        },
+                    },
+                },
+            )"#]],
+        );
+    }
+
+    #[test]
+    fn box_tokens() {
+        check(
+            "SELF.tokens",
+            expect![[r#"
+            ProperyCall(
+                PropertyCall {
+                    obj: GlobalVars(
+                        SelfBox,
+                    ),
+                    method: SMethod {
+                        obj_type: Box,
+                        method_raw: SMethodDesc {
+                            name: "tokens",
+                            method_id: MethodId(
+                                8,
+                            ),
+                            tpe: SFunc {
+                                t_dom: [
+                                    SBox,
+                                ],
+                                t_range: SColl(
+                                    STuple(
+                                        [
+                                            SColl(
+                                                SByte,
+                                            ),
+                                            SLong,
+                                        ],
+                                    ),
+                                ),
+                                tpe_params: [],
+                            },
+                        },
+                    },
+                },
+            )"#]],
+        );
+    }
+
+    #[test]
+    fn coll_literal_by_index() {
+        check(
+            "Coll(1, 2, 3)(HEIGHT)",
+            expect![[r#"
+            ByIndex(
+                ByIndex {
+                    input: Collection(
+                        Exprs {
+                            elem_tpe: SInt,
+                            items: [
+                                Const(
+                                    "1: SInt",
+                                ),
+                                Const(
+                                    "2: SInt",
+                                ),
+                                Const(
+                                    "3: SInt",
+                                ),
+                            ],
+                        },
+                    ),
+                    index: GlobalVars(
+                        Height,
+                    ),
+                    default: None,
+                    input_elem_tpe: SInt,
+                },
+            )"#]],
+        );
+    }
+
+    #[test]
+    fn coll_size_slice_append() {
+        check(
+            "(INPUTS ++ OUTPUTS).slice(1, 3).size",
+            expect![[r#"
+            SizeOf(
+                SizeOf {
+                    input: Slice(
+                        Slice {
+                            input: Append(
+                                Append {
+                                    input: GlobalVars(
+                                        Inputs,
+                                    ),
+                                    col_2: GlobalVars(
+                                        Outputs,
+                                    ),
+                                },
+                            ),
+                            from: Const(
+                                "1: SInt",
+                            ),
+                            until: Const(
+                                "3: SInt",
+                            ),
+                        },
+                    ),
+                },
+            )"#]],
+        );
+    }
+
+    #[test]
+    fn coll_map_filter() {
+        check(
+            "OUTPUTS.filter((b: Box) => b.value > 0L).map((b: Box) => b.value)",
+            expect![[r#"
+                Map(
+                    Map {
+                        input: Filter(
+                            Filter {
+                                input: GlobalVars(
+                                    Outputs,
+                                ),
+                                condition: FuncValue(
+                                    FuncValue {
+                                        args: [
+                                            FuncArg {
+                                                idx: ValId(
+                                                    0,
+                                                ),
+                                                tpe: SBox,
+                                            },
+                                        ],
+                                        body: BinOp(
+                                            BinOp {
+                                                kind: Relation(
+                                                    Gt,
+                                                ),
+                                                left: ExtractAmount(
+                                                    ExtractAmount {
+                                                        input: ValUse(
+                                                            ValUse {
+                                                                val_id: ValId(
+                                                                    0,
+                                                                ),
+                                                                tpe: SBox,
+                                                            },
+                                                        ),
+                                                    },
+                                                ),
+                                                right: Const(
+                                                    "0: SLong",
+                                                ),
+                                            },
+                                        ),
+                                        tpe: SFunc(
+                                            SFunc {
+                                                t_dom: [
+                                                    SBox,
+                                                ],
+                                                t_range: SBoolean,
+                                                tpe_params: [],
+                                            },
+                                        ),
+                                    },
+                                ),
+                                elem_tpe: SBox,
+                            },
+                        ),
+                        mapper: FuncValue(
+                            FuncValue {
+                                args: [
+                                    FuncArg {
+                                        idx: ValId(
+                                            1,
+                                        ),
+                                        tpe: SBox,
+                                    },
+                                ],
+                                body: ExtractAmount(
+                                    ExtractAmount {
+                                        input: ValUse(
+                                            ValUse {
+                                                val_id: ValId(
+                                                    1,
+                                                ),
+                                                tpe: SBox,
+                                            },
+                                        ),
+                                    },
+                                ),
+                                tpe: SFunc(
+                                    SFunc {
+                                        t_dom: [
+                                            SBox,
+                                        ],
+                                        t_range: SLong,
+                                        tpe_params: [],
+                                    },
+                                ),
+                            },
+                        ),
+                        mapper_sfunc: SFunc {
+                            t_dom: [
+                                SBox,
+                            ],
+                            t_range: SLong,
+                            tpe_params: [],
+                        },
+                    },
+                )"#]],
+        );
+    }
+
+    #[test]
+    fn coll_exists_forall() {
+        check(
+            "INPUTS.exists((b: Box) => b.id == SELF.id) && OUTPUTS.forall((b: Box) => b.value > 0L)",
+            expect![[r#"
+                BinOp(
+                    BinOp {
+                        kind: Logical(
+                            And,
+                        ),
+                        left: Exists(
+                            Exists {
+                                input: GlobalVars(
+                                    Inputs,
+                                ),
+                                condition: FuncValue(
+                                    FuncValue {
+                                        args: [
+                                            FuncArg {
+                                                idx: ValId(
+                                                    0,
+                                                ),
+                                                tpe: SBox,
+                                            },
+                                        ],
+                                        body: BinOp(
+                                            BinOp {
+                                                kind: Relation(
+                                                    Eq,
+                                                ),
+                                                left: ExtractId(
+                                                    ExtractId {
+                                                        input: ValUse(
+                                                            ValUse {
+                                                                val_id: ValId(
+                                                                    0,
+                                                                ),
+                                                                tpe: SBox,
+                                                            },
+                                                        ),
+                                                    },
+                                                ),
+                                                right: ExtractId(
+                                                    ExtractId {
+                                                        input: GlobalVars(
+                                                            SelfBox,
+                                                        ),
+                                                    },
+                                                ),
+                                            },
+                                        ),
+                                        tpe: SFunc(
+                                            SFunc {
+                                                t_dom: [
+                                                    SBox,
+                                                ],
+                                                t_range: SBoolean,
+                                                tpe_params: [],
+                                            },
+                                        ),
+                                    },
+                                ),
+                                elem_tpe: SBox,
+                            },
+                        ),
+                        right: ForAll(
+                            ForAll {
+                                input: GlobalVars(
+                                    Outputs,
+                                ),
+                                condition: FuncValue(
+                                    FuncValue {
+                                        args: [
+                                            FuncArg {
+                                                idx: ValId(
+                                                    1,
+                                                ),
+                                                tpe: SBox,
+                                            },
+                                        ],
+                                        body: BinOp(
+                                            BinOp {
+                                                kind: Relation(
+                                                    Gt,
+                                                ),
+                                                left: ExtractAmount(
+                                                    ExtractAmount {
+                                                        input: ValUse(
+                                                            ValUse {
+                                                                val_id: ValId(
+                                                                    1,
+                                                                ),
+                                                                tpe: SBox,
+                                                            },
+                                                        ),
+                                                    },
+                                                ),
+                                                right: Const(
+                                                    "0: SLong",
+                                                ),
+                                            },
+                                        ),
+                                        tpe: SFunc(
+                                            SFunc {
+                                                t_dom: [
+                                                    SBox,
+                                                ],
+                                                t_range: SBoolean,
+                                                tpe_params: [],
+                                            },
+                                        ),
+                                    },
+                                ),
+                                elem_tpe: SBox,
+                            },
+                        ),
+                    },
+                )"#]],
+        );
+    }
+
+    #[test]
+    fn coll_fold() {
+        check(
+            "OUTPUTS.fold(0L, (acc: Long, b: Box) => acc + b.value)",
+            expect![[r#"
+                Fold(
+                    Fold {
+                        input: GlobalVars(
+                            Outputs,
+                        ),
+                        zero: Const(
+                            "0: SLong",
+                        ),
+                        fold_op: FuncValue(
+                            FuncValue {
+                                args: [
+                                    FuncArg {
+                                        idx: ValId(
+                                            0,
+                                        ),
+                                        tpe: STuple(
+                                            [
+                                                SLong,
+                                                SBox,
+                                            ],
+                                        ),
+                                    },
+                                ],
+                                body: BlockValue(
+                                    BlockValue {
+                                        items: [
+                                            ValDef(
+                                                ValDef {
+                                                    id: ValId(
+                                                        1,
+                                                    ),
+                                                    rhs: SelectField(
+                                                        SelectField {
+                                                            input: ValUse(
+                                                                ValUse {
+                                                                    val_id: ValId(
+                                                                        0,
+                                                                    ),
+                                                                    tpe: STuple(
+                                                                        [
+                                                                            SLong,
+                                                                            SBox,
+                                                                        ],
+                                                                    ),
+                                                                },
+                                                            ),
+                                                            field_index: TupleFieldIndex(
+                                                                1,
+                                                            ),
+                                                            field_tpe: SLong,
+                                                        },
+                                                    ),
+                                                },
+                                            ),
+                                            ValDef(
+                                                ValDef {
+                                                    id: ValId(
+                                                        2,
+                                                    ),
+                                                    rhs: SelectField(
+                                                        SelectField {
+                                                            input: ValUse(
+                                                                ValUse {
+                                                                    val_id: ValId(
+                                                                        0,
+                                                                    ),
+                                                                    tpe: STuple(
+                                                                        [
+                                                                            SLong,
+                                                                            SBox,
+                                                                        ],
+                                                                    ),
+                                                                },
+                                                            ),
+                                                            field_index: TupleFieldIndex(
+                                                                2,
+                                                            ),
+                                                            field_tpe: SBox,
+                                                        },
+                                                    ),
+                                                },
+                                            ),
+                                        ],
+                                        result: BinOp(
+                                            BinOp {
+                                                kind: Arith(
+                                                    Plus,
+                                                ),
+                                                left: ValUse(
+                                                    ValUse {
+                                                        val_id: ValId(
+                                                            1,
+                                                        ),
+                                                        tpe: SLong,
+                                                    },
+                                                ),
+                                                right: ExtractAmount(
+                                                    ExtractAmount {
+                                                        input: ValUse(
+                                                            ValUse {
+                                                                val_id: ValId(
+                                                                    2,
+                                                                ),
+                                                                tpe: SBox,
+                                                            },
+                                                        ),
+                                                    },
+                                                ),
+                                            },
+                                        ),
+                                    },
+                                ),
+                                tpe: SFunc(
+                                    SFunc {
+                                        t_dom: [
+                                            STuple(
+                                                [
+                                                    SLong,
+                                                    SBox,
+                                                ],
+                                            ),
+                                        ],
+                                        t_range: SLong,
+                                        tpe_params: [],
+                                    },
+                                ),
+                            },
+                        ),
+                    },
+                )"#]],
+        );
+    }
+
+    #[test]
+    fn coll_generic_methods() {
+        check(
+            "OUTPUTS.indices.zip(OUTPUTS.flatMap((b: Box) => b.propositionBytes))",
+            expect![[r#"
+                MethodCall(
+                    MethodCall {
+                        obj: ProperyCall(
+                            PropertyCall {
+                                obj: GlobalVars(
+                                    Outputs,
+                                ),
+                                method: SMethod {
+                                    obj_type: Coll,
+                                    method_raw: SMethodDesc {
+                                        name: "indices",
+                                        method_id: MethodId(
+                                            14,
+                                        ),
+                                        tpe: SFunc {
+                                            t_dom: [
+                                                SColl(
+                                                    SBox,
+                                                ),
+                                            ],
+                                            t_range: SColl(
+                                                SInt,
+                                            ),
+                                            tpe_params: [],
+                                        },
+                                    },
+                                },
+                            },
+                        ),
+                        method: SMethod {
+                            obj_type: Coll,
+                            method_raw: SMethodDesc {
+                                name: "zip",
+                                method_id: MethodId(
+                                    29,
+                                ),
+                                tpe: SFunc {
+                                    t_dom: [
+                                        SColl(
+                                            SInt,
+                                        ),
+                                        SColl(
+                                            SByte,
+                                        ),
+                                    ],
+                                    t_range: SColl(
+                                        STuple(
+                                            [
+                                                SInt,
+                                                SByte,
+                                            ],
+                                        ),
+                                    ),
+                                    tpe_params: [],
+                                },
+                            },
+                        },
+                        args: [
+                            MethodCall(
+                                MethodCall {
+                                    obj: GlobalVars(
+                                        Outputs,
+                                    ),
+                                    method: SMethod {
+                                        obj_type: Coll,
+                                        method_raw: SMethodDesc {
+                                            name: "flatMap",
+                                            method_id: MethodId(
+                                                15,
+                                            ),
+                                            tpe: SFunc {
+                                                t_dom: [
+                                                    SColl(
+                                                        SBox,
+                                                    ),
+                                                    SFunc(
+                                                        SFunc {
+                                                            t_dom: [
+                                                                SBox,
+                                                            ],
+                                                            t_range: SColl(
+                                                                SByte,
+                                                            ),
+                                                            tpe_params: [],
+                                                        },
+                                                    ),
+                                                ],
+                                                t_range: SColl(
+                                                    SByte,
+                                                ),
+                                                tpe_params: [],
+                                            },
+                                        },
+                                    },
+                                    args: [
+                                        FuncValue(
+                                            FuncValue {
+                                                args: [
+                                                    FuncArg {
+                                                        idx: ValId(
+                                                            0,
+                                                        ),
+                                                        tpe: SBox,
+                                                    },
+                                                ],
+                                                body: ExtractScriptBytes(
+                                                    ExtractScriptBytes {
+                                                        input: ValUse(
+                                                            ValUse {
+                                                                val_id: ValId(
+                                                                    0,
+                                                                ),
+                                                                tpe: SBox,
+                                                            },
+                                                        ),
+                                                    },
+                                                ),
+                                                tpe: SFunc(
+                                                    SFunc {
+                                                        t_dom: [
+                                                            SBox,
+                                                        ],
+                                                        t_range: SColl(
+                                                            SByte,
+                                                        ),
+                                                        tpe_params: [],
+                                                    },
+                                                ),
+                                            },
+                                        ),
+                                    ],
+                                },
+                            ),
+                        ],
+                    },
+                )"#]],
+        );
+    }
+
+    #[test]
+    fn tuple_field() {
+        check(
+            "SELF.tokens(0)._2",
+            expect![[r#"
+            SelectField(
+                SelectField {
+                    input: ByIndex(
+                        ByIndex {
+                            input: ProperyCall(
+                                PropertyCall {
+                                    obj: GlobalVars(
+                                        SelfBox,
+                                    ),
+                                    method: SMethod {
+                                        obj_type: Box,
+                                        method_raw: SMethodDesc {
+                                            name: "tokens",
+                                            method_id: MethodId(
+                                                8,
+                                            ),
+                                            tpe: SFunc {
+                                                t_dom: [
+                                                    SBox,
+                                                ],
+                                                t_range: SColl(
+                                                    STuple(
+                                                        [
+                                                            SColl(
+                                                                SByte,
+                                                            ),
+                                                            SLong,
+                                                        ],
+                                                    ),
+                                                ),
+                                                tpe_params: [],
+                                            },
+                                        },
+                                    },
+                                },
+                            ),
+                            index: Const(
+                                "0: SInt",
+                            ),
+                            default: None,
+                            input_elem_tpe: STuple(
+                                [
+                                    SColl(
+                                        SByte,
+                                    ),
+                                    SLong,
+                                ],
+                            ),
+                        },
+                    ),
+                    field_index: TupleFieldIndex(
+                        2,
+                    ),
+                    field_tpe: SLong,
+                },
+            )"#]],
+        );
+    }
+
+    #[test]
+    fn sigma_prop_and_bool() {
+        check(
+            "sigmaProp(HEIGHT > 100) && proveDlog(decodePoint(minerPubKey)) || HEIGHT > 200",
+            expect![[r#"
+                SigmaOr(
+                    SigmaOr {
+                        items: BoundedVec {
+                            inner: [
+                                SigmaAnd(
+                                    SigmaAnd {
+                                        items: BoundedVec {
+                                            inner: [
+                                                BoolToSigmaProp(
+                                                    BoolToSigmaProp {
+                                                        input: BinOp(
+                                                            BinOp {
+                                                                kind: Relation(
+                                                                    Gt,
+                                                                ),
+                                                                left: GlobalVars(
+                                                                    Height,
+                                                                ),
+                                                                right: Const(
+                                                                    "100: SInt",
+                                                                ),
+                                                            },
+                                                        ),
+                                                    },
+                                                ),
+                                                CreateProveDlog(
+                                                    CreateProveDlog {
+                                                        input: DecodePoint(
+                                                            DecodePoint {
+                                                                input: GlobalVars(
+                                                                    MinerPubKey,
+                                                                ),
+                                                            },
+                                                        ),
+                                                    },
+                                                ),
+                                            ],
+                                        },
+                                    },
+                                ),
+                                BoolToSigmaProp(
+                                    BoolToSigmaProp {
+                                        input: BinOp(
+                                            BinOp {
+                                                kind: Relation(
+                                                    Gt,
+                                                ),
+                                                left: GlobalVars(
+                                                    Height,
+                                                ),
+                                                right: Const(
+                                                    "200: SInt",
+                                                ),
+                                            },
+                                        ),
+                                    },
+                                ),
+                            ],
+                        },
+                    },
+                )"#]],
+        );
+    }
+
+    #[test]
+    fn prove_dh_tuple() {
+        check(
+            "proveDHTuple(groupGenerator, groupGenerator, groupGenerator, groupGenerator)",
+            expect![[r#"
+                CreateProveDhTuple(
+                    CreateProveDhTuple {
+                        g: GlobalVars(
+                            GroupGenerator,
+                        ),
+                        h: GlobalVars(
+                            GroupGenerator,
+                        ),
+                        u: GlobalVars(
+                            GroupGenerator,
+                        ),
+                        v: GlobalVars(
+                            GroupGenerator,
+                        ),
+                    },
+                )"#]],
+        );
+    }
+
+    #[test]
+    fn at_least() {
+        check(
+            "atLeast(2, Coll(sigmaProp(true), sigmaProp(HEIGHT > 1), sigmaProp(false)))",
+            expect![[r#"
+                Atleast(
+                    Atleast {
+                        bound: Const(
+                            "2: SInt",
+                        ),
+                        input: Collection(
+                            Exprs {
+                                elem_tpe: SSigmaProp,
+                                items: [
+                                    BoolToSigmaProp(
+                                        BoolToSigmaProp {
+                                            input: Const(
+                                                "true: SBoolean",
+                                            ),
+                                        },
+                                    ),
+                                    BoolToSigmaProp(
+                                        BoolToSigmaProp {
+                                            input: BinOp(
+                                                BinOp {
+                                                    kind: Relation(
+                                                        Gt,
+                                                    ),
+                                                    left: GlobalVars(
+                                                        Height,
+                                                    ),
+                                                    right: Const(
+                                                        "1: SInt",
+                                                    ),
+                                                },
+                                            ),
+                                        },
+                                    ),
+                                    BoolToSigmaProp(
+                                        BoolToSigmaProp {
+                                            input: Const(
+                                                "false: SBoolean",
+                                            ),
+                                        },
+                                    ),
+                                ],
+                            },
+                        ),
+                    },
+                )"#]],
+        );
+    }
+
+    #[test]
+    fn all_of_any_of() {
+        check(
+            "allOf(Coll(HEIGHT > 1, true)) && anyOf(Coll(false, HEIGHT < 5))",
+            expect![[r#"
+                BinOp(
+                    BinOp {
+                        kind: Logical(
+                            And,
+                        ),
+                        left: And(
+                            And {
+                                input: Collection(
+                                    Exprs {
+                                        elem_tpe: SBoolean,
+                                        items: [
+                                            BinOp(
+                                                BinOp {
+                                                    kind: Relation(
+                                                        Gt,
+                                                    ),
+                                                    left: GlobalVars(
+                                                        Height,
+                                                    ),
+                                                    right: Const(
+                                                        "1: SInt",
+                                                    ),
+                                                },
+                                            ),
+                                            Const(
+                                                "true: SBoolean",
+                                            ),
+                                        ],
+                                    },
+                                ),
+                            },
+                        ),
+                        right: Or(
+                            Or {
+                                input: Collection(
+                                    Exprs {
+                                        elem_tpe: SBoolean,
+                                        items: [
+                                            Const(
+                                                "false: SBoolean",
+                                            ),
+                                            BinOp(
+                                                BinOp {
+                                                    kind: Relation(
+                                                        Lt,
+                                                    ),
+                                                    left: GlobalVars(
+                                                        Height,
+                                                    ),
+                                                    right: Const(
+                                                        "5: SInt",
+                                                    ),
+                                                },
+                                            ),
+                                        ],
+                                    },
+                                ),
+                            },
+                        ),
+                    },
+                )"#]],
+        );
+    }
+
+    #[test]
+    fn hashes() {
+        check(
+            "blake2b256(SELF.propositionBytes) == sha256(minerPubKey)",
+            expect![[r#"
+                BinOp(
+                    BinOp {
+                        kind: Relation(
+                            Eq,
+                        ),
+                        left: CalcBlake2b256(
+                            CalcBlake2b256 {
+                                input: ExtractScriptBytes(
+                                    ExtractScriptBytes {
+                                        input: GlobalVars(
+                                            SelfBox,
+                                        ),
+                                    },
+                                ),
+                            },
+                        ),
+                        right: CalcSha256(
+                            CalcSha256 {
+                                input: GlobalVars(
+                                    MinerPubKey,
+                                ),
+                            },
+                        ),
+                    },
+                )"#]],
+        );
+    }
 }
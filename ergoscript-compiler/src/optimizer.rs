@@ -0,0 +1,114 @@
+//! Optimisation passes over the compiled MIR ([`Expr`])
+//!
+//! Every pass preserves the result of the script on successful evaluation, the passes are
+//! switched on and off with [`Optimizations`].
+
+mod constant_folding;
+mod cse;
+mod dead_vals;
+mod inlining;
+mod simplification;
+mod traverse;
+mod validation;
+
+use ergotree_ir::mir::expr::Expr;
+use ergotree_ir::mir::expr::InvalidArgumentError;
+
+use crate::diagnostic::Diagnostic;
+use crate::diagnostic::DiagnosticCode;
+
+pub(crate) use validation::validate;
+
+/// Upper bound for the rounds of the passes that enable each other
+const MAX_ROUNDS: usize = 16;
+
+/// Optimisation passes to run
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Optimizations {
+    /// Evaluate operations on constants (`1 + 2` -> `3`, `if (true) a else b` -> `a`)
+    pub constant_folding: bool,
+    /// Simplify boolean and `SigmaProp` expressions (`x && true` -> `x`, `!(a < b)` -> `a >= b`,
+    /// `sigmaProp(a) && sigmaProp(b)` -> `sigmaProp(a && b)`)
+    pub boolean_simplification: bool,
+    /// Remove unused `val`s
+    pub dead_val_elimination: bool,
+    /// Inline the lambdas that are applied once
+    pub lambda_inlining: bool,
+    /// Compute the repeated sub-expressions once in a `val`
+    pub common_subexpression_extraction: bool,
+}
+
+impl Optimizations {
+    /// No passes
+    pub fn none() -> Self {
+        Optimizations {
+            constant_folding: false,
+            boolean_simplification: false,
+            dead_val_elimination: false,
+            lambda_inlining: false,
+            common_subexpression_extraction: false,
+        }
+    }
+}
+
+impl Default for Optimizations {
+    /// All passes
+    fn default() -> Self {
+        Optimizations {
+            constant_folding: true,
+            boolean_simplification: true,
+            dead_val_elimination: true,
+            lambda_inlining: true,
+            common_subexpression_extraction: true,
+        }
+    }
+}
+
+/// Error on optimisation (a rewritten node cannot be built or the optimised tree is invalid)
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct OptimizerError(pub String);
+
+impl OptimizerError {
+    /// Error description
+    pub fn pretty_desc(&self) -> String {
+        format!("Optimizer error: {}", self.0)
+    }
+
+    pub(crate) fn diagnostic(&self) -> Diagnostic {
+        Diagnostic::new(DiagnosticCode::OptimizerError, self.pretty_desc(), None)
+    }
+}
+
+impl From<InvalidArgumentError> for OptimizerError {
+    fn from(e: InvalidArgumentError) -> Self {
+        OptimizerError(e.0)
+    }
+}
+
+/// Runs the enabled passes over the expression. Folding, simplification, inlining and dead
+/// `val` elimination are repeated while they change the tree, sub-expressions are extracted last.
+pub fn optimize(expr: Expr, optimizations: &Optimizations) -> Result<Expr, OptimizerError> {
+    let mut expr = expr;
+    for _ in 0..MAX_ROUNDS {
+        let before = expr.clone();
+        if optimizations.lambda_inlining {
+            expr = inlining::inline_lambdas(expr)?;
+        }
+        if optimizations.constant_folding {
+            expr = constant_folding::fold_constants(expr)?;
+        }
+        if optimizations.boolean_simplification {
+            expr = simplification::simplify(expr)?;
+        }
+        if optimizations.dead_val_elimination {
+            expr = dead_vals::eliminate_dead_vals(expr)?;
+        }
+        if expr == before {
+            break;
+        }
+    }
+    if optimizations.common_subexpression_extraction {
+        expr = cse::extract_common_subexpressions(expr)?;
+    }
+    Ok(expr)
+}
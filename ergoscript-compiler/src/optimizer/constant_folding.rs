@@ -0,0 +1,207 @@
+//! Evaluation of the operations on constants at compile time
+
+use std::convert::TryFrom;
+
+use ergotree_ir::bigint256::BigInt256;
+use ergotree_ir::mir::bin_op::ArithOp;
+use ergotree_ir::mir::bin_op::BinOp;
+use ergotree_ir::mir::bin_op::BinOpKind;
+use ergotree_ir::mir::bin_op::LogicalOp;
+use ergotree_ir::mir::bin_op::RelationOp;
+use ergotree_ir::mir::constant::Constant;
+use ergotree_ir::mir::constant::Literal;
+use ergotree_ir::mir::expr::Expr;
+use ergotree_ir::mir::expr::InvalidArgumentError;
+use ergotree_ir::types::stype::SType;
+
+use super::traverse::rewrite_bottom_up;
+
+/// Replaces the operations on constants with their results. Operations failing on evaluation
+/// (overflow, division by zero, etc.) are left intact to fail at runtime.
+pub fn fold_constants(e: Expr) -> Result<Expr, InvalidArgumentError> {
+    rewrite_bottom_up(e, &mut |e| Ok(fold(e)))
+}
+
+fn fold(e: Expr) -> Expr {
+    let folded = match &e {
+        Expr::BinOp(op) => fold_bin_op(op),
+        Expr::LogicalNot(op) => bool_value(&op.input).map(|v| (!v).into()),
+        Expr::Negation(op) => match op.input.as_ref() {
+            Expr::Const(c) => int_value(c)
+                .and_then(i64::checked_neg)
+                .and_then(|v| int_constant(v, &c.tpe)),
+            _ => None,
+        },
+        Expr::Upcast(op) => match (op.input.as_ref(), &op.tpe) {
+            (Expr::Const(c), SType::SBigInt) => int_value(c).map(|v| BigInt256::from(v).into()),
+            (Expr::Const(c), tpe) => int_value(c).and_then(|v| int_constant(v, tpe)),
+            _ => None,
+        },
+        Expr::Downcast(op) => match op.input.as_ref() {
+            Expr::Const(c) => int_value(c).and_then(|v| int_constant(v, &op.tpe)),
+            _ => None,
+        },
+        Expr::If(op) => {
+            return match bool_value(&op.condition) {
+                Some(true) => *op.true_branch.clone(),
+                Some(false) => *op.false_branch.clone(),
+                None => e,
+            }
+        }
+        _ => None,
+    };
+    folded.map(Expr::Const).unwrap_or(e)
+}
+
+fn fold_bin_op(op: &BinOp) -> Option<Constant> {
+    let (l, r) = match (op.left.as_ref(), op.right.as_ref()) {
+        (Expr::Const(l), Expr::Const(r)) if l.tpe == r.tpe => (l, r),
+        _ => return None,
+    };
+    match op.kind {
+        BinOpKind::Arith(arith) => {
+            let (a, b) = (int_value(l)?, int_value(r)?);
+            let res = match arith {
+                ArithOp::Plus => a.checked_add(b),
+                ArithOp::Minus => a.checked_sub(b),
+                ArithOp::Multiply => a.checked_mul(b),
+                ArithOp::Divide => a.checked_div(b),
+                ArithOp::Modulo => a.checked_rem(b),
+                ArithOp::Max => Some(a.max(b)),
+                ArithOp::Min => Some(a.min(b)),
+            }?;
+            int_constant(res, &l.tpe)
+        }
+        BinOpKind::Relation(rel) => {
+            if let (Some(a), Some(b)) = (int_value(l), int_value(r)) {
+                return Some(
+                    match rel {
+                        RelationOp::Eq => a == b,
+                        RelationOp::NEq => a != b,
+                        RelationOp::Ge => a >= b,
+                        RelationOp::Gt => a > b,
+                        RelationOp::Le => a <= b,
+                        RelationOp::Lt => a < b,
+                    }
+                    .into(),
+                );
+            }
+            match (&l.v, &r.v, rel) {
+                (Literal::Boolean(a), Literal::Boolean(b), RelationOp::Eq) => Some((a == b).into()),
+                (Literal::Boolean(a), Literal::Boolean(b), RelationOp::NEq) => {
+                    Some((a != b).into())
+                }
+                _ => None,
+            }
+        }
+        BinOpKind::Logical(logical) => match (&l.v, &r.v) {
+            (Literal::Boolean(a), Literal::Boolean(b)) => Some(
+                match logical {
+                    LogicalOp::And => *a && *b,
+                    LogicalOp::Or => *a || *b,
+                    LogicalOp::Xor => a ^ b,
+                }
+                .into(),
+            ),
+            _ => None,
+        },
+        BinOpKind::Bit(_) => None,
+    }
+}
+
+fn bool_value(e: &Expr) -> Option<bool> {
+    match e {
+        Expr::Const(Constant {
+            v: Literal::Boolean(v),
+            ..
+        }) => Some(*v),
+        _ => None,
+    }
+}
+
+/// Value of `Byte`, `Short`, `Int` and `Long` constants
+fn int_value(c: &Constant) -> Option<i64> {
+    match c.v {
+        Literal::Byte(v) => Some(v as i64),
+        Literal::Short(v) => Some(v as i64),
+        Literal::Int(v) => Some(v as i64),
+        Literal::Long(v) => Some(v),
+        _ => None,
+    }
+}
+
+/// Constant of the given integer type, or `None` if the value is out of the type range
+fn int_constant(v: i64, tpe: &SType) -> Option<Constant> {
+    match tpe {
+        SType::SByte => i8::try_from(v).ok().map(Constant::from),
+        SType::SShort => i16::try_from(v).ok().map(Constant::from),
+        SType::SInt => i32::try_from(v).ok().map(Constant::from),
+        SType::SLong => Some(v.into()),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compiler::compile_expr;
+    use crate::script_env::ScriptEnv;
+    use ergotree_ir::mir::downcast::Downcast;
+    use ergotree_ir::mir::upcast::Upcast;
+    use ergotree_ir::pretty_printer::print_expr;
+    use expect_test::expect;
+
+    fn check(source: &str, expected: expect_test::Expect) {
+        let expr = compile_expr(source, ScriptEnv::new()).unwrap();
+        let folded = fold_constants(expr).unwrap();
+        expected.assert_eq(&print_expr(&folded, &[]).unwrap());
+    }
+
+    #[test]
+    fn fold_arithmetic() {
+        check("HEIGHT > 2 * 3 + 10 / 3 - 1", expect!["HEIGHT > 8"]);
+        check(
+            "SELF.value > 2L * 3L - 10L / 4L",
+            expect!["SELF.value > 4L"],
+        );
+    }
+
+    #[test]
+    fn fold_relations_and_logic() {
+        check("HEIGHT > 1 && 2 < 1", expect!["HEIGHT > 1 && false"]);
+        check("!(1 == 2) || false", expect!["true"]);
+    }
+
+    #[test]
+    fn fold_unary() {
+        check("-(3) + 1", expect!["-2"]);
+        let upcast: Expr = Upcast::new(Expr::from(1i8), SType::SLong).unwrap().into();
+        assert_eq!(fold_constants(upcast).unwrap(), Expr::from(1i64));
+        let upcast: Expr = Upcast::new(Expr::from(-1i32), SType::SBigInt)
+            .unwrap()
+            .into();
+        assert_eq!(
+            fold_constants(upcast).unwrap(),
+            Expr::from(BigInt256::from(-1i64))
+        );
+    }
+
+    #[test]
+    fn fold_if() {
+        check("if (1 > 2) HEIGHT else HEIGHT + 1", expect!["HEIGHT + 1"]);
+    }
+
+    #[test]
+    fn keep_failing_operations() {
+        check("HEIGHT / 0 + 1 / 0", expect!["HEIGHT / 0 + 1 / 0"]);
+        check("2147483647 + 1", expect!["2147483647 + 1"]);
+        let downcast: Expr = Downcast::new(Expr::from(300i32), SType::SByte)
+            .unwrap()
+            .into();
+        assert_eq!(fold_constants(downcast.clone()).unwrap(), downcast);
+        let downcast: Expr = Downcast::new(Expr::from(100i32), SType::SByte)
+            .unwrap()
+            .into();
+        assert_eq!(fold_constants(downcast).unwrap(), Expr::from(100i8));
+    }
+}
@@ -0,0 +1,184 @@
+//! Extraction of the common sub-expressions into `val`s
+
+use std::collections::BTreeMap;
+
+use ergotree_ir::mir::bin_op::BinOpKind;
+use ergotree_ir::mir::block::BlockValue;
+use ergotree_ir::mir::expr::Expr;
+use ergotree_ir::mir::expr::InvalidArgumentError;
+use ergotree_ir::mir::val_def::ValDef;
+use ergotree_ir::mir::val_def::ValId;
+use ergotree_ir::mir::val_use::ValUse;
+use ergotree_ir::serialization::SigmaSerializable;
+
+use super::traverse::children;
+use super::traverse::map_children;
+use super::traverse::max_val_id;
+
+/// Approximate size of a `ValDef` without the right-hand side and of a `ValUse` (opcode and id)
+const VAL_REF_SIZE: usize = 2;
+
+/// Sub-expression occurring more than once
+struct Candidate {
+    expr: Expr,
+    count: usize,
+    /// Evaluated on every evaluation of the script (outside of `if` branches, lambdas, etc.)
+    eager: bool,
+}
+
+/// Moves the sub-expressions occurring more than once into `val`s at the beginning of the root
+/// block (the largest first), while it makes the tree smaller. Only the sub-expressions not
+/// depending on vals and evaluated unconditionally at least once are extracted, so that no
+/// evaluation is added to the script.
+pub fn extract_common_subexpressions(e: Expr) -> Result<Expr, InvalidArgumentError> {
+    let mut e = e;
+    while let Some(candidate) = best_candidate(&e) {
+        let id = ValId(max_val_id(&e).map(|id| id + 1).unwrap_or(0));
+        let val_use: Expr = ValUse {
+            val_id: id,
+            tpe: candidate.tpe(),
+        }
+        .into();
+        let val_def: Expr = ValDef {
+            id,
+            rhs: Box::new(candidate.clone()),
+        }
+        .into();
+        e = match replace(e, &candidate, &val_use)? {
+            Expr::BlockValue(mut block) => {
+                block.items.insert(0, val_def);
+                block.into()
+            }
+            e => BlockValue {
+                items: vec![val_def],
+                result: Box::new(e),
+            }
+            .into(),
+        };
+    }
+    Ok(e)
+}
+
+fn best_candidate(e: &Expr) -> Option<Expr> {
+    let mut candidates = BTreeMap::new();
+    collect(e, false, &mut candidates);
+    candidates
+        .into_iter()
+        .filter(|(bytes, c)| {
+            let size = bytes.len();
+            c.eager && c.count > 1 && (c.count - 1) * size > VAL_REF_SIZE * (c.count + 2)
+        })
+        .max_by_key(|(bytes, _)| bytes.len())
+        .map(|(_, c)| c.expr)
+}
+
+/// Collects the candidates in `e`, returns `false` if `e` depends on the vals
+fn collect(e: &Expr, is_lazy: bool, candidates: &mut BTreeMap<Vec<u8>, Candidate>) -> bool {
+    let mut is_closed = !matches!(
+        e,
+        Expr::ValUse(_) | Expr::ValDef(_) | Expr::FuncValue(_) | Expr::BlockValue(_)
+    );
+    for (i, child) in children(e).into_iter().enumerate() {
+        is_closed &= collect(child, is_lazy || is_lazy_child(e, i), candidates);
+    }
+    if is_closed && !is_trivial(e) {
+        if let Ok(bytes) = e.sigma_serialize_bytes() {
+            let candidate = candidates.entry(bytes).or_insert_with(|| Candidate {
+                expr: e.clone(),
+                count: 0,
+                eager: false,
+            });
+            candidate.count += 1;
+            candidate.eager |= !is_lazy;
+        }
+    }
+    is_closed
+}
+
+/// The child (in the order of [`children`]) is evaluated only on some evaluations of the parent
+fn is_lazy_child(parent: &Expr, child_index: usize) -> bool {
+    match parent {
+        Expr::If(_) => child_index > 0,
+        Expr::BinOp(op) => matches!(op.kind, BinOpKind::Logical(_)) && child_index == 1,
+        Expr::ByIndex(_) => child_index == 2,
+        Expr::OptionGetOrElse(_) => child_index == 1,
+        Expr::FuncValue(_) | Expr::DeserializeRegister(_) => true,
+        _ => false,
+    }
+}
+
+fn is_trivial(e: &Expr) -> bool {
+    matches!(
+        e,
+        Expr::Const(_)
+            | Expr::ConstPlaceholder(_)
+            | Expr::Context
+            | Expr::Global
+            | Expr::GlobalVars(_)
+    )
+}
+
+fn replace(e: Expr, target: &Expr, replacement: &Expr) -> Result<Expr, InvalidArgumentError> {
+    if e == *target {
+        Ok(replacement.clone())
+    } else {
+        map_children(e, |child| replace(child, target, replacement))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compiler::compile_expr;
+    use crate::script_env::ScriptEnv;
+    use ergotree_ir::pretty_printer::print_expr;
+    use expect_test::expect;
+
+    fn check(source: &str, expected: expect_test::Expect) {
+        let expr = compile_expr(source, ScriptEnv::new()).unwrap();
+        let res = extract_common_subexpressions(expr).unwrap();
+        expected.assert_eq(&print_expr(&res, &[]).unwrap());
+    }
+
+    #[test]
+    fn extract_repeated_subexpression() {
+        check(
+            "OUTPUTS(0).R4[Long].get > 1L && OUTPUTS(0).R4[Long].get < 10L",
+            expect![[r#"
+                {
+                  val v0 = OUTPUTS(0).R4[Long].get
+                  v0 > 1L && v0 < 10L
+                }"#]],
+        );
+    }
+
+    #[test]
+    fn keep_lazy_only_subexpression() {
+        check(
+            "if (OUTPUTS.size > 1) OUTPUTS(1).R4[Long].get == OUTPUTS(1).R5[Long].get + OUTPUTS(1).R4[Long].get else false",
+            expect![[r#"
+                if (OUTPUTS.size > 1) OUTPUTS(1).R4[Long].get == OUTPUTS(1).R5[Long].get + OUTPUTS(1).R4[Long].get else false"#]],
+        );
+    }
+
+    #[test]
+    fn keep_small_subexpression() {
+        check(
+            "SELF.value > 1L && SELF.value < 10L",
+            expect!["SELF.value > 1L && SELF.value < 10L"],
+        );
+    }
+
+    #[test]
+    fn extract_into_existing_block() {
+        check(
+            "{ val limit = 10L; OUTPUTS(0).R4[Long].get > 1L && OUTPUTS(0).R4[Long].get < limit }",
+            expect![[r#"
+                {
+                  val v1 = OUTPUTS(0).R4[Long].get
+                  val v0 = 10L
+                  v1 > 1L && v1 < v0
+                }"#]],
+        );
+    }
+}
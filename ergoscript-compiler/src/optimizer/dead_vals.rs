@@ -0,0 +1,77 @@
+//! Elimination of unused `val`s
+
+use ergotree_ir::mir::expr::Expr;
+use ergotree_ir::mir::expr::InvalidArgumentError;
+
+use super::traverse::rewrite_bottom_up;
+use super::traverse::val_use_counts;
+
+/// Removes the `val`s that are never used (the right-hand side is not evaluated anymore) and the
+/// blocks left without `val`s
+pub fn eliminate_dead_vals(e: Expr) -> Result<Expr, InvalidArgumentError> {
+    let mut e = e;
+    // removing a val may leave the vals it used unused
+    loop {
+        let uses = val_use_counts(&e);
+        let mut removed = false;
+        e = rewrite_bottom_up(e, &mut |e| {
+            Ok(match e {
+                Expr::BlockValue(mut block) => {
+                    let len = block.items.len();
+                    block.items.retain(
+                        |item| !matches!(item, Expr::ValDef(v) if !uses.contains_key(&v.id)),
+                    );
+                    removed |= block.items.len() != len;
+                    if block.items.is_empty() {
+                        *block.result
+                    } else {
+                        block.into()
+                    }
+                }
+                e => e,
+            })
+        })?;
+        if !removed {
+            return Ok(e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compiler::compile_expr;
+    use crate::script_env::ScriptEnv;
+    use ergotree_ir::pretty_printer::print_expr;
+    use expect_test::expect;
+
+    fn check(source: &str, expected: expect_test::Expect) {
+        let expr = compile_expr(source, ScriptEnv::new()).unwrap();
+        let res = eliminate_dead_vals(expr).unwrap();
+        expected.assert_eq(&print_expr(&res, &[]).unwrap());
+    }
+
+    #[test]
+    fn remove_unused_vals() {
+        check(
+            "{ val a = HEIGHT + 1; val b = a * 2; val c = HEIGHT; c > 1 }",
+            expect![[r#"
+                {
+                  val v2 = HEIGHT
+                  v2 > 1
+                }"#]],
+        );
+        check(
+            "{ val a = SELF.R4[Int].get; HEIGHT > 1 }",
+            expect!["HEIGHT > 1"],
+        );
+    }
+
+    #[test]
+    fn remove_unused_lambda() {
+        check(
+            "{ val f = { (x: Int) => x + 1 }; OUTPUTS.size > 1 }",
+            expect!["OUTPUTS.size > 1"],
+        );
+    }
+}
@@ -0,0 +1,189 @@
+//! Inlining of the lambdas applied once
+
+use std::collections::HashMap;
+use std::collections::HashSet;
+
+use ergotree_ir::mir::block::BlockValue;
+use ergotree_ir::mir::expr::Expr;
+use ergotree_ir::mir::expr::InvalidArgumentError;
+use ergotree_ir::mir::func_value::FuncValue;
+use ergotree_ir::mir::val_def::ValDef;
+use ergotree_ir::mir::val_def::ValId;
+
+use super::traverse::children;
+use super::traverse::rewrite_bottom_up;
+use super::traverse::val_use_counts;
+
+/// Replaces the application of a lambda with its body, where the arguments are substituted or
+/// bound to `val`s with the lambda argument ids. Inlined are the lambdas applied in place and the lambdas bound to
+/// a `val` used once (the `val` is removed).
+pub fn inline_lambdas(e: Expr) -> Result<Expr, InvalidArgumentError> {
+    let uses = val_use_counts(&e);
+    let mut single_use_funcs = HashSet::new();
+    collect_funcs(&e, &uses, &mut single_use_funcs);
+    // the lambdas are taken after their own body is rewritten (the vals are visited in the
+    // definition order, before their uses), so the lambdas inlined in them are not lost
+    let mut funcs: HashMap<ValId, FuncValue> = HashMap::new();
+    let mut inlined = HashSet::new();
+    rewrite_bottom_up(e, &mut |e| {
+        Ok(match e {
+            Expr::ValDef(def) => {
+                if let Expr::FuncValue(func) = def.rhs.as_ref() {
+                    if single_use_funcs.contains(&def.id) {
+                        funcs.insert(def.id, func.clone());
+                    }
+                }
+                def.into()
+            }
+            Expr::Apply(apply) => match apply.func.as_ref() {
+                Expr::FuncValue(func) => inline(func, apply.args)?,
+                Expr::ValUse(v) if funcs.contains_key(&v.val_id) => {
+                    inlined.insert(v.val_id);
+                    inline(&funcs[&v.val_id], apply.args)?
+                }
+                _ => apply.into(),
+            },
+            Expr::BlockValue(mut block) => {
+                block
+                    .items
+                    .retain(|item| !matches!(item, Expr::ValDef(v) if inlined.contains(&v.id)));
+                if block.items.is_empty() {
+                    *block.result
+                } else {
+                    block.into()
+                }
+            }
+            e => e,
+        })
+    })
+}
+
+/// Ids of the vals bound to lambdas and used once
+fn collect_funcs(e: &Expr, uses: &HashMap<ValId, usize>, funcs: &mut HashSet<ValId>) {
+    if let Expr::ValDef(ValDef { id, rhs }) = e {
+        if let Expr::FuncValue(_) = rhs.as_ref() {
+            if uses.get(id) == Some(&1) {
+                funcs.insert(*id);
+            }
+        }
+    }
+    children(e)
+        .into_iter()
+        .for_each(|child| collect_funcs(child, uses, funcs));
+}
+
+/// Lambda body with the arguments substituted (vals, context variables and constants used at most
+/// once) or bound to vals
+fn inline(func: &FuncValue, args: Vec<Expr>) -> Result<Expr, InvalidArgumentError> {
+    let body_uses = val_use_counts(func.body());
+    let mut substitutions = HashMap::new();
+    let mut items = Vec::new();
+    for (arg, rhs) in func.args().iter().zip(args) {
+        let substitute = match rhs {
+            Expr::ValUse(_) | Expr::GlobalVars(_) | Expr::Context | Expr::Global => true,
+            Expr::Const(_) | Expr::ConstPlaceholder(_) => {
+                body_uses.get(&arg.idx).copied().unwrap_or(0) <= 1
+            }
+            _ => false,
+        };
+        if substitute {
+            substitutions.insert(arg.idx, rhs);
+        } else {
+            items.push(
+                ValDef {
+                    id: arg.idx,
+                    rhs: Box::new(rhs),
+                }
+                .into(),
+            );
+        }
+    }
+    let body = rewrite_bottom_up(func.body().clone(), &mut |e| {
+        Ok(match e {
+            Expr::ValUse(v) if substitutions.contains_key(&v.val_id) => {
+                substitutions[&v.val_id].clone()
+            }
+            e => e,
+        })
+    })?;
+    Ok(if items.is_empty() {
+        body
+    } else {
+        BlockValue {
+            items,
+            result: Box::new(body),
+        }
+        .into()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compiler::compile_expr;
+    use crate::script_env::ScriptEnv;
+    use ergotree_ir::pretty_printer::print_expr;
+    use expect_test::expect;
+
+    fn check(source: &str, expected: expect_test::Expect) {
+        let expr = compile_expr(source, ScriptEnv::new()).unwrap();
+        let res = inline_lambdas(expr).unwrap();
+        expected.assert_eq(&print_expr(&res, &[]).unwrap());
+    }
+
+    #[test]
+    fn inline_single_use_lambda() {
+        check(
+            "{ val f = { (x: Int) => x + 1 }; f(HEIGHT) > 10 }",
+            expect!["HEIGHT + 1 > 10"],
+        );
+        check(
+            "{ val f = { (x: Int) => x * x }; f(SELF.R4[Int].get) > 10 }",
+            expect![[r#"
+                {
+                  val v0 = SELF.R4[Int].get
+                  v0 * v0
+                } > 10"#]],
+        );
+        check(
+            "{ val f = { (x: Int, y: Int) => x * x + y }; f(1, 2) > HEIGHT }",
+            expect![[r#"
+                {
+                  val v0 = 1
+                  v0 * v0 + 2
+                } > HEIGHT"#]],
+        );
+    }
+
+    #[test]
+    fn inline_lambda_applied_in_inlined_lambda() {
+        check(
+            "{ val g = { (x: Int) => x + 1 }; val f = { (y: Int) => g(y) * 2 }; f(HEIGHT) > 10 }",
+            expect!["(HEIGHT + 1) * 2 > 10"],
+        );
+    }
+
+    #[test]
+    fn keep_lambda_used_twice() {
+        check(
+            "{ val f = { (x: Int) => x + 1 }; f(HEIGHT) > f(10) }",
+            expect![[r#"
+                {
+                  val v1 = (v0: Int) => v0 + 1
+                  v1(HEIGHT) > v1(10)
+                }"#]],
+        );
+    }
+
+    #[test]
+    fn keep_lambda_passed_as_argument() {
+        check(
+            "{ val f = { (b: Box) => b.value > 1L }; OUTPUTS.forall(f) }",
+            expect![[r#"
+                {
+                  val v1 = (v0: Box) => v0.value > 1L
+                  OUTPUTS.forall(v1)
+                }"#]],
+        );
+    }
+}
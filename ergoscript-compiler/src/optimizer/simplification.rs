@@ -0,0 +1,245 @@
+//! Simplification of boolean and `SigmaProp` expressions
+
+use ergotree_ir::mir::bin_op::BinOp;
+use ergotree_ir::mir::bin_op::BinOpKind;
+use ergotree_ir::mir::bin_op::LogicalOp;
+use ergotree_ir::mir::bin_op::RelationOp;
+use ergotree_ir::mir::bool_to_sigma::BoolToSigmaProp;
+use ergotree_ir::mir::constant::Constant;
+use ergotree_ir::mir::constant::Literal;
+use ergotree_ir::mir::expr::Expr;
+use ergotree_ir::mir::expr::InvalidArgumentError;
+use ergotree_ir::mir::if_op::If;
+use ergotree_ir::mir::logical_not::LogicalNot;
+use ergotree_ir::mir::sigma_and::SigmaAnd;
+use ergotree_ir::mir::sigma_or::SigmaOr;
+
+use super::traverse::rewrite_bottom_up;
+
+/// Removes the redundant boolean constants, negations and `SigmaProp` conversions
+pub fn simplify(e: Expr) -> Result<Expr, InvalidArgumentError> {
+    rewrite_bottom_up(e, &mut simplify_node)
+}
+
+fn simplify_node(e: Expr) -> Result<Expr, InvalidArgumentError> {
+    Ok(match e {
+        Expr::BinOp(op) => simplify_bin_op(op),
+        Expr::LogicalNot(op) => not(*op.input),
+        Expr::If(op) => match (
+            *op.condition,
+            bool_value(&op.true_branch),
+            bool_value(&op.false_branch),
+        ) {
+            (condition, Some(true), Some(false)) => condition,
+            (condition, Some(false), Some(true)) => not(condition),
+            (Expr::LogicalNot(condition), _, _) => If {
+                condition: condition.input,
+                true_branch: op.false_branch,
+                false_branch: op.true_branch,
+            }
+            .into(),
+            (condition, _, _) => If {
+                condition: Box::new(condition),
+                ..op
+            }
+            .into(),
+        },
+        Expr::SigmaAnd(op) => simplify_sigma_conj(op.items.into(), true)?,
+        Expr::SigmaOr(op) => simplify_sigma_conj(op.items.into(), false)?,
+        e => e,
+    })
+}
+
+fn simplify_bin_op(op: BinOp) -> Expr {
+    let (l, r) = (bool_value(&op.left), bool_value(&op.right));
+    match (op.kind, l, r) {
+        // `x && true`, `x || false`, `x ^ false`
+        (BinOpKind::Logical(LogicalOp::And), Some(true), _)
+        | (BinOpKind::Logical(LogicalOp::Or), Some(false), _)
+        | (BinOpKind::Logical(LogicalOp::Xor), Some(false), _) => *op.right,
+        (BinOpKind::Logical(LogicalOp::And), _, Some(true))
+        | (BinOpKind::Logical(LogicalOp::Or), _, Some(false))
+        | (BinOpKind::Logical(LogicalOp::Xor), _, Some(false)) => *op.left,
+        // the right operand is not evaluated
+        (BinOpKind::Logical(LogicalOp::And), Some(false), _)
+        | (BinOpKind::Logical(LogicalOp::Or), Some(true), _) => *op.left,
+        // the left operand is dropped only if its evaluation cannot fail
+        (BinOpKind::Logical(LogicalOp::And), _, Some(false))
+        | (BinOpKind::Logical(LogicalOp::Or), _, Some(true))
+            if is_total(&op.left) =>
+        {
+            *op.right
+        }
+        // `x == true`, `x != false`
+        (BinOpKind::Relation(RelationOp::Eq), Some(true), _)
+        | (BinOpKind::Relation(RelationOp::NEq), Some(false), _) => *op.right,
+        (BinOpKind::Relation(RelationOp::Eq), _, Some(true))
+        | (BinOpKind::Relation(RelationOp::NEq), _, Some(false)) => *op.left,
+        // `x == false`, `x != true`
+        (BinOpKind::Relation(RelationOp::Eq), Some(false), _)
+        | (BinOpKind::Relation(RelationOp::NEq), Some(true), _) => not(*op.right),
+        (BinOpKind::Relation(RelationOp::Eq), _, Some(false))
+        | (BinOpKind::Relation(RelationOp::NEq), _, Some(true)) => not(*op.left),
+        _ => op.into(),
+    }
+}
+
+/// Flattens nested conjunctions (`is_and`) or disjunctions, drops the neutral
+/// `sigmaProp(true)` (`sigmaProp(false)`) items and merges the `sigmaProp(bool)` items into one
+fn simplify_sigma_conj(items: Vec<Expr>, is_and: bool) -> Result<Expr, InvalidArgumentError> {
+    let mut flat = Vec::new();
+    let mut conditions = Vec::new();
+    let mut conditions_pos = None;
+    for item in items {
+        match item {
+            Expr::SigmaAnd(op) if is_and => flat.extend(op.items),
+            Expr::SigmaOr(op) if !is_and => flat.extend(op.items),
+            item => flat.push(item),
+        }
+    }
+    let mut props = Vec::new();
+    for item in flat {
+        match item {
+            Expr::BoolToSigmaProp(op) => {
+                if bool_value(&op.input) == Some(is_and) {
+                    continue;
+                }
+                conditions_pos.get_or_insert(props.len());
+                conditions.push(*op.input);
+            }
+            item => props.push(item),
+        }
+    }
+    let logical_op = if is_and {
+        LogicalOp::And
+    } else {
+        LogicalOp::Or
+    };
+    if let Some(condition) = conditions.into_iter().reduce(|acc, c| {
+        BinOp {
+            kind: BinOpKind::Logical(logical_op),
+            left: Box::new(acc),
+            right: Box::new(c),
+        }
+        .into()
+    }) {
+        props.insert(
+            conditions_pos.unwrap_or(0),
+            BoolToSigmaProp {
+                input: Box::new(condition),
+            }
+            .into(),
+        );
+    }
+    Ok(match props.len() {
+        0 => BoolToSigmaProp {
+            input: Box::new(is_and.into()),
+        }
+        .into(),
+        1 => props.remove(0),
+        _ if is_and => SigmaAnd::new(props)?.into(),
+        _ => SigmaOr::new(props)?.into(),
+    })
+}
+
+/// Negation without double negations (`!!x`) and negated relations (`!(a < b)`)
+fn not(e: Expr) -> Expr {
+    match e {
+        Expr::LogicalNot(op) => *op.input,
+        Expr::BinOp(BinOp {
+            kind: BinOpKind::Relation(rel),
+            left,
+            right,
+        }) => BinOp {
+            kind: BinOpKind::Relation(negated(rel)),
+            left,
+            right,
+        }
+        .into(),
+        e => LogicalNot { input: Box::new(e) }.into(),
+    }
+}
+
+fn negated(rel: RelationOp) -> RelationOp {
+    match rel {
+        RelationOp::Eq => RelationOp::NEq,
+        RelationOp::NEq => RelationOp::Eq,
+        RelationOp::Ge => RelationOp::Lt,
+        RelationOp::Gt => RelationOp::Le,
+        RelationOp::Le => RelationOp::Gt,
+        RelationOp::Lt => RelationOp::Ge,
+    }
+}
+
+fn bool_value(e: &Expr) -> Option<bool> {
+    match e {
+        Expr::Const(Constant {
+            v: Literal::Boolean(v),
+            ..
+        }) => Some(*v),
+        _ => None,
+    }
+}
+
+/// Evaluation of the expression cannot fail
+fn is_total(e: &Expr) -> bool {
+    matches!(
+        e,
+        Expr::Const(_) | Expr::ConstPlaceholder(_) | Expr::GlobalVars(_) | Expr::ValUse(_)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compiler::compile_expr;
+    use crate::script_env::ScriptEnv;
+    use ergotree_ir::pretty_printer::print_expr;
+    use expect_test::expect;
+
+    fn check(source: &str, expected: expect_test::Expect) {
+        let expr = compile_expr(source, ScriptEnv::new()).unwrap();
+        let simplified = simplify(expr).unwrap();
+        expected.assert_eq(&print_expr(&simplified, &[]).unwrap());
+    }
+
+    #[test]
+    fn simplify_logical_constants() {
+        check("true && HEIGHT > 1", expect!["HEIGHT > 1"]);
+        check("HEIGHT > 1 || false", expect!["HEIGHT > 1"]);
+        check("false && HEIGHT > 1", expect!["false"]);
+        check("(HEIGHT > 1) == true", expect!["HEIGHT > 1"]);
+        check("(HEIGHT > 1) != true", expect!["HEIGHT <= 1"]);
+    }
+
+    #[test]
+    fn keep_partial_operand() {
+        check(
+            "SELF.R4[Boolean].get || true",
+            expect!["SELF.R4[Boolean].get || true"],
+        );
+    }
+
+    #[test]
+    fn simplify_negations() {
+        check("!!(HEIGHT > 1)", expect!["HEIGHT > 1"]);
+        check("!(HEIGHT == 1)", expect!["HEIGHT != 1"]);
+        check(
+            "if (!OUTPUTS(0).R4[Boolean].get) INPUTS.size else OUTPUTS.size",
+            expect!["if (OUTPUTS(0).R4[Boolean].get) OUTPUTS.size else INPUTS.size"],
+        );
+        check("if (HEIGHT > 1) false else true", expect!["HEIGHT <= 1"]);
+    }
+
+    #[test]
+    fn simplify_sigma_props() {
+        check(
+            "sigmaProp(HEIGHT > 1) && sigmaProp(true) && sigmaProp(HEIGHT < 10)",
+            expect!["sigmaProp(HEIGHT > 1 && HEIGHT < 10)"],
+        );
+        check(
+            "sigmaProp(false) || sigmaProp(HEIGHT > 1)",
+            expect!["sigmaProp(HEIGHT > 1)"],
+        );
+    }
+}
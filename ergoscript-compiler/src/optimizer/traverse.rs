@@ -0,0 +1,441 @@
+//! Generic traversal of the MIR expressions
+
+use std::collections::HashMap;
+
+use ergotree_ir::mir::apply::Apply;
+use ergotree_ir::mir::coll_by_index::ByIndex;
+use ergotree_ir::mir::collection::Collection;
+use ergotree_ir::mir::expr::Expr;
+use ergotree_ir::mir::expr::InvalidArgumentError;
+use ergotree_ir::mir::func_value::FuncValue;
+use ergotree_ir::mir::option_get::OptionGet;
+use ergotree_ir::mir::option_get_or_else::OptionGetOrElse;
+use ergotree_ir::mir::select_field::SelectField;
+use ergotree_ir::mir::unary_op::OneArgOpTryBuild;
+use ergotree_ir::mir::val_def::ValId;
+
+/// Direct children of the expression
+pub fn children(e: &Expr) -> Vec<&Expr> {
+    match e {
+        Expr::Const(_)
+        | Expr::ConstPlaceholder(_)
+        | Expr::Context
+        | Expr::Global
+        | Expr::GlobalVars(_)
+        | Expr::ValUse(_)
+        | Expr::GetVar(_)
+        | Expr::DeserializeContext(_)
+        | Expr::Collection(Collection::BoolConstants(_)) => vec![],
+        Expr::Collection(Collection::Exprs { items, .. }) => items.iter().collect(),
+        Expr::Tuple(op) => op.items.iter().collect(),
+        Expr::SigmaAnd(op) => op.items.iter().collect(),
+        Expr::SigmaOr(op) => op.items.iter().collect(),
+        Expr::BlockValue(op) => op
+            .items
+            .iter()
+            .chain(std::iter::once(op.result.as_ref()))
+            .collect(),
+        Expr::FuncValue(op) => vec![op.body()],
+        Expr::Apply(op) => std::iter::once(op.func.as_ref())
+            .chain(op.args.iter())
+            .collect(),
+        Expr::MethodCall(op) => std::iter::once(op.obj.as_ref())
+            .chain(op.args.iter())
+            .collect(),
+        Expr::ProperyCall(op) => vec![&op.obj],
+        Expr::ValDef(op) => vec![&op.rhs],
+        Expr::SubstConstants(op) => vec![&op.script_bytes, &op.positions, &op.new_values],
+        Expr::If(op) => vec![&op.condition, &op.true_branch, &op.false_branch],
+        Expr::BinOp(op) => vec![&op.left, &op.right],
+        Expr::Xor(op) => vec![&op.left, &op.right],
+        Expr::MultiplyGroup(op) => vec![&op.left, &op.right],
+        Expr::Exponentiate(op) => vec![&op.left, &op.right],
+        Expr::Append(op) => vec![&op.input, &op.col_2],
+        Expr::Atleast(op) => vec![&op.bound, &op.input],
+        Expr::OptionGetOrElse(op) => vec![&op.input, &op.default],
+        Expr::ByIndex(op) => std::iter::once(op.input.as_ref())
+            .chain(std::iter::once(op.index.as_ref()))
+            .chain(op.default.as_deref())
+            .collect(),
+        Expr::Slice(op) => vec![&op.input, &op.from, &op.until],
+        Expr::Fold(op) => vec![&op.input, &op.zero, &op.fold_op],
+        Expr::Map(op) => vec![&op.input, &op.mapper],
+        Expr::Filter(op) => vec![&op.input, &op.condition],
+        Expr::Exists(op) => vec![&op.input, &op.condition],
+        Expr::ForAll(op) => vec![&op.input, &op.condition],
+        Expr::CreateProveDhTuple(op) => vec![&op.g, &op.h, &op.u, &op.v],
+        Expr::DeserializeRegister(op) => op.default.iter().map(|d| d.as_ref()).collect(),
+        Expr::TreeLookup(op) => vec![&op.tree, &op.key, &op.proof],
+        Expr::CreateAvlTree(op) => vec![op.flags.as_ref(), &op.digest, &op.key_length]
+            .into_iter()
+            .chain(op.value_length.as_deref())
+            .collect(),
+        Expr::ByteArrayToLong(op) => vec![&op.input],
+        Expr::ByteArrayToBigInt(op) => vec![&op.input],
+        Expr::LongToByteArray(op) => vec![&op.input],
+        Expr::CalcBlake2b256(op) => vec![&op.input],
+        Expr::CalcSha256(op) => vec![&op.input],
+        Expr::And(op) => vec![&op.input],
+        Expr::Or(op) => vec![&op.input],
+        Expr::LogicalNot(op) => vec![&op.input],
+        Expr::Negation(op) => vec![&op.input],
+        Expr::BitInversion(op) => vec![&op.input],
+        Expr::OptionGet(op) => vec![&op.input],
+        Expr::OptionIsDefined(op) => vec![&op.input],
+        Expr::ExtractAmount(op) => vec![&op.input],
+        Expr::ExtractRegisterAs(op) => vec![&op.input],
+        Expr::ExtractBytes(op) => vec![&op.input],
+        Expr::ExtractBytesWithNoRef(op) => vec![&op.input],
+        Expr::ExtractScriptBytes(op) => vec![&op.input],
+        Expr::ExtractCreationInfo(op) => vec![&op.input],
+        Expr::ExtractId(op) => vec![&op.input],
+        Expr::SizeOf(op) => vec![&op.input],
+        Expr::SelectField(op) => vec![&op.input],
+        Expr::BoolToSigmaProp(op) => vec![&op.input],
+        Expr::Upcast(op) => vec![&op.input],
+        Expr::Downcast(op) => vec![&op.input],
+        Expr::CreateProveDlog(op) => vec![&op.input],
+        Expr::SigmaPropBytes(op) => vec![&op.input],
+        Expr::DecodePoint(op) => vec![&op.input],
+        Expr::XorOf(op) => vec![&op.input],
+    }
+}
+
+/// Rebuilds the expression with every direct child replaced with `f(child)`
+pub fn map_children<F>(e: Expr, mut f: F) -> Result<Expr, InvalidArgumentError>
+where
+    F: FnMut(Expr) -> Result<Expr, InvalidArgumentError>,
+{
+    let mut boxed =
+        |e: Box<Expr>| -> Result<Box<Expr>, InvalidArgumentError> { Ok(Box::new(f(*e)?)) };
+    Ok(match e {
+        Expr::Const(_)
+        | Expr::ConstPlaceholder(_)
+        | Expr::Context
+        | Expr::Global
+        | Expr::GlobalVars(_)
+        | Expr::ValUse(_)
+        | Expr::GetVar(_)
+        | Expr::DeserializeContext(_)
+        | Expr::Collection(Collection::BoolConstants(_)) => e,
+        Expr::Collection(Collection::Exprs { elem_tpe, items }) => Collection::Exprs {
+            elem_tpe,
+            items: items
+                .into_iter()
+                .map(|item| boxed(item.into()).map(|item| *item))
+                .collect::<Result<Vec<Expr>, InvalidArgumentError>>()?,
+        }
+        .into(),
+        Expr::Tuple(mut op) => {
+            op.items = op
+                .items
+                .try_mapped(|item| boxed(item.into()).map(|item| *item))?;
+            op.into()
+        }
+        Expr::SigmaAnd(mut op) => {
+            op.items = op
+                .items
+                .try_mapped(|item| boxed(item.into()).map(|item| *item))?;
+            op.into()
+        }
+        Expr::SigmaOr(mut op) => {
+            op.items = op
+                .items
+                .try_mapped(|item| boxed(item.into()).map(|item| *item))?;
+            op.into()
+        }
+        Expr::BlockValue(mut op) => {
+            op.items = op
+                .items
+                .into_iter()
+                .map(|item| boxed(item.into()).map(|item| *item))
+                .collect::<Result<Vec<Expr>, InvalidArgumentError>>()?;
+            op.result = boxed(op.result)?;
+            op.into()
+        }
+        Expr::FuncValue(op) => {
+            let body = *boxed(op.body().clone().into())?;
+            FuncValue::new(op.args().to_vec(), body).into()
+        }
+        Expr::Apply(op) => {
+            let func = *boxed(op.func)?;
+            let args = op
+                .args
+                .into_iter()
+                .map(|arg| boxed(arg.into()).map(|arg| *arg))
+                .collect::<Result<Vec<Expr>, InvalidArgumentError>>()?;
+            Apply::new(func, args)?.into()
+        }
+        Expr::MethodCall(mut op) => {
+            op.obj = boxed(op.obj)?;
+            op.args = op
+                .args
+                .into_iter()
+                .map(|arg| boxed(arg.into()).map(|arg| *arg))
+                .collect::<Result<Vec<Expr>, InvalidArgumentError>>()?;
+            op.into()
+        }
+        Expr::ProperyCall(mut op) => {
+            op.obj = boxed(op.obj)?;
+            op.into()
+        }
+        Expr::ValDef(mut op) => {
+            op.rhs = boxed(op.rhs)?;
+            op.into()
+        }
+        Expr::SubstConstants(mut op) => {
+            op.script_bytes = boxed(op.script_bytes)?;
+            op.positions = boxed(op.positions)?;
+            op.new_values = boxed(op.new_values)?;
+            op.into()
+        }
+        Expr::If(mut op) => {
+            op.condition = boxed(op.condition)?;
+            op.true_branch = boxed(op.true_branch)?;
+            op.false_branch = boxed(op.false_branch)?;
+            op.into()
+        }
+        Expr::BinOp(mut op) => {
+            op.left = boxed(op.left)?;
+            op.right = boxed(op.right)?;
+            op.into()
+        }
+        Expr::Xor(mut op) => {
+            op.left = boxed(op.left)?;
+            op.right = boxed(op.right)?;
+            op.into()
+        }
+        Expr::MultiplyGroup(mut op) => {
+            op.left = boxed(op.left)?;
+            op.right = boxed(op.right)?;
+            op.into()
+        }
+        Expr::Exponentiate(mut op) => {
+            op.left = boxed(op.left)?;
+            op.right = boxed(op.right)?;
+            op.into()
+        }
+        Expr::Append(mut op) => {
+            op.input = boxed(op.input)?;
+            op.col_2 = boxed(op.col_2)?;
+            op.into()
+        }
+        Expr::Atleast(mut op) => {
+            op.bound = boxed(op.bound)?;
+            op.input = boxed(op.input)?;
+            op.into()
+        }
+        Expr::OptionGetOrElse(op) => {
+            let input = *boxed(op.input)?;
+            let default = *boxed(op.default)?;
+            OptionGetOrElse::new(input, default)?.into()
+        }
+        Expr::ByIndex(op) => {
+            let input = *boxed(op.input)?;
+            let index = *boxed(op.index)?;
+            let default = op.default.map(&mut boxed).transpose()?;
+            ByIndex::new(input, index, default)?.into()
+        }
+        Expr::Slice(mut op) => {
+            op.input = boxed(op.input)?;
+            op.from = boxed(op.from)?;
+            op.until = boxed(op.until)?;
+            op.into()
+        }
+        Expr::Fold(mut op) => {
+            op.input = boxed(op.input)?;
+            op.zero = boxed(op.zero)?;
+            op.fold_op = boxed(op.fold_op)?;
+            op.into()
+        }
+        Expr::Map(mut op) => {
+            op.input = boxed(op.input)?;
+            op.mapper = boxed(op.mapper)?;
+            op.into()
+        }
+        Expr::Filter(mut op) => {
+            op.input = boxed(op.input)?;
+            op.condition = boxed(op.condition)?;
+            op.into()
+        }
+        Expr::Exists(mut op) => {
+            op.input = boxed(op.input)?;
+            op.condition = boxed(op.condition)?;
+            op.into()
+        }
+        Expr::ForAll(mut op) => {
+            op.input = boxed(op.input)?;
+            op.condition = boxed(op.condition)?;
+            op.into()
+        }
+        Expr::CreateProveDhTuple(mut op) => {
+            op.g = boxed(op.g)?;
+            op.h = boxed(op.h)?;
+            op.u = boxed(op.u)?;
+            op.v = boxed(op.v)?;
+            op.into()
+        }
+        Expr::DeserializeRegister(mut op) => {
+            op.default = op.default.map(&mut boxed).transpose()?;
+            op.into()
+        }
+        Expr::TreeLookup(mut op) => {
+            op.tree = boxed(op.tree)?;
+            op.key = boxed(op.key)?;
+            op.proof = boxed(op.proof)?;
+            op.into()
+        }
+        Expr::CreateAvlTree(mut op) => {
+            op.flags = boxed(op.flags)?;
+            op.digest = boxed(op.digest)?;
+            op.key_length = boxed(op.key_length)?;
+            op.value_length = op.value_length.map(&mut boxed).transpose()?;
+            op.into()
+        }
+        Expr::OptionGet(op) => OptionGet::try_build(*boxed(op.input)?)?.into(),
+        Expr::SelectField(op) => SelectField::new(*boxed(op.input)?, op.field_index)?.into(),
+        Expr::ByteArrayToLong(mut op) => {
+            op.input = boxed(op.input)?;
+            op.into()
+        }
+        Expr::ByteArrayToBigInt(mut op) => {
+            op.input = boxed(op.input)?;
+            op.into()
+        }
+        Expr::LongToByteArray(mut op) => {
+            op.input = boxed(op.input)?;
+            op.into()
+        }
+        Expr::CalcBlake2b256(mut op) => {
+            op.input = boxed(op.input)?;
+            op.into()
+        }
+        Expr::CalcSha256(mut op) => {
+            op.input = boxed(op.input)?;
+            op.into()
+        }
+        Expr::And(mut op) => {
+            op.input = boxed(op.input)?;
+            op.into()
+        }
+        Expr::Or(mut op) => {
+            op.input = boxed(op.input)?;
+            op.into()
+        }
+        Expr::LogicalNot(mut op) => {
+            op.input = boxed(op.input)?;
+            op.into()
+        }
+        Expr::Negation(mut op) => {
+            op.input = boxed(op.input)?;
+            op.into()
+        }
+        Expr::BitInversion(mut op) => {
+            op.input = boxed(op.input)?;
+            op.into()
+        }
+        Expr::OptionIsDefined(mut op) => {
+            op.input = boxed(op.input)?;
+            op.into()
+        }
+        Expr::ExtractAmount(mut op) => {
+            op.input = boxed(op.input)?;
+            op.into()
+        }
+        Expr::ExtractRegisterAs(mut op) => {
+            op.input = boxed(op.input)?;
+            op.into()
+        }
+        Expr::ExtractBytes(mut op) => {
+            op.input = boxed(op.input)?;
+            op.into()
+        }
+        Expr::ExtractBytesWithNoRef(mut op) => {
+            op.input = boxed(op.input)?;
+            op.into()
+        }
+        Expr::ExtractScriptBytes(mut op) => {
+            op.input = boxed(op.input)?;
+            op.into()
+        }
+        Expr::ExtractCreationInfo(mut op) => {
+            op.input = boxed(op.input)?;
+            op.into()
+        }
+        Expr::ExtractId(mut op) => {
+            op.input = boxed(op.input)?;
+            op.into()
+        }
+        Expr::SizeOf(mut op) => {
+            op.input = boxed(op.input)?;
+            op.into()
+        }
+        Expr::BoolToSigmaProp(mut op) => {
+            op.input = boxed(op.input)?;
+            op.into()
+        }
+        Expr::Upcast(mut op) => {
+            op.input = boxed(op.input)?;
+            op.into()
+        }
+        Expr::Downcast(mut op) => {
+            op.input = boxed(op.input)?;
+            op.into()
+        }
+        Expr::CreateProveDlog(mut op) => {
+            op.input = boxed(op.input)?;
+            op.into()
+        }
+        Expr::SigmaPropBytes(mut op) => {
+            op.input = boxed(op.input)?;
+            op.into()
+        }
+        Expr::DecodePoint(mut op) => {
+            op.input = boxed(op.input)?;
+            op.into()
+        }
+        Expr::XorOf(mut op) => {
+            op.input = boxed(op.input)?;
+            op.into()
+        }
+    })
+}
+
+/// Replaces every node bottom-up (children first) with `f(node)`
+pub fn rewrite_bottom_up<F>(e: Expr, f: &mut F) -> Result<Expr, InvalidArgumentError>
+where
+    F: FnMut(Expr) -> Result<Expr, InvalidArgumentError>,
+{
+    let e = map_children(e, |child| rewrite_bottom_up(child, f))?;
+    f(e)
+}
+
+/// Number of `ValUse` nodes for every val (or lambda argument) id
+pub fn val_use_counts(e: &Expr) -> HashMap<ValId, usize> {
+    let mut counts = HashMap::new();
+    count_val_uses(e, &mut counts);
+    counts
+}
+
+fn count_val_uses(e: &Expr, counts: &mut HashMap<ValId, usize>) {
+    if let Expr::ValUse(v) = e {
+        *counts.entry(v.val_id).or_insert(0) += 1;
+    }
+    children(e)
+        .into_iter()
+        .for_each(|child| count_val_uses(child, counts));
+}
+
+/// The largest id of the vals and lambda arguments defined in the expression
+pub fn max_val_id(e: &Expr) -> Option<u32> {
+    let own = match e {
+        Expr::ValDef(v) => Some(v.id.0),
+        Expr::FuncValue(f) => f.args().iter().map(|arg| arg.idx.0).max(),
+        _ => None,
+    };
+    children(e)
+        .into_iter()
+        .filter_map(max_val_id)
+        .chain(own)
+        .max()
+}
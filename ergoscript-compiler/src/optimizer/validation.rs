@@ -0,0 +1,156 @@
+//! Checks of the optimised tree
+
+use ergotree_ir::mir::expr::Expr;
+use ergotree_ir::mir::val_def::ValId;
+use ergotree_ir::types::stype::SType;
+
+use super::traverse::children;
+use super::OptimizerError;
+
+/// Checks that the optimised expression has the type of the original one, every `ValUse` refers
+/// to a val (or lambda argument) in scope and has its type, and the operands of every binary
+/// operation have the same type
+pub fn validate(expr: &Expr, original_tpe: &SType) -> Result<(), OptimizerError> {
+    if &expr.tpe() != original_tpe {
+        return Err(OptimizerError(format!(
+            "expression type changed from {:?} to {:?}",
+            original_tpe,
+            expr.tpe()
+        )));
+    }
+    check_node(expr, &mut Vec::new())
+}
+
+/// `scope` holds the vals and lambda arguments visible at the node (innermost last)
+fn check_node(e: &Expr, scope: &mut Vec<(ValId, SType)>) -> Result<(), OptimizerError> {
+    match e {
+        Expr::ValUse(v) => match scope.iter().rev().find(|(id, _)| *id == v.val_id) {
+            Some((_, tpe)) if tpe == &v.tpe => Ok(()),
+            Some((_, tpe)) => Err(OptimizerError(format!(
+                "v{} of type {:?} is used as {:?}",
+                v.val_id.0, tpe, v.tpe
+            ))),
+            None => Err(OptimizerError(format!(
+                "v{} is used out of its scope",
+                v.val_id.0
+            ))),
+        },
+        Expr::BlockValue(block) => {
+            let outer_len = scope.len();
+            for item in &block.items {
+                check_node(item, scope)?;
+            }
+            check_node(&block.result, scope)?;
+            scope.truncate(outer_len);
+            Ok(())
+        }
+        Expr::ValDef(def) => {
+            // visible in the rest of the block
+            check_node(&def.rhs, scope)?;
+            scope.push((def.id, def.rhs.tpe()));
+            Ok(())
+        }
+        Expr::FuncValue(func) => {
+            let outer_len = scope.len();
+            scope.extend(func.args().iter().map(|arg| (arg.idx, arg.tpe.clone())));
+            check_node(func.body(), scope)?;
+            scope.truncate(outer_len);
+            Ok(())
+        }
+        Expr::BinOp(op) if op.left.tpe() != op.right.tpe() => Err(OptimizerError(format!(
+            "operands of {:?} have types {:?} and {:?}",
+            op.kind,
+            op.left.tpe(),
+            op.right.tpe()
+        ))),
+        _ => children(e)
+            .into_iter()
+            .try_for_each(|child| check_node(child, scope)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compiler::compile_expr;
+    use crate::script_env::ScriptEnv;
+    use ergotree_ir::mir::bin_op::BinOp;
+    use ergotree_ir::mir::bin_op::RelationOp;
+    use ergotree_ir::mir::block::BlockValue;
+    use ergotree_ir::mir::global_vars::GlobalVars;
+    use ergotree_ir::mir::val_def::ValDef;
+    use ergotree_ir::mir::val_use::ValUse;
+
+    fn height_gt(lhs: Expr) -> Expr {
+        BinOp {
+            kind: RelationOp::Gt.into(),
+            left: Box::new(lhs),
+            right: Box::new(GlobalVars::Height.into()),
+        }
+        .into()
+    }
+
+    #[test]
+    fn compiled_tree_is_valid() {
+        let expr = compile_expr(
+            "{ val f = { (x: Int) => x + 1 }; val a = HEIGHT; f(a) > f(10) }",
+            ScriptEnv::new(),
+        )
+        .unwrap();
+        assert_eq!(validate(&expr, &SType::SBoolean), Ok(()));
+    }
+
+    #[test]
+    fn changed_type() {
+        let expr: Expr = GlobalVars::Height.into();
+        assert!(validate(&expr, &SType::SBoolean).is_err());
+    }
+
+    #[test]
+    fn val_use_out_of_scope() {
+        let val_use: Expr = ValUse {
+            val_id: ValId(0),
+            tpe: SType::SInt,
+        }
+        .into();
+        let block: Expr = BlockValue {
+            items: vec![ValDef {
+                id: ValId(0),
+                rhs: Box::new(GlobalVars::Height.into()),
+            }
+            .into()],
+            result: Box::new(height_gt(val_use.clone())),
+        }
+        .into();
+        assert_eq!(validate(&block, &SType::SBoolean), Ok(()));
+        assert_eq!(
+            validate(&height_gt(val_use), &SType::SBoolean),
+            Err(OptimizerError("v0 is used out of its scope".to_string()))
+        );
+    }
+
+    #[test]
+    fn val_use_of_other_type() {
+        let block: Expr = BlockValue {
+            items: vec![ValDef {
+                id: ValId(0),
+                rhs: Box::new(GlobalVars::Height.into()),
+            }
+            .into()],
+            result: Box::new(
+                ValUse {
+                    val_id: ValId(0),
+                    tpe: SType::SBoolean,
+                }
+                .into(),
+            ),
+        }
+        .into();
+        assert_eq!(
+            validate(&block, &SType::SBoolean),
+            Err(OptimizerError(
+                "v0 of type SInt is used as SBoolean".to_string()
+            ))
+        );
+    }
+}
@@ -21,7 +21,8 @@ use self::marker::Marker;
 use self::parse_error::ParseError;
 use self::source::Source;
 
-// const RECOVERY_SET: [TokenKind; 1] = [TokenKind::ValKw];
+/// Tokens starting the next statement, which are not skipped on error
+const RECOVERY_SET: [TokenKind; 2] = [TokenKind::ValKw, TokenKind::Semicolon];
 
 pub struct Parser<'t, 'input> {
     pub source: Source<'t, 'input>,
@@ -75,8 +76,7 @@ impl<'t, 'input> Parser<'t, 'input> {
             span: range,
         }));
 
-        // if !self.at_set(&RECOVERY_SET) && !self.at_end() {
-        if !self.at_end() {
+        if !self.at_set(&RECOVERY_SET) && !self.at_end() {
             let m = self.start();
             self.bump();
             m.complete(self, SyntaxKind::Error);
@@ -105,6 +105,11 @@ impl<'t, 'input> Parser<'t, 'input> {
     fn peek(&mut self) -> Option<TokenKind> {
         self.source.peek_kind()
     }
+
+    /// Kind of the `n`-th token ahead (`0` is the current one), not recorded as expected
+    fn lookahead(&mut self, n: usize) -> Option<TokenKind> {
+        self.source.lookahead_kind(n)
+    }
 }
 
 #[cfg(test)]
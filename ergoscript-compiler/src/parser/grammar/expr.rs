@@ -63,14 +63,22 @@ fn expr_binding_power(p: &mut Parser, minimum_binding_power: u8) -> Option<Compl
     let mut lhs = lhs(p)?;
 
     loop {
-        let op = if p.at(TokenKind::Plus) {
-            BinaryOp::Add
-        } else if p.at(TokenKind::Minus) {
-            BinaryOp::Sub
-        } else if p.at(TokenKind::Star) {
-            BinaryOp::Mul
-        } else if p.at(TokenKind::Slash) {
-            BinaryOp::Div
+        let op = if p.at(TokenKind::LParen) {
+            // Function application binds tighter than any operator, e.g. `-f(x)` is `-(f(x))`
+            let m = lhs.precede(p);
+            arg_list(p);
+            lhs = m.complete(p, SyntaxKind::CallExpr);
+            continue;
+        } else if p.at(TokenKind::Dot) {
+            // Field/method selection binds as tight as application, e.g. `SELF.R4[Int].get`
+            let m = lhs.precede(p);
+            p.bump();
+            p.expect(TokenKind::Ident);
+            types::type_args(p);
+            lhs = m.complete(p, SyntaxKind::SelectExpr);
+            continue;
+        } else if let Some(op) = p.peek().and_then(BinaryOp::from_token) {
+            op
         } else {
             // We’re not at an operator; we don’t know what to do next, so we return and let the
             // caller decide.
@@ -108,15 +116,27 @@ fn lhs(p: &mut Parser) -> Option<CompletedMarker> {
         int_number(p)
     } else if p.at(TokenKind::LongNumber) {
         long_number(p)
+    } else if p.at(TokenKind::StringLiteral) {
+        string_literal(p)
     } else if p.at(TokenKind::Ident) {
         ident(p)
         // variable_ref(p)
         // } else if p.at(TokenKind::ValKw) {
         //     variable_ref(p)
-    } else if p.at(TokenKind::Minus) {
+    } else if p.at(TokenKind::TrueKw) || p.at(TokenKind::FalseKw) {
+        bool_literal(p)
+    } else if p.at(TokenKind::Minus) || p.at(TokenKind::Bang) || p.at(TokenKind::Tilde) {
         prefix_expr(p)
+    } else if p.at(TokenKind::IfKw) {
+        if_expr(p)
     } else if p.at(TokenKind::LParen) {
-        paren_expr(p)
+        if at_lambda(p) {
+            lambda_expr(p)
+        } else {
+            paren_expr(p)
+        }
+    } else if p.at(TokenKind::LBrace) {
+        block_expr(p)
     } else {
         p.error();
         return None;
@@ -130,25 +150,67 @@ enum BinaryOp {
     Sub,
     Mul,
     Div,
+    Append,
+    Eq,
+    NotEq,
+    Less,
+    LessEq,
+    Greater,
+    GreaterEq,
+    And,
+    Or,
+    Xor,
+    BitAnd,
+    BitOr,
 }
 
 impl BinaryOp {
+    fn from_token(kind: TokenKind) -> Option<Self> {
+        Some(match kind {
+            TokenKind::Plus => Self::Add,
+            TokenKind::Minus => Self::Sub,
+            TokenKind::Star => Self::Mul,
+            TokenKind::Slash => Self::Div,
+            TokenKind::PlusPlus => Self::Append,
+            TokenKind::EqualsEquals => Self::Eq,
+            TokenKind::NotEquals => Self::NotEq,
+            TokenKind::Less => Self::Less,
+            TokenKind::LessEquals => Self::LessEq,
+            TokenKind::Greater => Self::Greater,
+            TokenKind::GreaterEquals => Self::GreaterEq,
+            TokenKind::And => Self::And,
+            TokenKind::Or => Self::Or,
+            TokenKind::Caret => Self::Xor,
+            TokenKind::Amp => Self::BitAnd,
+            TokenKind::Pipe => Self::BitOr,
+            _ => return None,
+        })
+    }
+
+    // Scala precedence (by the first character of the operator), all left-associative
     fn binding_power(&self) -> (u8, u8) {
         match self {
-            Self::Add | Self::Sub => (1, 2),
-            Self::Mul | Self::Div => (3, 4),
+            Self::Or | Self::BitOr => (1, 2),
+            Self::Xor => (3, 4),
+            Self::And | Self::BitAnd => (5, 6),
+            Self::Eq | Self::NotEq => (7, 8),
+            Self::Less | Self::LessEq | Self::Greater | Self::GreaterEq => (9, 10),
+            Self::Add | Self::Sub | Self::Append => (11, 12),
+            Self::Mul | Self::Div => (13, 14),
         }
     }
 }
 
 enum UnaryOp {
     Neg,
+    Not,
+    BitInversion,
 }
 
 impl UnaryOp {
     fn binding_power(&self) -> ((), u8) {
         match self {
-            Self::Neg => ((), 5),
+            Self::Neg | Self::Not | Self::BitInversion => ((), 15),
         }
     }
 }
@@ -167,6 +229,13 @@ fn long_number(p: &mut Parser) -> CompletedMarker {
     m.complete(p, SyntaxKind::LongNumber)
 }
 
+fn string_literal(p: &mut Parser) -> CompletedMarker {
+    assert!(p.at(TokenKind::StringLiteral));
+    let m = p.start();
+    p.bump();
+    m.complete(p, SyntaxKind::StringLiteral)
+}
+
 // fn variable_ref(p: &mut Parser) -> CompletedMarker {
 //     assert!(p.at(TokenKind::Ident));
 
@@ -183,12 +252,23 @@ fn ident(p: &mut Parser) -> CompletedMarker {
     m.complete(p, SyntaxKind::Ident)
 }
 
+fn bool_literal(p: &mut Parser) -> CompletedMarker {
+    assert!(p.at(TokenKind::TrueKw) || p.at(TokenKind::FalseKw));
+    let m = p.start();
+    p.bump();
+    m.complete(p, SyntaxKind::BoolLiteral)
+}
+
 fn prefix_expr(p: &mut Parser) -> CompletedMarker {
-    assert!(p.at(TokenKind::Minus));
+    let op = match p.peek() {
+        Some(TokenKind::Minus) => UnaryOp::Neg,
+        Some(TokenKind::Bang) => UnaryOp::Not,
+        Some(TokenKind::Tilde) => UnaryOp::BitInversion,
+        _ => unreachable!(),
+    };
 
     let m = p.start();
 
-    let op = UnaryOp::Neg;
     let ((), right_binding_power) = op.binding_power();
 
     // Eat the operator’s token.
@@ -199,6 +279,22 @@ fn prefix_expr(p: &mut Parser) -> CompletedMarker {
     m.complete(p, SyntaxKind::PrefixExpr)
 }
 
+/// `if (cond) a else b`
+fn if_expr(p: &mut Parser) -> CompletedMarker {
+    assert!(p.at(TokenKind::IfKw));
+
+    let m = p.start();
+    p.bump();
+    p.expect(TokenKind::LParen);
+    expr_binding_power(p, 0);
+    p.expect(TokenKind::RParen);
+    expr_binding_power(p, 0);
+    p.expect(TokenKind::ElseKw);
+    expr_binding_power(p, 0);
+
+    m.complete(p, SyntaxKind::IfExpr)
+}
+
 fn paren_expr(p: &mut Parser) -> CompletedMarker {
     assert!(p.at(TokenKind::LParen));
 
@@ -210,6 +306,79 @@ fn paren_expr(p: &mut Parser) -> CompletedMarker {
     m.complete(p, SyntaxKind::ParenExpr)
 }
 
+/// At `(x: T, ...) =>` or `() =>`
+fn at_lambda(p: &mut Parser) -> bool {
+    match p.lookahead(1) {
+        Some(TokenKind::RParen) => p.lookahead(2) == Some(TokenKind::FatArrow),
+        Some(TokenKind::Ident) => p.lookahead(2) == Some(TokenKind::Colon),
+        _ => false,
+    }
+}
+
+fn lambda_expr(p: &mut Parser) -> CompletedMarker {
+    assert!(p.at(TokenKind::LParen));
+
+    let m = p.start();
+    p.bump();
+    if !p.at(TokenKind::RParen) {
+        loop {
+            lambda_arg(p);
+            if p.at(TokenKind::Comma) {
+                p.bump();
+            } else {
+                break;
+            }
+        }
+    }
+    p.expect(TokenKind::RParen);
+    p.expect(TokenKind::FatArrow);
+    expr_binding_power(p, 0);
+
+    m.complete(p, SyntaxKind::LambdaExpr)
+}
+
+fn lambda_arg(p: &mut Parser) -> CompletedMarker {
+    let m = p.start();
+    p.expect(TokenKind::Ident);
+    p.expect(TokenKind::Colon);
+    types::type_ref(p);
+    m.complete(p, SyntaxKind::LambdaArg)
+}
+
+fn arg_list(p: &mut Parser) -> CompletedMarker {
+    assert!(p.at(TokenKind::LParen));
+
+    let m = p.start();
+    p.bump();
+    if !p.at(TokenKind::RParen) {
+        loop {
+            expr_binding_power(p, 0);
+            if p.at(TokenKind::Comma) {
+                p.bump();
+            } else {
+                break;
+            }
+        }
+    }
+    p.expect(TokenKind::RParen);
+
+    m.complete(p, SyntaxKind::ArgList)
+}
+
+fn block_expr(p: &mut Parser) -> CompletedMarker {
+    assert!(p.at(TokenKind::LBrace));
+
+    let m = p.start();
+    p.bump();
+    while !p.at(TokenKind::RBrace) && !p.at_end() {
+        stmt::stmt(p);
+        stmt::separators(p);
+    }
+    p.expect(TokenKind::RBrace);
+
+    m.complete(p, SyntaxKind::BlockExpr)
+}
+
 #[cfg(test)]
 mod tests {
     use crate::parser::check;
@@ -387,7 +556,7 @@ mod tests {
                       IntNumber@1..2
                         IntNumber@1..2 "2"
                       Plus@2..3 "+"
-                error: expected number, number, identifier, ‘-’ or ‘(’
+                error: expected number, number, string, identifier, ‘true’, ‘false’, ‘-’, ‘!’, ‘~’, ‘if’, ‘(’ or ‘{’
                 error: expected ‘)’"#]],
         );
     }
@@ -472,4 +641,259 @@ mod tests {
                       RParen@6..7 ")""#]],
         );
     }
+
+    #[test]
+    fn parse_block() {
+        check(
+            "{ val x = 1; x }",
+            expect![[r#"
+                Root@0..16
+                  BlockExpr@0..16
+                    LBrace@0..1 "{"
+                    Whitespace@1..2 " "
+                    VariableDef@2..11
+                      ValKw@2..5 "val"
+                      Whitespace@5..6 " "
+                      Ident@6..7 "x"
+                      Whitespace@7..8 " "
+                      Equals@8..9 "="
+                      Whitespace@9..10 " "
+                      IntNumber@10..11
+                        IntNumber@10..11 "1"
+                    Semicolon@11..12 ";"
+                    Whitespace@12..13 " "
+                    Ident@13..15
+                      Ident@13..14 "x"
+                      Whitespace@14..15 " "
+                    RBrace@15..16 "}""#]],
+        );
+    }
+
+    #[test]
+    fn parse_lambda() {
+        check(
+            "(x: Int, y: Coll[Byte]) => x",
+            expect![[r#"
+                Root@0..28
+                  LambdaExpr@0..28
+                    LParen@0..1 "("
+                    LambdaArg@1..7
+                      Ident@1..2 "x"
+                      Colon@2..3 ":"
+                      Whitespace@3..4 " "
+                      TypeRef@4..7
+                        Ident@4..7 "Int"
+                    Comma@7..8 ","
+                    Whitespace@8..9 " "
+                    LambdaArg@9..22
+                      Ident@9..10 "y"
+                      Colon@10..11 ":"
+                      Whitespace@11..12 " "
+                      TypeRef@12..22
+                        Ident@12..16 "Coll"
+                        LBracket@16..17 "["
+                        TypeRef@17..21
+                          Ident@17..21 "Byte"
+                        RBracket@21..22 "]"
+                    RParen@22..23 ")"
+                    Whitespace@23..24 " "
+                    FatArrow@24..26 "=>"
+                    Whitespace@26..27 " "
+                    Ident@27..28
+                      Ident@27..28 "x""#]],
+        );
+    }
+
+    #[test]
+    fn parse_call() {
+        check(
+            "f(1, x)",
+            expect![[r#"
+                Root@0..7
+                  CallExpr@0..7
+                    Ident@0..1
+                      Ident@0..1 "f"
+                    ArgList@1..7
+                      LParen@1..2 "("
+                      IntNumber@2..3
+                        IntNumber@2..3 "1"
+                      Comma@3..4 ","
+                      Whitespace@4..5 " "
+                      Ident@5..6
+                        Ident@5..6 "x"
+                      RParen@6..7 ")""#]],
+        );
+    }
+
+    #[test]
+    fn call_has_higher_binding_power_than_operators() {
+        check(
+            "-f()+1",
+            expect![[r#"
+                Root@0..6
+                  InfixExpr@0..6
+                    PrefixExpr@0..4
+                      Minus@0..1 "-"
+                      CallExpr@1..4
+                        Ident@1..2
+                          Ident@1..2 "f"
+                        ArgList@2..4
+                          LParen@2..3 "("
+                          RParen@3..4 ")"
+                    Plus@4..5 "+"
+                    IntNumber@5..6
+                      IntNumber@5..6 "1""#]],
+        );
+    }
+
+    #[test]
+    fn comparison_binds_weaker_than_arithmetic() {
+        check(
+            "a + 1 > b",
+            expect![[r#"
+            Root@0..9
+              InfixExpr@0..9
+                InfixExpr@0..6
+                  Ident@0..2
+                    Ident@0..1 "a"
+                    Whitespace@1..2 " "
+                  Plus@2..3 "+"
+                  Whitespace@3..4 " "
+                  IntNumber@4..6
+                    IntNumber@4..5 "1"
+                    Whitespace@5..6 " "
+                Greater@6..7 ">"
+                Whitespace@7..8 " "
+                Ident@8..9
+                  Ident@8..9 "b""#]],
+        );
+    }
+
+    #[test]
+    fn and_binds_tighter_than_or() {
+        check(
+            "a || b && c",
+            expect![[r#"
+            Root@0..11
+              InfixExpr@0..11
+                Ident@0..2
+                  Ident@0..1 "a"
+                  Whitespace@1..2 " "
+                Or@2..4 "||"
+                Whitespace@4..5 " "
+                InfixExpr@5..11
+                  Ident@5..7
+                    Ident@5..6 "b"
+                    Whitespace@6..7 " "
+                  And@7..9 "&&"
+                  Whitespace@9..10 " "
+                  Ident@10..11
+                    Ident@10..11 "c""#]],
+        );
+    }
+
+    #[test]
+    fn parse_not_and_bool_literals() {
+        check(
+            "!true ^ false",
+            expect![[r#"
+            Root@0..13
+              InfixExpr@0..13
+                PrefixExpr@0..6
+                  Bang@0..1 "!"
+                  BoolLiteral@1..6
+                    TrueKw@1..5 "true"
+                    Whitespace@5..6 " "
+                Caret@6..7 "^"
+                Whitespace@7..8 " "
+                BoolLiteral@8..13
+                  FalseKw@8..13 "false""#]],
+        );
+    }
+
+    #[test]
+    fn parse_if_else() {
+        check(
+            "if (a == 1) b else c + 1",
+            expect![[r#"
+            Root@0..24
+              IfExpr@0..24
+                IfKw@0..2 "if"
+                Whitespace@2..3 " "
+                LParen@3..4 "("
+                InfixExpr@4..10
+                  Ident@4..6
+                    Ident@4..5 "a"
+                    Whitespace@5..6 " "
+                  EqualsEquals@6..8 "=="
+                  Whitespace@8..9 " "
+                  IntNumber@9..10
+                    IntNumber@9..10 "1"
+                RParen@10..11 ")"
+                Whitespace@11..12 " "
+                Ident@12..14
+                  Ident@12..13 "b"
+                  Whitespace@13..14 " "
+                ElseKw@14..18 "else"
+                Whitespace@18..19 " "
+                InfixExpr@19..24
+                  Ident@19..21
+                    Ident@19..20 "c"
+                    Whitespace@20..21 " "
+                  Plus@21..22 "+"
+                  Whitespace@22..23 " "
+                  IntNumber@23..24
+                    IntNumber@23..24 "1""#]],
+        );
+    }
+
+    #[test]
+    fn parse_select_with_type_args() {
+        check(
+            "SELF.R4[Long].getOrElse(0L)",
+            expect![[r#"
+            Root@0..27
+              CallExpr@0..27
+                SelectExpr@0..23
+                  SelectExpr@0..13
+                    Ident@0..4
+                      Ident@0..4 "SELF"
+                    Dot@4..5 "."
+                    Ident@5..7 "R4"
+                    LBracket@7..8 "["
+                    TypeRef@8..12
+                      Ident@8..12 "Long"
+                    RBracket@12..13 "]"
+                  Dot@13..14 "."
+                  Ident@14..23 "getOrElse"
+                ArgList@23..27
+                  LParen@23..24 "("
+                  LongNumber@24..26
+                    LongNumber@24..26 "0L"
+                  RParen@26..27 ")""#]],
+        );
+    }
+
+    #[test]
+    fn parse_append_precedence() {
+        check(
+            "a ++ b == c",
+            expect![[r#"
+            Root@0..11
+              InfixExpr@0..11
+                InfixExpr@0..7
+                  Ident@0..2
+                    Ident@0..1 "a"
+                    Whitespace@1..2 " "
+                  PlusPlus@2..4 "++"
+                  Whitespace@4..5 " "
+                  Ident@5..7
+                    Ident@5..6 "b"
+                    Whitespace@6..7 " "
+                EqualsEquals@7..9 "=="
+                Whitespace@9..10 " "
+                Ident@10..11
+                  Ident@10..11 "c""#]],
+        );
+    }
 }
@@ -8,12 +8,23 @@ pub(super) fn stmt(p: &mut Parser) -> Option<CompletedMarker> {
     }
 }
 
+/// Optional `;` between statements (a line break separates them as well)
+pub(super) fn separators(p: &mut Parser) {
+    while p.at_set(&[TokenKind::Semicolon]) {
+        p.bump();
+    }
+}
+
 fn variable_def(p: &mut Parser) -> CompletedMarker {
     assert!(p.at(TokenKind::ValKw));
     let m = p.start();
     p.bump();
 
     p.expect(TokenKind::Ident);
+    if p.at(TokenKind::Colon) {
+        p.bump();
+        types::type_ref(p);
+    }
     p.expect(TokenKind::Equals);
 
     expr::expr(p);
@@ -43,4 +54,52 @@ mod tests {
                   Ident@10..13 "bar""#]],
         );
     }
+
+    #[test]
+    fn parse_variable_definition_with_type() {
+        check(
+            "val foo: Long = 1L",
+            expect![[r#"
+                Root@0..18
+                  VariableDef@0..18
+                    ValKw@0..3 "val"
+                    Whitespace@3..4 " "
+                    Ident@4..7 "foo"
+                    Colon@7..8 ":"
+                    Whitespace@8..9 " "
+                    TypeRef@9..14
+                      Ident@9..13 "Long"
+                      Whitespace@13..14 " "
+                    Equals@14..15 "="
+                    Whitespace@15..16 " "
+                    LongNumber@16..18
+                      LongNumber@16..18 "1L""#]],
+        );
+    }
+
+    #[test]
+    fn recover_on_next_variable_definition() {
+        check(
+            "val a =\nval b = 1",
+            expect![[r#"
+            Root@0..17
+              VariableDef@0..8
+                ValKw@0..3 "val"
+                Whitespace@3..4 " "
+                Ident@4..5 "a"
+                Whitespace@5..6 " "
+                Equals@6..7 "="
+                Whitespace@7..8 "\n"
+              VariableDef@8..17
+                ValKw@8..11 "val"
+                Whitespace@11..12 " "
+                Ident@12..13 "b"
+                Whitespace@13..14 " "
+                Equals@14..15 "="
+                Whitespace@15..16 " "
+                IntNumber@16..17
+                  IntNumber@16..17 "1"
+            error: expected number, number, string, identifier, ‘true’, ‘false’, ‘-’, ‘!’, ‘~’, ‘if’, ‘(’ or ‘{’, but found ‘val’"#]],
+        );
+    }
 }
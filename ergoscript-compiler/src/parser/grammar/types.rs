@@ -0,0 +1,25 @@
+use super::*;
+
+/// Type, e.g. `Int` or `Coll[Byte]`
+pub(super) fn type_ref(p: &mut Parser) -> CompletedMarker {
+    let m = p.start();
+    p.expect(TokenKind::Ident);
+    type_args(p);
+    m.complete(p, SyntaxKind::TypeRef)
+}
+
+/// Optional type arguments, e.g. `[Byte]` in `Coll[Byte]` or `[Long]` in `SELF.R4[Long]`
+pub(super) fn type_args(p: &mut Parser) {
+    if p.at(TokenKind::LBracket) {
+        p.bump();
+        loop {
+            type_ref(p);
+            if p.at(TokenKind::Comma) {
+                p.bump();
+            } else {
+                break;
+            }
+        }
+        p.expect(TokenKind::RBracket);
+    }
+}
@@ -1,6 +1,8 @@
 use std::fmt;
 use text_size::TextRange;
 
+use crate::diagnostic::Diagnostic;
+use crate::diagnostic::DiagnosticCode;
 use crate::error::pretty_error_desc;
 use crate::lexer::TokenKind;
 
@@ -15,17 +17,34 @@ impl ParseError {
     pub fn pretty_desc(&self, source: &str) -> String {
         pretty_error_desc(source, self.span, &self.to_string())
     }
+
+    pub fn diagnostic(&self) -> Diagnostic {
+        Diagnostic::new(
+            DiagnosticCode::ParseError,
+            Expectation(self).to_string(),
+            Some(self.span),
+        )
+    }
 }
 
 impl fmt::Display for ParseError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "error: expected ")?;
+        write!(f, "error: {}", Expectation(self))
+    }
+}
+
+/// Expected and found tokens of the error
+struct Expectation<'a>(&'a ParseError);
+
+impl fmt::Display for Expectation<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "expected ")?;
 
-        let num_expected = self.expected.len();
+        let num_expected = self.0.expected.len();
         let is_first = |idx| idx == 0;
         let is_last = |idx| idx == num_expected - 1;
 
-        for (idx, expected_kind) in self.expected.iter().enumerate() {
+        for (idx, expected_kind) in self.0.expected.iter().enumerate() {
             if is_first(idx) {
                 write!(f, "{}", expected_kind)?;
             } else if is_last(idx) {
@@ -35,7 +54,7 @@ impl fmt::Display for ParseError {
             }
         }
 
-        if let Some(found) = self.found {
+        if let Some(found) = self.0.found {
             write!(f, ", but found {}", found)?;
         }
 
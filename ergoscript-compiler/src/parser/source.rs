@@ -32,6 +32,16 @@ impl<'t, 'input> Source<'t, 'input> {
         self.peek_token_raw()
     }
 
+    /// Kind of the `n`-th (zero-based) non-trivia token ahead
+    pub fn lookahead_kind(&mut self, n: usize) -> Option<TokenKind> {
+        self.eat_trivia();
+        self.tokens[self.cursor..]
+            .iter()
+            .filter(|Token { kind, .. }| !kind.is_trivia())
+            .nth(n)
+            .map(|Token { kind, .. }| *kind)
+    }
+
     fn eat_trivia(&mut self) {
         while self.at_trivia() {
             self.cursor += 1;
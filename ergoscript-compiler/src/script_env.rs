@@ -1,11 +1,10 @@
 //! Compilation environment
 
-use std::collections::HashMap;
-
 use ergotree_ir::mir::constant::Constant;
 
 /// Environment with values substituted for identifiers during compilation
-pub struct ScriptEnv(HashMap<String, Constant>);
+#[derive(Debug, Clone)]
+pub struct ScriptEnv(Vec<(String, Constant)>);
 
 impl Default for ScriptEnv {
     fn default() -> Self {
@@ -16,11 +15,33 @@ impl Default for ScriptEnv {
 impl ScriptEnv {
     /// Empty environment
     pub fn new() -> Self {
-        ScriptEnv(HashMap::new())
+        ScriptEnv(Vec::new())
+    }
+
+    /// Sets the value for the given identifier (an existing value is replaced, keeping its index)
+    pub fn insert(&mut self, ident: &str, value: Constant) {
+        match self.0.iter_mut().find(|(name, _)| name == ident) {
+            Some((_, v)) => *v = value,
+            None => self.0.push((ident.to_string(), value)),
+        }
     }
 
     /// Returns the value([`Constant`]) for the given identifier (if any)
     pub fn get(&self, ident: &str) -> Option<&Constant> {
-        self.0.get(ident)
+        self.0
+            .iter()
+            .find(|(name, _)| name == ident)
+            .map(|(_, v)| v)
+    }
+
+    /// Index of the given identifier (in the order of insertion), which is also the index of its
+    /// value in the constants of a tree compiled with constant segregation
+    pub fn index_of(&self, ident: &str) -> Option<usize> {
+        self.0.iter().position(|(name, _)| name == ident)
+    }
+
+    /// Values in the order of insertion
+    pub fn values(&self) -> Vec<Constant> {
+        self.0.iter().map(|(_, v)| v.clone()).collect()
     }
 }
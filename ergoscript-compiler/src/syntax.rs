@@ -9,19 +9,44 @@ pub enum SyntaxKind {
     Whitespace,
     FnKw,
     ValKw,
+    IfKw,
+    ElseKw,
+    TrueKw,
+    FalseKw,
     Ident,
     IntNumber,
     LongNumber,
+    StringLiteral,
     Plus,
+    PlusPlus,
     Minus,
     Star,
     Slash,
     And,
+    Or,
+    Amp,
+    Pipe,
+    Caret,
+    Bang,
+    Tilde,
+    EqualsEquals,
+    NotEquals,
+    Less,
+    LessEquals,
+    Greater,
+    GreaterEquals,
     Equals,
+    FatArrow,
+    Colon,
+    Comma,
+    Dot,
+    Semicolon,
     LParen,
     RParen,
     LBrace,
     RBrace,
+    LBracket,
+    RBracket,
     Comment,
     Error,
     Root,
@@ -29,6 +54,15 @@ pub enum SyntaxKind {
     ParenExpr,
     PrefixExpr,
     VariableDef,
+    BlockExpr,
+    LambdaExpr,
+    LambdaArg,
+    CallExpr,
+    ArgList,
+    TypeRef,
+    IfExpr,
+    BoolLiteral,
+    SelectExpr,
 }
 
 impl From<TokenKind> for SyntaxKind {
@@ -37,21 +71,46 @@ impl From<TokenKind> for SyntaxKind {
             TokenKind::Whitespace => Self::Whitespace,
             TokenKind::FnKw => Self::FnKw,
             TokenKind::ValKw => Self::ValKw,
+            TokenKind::IfKw => Self::IfKw,
+            TokenKind::ElseKw => Self::ElseKw,
+            TokenKind::TrueKw => Self::TrueKw,
+            TokenKind::FalseKw => Self::FalseKw,
             TokenKind::Ident => Self::Ident,
             TokenKind::IntNumber => Self::IntNumber,
             TokenKind::LongNumber => Self::LongNumber,
+            TokenKind::StringLiteral => Self::StringLiteral,
             TokenKind::Plus => Self::Plus,
+            TokenKind::PlusPlus => Self::PlusPlus,
             TokenKind::Minus => Self::Minus,
             TokenKind::Star => Self::Star,
             TokenKind::Slash => Self::Slash,
             TokenKind::Equals => Self::Equals,
+            TokenKind::FatArrow => Self::FatArrow,
+            TokenKind::Colon => Self::Colon,
+            TokenKind::Comma => Self::Comma,
+            TokenKind::Dot => Self::Dot,
+            TokenKind::Semicolon => Self::Semicolon,
             TokenKind::LParen => Self::LParen,
             TokenKind::RParen => Self::RParen,
             TokenKind::LBrace => Self::LBrace,
             TokenKind::RBrace => Self::RBrace,
+            TokenKind::LBracket => Self::LBracket,
+            TokenKind::RBracket => Self::RBracket,
             TokenKind::Comment => Self::Comment,
             TokenKind::Error => Self::Error,
             TokenKind::And => Self::And,
+            TokenKind::Or => Self::Or,
+            TokenKind::Amp => Self::Amp,
+            TokenKind::Pipe => Self::Pipe,
+            TokenKind::Caret => Self::Caret,
+            TokenKind::Bang => Self::Bang,
+            TokenKind::Tilde => Self::Tilde,
+            TokenKind::EqualsEquals => Self::EqualsEquals,
+            TokenKind::NotEquals => Self::NotEquals,
+            TokenKind::Less => Self::Less,
+            TokenKind::LessEquals => Self::LessEquals,
+            TokenKind::Greater => Self::Greater,
+            TokenKind::GreaterEquals => Self::GreaterEquals,
         }
     }
 }
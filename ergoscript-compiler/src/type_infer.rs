@@ -1,10 +1,33 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::convert::Infallible;
+
+use ergotree_ir::types::sfunc::SFunc;
+use ergotree_ir::types::stype::SType;
+use ergotree_ir::types::type_unify::unify_many;
 use rowan::TextRange;
 
+use crate::diagnostic::Diagnostic;
+use crate::diagnostic::DiagnosticCode;
 use crate::error::pretty_error_desc;
 use crate::hir;
+use crate::hir::Apply;
 use crate::hir::Binary;
+use crate::hir::BinaryOp;
+use crate::hir::BindingId;
+use crate::hir::Block;
+use crate::hir::Collection;
 use crate::hir::Expr;
 use crate::hir::ExprKind;
+use crate::hir::If;
+use crate::hir::Lambda;
+use crate::hir::Method;
+use crate::hir::MethodCall;
+use crate::hir::Select;
+use crate::hir::Spanned;
+use crate::hir::Unary;
+use crate::hir::UnaryOp;
+use crate::hir::Val;
 
 #[derive(Debug, PartialEq, Eq)]
 pub struct TypeInferenceError {
@@ -20,40 +43,530 @@ impl TypeInferenceError {
     pub fn pretty_desc(&self, source: &str) -> String {
         pretty_error_desc(source, self.span, &self.msg)
     }
+
+    pub fn diagnostic(&self) -> Diagnostic {
+        Diagnostic::new(
+            DiagnosticCode::TypeInferenceError,
+            self.msg.clone(),
+            Some(self.span),
+        )
+    }
+}
+
+/// Assigns the types, reporting all the errors (ordered by position). Every operand, argument and
+/// item is inferred even if another one fails, the inference goes on after a failed `val`
+/// definition, without reporting the errors caused by it again.
+pub fn assign_type(expr: Expr) -> Result<Expr, Vec<TypeInferenceError>> {
+    let mut env = Env::default();
+    match infer(expr, &mut env) {
+        Ok(expr) if env.errors.is_empty() => return Ok(expr),
+        Ok(_) => (),
+        Err(e) => env.report(e),
+    }
+    env.errors.sort_by_key(|e| e.span.start());
+    Err(env.errors)
 }
 
-pub fn assign_type(expr: Expr) -> Result<Expr, TypeInferenceError> {
-    hir::rewrite(expr, |e| {
-        Ok(match &e.kind {
-            ExprKind::Binary(Binary { op, lhs, rhs }) => match op.node {
-                hir::BinaryOp::Plus => {
-                    let l = assign_type(*lhs.clone())?;
-                    let r = assign_type(*rhs.clone())?;
-                    let tpe = l.tpe.clone();
-                    Some(Expr {
-                        kind: Binary {
-                            op: op.clone(),
-                            lhs: l.into(),
-                            rhs: r.into(),
+/// Assigns the types of the subexpressions that type check, the other ones are left untyped (for
+/// the IDE, to show the types in a source with errors)
+pub fn assign_partial_type(expr: Expr) -> Expr {
+    // a failed inference still visits every `val` and lambda, so the types of the values are
+    // known when the subexpressions are inferred one by one
+    let env = RefCell::new(Env::default());
+    let res: Result<Expr, Infallible> =
+        hir::rewrite(expr, |e| Ok(infer(e.clone(), &mut env.borrow_mut()).ok()));
+    match res {
+        Ok(expr) => expr,
+        Err(never) => match never {},
+    }
+}
+
+/// Failed inference of a subexpression
+enum InferError {
+    /// Error to report
+    Error(TypeInferenceError),
+    /// The errors are already reported (or caused by a failed `val` definition)
+    Reported,
+}
+
+impl From<TypeInferenceError> for InferError {
+    fn from(e: TypeInferenceError) -> Self {
+        InferError::Error(e)
+    }
+}
+
+#[derive(Default)]
+struct Env {
+    /// Types of the values in scope (`None` for a failed definition)
+    types: HashMap<BindingId, Option<SType>>,
+    /// Errors reported so far
+    errors: Vec<TypeInferenceError>,
+}
+
+impl Env {
+    fn report(&mut self, e: InferError) {
+        if let InferError::Error(e) = e {
+            self.errors.push(e);
+        }
+    }
+
+    /// Both values, or the errors of both
+    fn join<A, B>(
+        &mut self,
+        a: Result<A, InferError>,
+        b: Result<B, InferError>,
+    ) -> Result<(A, B), InferError> {
+        match (a, b) {
+            (Ok(a), Ok(b)) => Ok((a, b)),
+            (Err(a), Err(b)) => {
+                self.report(a);
+                self.report(b);
+                Err(InferError::Reported)
+            }
+            (Err(e), _) | (_, Err(e)) => Err(e),
+        }
+    }
+
+    /// All the values, or the errors of all the failed ones
+    fn collect_all<T>(
+        &mut self,
+        results: Vec<Result<T, InferError>>,
+    ) -> Result<Vec<T>, InferError> {
+        let mut values = Vec::with_capacity(results.len());
+        let mut errors = Vec::new();
+        for res in results {
+            match res {
+                Ok(v) => values.push(v),
+                Err(e) => errors.push(e),
+            }
+        }
+        self.fail_with(errors)?;
+        Ok(values)
+    }
+
+    /// Fails with the errors (if any)
+    fn fail_with<E: Into<InferError>>(&mut self, errors: Vec<E>) -> Result<(), InferError> {
+        let mut errors = errors.into_iter().map(Into::into);
+        match (errors.next(), errors.next()) {
+            (None, _) => Ok(()),
+            (Some(e), None) => Err(e),
+            (Some(first), Some(second)) => {
+                self.report(first);
+                self.report(second);
+                errors.for_each(|e| self.report(e));
+                Err(InferError::Reported)
+            }
+        }
+    }
+}
+
+/// Infers all the expressions, including the ones after a failed one
+fn infer_all(exprs: Vec<Expr>, env: &mut Env) -> Result<Vec<Expr>, InferError> {
+    let results = exprs.into_iter().map(|e| infer(e, env)).collect();
+    env.collect_all(results)
+}
+
+/// Assigns types bottom-up, `env` holds the types of the values in scope
+fn infer(expr: Expr, env: &mut Env) -> Result<Expr, InferError> {
+    let span = expr.span;
+    let (kind, tpe): (ExprKind, Option<SType>) = match expr.kind {
+        ExprKind::Binary(Binary { op, lhs, rhs }) => {
+            let l = infer(*lhs, env);
+            let r = infer(*rhs, env);
+            let (l, r) = env.join(l, r)?;
+            let tpe = binary_tpe(&op.node, &l, &r)?;
+            (
+                Binary {
+                    op,
+                    lhs: l.into(),
+                    rhs: r.into(),
+                }
+                .into(),
+                Some(tpe),
+            )
+        }
+        ExprKind::Unary(Unary { op, expr }) => {
+            let expr = infer(*expr, env)?;
+            let tpe = expect_tpe(&expr)?;
+            let valid = match op.node {
+                UnaryOp::Neg | UnaryOp::BitInversion => tpe.is_numeric(),
+                UnaryOp::Not => tpe == SType::SBoolean,
+            };
+            if !valid {
+                return Err(TypeInferenceError::new(
+                    format!("Invalid operand type {:?} for {:?}", tpe, op.node),
+                    expr.span,
+                )
+                .into());
+            }
+            (
+                Unary {
+                    op,
+                    expr: Box::new(expr),
+                }
+                .into(),
+                Some(tpe),
+            )
+        }
+        ExprKind::If(If {
+            condition,
+            then_branch,
+            else_branch,
+        }) => {
+            let condition = infer(*condition, env);
+            let then_branch = infer(*then_branch, env);
+            let else_branch = infer(*else_branch, env);
+            let branches = env.join(then_branch, else_branch);
+            let (condition, (then_branch, else_branch)) = env.join(condition, branches)?;
+            let condition_tpe = expect_tpe(&condition)?;
+            let tpe = expect_tpe(&then_branch)?;
+            let else_tpe = expect_tpe(&else_branch)?;
+            let mut errors = Vec::new();
+            if condition_tpe != SType::SBoolean {
+                errors.push(TypeInferenceError::new(
+                    format!("Expected Boolean condition, got {:?}", condition_tpe),
+                    condition.span,
+                ));
+            }
+            if tpe != else_tpe {
+                errors.push(TypeInferenceError::new(
+                    format!(
+                        "Type mismatch: branches have types {:?} and {:?}",
+                        tpe, else_tpe
+                    ),
+                    else_branch.span,
+                ));
+            }
+            env.fail_with(errors)?;
+            (
+                If {
+                    condition: Box::new(condition),
+                    then_branch: Box::new(then_branch),
+                    else_branch: Box::new(else_branch),
+                }
+                .into(),
+                Some(tpe),
+            )
+        }
+        ExprKind::Block(Block { bindings, body }) => {
+            let mut typed_bindings = Vec::with_capacity(bindings.len());
+            let mut failed = false;
+            for val in bindings {
+                let id = binding_id(val.id, val.name.span)?;
+                let typed = infer(*val.rhs, env).and_then(|rhs| {
+                    let rhs_tpe = expect_tpe(&rhs)?;
+                    if let Some(declared) = &val.declared_tpe {
+                        if declared != &rhs_tpe {
+                            return Err(TypeInferenceError::new(
+                                format!(
+                                    "Type mismatch: {} is declared as {:?}, but has type {:?}",
+                                    val.name.node, declared, rhs_tpe
+                                ),
+                                rhs.span,
+                            )
+                            .into());
                         }
-                        .into(),
-                        span: e.span,
-                        tpe,
-                    })
+                    }
+                    Ok((rhs, rhs_tpe))
+                });
+                match typed {
+                    Ok((rhs, rhs_tpe)) => {
+                        env.types.insert(id, Some(rhs_tpe));
+                        typed_bindings.push(Val {
+                            rhs: Box::new(rhs),
+                            ..val
+                        });
+                    }
+                    Err(e) => {
+                        // the uses of the value are checked against the declared type (if any)
+                        env.report(e);
+                        env.types.insert(id, val.declared_tpe.clone());
+                        failed = true;
+                    }
+                }
+            }
+            let body = infer(*body, env)?;
+            if failed {
+                return Err(InferError::Reported);
+            }
+            let tpe = body.tpe.clone();
+            (
+                Block {
+                    bindings: typed_bindings,
+                    body: Box::new(body),
+                }
+                .into(),
+                tpe,
+            )
+        }
+        ExprKind::Lambda(Lambda { args, body }) => {
+            for arg in &args {
+                env.types
+                    .insert(binding_id(arg.id, arg.name.span)?, Some(arg.tpe.clone()));
+            }
+            let body = infer(*body, env)?;
+            let tpe = SType::SFunc(SFunc::new(
+                args.iter().map(|arg| arg.tpe.clone()).collect(),
+                expect_tpe(&body)?,
+            ));
+            (
+                Lambda {
+                    args,
+                    body: Box::new(body),
+                }
+                .into(),
+                Some(tpe),
+            )
+        }
+        ExprKind::Select(select) => method_call(select, vec![], span, env)?,
+        ExprKind::Apply(Apply { func, args }) => {
+            let func = match func.kind {
+                ExprKind::Select(select) => {
+                    let (kind, tpe) = method_call(select, args, span, env)?;
+                    return Ok(Expr { kind, span, tpe });
                 }
-                _ => todo!(),
-            },
-            _ => None,
-        })
+                kind => infer(
+                    Expr {
+                        kind,
+                        span: func.span,
+                        tpe: func.tpe,
+                    },
+                    env,
+                ),
+            };
+            let args = infer_all(args, env);
+            let (func, args) = env.join(func, args)?;
+            let tpe = match expect_tpe(&func)? {
+                SType::SColl(_) => {
+                    // `xs(i)` is `xs.apply(i)`
+                    let name = Spanned {
+                        node: "apply".to_string(),
+                        span: func.span,
+                    };
+                    let (kind, tpe) = typed_method_call(func, &name, &[], args, span)?;
+                    return Ok(Expr { kind, span, tpe });
+                }
+                SType::SFunc(sfunc) => {
+                    let arg_types = args.iter().map(expect_tpe).collect::<Result<Vec<_>, _>>()?;
+                    if sfunc.t_dom != arg_types {
+                        return Err(TypeInferenceError::new(
+                            format!(
+                                "Expected arguments of types {:?}, got {:?}",
+                                sfunc.t_dom, arg_types
+                            ),
+                            span,
+                        )
+                        .into());
+                    }
+                    *sfunc.t_range
+                }
+                tpe => {
+                    return Err(TypeInferenceError::new(
+                        format!("Expected a function, got an expression of type {:?}", tpe),
+                        func.span,
+                    )
+                    .into())
+                }
+            };
+            (
+                Apply {
+                    func: Box::new(func),
+                    args,
+                }
+                .into(),
+                Some(tpe),
+            )
+        }
+        ExprKind::Collection(Collection { items }) => {
+            let items = infer_all(items, env)?;
+            let elem_tpe = match items.first() {
+                Some(first) => expect_tpe(first)?,
+                None => {
+                    return Err(TypeInferenceError::new(
+                        "Cannot infer the element type of an empty collection".to_string(),
+                        span,
+                    )
+                    .into())
+                }
+            };
+            let mut errors = Vec::new();
+            for item in &items {
+                let item_tpe = expect_tpe(item)?;
+                if item_tpe != elem_tpe {
+                    errors.push(TypeInferenceError::new(
+                        format!(
+                            "Type mismatch: collection items have types {:?} and {:?}",
+                            elem_tpe, item_tpe
+                        ),
+                        item.span,
+                    ));
+                }
+            }
+            env.fail_with(errors)?;
+            (
+                Collection { items }.into(),
+                Some(SType::SColl(elem_tpe.into())),
+            )
+        }
+        ExprKind::ValUse(val_use) => {
+            let tpe = match env.types.get(&val_use.id) {
+                Some(Some(tpe)) => tpe.clone(),
+                Some(None) => return Err(InferError::Reported),
+                None => {
+                    return Err(TypeInferenceError::new(
+                        format!("Unknown type of {}", val_use.name),
+                        span,
+                    )
+                    .into())
+                }
+            };
+            (val_use.into(), Some(tpe))
+        }
+        // method calls are only produced by the inference
+        kind @ (ExprKind::Ident(_)
+        | ExprKind::GlobalVars(_)
+        | ExprKind::PredefFunc(_)
+        | ExprKind::Literal(_)
+        | ExprKind::Constant(_)
+        | ExprKind::ConstantPlaceholder(_)
+        | ExprKind::MethodCall(_)) => (kind, expr.tpe),
+    };
+    Ok(Expr { kind, span, tpe })
+}
+
+/// Resolves the method `select.field` of the object and checks the arguments
+fn method_call(
+    select: Select,
+    args: Vec<Expr>,
+    span: TextRange,
+    env: &mut Env,
+) -> Result<(ExprKind, Option<SType>), InferError> {
+    let obj = infer(*select.obj, env);
+    let args = infer_all(args, env);
+    let (obj, args) = env.join(obj, args)?;
+    typed_method_call(obj, &select.field, &select.tpe_args, args, span)
+}
+
+/// Resolves the method `name` of the (typed) object and checks the (typed) arguments,
+/// type variables of generic methods are inferred from the argument types
+fn typed_method_call(
+    obj: Expr,
+    name: &Spanned<String>,
+    tpe_args: &[SType],
+    args: Vec<Expr>,
+    span: TextRange,
+) -> Result<(ExprKind, Option<SType>), InferError> {
+    let (method, signature) = Method::resolve(&expect_tpe(&obj)?, &name.node, tpe_args)
+        .map_err(|msg| TypeInferenceError::new(msg, name.span))?;
+    if signature.t_dom.is_empty() && !args.is_empty() {
+        // `SELF.tokens(0)` is `SELF.tokens.apply(0)`
+        let property = Expr {
+            span: TextRange::new(obj.span.start(), name.span.end()),
+            kind: MethodCall {
+                obj: Box::new(obj),
+                method,
+                args: vec![],
+            }
+            .into(),
+            tpe: Some(*signature.t_range),
+        };
+        let apply = Spanned {
+            node: "apply".to_string(),
+            span: name.span,
+        };
+        return typed_method_call(property, &apply, &[], args, span);
+    }
+    let arg_types = args.iter().map(expect_tpe).collect::<Result<Vec<_>, _>>()?;
+    let args_mismatch = || {
+        TypeInferenceError::new(
+            format!(
+                "Expected arguments of types {:?}, got {:?}",
+                signature.t_dom, arg_types
+            ),
+            span,
+        )
+    };
+    let subst =
+        unify_many(signature.t_dom.clone(), arg_types.clone()).map_err(|_| args_mismatch())?;
+    let expected_types: Vec<SType> = signature
+        .t_dom
+        .iter()
+        .map(|tpe| tpe.clone().with_subst(&subst))
+        .collect();
+    if expected_types != arg_types {
+        return Err(args_mismatch().into());
+    }
+    Ok((
+        MethodCall {
+            obj: Box::new(obj),
+            method: method.with_subst(&subst),
+            args,
+        }
+        .into(),
+        Some(signature.t_range.clone().with_subst(&subst)),
+    ))
+}
+
+/// Checks the operand types, returns the type of the result
+fn binary_tpe(op: &BinaryOp, lhs: &Expr, rhs: &Expr) -> Result<SType, TypeInferenceError> {
+    let l = expect_tpe(lhs)?;
+    let r = expect_tpe(rhs)?;
+    if let BinaryOp::And | BinaryOp::Or = op {
+        // a `Boolean` operand is converted to `SigmaProp` if the other one is `SigmaProp`
+        if let (SType::SSigmaProp, SType::SBoolean) | (SType::SBoolean, SType::SSigmaProp) =
+            (&l, &r)
+        {
+            return Ok(SType::SSigmaProp);
+        }
+    }
+    if l != r {
+        return Err(TypeInferenceError::new(
+            format!(
+                "Type mismatch: operands of {:?} have types {:?} and {:?}",
+                op, l, r
+            ),
+            rhs.span,
+        ));
+    }
+    let (valid, tpe) = match op {
+        BinaryOp::Plus
+        | BinaryOp::Minus
+        | BinaryOp::Multiply
+        | BinaryOp::Divide
+        | BinaryOp::BitAnd
+        | BinaryOp::BitOr => (l.is_numeric(), l.clone()),
+        BinaryOp::Lt | BinaryOp::Le | BinaryOp::Gt | BinaryOp::Ge => {
+            (l.is_numeric(), SType::SBoolean)
+        }
+        BinaryOp::Eq | BinaryOp::NEq => (true, SType::SBoolean),
+        BinaryOp::And | BinaryOp::Or => (l == SType::SBoolean || l == SType::SSigmaProp, l.clone()),
+        BinaryOp::Xor => (l == SType::SBoolean || l.is_numeric(), l.clone()),
+    };
+    if !valid {
+        return Err(TypeInferenceError::new(
+            format!("Invalid operand type {:?} for {:?}", l, op),
+            lhs.span,
+        ));
+    }
+    Ok(tpe)
+}
+
+fn expect_tpe(expr: &Expr) -> Result<SType, TypeInferenceError> {
+    expr.tpe.clone().ok_or_else(|| {
+        TypeInferenceError::new(format!("Cannot infer type of {:?}", expr.kind), expr.span)
     })
 }
 
+fn binding_id(id: Option<BindingId>, span: TextRange) -> Result<BindingId, TypeInferenceError> {
+    id.ok_or_else(|| TypeInferenceError::new("Unbound value".to_string(), span))
+}
+
 #[cfg(test)]
 pub fn check(input: &str, expected_tree: expect_test::Expect) {
     let parse = super::parser::parse(input);
     let syntax = parse.syntax();
     let root = crate::ast::Root::cast(syntax).unwrap();
-    let hir = hir::lower(root).unwrap();
+    let hir = crate::hir::lower(root).unwrap();
     let binder = crate::binder::Binder::new(crate::script_env::ScriptEnv::new());
     let bind = binder.bind(hir).unwrap();
     let res = assign_type(bind).unwrap();
@@ -104,4 +617,230 @@ mod tests {
             }"#]],
         );
     }
+
+    #[test]
+    fn lambda_type() {
+        check(
+            "(x: Int) => x",
+            expect![[r#"
+                Expr {
+                    kind: Lambda(
+                        Lambda {
+                            args: [
+                                LambdaArg {
+                                    name: Spanned {
+                                        node: "x",
+                                        span: 1..2,
+                                    },
+                                    id: Some(
+                                        BindingId(
+                                            0,
+                                        ),
+                                    ),
+                                    tpe: SInt,
+                                },
+                            ],
+                            body: Expr {
+                                kind: ValUse(
+                                    ValUse {
+                                        name: "x",
+                                        id: BindingId(
+                                            0,
+                                        ),
+                                    },
+                                ),
+                                span: 12..13,
+                                tpe: Some(
+                                    SInt,
+                                ),
+                            },
+                        },
+                    ),
+                    span: 0..13,
+                    tpe: Some(
+                        SFunc(
+                            SFunc {
+                                t_dom: [
+                                    SInt,
+                                ],
+                                t_range: SInt,
+                                tpe_params: [],
+                            },
+                        ),
+                    ),
+                }"#]],
+        );
+    }
+
+    #[test]
+    fn relation_type() {
+        check(
+            "HEIGHT < 1",
+            expect![[r#"
+            Expr {
+                kind: Binary(
+                    Binary {
+                        op: Spanned {
+                            node: Lt,
+                            span: 7..8,
+                        },
+                        lhs: Expr {
+                            kind: GlobalVars(
+                                Height,
+                            ),
+                            span: 0..7,
+                            tpe: Some(
+                                SInt,
+                            ),
+                        },
+                        rhs: Expr {
+                            kind: Literal(
+                                Int(
+                                    1,
+                                ),
+                            ),
+                            span: 9..10,
+                            tpe: Some(
+                                SInt,
+                            ),
+                        },
+                    },
+                ),
+                span: 0..10,
+                tpe: Some(
+                    SBoolean,
+                ),
+            }"#]],
+        );
+    }
+
+    #[test]
+    fn coll_map_type() {
+        check(
+            "Coll(1, 2).map((x: Int) => x > 1)",
+            expect![[r#"
+            Expr {
+                kind: MethodCall(
+                    MethodCall {
+                        obj: Expr {
+                            kind: Collection(
+                                Collection {
+                                    items: [
+                                        Expr {
+                                            kind: Literal(
+                                                Int(
+                                                    1,
+                                                ),
+                                            ),
+                                            span: 5..6,
+                                            tpe: Some(
+                                                SInt,
+                                            ),
+                                        },
+                                        Expr {
+                                            kind: Literal(
+                                                Int(
+                                                    2,
+                                                ),
+                                            ),
+                                            span: 8..9,
+                                            tpe: Some(
+                                                SInt,
+                                            ),
+                                        },
+                                    ],
+                                },
+                            ),
+                            span: 0..10,
+                            tpe: Some(
+                                SColl(
+                                    SInt,
+                                ),
+                            ),
+                        },
+                        method: CollMap,
+                        args: [
+                            Expr {
+                                kind: Lambda(
+                                    Lambda {
+                                        args: [
+                                            LambdaArg {
+                                                name: Spanned {
+                                                    node: "x",
+                                                    span: 16..17,
+                                                },
+                                                id: Some(
+                                                    BindingId(
+                                                        0,
+                                                    ),
+                                                ),
+                                                tpe: SInt,
+                                            },
+                                        ],
+                                        body: Expr {
+                                            kind: Binary(
+                                                Binary {
+                                                    op: Spanned {
+                                                        node: Gt,
+                                                        span: 29..30,
+                                                    },
+                                                    lhs: Expr {
+                                                        kind: ValUse(
+                                                            ValUse {
+                                                                name: "x",
+                                                                id: BindingId(
+                                                                    0,
+                                                                ),
+                                                            },
+                                                        ),
+                                                        span: 27..29,
+                                                        tpe: Some(
+                                                            SInt,
+                                                        ),
+                                                    },
+                                                    rhs: Expr {
+                                                        kind: Literal(
+                                                            Int(
+                                                                1,
+                                                            ),
+                                                        ),
+                                                        span: 31..32,
+                                                        tpe: Some(
+                                                            SInt,
+                                                        ),
+                                                    },
+                                                },
+                                            ),
+                                            span: 27..32,
+                                            tpe: Some(
+                                                SBoolean,
+                                            ),
+                                        },
+                                    },
+                                ),
+                                span: 15..32,
+                                tpe: Some(
+                                    SFunc(
+                                        SFunc {
+                                            t_dom: [
+                                                SInt,
+                                            ],
+                                            t_range: SBoolean,
+                                            tpe_params: [],
+                                        },
+                                    ),
+                                ),
+                            },
+                        ],
+                    },
+                ),
+                span: 0..33,
+                tpe: Some(
+                    SColl(
+                        SBoolean,
+                    ),
+                ),
+            }"#]],
+        );
+    }
 }
@@ -0,0 +1,91 @@
+//! Conversion between the source offsets and LSP positions
+
+use text_size::TextSize;
+
+use crate::protocol::Position;
+
+/// Line starts of a text, to convert byte offsets to LSP positions (zero-based line and UTF-16
+/// column) and back
+pub struct LineIndex<'a> {
+    text: &'a str,
+    line_starts: Vec<usize>,
+}
+
+impl<'a> LineIndex<'a> {
+    /// Index of the given text
+    pub fn new(text: &'a str) -> Self {
+        let line_starts = std::iter::once(0)
+            .chain(text.match_indices('\n').map(|(i, _)| i + 1))
+            .collect();
+        LineIndex { text, line_starts }
+    }
+
+    /// Position of the byte offset
+    pub fn position(&self, offset: TextSize) -> Position {
+        let offset = usize::from(offset).min(self.text.len());
+        let line = self.line_starts.partition_point(|start| *start <= offset) - 1;
+        let line_start = self.line_starts[line];
+        let character = self.text[line_start..offset]
+            .chars()
+            .map(char::len_utf16)
+            .sum::<usize>();
+        Position {
+            line: line as u32,
+            character: character as u32,
+        }
+    }
+
+    /// Byte offset of the position (clamped to the end of the line or text)
+    pub fn offset(&self, position: Position) -> TextSize {
+        let line_start = match self.line_starts.get(position.line as usize) {
+            Some(start) => *start,
+            None => return TextSize::of(self.text),
+        };
+        let line = self.text[line_start..].split('\n').next().unwrap_or("");
+        let mut utf16_len = 0;
+        let mut offset = line_start;
+        for c in line.chars() {
+            if utf16_len >= position.character as usize {
+                break;
+            }
+            utf16_len += c.len_utf16();
+            offset += c.len_utf8();
+        }
+        TextSize::from(offset as u32)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pos(line: u32, character: u32) -> Position {
+        Position { line, character }
+    }
+
+    #[test]
+    fn positions_and_offsets() {
+        let text = "val a = 1\nval b = \"\u{1F600}\"\nb";
+        let index = LineIndex::new(text);
+        for (offset, position) in [
+            (0, pos(0, 0)),
+            (4, pos(0, 4)),
+            (9, pos(0, 9)),
+            (10, pos(1, 0)),
+            // after the 4-byte (2 UTF-16 units) character
+            (23, pos(1, 11)),
+            (25, pos(2, 0)),
+            (26, pos(2, 1)),
+        ] {
+            assert_eq!(index.position(TextSize::from(offset)), position);
+            assert_eq!(index.offset(position), TextSize::from(offset));
+        }
+    }
+
+    #[test]
+    fn clamp_out_of_range_positions() {
+        let index = LineIndex::new("ab\ncd");
+        assert_eq!(index.offset(pos(0, 10)), TextSize::from(2));
+        assert_eq!(index.offset(pos(5, 0)), TextSize::from(5));
+    }
+}
@@ -0,0 +1,29 @@
+//! ErgoScript language server (diagnostics, hover, go-to-definition, completion and formatting)
+//! communicating over stdio
+
+// Coding conventions
+#![forbid(unsafe_code)]
+#![deny(non_upper_case_globals)]
+#![deny(non_camel_case_types)]
+#![deny(non_snake_case)]
+#![deny(unused_mut)]
+#![deny(dead_code)]
+#![deny(unused_imports)]
+#![deny(missing_docs)]
+#![deny(rustdoc::broken_intra_doc_links)]
+
+mod line_index;
+mod protocol;
+mod server;
+mod transport;
+
+use server::Server;
+
+fn main() -> std::io::Result<()> {
+    let stdin = std::io::stdin();
+    let stdout = std::io::stdout();
+    let mut server = Server::new();
+    server.run(&mut stdin.lock(), &mut stdout.lock())?;
+    // the exit code is 1 if the client exits without `shutdown`
+    std::process::exit(if server.is_shutdown() { 0 } else { 1 })
+}
@@ -0,0 +1,223 @@
+//! Subset of the Language Server Protocol types used by the server
+
+use serde::Deserialize;
+use serde::Serialize;
+use serde_json::json;
+use serde_json::Value;
+
+/// JSON-RPC error code of an unknown method
+pub const METHOD_NOT_FOUND: i64 = -32601;
+/// JSON-RPC error code of a request after `shutdown`
+pub const INVALID_REQUEST: i64 = -32600;
+/// JSON-RPC error code of a malformed message
+pub const PARSE_ERROR: i64 = -32700;
+/// JSON-RPC error code of invalid request parameters
+pub const INVALID_PARAMS: i64 = -32602;
+
+/// Incoming request (with `id`) or notification (without `id`)
+#[derive(Debug, Deserialize)]
+pub struct Message {
+    /// Request id
+    pub id: Option<Value>,
+    /// `None` for the responses to the server requests
+    pub method: Option<String>,
+    /// Parameters
+    #[serde(default)]
+    pub params: Value,
+}
+
+/// Successful response to the request
+pub fn response(id: Value, result: Value) -> Value {
+    json!({"jsonrpc": "2.0", "id": id, "result": result})
+}
+
+/// Error response to the request
+pub fn error_response(id: Value, code: i64, message: &str) -> Value {
+    json!({"jsonrpc": "2.0", "id": id, "error": {"code": code, "message": message}})
+}
+
+/// Notification from the server
+pub fn notification(method: &str, params: Value) -> Value {
+    json!({"jsonrpc": "2.0", "method": method, "params": params})
+}
+
+/// Zero-based line and UTF-16 column
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
+pub struct Position {
+    /// Line
+    pub line: u32,
+    /// Column (in UTF-16 code units)
+    pub character: u32,
+}
+
+/// Range of positions (end is exclusive)
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
+pub struct Range {
+    /// Start
+    pub start: Position,
+    /// End
+    pub end: Position,
+}
+
+/// Range in a document
+#[derive(Debug, PartialEq, Eq, Clone, Serialize)]
+pub struct Location {
+    /// Document URI
+    pub uri: String,
+    /// Range
+    pub range: Range,
+}
+
+/// Document reference
+#[derive(Debug, Deserialize)]
+pub struct TextDocumentIdentifier {
+    /// Document URI
+    pub uri: String,
+}
+
+/// Opened document
+#[derive(Debug, Deserialize)]
+pub struct TextDocumentItem {
+    /// Document URI
+    pub uri: String,
+    /// Content
+    pub text: String,
+}
+
+/// `textDocument/didOpen` parameters
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DidOpenTextDocumentParams {
+    /// Opened document
+    pub text_document: TextDocumentItem,
+}
+
+/// Document change (the whole content, since the server only supports full sync)
+#[derive(Debug, Deserialize)]
+pub struct TextDocumentContentChangeEvent {
+    /// New content
+    pub text: String,
+}
+
+/// `textDocument/didChange` parameters
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DidChangeTextDocumentParams {
+    /// Changed document
+    pub text_document: TextDocumentIdentifier,
+    /// Changes (in order)
+    pub content_changes: Vec<TextDocumentContentChangeEvent>,
+}
+
+/// Parameters with a document only (`textDocument/didClose`, `textDocument/formatting`)
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TextDocumentParams {
+    /// Document
+    pub text_document: TextDocumentIdentifier,
+}
+
+/// Parameters with a position in a document (`textDocument/hover`, etc.)
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TextDocumentPositionParams {
+    /// Document
+    pub text_document: TextDocumentIdentifier,
+    /// Position in the document
+    pub position: Position,
+}
+
+/// Severity of a [`Diagnostic`]
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum DiagnosticSeverity {
+    /// Error
+    Error = 1,
+    /// Warning
+    Warning = 2,
+}
+
+impl Serialize for DiagnosticSeverity {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u8(*self as u8)
+    }
+}
+
+/// Compiler error or warning
+#[derive(Debug, PartialEq, Eq, Clone, Serialize)]
+pub struct Diagnostic {
+    /// Source range
+    pub range: Range,
+    /// Severity
+    pub severity: DiagnosticSeverity,
+    /// Code (`E0001`, etc.)
+    pub code: String,
+    /// Name of the diagnostics producer
+    pub source: String,
+    /// Description
+    pub message: String,
+}
+
+/// `textDocument/publishDiagnostics` parameters
+#[derive(Debug, Serialize)]
+pub struct PublishDiagnosticsParams {
+    /// Document URI
+    pub uri: String,
+    /// All the diagnostics of the document
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+/// Hover content
+#[derive(Debug, Serialize)]
+pub struct MarkupContent {
+    /// `plaintext` or `markdown`
+    pub kind: String,
+    /// Content
+    pub value: String,
+}
+
+/// `textDocument/hover` result
+#[derive(Debug, Serialize)]
+pub struct Hover {
+    /// Content
+    pub contents: MarkupContent,
+    /// Range of the hovered expression
+    pub range: Range,
+}
+
+/// Kind of a [`CompletionItem`]
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum CompletionItemKind {
+    /// Method or property
+    Method = 2,
+    /// Function
+    Function = 3,
+    /// Global variable
+    Variable = 6,
+}
+
+impl Serialize for CompletionItemKind {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u8(*self as u8)
+    }
+}
+
+/// Completion candidate
+#[derive(Debug, Serialize)]
+pub struct CompletionItem {
+    /// Name to insert
+    pub label: String,
+    /// Kind
+    pub kind: CompletionItemKind,
+    /// Type
+    pub detail: String,
+}
+
+/// Text replacement
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TextEdit {
+    /// Replaced range
+    pub range: Range,
+    /// New text
+    pub new_text: String,
+}
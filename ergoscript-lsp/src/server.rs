@@ -0,0 +1,399 @@
+//! Handlers of the requests and notifications, and the state of the open documents
+
+use std::collections::HashMap;
+use std::io;
+use std::io::BufRead;
+use std::io::Write;
+
+use ergoscript_compiler::compiler;
+use ergoscript_compiler::diagnostic::Severity;
+use ergoscript_compiler::ide;
+use ergoscript_compiler::ide::CompletionKind;
+use ergoscript_compiler::script_env::ScriptEnv;
+use serde::de::DeserializeOwned;
+use serde_json::json;
+use serde_json::Value;
+use text_size::TextRange;
+
+use crate::line_index::LineIndex;
+use crate::protocol::error_response;
+use crate::protocol::notification;
+use crate::protocol::response;
+use crate::protocol::CompletionItem;
+use crate::protocol::CompletionItemKind;
+use crate::protocol::DiagnosticSeverity;
+use crate::protocol::DidChangeTextDocumentParams;
+use crate::protocol::DidOpenTextDocumentParams;
+use crate::protocol::Hover;
+use crate::protocol::Location;
+use crate::protocol::MarkupContent;
+use crate::protocol::Message;
+use crate::protocol::PublishDiagnosticsParams;
+use crate::protocol::Range;
+use crate::protocol::TextDocumentParams;
+use crate::protocol::TextDocumentPositionParams;
+use crate::protocol::TextEdit;
+use crate::protocol::INVALID_PARAMS;
+use crate::protocol::INVALID_REQUEST;
+use crate::protocol::METHOD_NOT_FOUND;
+use crate::protocol::PARSE_ERROR;
+use crate::transport::read_message;
+use crate::transport::write_message;
+
+/// Name of the server (and the source of the diagnostics)
+const NAME: &str = "ergoscript";
+
+/// Language server state
+#[derive(Default)]
+pub struct Server {
+    /// Content of the open documents by URI
+    documents: HashMap<String, String>,
+    /// `shutdown` is received, only `exit` is expected
+    shutdown: bool,
+    /// `exit` is received
+    exit: bool,
+}
+
+impl Server {
+    /// Server without open documents
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Whether `shutdown` was received (before `exit`)
+    pub fn is_shutdown(&self) -> bool {
+        self.shutdown
+    }
+
+    /// Handles the messages until `exit` or the end of the input
+    pub fn run(&mut self, r: &mut impl BufRead, w: &mut impl Write) -> io::Result<()> {
+        while let Some(content) = read_message(r)? {
+            let outgoing = match serde_json::from_str::<Message>(&content) {
+                Ok(message) => self.handle(message),
+                Err(e) => vec![error_response(Value::Null, PARSE_ERROR, &e.to_string())],
+            };
+            for message in outgoing {
+                write_message(w, &message)?;
+            }
+            if self.exit {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    /// Responses and notifications to send for the message
+    pub fn handle(&mut self, message: Message) -> Vec<Value> {
+        match (message.id, message.method) {
+            (Some(id), Some(method)) => vec![self.handle_request(id, &method, message.params)],
+            (None, Some(method)) => self.handle_notification(&method, message.params),
+            // responses to the server requests (never sent)
+            (_, None) => Vec::new(),
+        }
+    }
+
+    fn handle_request(&mut self, id: Value, method: &str, params: Value) -> Value {
+        if self.shutdown {
+            return error_response(id, INVALID_REQUEST, "Server is shut down");
+        }
+        let result = match method {
+            "initialize" => Ok(capabilities()),
+            "shutdown" => {
+                self.shutdown = true;
+                Ok(Value::Null)
+            }
+            "textDocument/hover" => parse_params(params).map(|p| self.hover(p)),
+            "textDocument/definition" => parse_params(params).map(|p| self.definition(p)),
+            "textDocument/completion" => parse_params(params).map(|p| self.completion(p)),
+            "textDocument/formatting" => parse_params(params).map(|p| self.formatting(p)),
+            _ => {
+                return error_response(id, METHOD_NOT_FOUND, &format!("Unknown method {}", method))
+            }
+        };
+        match result {
+            Ok(result) => response(id, result),
+            Err(e) => error_response(id, INVALID_PARAMS, &e.to_string()),
+        }
+    }
+
+    fn handle_notification(&mut self, method: &str, params: Value) -> Vec<Value> {
+        match method {
+            "exit" => {
+                self.exit = true;
+                Vec::new()
+            }
+            "textDocument/didOpen" => match parse_params::<DidOpenTextDocumentParams>(params) {
+                Ok(p) => {
+                    let uri = p.text_document.uri;
+                    self.documents.insert(uri.clone(), p.text_document.text);
+                    vec![self.publish_diagnostics(uri)]
+                }
+                Err(_) => Vec::new(),
+            },
+            "textDocument/didChange" => match parse_params::<DidChangeTextDocumentParams>(params) {
+                Ok(mut p) => {
+                    let uri = p.text_document.uri;
+                    // full sync, the last change has the whole content
+                    if let Some(change) = p.content_changes.pop() {
+                        self.documents.insert(uri.clone(), change.text);
+                    }
+                    vec![self.publish_diagnostics(uri)]
+                }
+                Err(_) => Vec::new(),
+            },
+            "textDocument/didClose" => match parse_params::<TextDocumentParams>(params) {
+                Ok(p) => {
+                    let uri = p.text_document.uri;
+                    self.documents.remove(&uri);
+                    vec![self.publish_diagnostics(uri)]
+                }
+                Err(_) => Vec::new(),
+            },
+            _ => Vec::new(),
+        }
+    }
+
+    /// Diagnostics of the document (empty for a closed one)
+    fn publish_diagnostics(&self, uri: String) -> Value {
+        let diagnostics = match self.documents.get(&uri) {
+            Some(text) => {
+                let index = LineIndex::new(text);
+                compiler::diagnostics(text, ScriptEnv::new())
+                    .into_iter()
+                    .map(|d| crate::protocol::Diagnostic {
+                        range: d
+                            .span
+                            .map(|span| range(&index, span))
+                            .unwrap_or_else(|| range(&index, TextRange::default())),
+                        severity: match d.severity {
+                            Severity::Error => DiagnosticSeverity::Error,
+                            Severity::Warning => DiagnosticSeverity::Warning,
+                        },
+                        code: d.code.as_str().to_string(),
+                        source: NAME.to_string(),
+                        message: d.message,
+                    })
+                    .collect()
+            }
+            None => Vec::new(),
+        };
+        notification(
+            "textDocument/publishDiagnostics",
+            json!(PublishDiagnosticsParams { uri, diagnostics }),
+        )
+    }
+
+    fn hover(&self, p: TextDocumentPositionParams) -> Value {
+        let text = match self.documents.get(&p.text_document.uri) {
+            Some(text) => text,
+            None => return Value::Null,
+        };
+        let index = LineIndex::new(text);
+        match ide::type_at(text, ScriptEnv::new(), index.offset(p.position)) {
+            Some((span, tpe)) => json!(Hover {
+                contents: MarkupContent {
+                    kind: "plaintext".to_string(),
+                    value: ide::type_name(&tpe),
+                },
+                range: range(&index, span),
+            }),
+            None => Value::Null,
+        }
+    }
+
+    fn definition(&self, p: TextDocumentPositionParams) -> Value {
+        let text = match self.documents.get(&p.text_document.uri) {
+            Some(text) => text,
+            None => return Value::Null,
+        };
+        let index = LineIndex::new(text);
+        match ide::definition_at(text, index.offset(p.position)) {
+            Some(span) => json!(Location {
+                uri: p.text_document.uri,
+                range: range(&index, span),
+            }),
+            None => Value::Null,
+        }
+    }
+
+    fn completion(&self, p: TextDocumentPositionParams) -> Value {
+        let text = match self.documents.get(&p.text_document.uri) {
+            Some(text) => text,
+            None => return Value::Null,
+        };
+        let index = LineIndex::new(text);
+        let items: Vec<CompletionItem> = ide::completions(text, index.offset(p.position))
+            .into_iter()
+            .map(|c| CompletionItem {
+                label: c.label,
+                kind: match c.kind {
+                    CompletionKind::Global => CompletionItemKind::Variable,
+                    CompletionKind::Function => CompletionItemKind::Function,
+                    CompletionKind::Method => CompletionItemKind::Method,
+                },
+                detail: c.detail,
+            })
+            .collect();
+        json!(items)
+    }
+
+    /// Replaces the whole document, nothing if it is already formatted or has syntax errors
+    fn formatting(&self, p: TextDocumentParams) -> Value {
+        let text = match self.documents.get(&p.text_document.uri) {
+            Some(text) => text,
+            None => return Value::Null,
+        };
+        let index = LineIndex::new(text);
+        let edits: Vec<TextEdit> = match ide::format(text) {
+            Some(formatted) if formatted != *text => vec![TextEdit {
+                range: range(
+                    &index,
+                    TextRange::up_to(text_size::TextSize::of(text.as_str())),
+                ),
+                new_text: formatted,
+            }],
+            _ => Vec::new(),
+        };
+        json!(edits)
+    }
+}
+
+/// Features of the server (`initialize` result)
+fn capabilities() -> Value {
+    json!({
+        "capabilities": {
+            // full document sync
+            "textDocumentSync": 1,
+            "hoverProvider": true,
+            "definitionProvider": true,
+            "completionProvider": {"triggerCharacters": ["."]},
+            "documentFormattingProvider": true,
+        },
+        "serverInfo": {"name": NAME, "version": env!("CARGO_PKG_VERSION")},
+    })
+}
+
+fn parse_params<T: DeserializeOwned>(params: Value) -> Result<T, serde_json::Error> {
+    serde_json::from_value(params)
+}
+
+fn range(index: &LineIndex, span: TextRange) -> Range {
+    Range {
+        start: index.position(span.start()),
+        end: index.position(span.end()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use expect_test::expect;
+
+    const URI: &str = "file:///contract.es";
+
+    fn request(server: &mut Server, method: &str, params: Value) -> Value {
+        let message = serde_json::from_value(json!({"id": 1, "method": method, "params": params}));
+        let mut outgoing = server.handle(message.unwrap());
+        assert_eq!(outgoing.len(), 1);
+        outgoing.pop().unwrap()
+    }
+
+    fn notify(server: &mut Server, method: &str, params: Value) -> Vec<Value> {
+        let message = serde_json::from_value(json!({"method": method, "params": params}));
+        server.handle(message.unwrap())
+    }
+
+    fn open(text: &str) -> (Server, Vec<Value>) {
+        let mut server = Server::new();
+        let published = notify(
+            &mut server,
+            "textDocument/didOpen",
+            json!({"textDocument": {"uri": URI, "languageId": "ergoscript", "version": 1, "text": text}}),
+        );
+        (server, published)
+    }
+
+    fn position_params(line: u32, character: u32) -> Value {
+        json!({"textDocument": {"uri": URI}, "position": {"line": line, "character": character}})
+    }
+
+    #[test]
+    fn initialize_and_shutdown() {
+        let mut server = Server::new();
+        let init = request(&mut server, "initialize", json!({"capabilities": {}}));
+        assert_eq!(init["result"]["capabilities"]["hoverProvider"], json!(true));
+        let shutdown = request(&mut server, "shutdown", Value::Null);
+        assert_eq!(shutdown, json!({"jsonrpc": "2.0", "id": 1, "result": null}));
+        let after = request(&mut server, "textDocument/hover", position_params(0, 0));
+        assert_eq!(after["error"]["code"], json!(INVALID_REQUEST));
+        assert!(notify(&mut server, "exit", Value::Null).is_empty());
+        assert!(server.is_shutdown() && server.exit);
+    }
+
+    #[test]
+    fn unknown_method() {
+        let mut server = Server::new();
+        let res = request(&mut server, "workspace/symbol", json!({}));
+        assert_eq!(res["error"]["code"], json!(METHOD_NOT_FOUND));
+    }
+
+    #[test]
+    fn publish_diagnostics_on_open_and_change() {
+        let (mut server, published) = open("{ val unused = 1\n  HEIGHT > 1L }");
+        expect![[r#"[{"jsonrpc":"2.0","method":"textDocument/publishDiagnostics","params":{"uri":"file:///contract.es","diagnostics":[{"range":{"start":{"line":1,"character":11},"end":{"line":1,"character":14}},"severity":1,"code":"E0004","source":"ergoscript","message":"Type mismatch: operands of Gt have types SInt and SLong"}]}}]"#]]
+        .assert_eq(&json!(published).to_string());
+        let published = notify(
+            &mut server,
+            "textDocument/didChange",
+            json!({"textDocument": {"uri": URI, "version": 2}, "contentChanges": [{"text": "{ val unused = 1\n  HEIGHT > 1 }"}]}),
+        );
+        expect![[r#"[{"jsonrpc":"2.0","method":"textDocument/publishDiagnostics","params":{"uri":"file:///contract.es","diagnostics":[{"range":{"start":{"line":0,"character":6},"end":{"line":0,"character":12}},"severity":2,"code":"W0001","source":"ergoscript","message":"Unused val unused"}]}}]"#]].assert_eq(&json!(published).to_string());
+        let published = notify(
+            &mut server,
+            "textDocument/didClose",
+            json!({"textDocument": {"uri": URI}}),
+        );
+        assert_eq!(published[0]["params"]["diagnostics"], json!([]));
+    }
+
+    #[test]
+    fn hover() {
+        let (mut server, _) = open("{\n  val out = OUTPUTS(0)\n  out.value > 1L\n}");
+        let res = request(&mut server, "textDocument/hover", position_params(2, 2));
+        expect![[r#"{"contents":{"kind":"plaintext","value":"Box"},"range":{"start":{"line":2,"character":2},"end":{"line":2,"character":5}}}"#]].assert_eq(&res["result"].to_string());
+    }
+
+    #[test]
+    fn definition() {
+        let (mut server, _) = open("{\n  val out = OUTPUTS(0)\n  out.value > 1L\n}");
+        let res = request(
+            &mut server,
+            "textDocument/definition",
+            position_params(2, 2),
+        );
+        expect![[r#"{"uri":"file:///contract.es","range":{"start":{"line":1,"character":6},"end":{"line":1,"character":9}}}"#]].assert_eq(&res["result"].to_string());
+    }
+
+    #[test]
+    fn completion() {
+        let (mut server, _) = open("SELF.va");
+        let res = request(
+            &mut server,
+            "textDocument/completion",
+            position_params(0, 7),
+        );
+        expect![[r#"[{"label":"value","kind":2,"detail":"Long"}]"#]]
+            .assert_eq(&res["result"].to_string());
+    }
+
+    #[test]
+    fn formatting() {
+        let (mut server, _) = open("HEIGHT>1&&\nSELF.value>1L");
+        let res = request(
+            &mut server,
+            "textDocument/formatting",
+            json!({"textDocument": {"uri": URI}, "options": {"tabSize": 2, "insertSpaces": true}}),
+        );
+        expect![[r#"[{"range":{"start":{"line":0,"character":0},"end":{"line":1,"character":13}},"newText":"HEIGHT > 1 &&\nSELF.value > 1L"}]"#]].assert_eq(&res["result"].to_string());
+    }
+}
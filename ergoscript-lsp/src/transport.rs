@@ -0,0 +1,99 @@
+//! JSON-RPC message framing (`Content-Length` header) over stdio
+
+use std::io;
+use std::io::BufRead;
+use std::io::Write;
+
+use serde_json::Value;
+
+/// Maximum content length of a message, to not allocate whatever the header says
+pub const MAX_CONTENT_LENGTH: usize = 16 * 1024 * 1024;
+
+/// Reads the content of the next message, `None` at the end of the input
+pub fn read_message(r: &mut impl BufRead) -> io::Result<Option<String>> {
+    let mut content_length = None;
+    loop {
+        let mut header = String::new();
+        if r.read_line(&mut header)? == 0 {
+            return Ok(None);
+        }
+        let header = header.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.eq_ignore_ascii_case("Content-Length") {
+                content_length = Some(value.trim().parse::<usize>().map_err(|e| {
+                    io::Error::new(io::ErrorKind::InvalidData, format!("{}: {}", header, e))
+                })?);
+            }
+        }
+    }
+    let content_length = content_length.ok_or_else(|| {
+        io::Error::new(io::ErrorKind::InvalidData, "missing Content-Length header")
+    })?;
+    if content_length > MAX_CONTENT_LENGTH {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "Content-Length {} exceeds the maximum of {}",
+                content_length, MAX_CONTENT_LENGTH
+            ),
+        ));
+    }
+    let mut content = vec![0; content_length];
+    r.read_exact(&mut content)?;
+    String::from_utf8(content)
+        .map(Some)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+/// Writes the message with the `Content-Length` header
+pub fn write_message(w: &mut impl Write, message: &Value) -> io::Result<()> {
+    let content = message.to_string();
+    write!(w, "Content-Length: {}\r\n\r\n{}", content.len(), content)?;
+    w.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn write_read_roundtrip() {
+        let message = json!({"jsonrpc": "2.0", "method": "exit"});
+        let mut data = Vec::new();
+        write_message(&mut data, &message).unwrap();
+        write_message(&mut data, &message).unwrap();
+        let mut r = io::Cursor::new(data);
+        for _ in 0..2 {
+            let content = read_message(&mut r).unwrap().unwrap();
+            assert_eq!(serde_json::from_str::<Value>(&content).unwrap(), message);
+        }
+        assert_eq!(read_message(&mut r).unwrap(), None);
+    }
+
+    #[test]
+    fn read_with_content_type() {
+        let data = "Content-Type: application/vscode-jsonrpc; charset=utf-8\r\ncontent-length: 2\r\n\r\n{}";
+        let mut r = io::Cursor::new(data);
+        assert_eq!(read_message(&mut r).unwrap(), Some("{}".to_string()));
+    }
+
+    #[test]
+    fn read_without_content_length() {
+        let mut r = io::Cursor::new("Content-Type: x\r\n\r\n{}");
+        assert!(read_message(&mut r).is_err());
+    }
+
+    #[test]
+    fn read_too_long_content() {
+        let data = format!("Content-Length: {}\r\n\r\n{{}}", MAX_CONTENT_LENGTH + 1);
+        let mut r = io::Cursor::new(data);
+        assert_eq!(
+            read_message(&mut r).unwrap_err().kind(),
+            io::ErrorKind::InvalidData
+        );
+    }
+}
@@ -4,7 +4,7 @@ use ergotree_ir::mir::constant::TryExtractInto;
 use ergotree_ir::sigma_protocol::sigma_boolean::SigmaProp;
 use sigma_ser::ScorexParsingError;
 use sigma_ser::ScorexSerializationError;
-use std::rc::Rc;
+use std::sync::Arc;
 
 use ergotree_ir::ergo_tree::ErgoTreeError;
 use ergotree_ir::mir::constant::TryExtractFromError;
@@ -170,7 +170,7 @@ pub struct ReductionResult {
 pub fn reduce_to_crypto(
     expr: &Expr,
     env: &Env,
-    ctx: Rc<Context>,
+    ctx: Arc<Context>,
 ) -> Result<ReductionResult, EvalError> {
     let cost_accum = CostAccumulator::new(0, None);
     let mut ectx = EvalContext::new(ctx, cost_accum);
@@ -200,12 +200,12 @@ pub fn extract_sigma_boolean(expr: &Expr) -> Result<SigmaBoolean, EvalError> {
 
 #[derive(Debug)]
 pub(crate) struct EvalContext {
-    pub(crate) ctx: Rc<Context>,
+    pub(crate) ctx: Arc<Context>,
     pub(crate) cost_accum: CostAccumulator,
 }
 
 impl EvalContext {
-    pub fn new(ctx: Rc<Context>, cost_accum: CostAccumulator) -> Self {
+    pub fn new(ctx: Arc<Context>, cost_accum: CostAccumulator) -> Self {
         EvalContext { ctx, cost_accum }
     }
 }
@@ -380,11 +380,11 @@ pub(crate) mod tests {
     use sigma_test_util::force_any_val;
 
     pub fn eval_out_wo_ctx<T: TryExtractFrom<Value>>(expr: &Expr) -> T {
-        let ctx = Rc::new(force_any_val::<Context>());
+        let ctx = Arc::new(force_any_val::<Context>());
         eval_out(expr, ctx)
     }
 
-    pub fn eval_out<T: TryExtractFrom<Value>>(expr: &Expr, ctx: Rc<Context>) -> T {
+    pub fn eval_out<T: TryExtractFrom<Value>>(expr: &Expr, ctx: Arc<Context>) -> T {
         let cost_accum = CostAccumulator::new(0, None);
         let mut ectx = EvalContext::new(ctx, cost_accum);
         expr.eval(&Env::empty(), &mut ectx)
@@ -395,7 +395,7 @@ pub(crate) mod tests {
 
     pub fn try_eval_out<T: TryExtractFrom<Value>>(
         expr: &Expr,
-        ctx: Rc<Context>,
+        ctx: Arc<Context>,
     ) -> Result<T, EvalError> {
         let cost_accum = CostAccumulator::new(0, None);
         let mut ectx = EvalContext::new(ctx, cost_accum);
@@ -404,7 +404,7 @@ pub(crate) mod tests {
     }
 
     pub fn try_eval_out_wo_ctx<T: TryExtractFrom<Value>>(expr: &Expr) -> Result<T, EvalError> {
-        let ctx = Rc::new(force_any_val::<Context>());
+        let ctx = Arc::new(force_any_val::<Context>());
         try_eval_out(expr, ctx)
     }
 }
@@ -72,7 +72,6 @@ impl Evaluable for Filter {
 #[allow(clippy::panic)]
 #[cfg(test)]
 mod tests {
-    use std::rc::Rc;
     use std::sync::Arc;
 
     use crate::eval::context::Context;
@@ -129,7 +128,7 @@ mod tests {
             )
             .unwrap()
             .into();
-            let ctx = Rc::new(ctx);
+            let ctx = Arc::new(ctx);
             let expected: Vec<_> = ctx
                 .data_inputs
                 .clone()
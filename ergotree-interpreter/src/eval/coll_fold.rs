@@ -57,7 +57,7 @@ impl Evaluable for Fold {
 #[cfg(test)]
 mod tests {
     use std::convert::TryInto;
-    use std::rc::Rc;
+    use std::sync::Arc;
 
     use crate::eval::context::Context;
     use crate::eval::tests::eval_out;
@@ -123,7 +123,7 @@ mod tests {
             )
             .unwrap()
             .into();
-            let ctx = Rc::new(ctx);
+            let ctx = Arc::new(ctx);
             assert_eq!(
                 eval_out::<i64>(&expr, ctx.clone()),
                 ctx.data_inputs.clone()
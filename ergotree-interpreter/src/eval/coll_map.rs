@@ -69,7 +69,7 @@ impl Evaluable for Map {
 #[allow(clippy::unwrap_used)]
 #[cfg(test)]
 mod tests {
-    use std::rc::Rc;
+    use std::sync::Arc;
 
     use crate::eval::context::Context;
     use crate::eval::context::TxIoVec;
@@ -125,7 +125,7 @@ mod tests {
             )
             .unwrap()
             .into();
-            let ctx = Rc::new(ctx);
+            let ctx = Arc::new(ctx);
             let output = {
                 let e = eval_out::<Vec<i64>>(&expr, ctx.clone());
                 if e.is_empty() {
@@ -94,4 +94,17 @@ mod arbitrary {
 }
 
 #[cfg(test)]
-mod tests {}
+mod tests {
+    use super::*;
+    use crate::sigma_protocol::prover::TestProver;
+    use ergotree_ir::ergo_tree::ErgoTree;
+
+    fn assert_send_sync<T: Send + Sync>() {}
+
+    #[test]
+    fn context_is_send_sync() {
+        assert_send_sync::<Arc<Context>>();
+        assert_send_sync::<ErgoTree>();
+        assert_send_sync::<TestProver>();
+    }
+}
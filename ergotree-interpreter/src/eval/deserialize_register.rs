@@ -79,7 +79,6 @@ fn eval_default(
 #[cfg(test)]
 mod tests {
 
-    use std::rc::Rc;
     use std::sync::Arc;
 
     use ergotree_ir::chain::ergo_box::ErgoBox;
@@ -127,7 +126,7 @@ mod tests {
         }
         .into();
         let ctx = make_ctx_with_self_box(b);
-        assert!(try_eval_out::<bool>(&expr, Rc::new(ctx)).unwrap());
+        assert!(try_eval_out::<bool>(&expr, Arc::new(ctx)).unwrap());
     }
 
     #[test]
@@ -142,7 +141,7 @@ mod tests {
         }
         .into();
         let ctx = make_ctx_with_self_box(b.clone());
-        assert!(try_eval_out::<Value>(&expr, Rc::new(ctx)).is_err());
+        assert!(try_eval_out::<Value>(&expr, Arc::new(ctx)).is_err());
 
         // default with wrong type provided
         let expr: Expr = DeserializeRegister {
@@ -152,7 +151,7 @@ mod tests {
         }
         .into();
         let ctx = make_ctx_with_self_box(b.clone());
-        assert!(try_eval_out::<i32>(&expr, Rc::new(ctx)).is_err());
+        assert!(try_eval_out::<i32>(&expr, Arc::new(ctx)).is_err());
 
         // default provided
         let expr: Expr = DeserializeRegister {
@@ -162,7 +161,7 @@ mod tests {
         }
         .into();
         let ctx = make_ctx_with_self_box(b);
-        assert_eq!(try_eval_out::<i32>(&expr, Rc::new(ctx)).unwrap(), 1i32);
+        assert_eq!(try_eval_out::<i32>(&expr, Arc::new(ctx)).unwrap(), 1i32);
     }
 
     #[test]
@@ -178,7 +177,7 @@ mod tests {
         }
         .into();
         let ctx = make_ctx_with_self_box(b);
-        assert!(try_eval_out::<Value>(&expr, Rc::new(ctx)).is_err());
+        assert!(try_eval_out::<Value>(&expr, Arc::new(ctx)).is_err());
     }
 
     #[test]
@@ -196,6 +195,6 @@ mod tests {
         }
         .into();
         let ctx = make_ctx_with_self_box(b);
-        assert!(try_eval_out::<bool>(&expr, Rc::new(ctx)).is_err());
+        assert!(try_eval_out::<bool>(&expr, Arc::new(ctx)).is_err());
     }
 }
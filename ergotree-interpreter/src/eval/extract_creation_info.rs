@@ -24,7 +24,7 @@ impl Evaluable for ExtractCreationInfo {
 mod tests {
     use crate::eval::tests::eval_out;
     use crate::eval::Context;
-    use std::rc::Rc;
+    use std::sync::Arc;
 
     use ergotree_ir::mir::expr::Expr;
     use ergotree_ir::mir::global_vars::GlobalVars;
@@ -38,7 +38,7 @@ mod tests {
         let expr: Expr = ExtractCreationInfo::try_build(GlobalVars::SelfBox.into())
             .unwrap()
             .into();
-        let ctx = Rc::new(force_any_val::<Context>());
+        let ctx = Arc::new(force_any_val::<Context>());
         let v = eval_out::<(i32, Vec<i8>)>(&expr, ctx.clone());
         assert_eq!(v, ctx.self_box.creation_info());
     }
@@ -42,7 +42,7 @@ mod tests {
     use ergotree_ir::mir::unary_op::OneArgOpTryBuild;
     use ergotree_ir::types::stype::SType;
     use sigma_test_util::force_any_val;
-    use std::rc::Rc;
+    use std::sync::Arc;
 
     #[test]
     fn eval_box_get_reg_r0() {
@@ -54,7 +54,7 @@ mod tests {
         .unwrap()
         .into();
         let option_get_expr: Expr = OptionGet::try_build(get_reg_expr).unwrap().into();
-        let ctx = Rc::new(force_any_val::<Context>());
+        let ctx = Arc::new(force_any_val::<Context>());
         let v = eval_out::<i64>(&option_get_expr, ctx.clone());
         assert_eq!(v, ctx.self_box.value.as_i64());
     }
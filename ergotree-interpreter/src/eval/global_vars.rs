@@ -25,7 +25,6 @@ impl Evaluable for GlobalVars {
 #[allow(clippy::unwrap_used)]
 #[cfg(test)]
 mod tests {
-    use std::rc::Rc;
     use std::sync::Arc;
 
     use crate::eval::context::Context;
@@ -40,14 +39,14 @@ mod tests {
 
     #[test]
     fn eval_height() {
-        let ctx = Rc::new(force_any_val::<Context>());
+        let ctx = Arc::new(force_any_val::<Context>());
         let expr = compile_expr("HEIGHT", ScriptEnv::new()).unwrap();
         assert_eq!(eval_out::<i32>(&expr, ctx.clone()), ctx.height as i32);
     }
 
     #[test]
     fn eval_self_box() {
-        let ctx = Rc::new(force_any_val::<Context>());
+        let ctx = Arc::new(force_any_val::<Context>());
         assert_eq!(
             eval_out::<Arc<ErgoBox>>(&GlobalVars::SelfBox.into(), ctx.clone()).as_ref(),
             ctx.self_box.as_ref()
@@ -56,7 +55,7 @@ mod tests {
 
     #[test]
     fn eval_outputs() {
-        let ctx = Rc::new(force_any_val::<Context>());
+        let ctx = Arc::new(force_any_val::<Context>());
         assert_eq!(
             eval_out::<Vec<Arc<ErgoBox>>>(&GlobalVars::Outputs.into(), ctx.clone()),
             ctx.outputs
@@ -65,7 +64,7 @@ mod tests {
 
     #[test]
     fn eval_inputs() {
-        let ctx = Rc::new(force_any_val::<Context>());
+        let ctx = Arc::new(force_any_val::<Context>());
         assert_eq!(
             eval_out::<Vec<Arc<ErgoBox>>>(&GlobalVars::Inputs.into(), ctx.clone()),
             *ctx.inputs.as_vec()
@@ -74,7 +73,7 @@ mod tests {
 
     #[test]
     fn eval_group_generator() {
-        let ctx = Rc::new(force_any_val::<Context>());
+        let ctx = Arc::new(force_any_val::<Context>());
         assert_eq!(
             eval_out::<EcPoint>(&GlobalVars::GroupGenerator.into(), ctx),
             ergo_chain_types::ec_point::generator()
@@ -25,14 +25,14 @@ mod tests {
     use proptest::collection;
     use proptest::prelude::*;
     use sigma_test_util::force_any_val;
-    use std::rc::Rc;
+    use std::sync::Arc;
 
     proptest! {
 
         #[test]
         fn eval(bools in collection::vec(any::<bool>(), 0..10)) {
             let expr: Expr = Or {input: Expr::Const(bools.clone().into()).into()}.into();
-            let ctx = Rc::new(force_any_val::<Context>());
+            let ctx = Arc::new(force_any_val::<Context>());
             let res = eval_out::<bool>(&expr, ctx);
             prop_assert_eq!(res, bools.iter().any(|b| *b));
         }
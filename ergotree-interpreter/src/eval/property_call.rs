@@ -24,7 +24,6 @@ mod tests {
     use ergotree_ir::mir::expr::Expr;
     use ergotree_ir::types::scontext;
     use sigma_test_util::force_any_val;
-    use std::rc::Rc;
     use std::sync::Arc;
 
     #[test]
@@ -32,7 +31,7 @@ mod tests {
         let pc: Expr = PropertyCall::new(Expr::Context, scontext::DATA_INPUTS_PROPERTY.clone())
             .unwrap()
             .into();
-        let ctx = Rc::new(force_any_val::<Context>());
+        let ctx = Arc::new(force_any_val::<Context>());
         let expected = ctx
             .data_inputs
             .clone()
@@ -56,14 +56,14 @@ mod tests {
 
     use crate::eval::context::Context;
     use crate::eval::tests::eval_out;
-    use std::rc::Rc;
+    use std::sync::Arc;
 
     #[test]
     fn eval_box_value() {
         let expr: Expr = PropertyCall::new(GlobalVars::SelfBox.into(), sbox::VALUE_METHOD.clone())
             .unwrap()
             .into();
-        let ctx = Rc::new(force_any_val::<Context>());
+        let ctx = Arc::new(force_any_val::<Context>());
         assert_eq!(
             eval_out::<i64>(&expr, ctx.clone()),
             ctx.self_box.value.as_i64()
@@ -75,7 +75,7 @@ mod tests {
         let expr: Expr = PropertyCall::new(GlobalVars::SelfBox.into(), sbox::TOKENS_METHOD.clone())
             .unwrap()
             .into();
-        let ctx = Rc::new(force_any_val::<Context>());
+        let ctx = Arc::new(force_any_val::<Context>());
         assert_eq!(
             eval_out::<Vec<(Vec<i8>, i64)>>(&expr, ctx.clone()),
             ctx.self_box.tokens_raw()
@@ -100,7 +100,7 @@ mod tests {
     use ergotree_ir::mir::property_call::PropertyCall;
     use ergotree_ir::types::scontext;
     use sigma_test_util::force_any_val;
-    use std::rc::Rc;
+    use std::sync::Arc;
 
     fn make_ctx_inputs_includes_self_box() -> Context {
         let ctx = force_any_val::<Context>();
@@ -124,7 +124,7 @@ mod tests {
             PropertyCall::new(Expr::Context, scontext::SELF_BOX_INDEX_PROPERTY.clone())
                 .unwrap()
                 .into();
-        let rc = Rc::new(make_ctx_inputs_includes_self_box());
+        let rc = Arc::new(make_ctx_inputs_includes_self_box());
         assert_eq!(eval_out::<i32>(&expr, rc), 1);
     }
 
@@ -133,7 +133,7 @@ mod tests {
         let expr: Expr = PropertyCall::new(Expr::Context, scontext::HEADERS_PROPERTY.clone())
             .expect("internal error: `headers` method has parameters length != 1")
             .into();
-        let ctx = Rc::new(force_any_val::<Context>());
+        let ctx = Arc::new(force_any_val::<Context>());
         assert_eq!(eval_out::<[Header; 10]>(&expr, ctx.clone()), ctx.headers);
     }
 
@@ -142,7 +142,7 @@ mod tests {
         let expr: Expr = PropertyCall::new(Expr::Context, scontext::PRE_HEADER_PROPERTY.clone())
             .unwrap()
             .into();
-        let ctx = Rc::new(force_any_val::<Context>());
+        let ctx = Arc::new(force_any_val::<Context>());
         assert_eq!(eval_out::<PreHeader>(&expr, ctx.clone()), ctx.pre_header);
     }
 
@@ -154,7 +154,7 @@ mod tests {
         )
         .unwrap()
         .into();
-        let ctx = Rc::new(force_any_val::<Context>());
+        let ctx = Arc::new(force_any_val::<Context>());
         let digest = ctx.headers[0].state_root;
         let tree_flags = AvlTreeFlags::new(true, true, true);
         let avl_tree_data = AvlTreeData {
@@ -97,7 +97,7 @@ pub(crate) static VOTES_EVAL_FN: EvalFn = |_env, _ctx, obj, _args| {
 #[allow(clippy::expect_used, clippy::panic, clippy::unwrap_used)]
 mod tests {
     use std::convert::{TryFrom, TryInto};
-    use std::rc::Rc;
+    use std::sync::Arc;
 
     use ergo_chain_types::{BlockId, Digest, Digest32, EcPoint, Votes};
     use ergotree_ir::{
@@ -117,7 +117,7 @@ mod tests {
     const HEADER_INDEX: usize = 0;
 
     // Evaluates `Header.minerPk`, `Header.powOnetimePk`
-    fn eval_header_pks(ctx: Rc<Context>) -> [Box<EcPoint>; 2] {
+    fn eval_header_pks(ctx: Arc<Context>) -> [Box<EcPoint>; 2] {
         let miner_pk = eval_out::<EcPoint>(
             &create_get_header_property_expr(sheader::MINER_PK_PROPERTY.clone()),
             ctx.clone(),
@@ -130,7 +130,7 @@ mod tests {
     }
 
     // Evaluates `Header.AdProofsRoot`, `Header.transactionRoot`, `Header.extensionRoot`
-    fn eval_header_roots(ctx: Rc<Context>) -> [Digest32; 3] {
+    fn eval_header_roots(ctx: Arc<Context>) -> [Digest32; 3] {
         vec![
             sheader::AD_PROOFS_ROOT_PROPERTY.clone(),
             sheader::TRANSACTIONS_ROOT_PROPERTY.clone(),
@@ -145,7 +145,7 @@ mod tests {
     }
 
     // Evaluates `Header.id` and `Header.parentId`
-    fn eval_header_ids(ctx: Rc<Context>) -> [BlockId; 2] {
+    fn eval_header_ids(ctx: Arc<Context>) -> [BlockId; 2] {
         let id = eval_out::<Vec<i8>>(
             &create_get_header_property_expr(sheader::ID_PROPERTY.clone()),
             ctx.clone(),
@@ -198,14 +198,14 @@ mod tests {
     #[test]
     fn test_eval_version() {
         let expr = create_get_header_property_expr(sheader::VERSION_PROPERTY.clone());
-        let ctx = Rc::new(force_any_val::<Context>());
+        let ctx = Arc::new(force_any_val::<Context>());
         let version = ctx.headers[HEADER_INDEX].version as i8;
         assert_eq!(version, eval_out::<i8>(&expr, ctx));
     }
 
     #[test]
     fn test_eval_ids() {
-        let ctx = Rc::new(force_any_val::<Context>());
+        let ctx = Arc::new(force_any_val::<Context>());
         let expected = ctx
             .headers
             .get(HEADER_INDEX)
@@ -217,7 +217,7 @@ mod tests {
 
     #[test]
     fn test_eval_roots() {
-        let ctx = Rc::new(force_any_val::<Context>());
+        let ctx = Arc::new(force_any_val::<Context>());
         let expected = ctx
             .headers
             .get(HEADER_INDEX)
@@ -230,7 +230,7 @@ mod tests {
     #[test]
     fn test_eval_state_root() {
         let expr = create_get_header_property_expr(sheader::STATE_ROOT_PROPERTY.clone());
-        let ctx = Rc::new(force_any_val::<Context>());
+        let ctx = Arc::new(force_any_val::<Context>());
         let expected = ctx.headers[HEADER_INDEX].state_root;
         let actual = digest_from_bytes_signed::<33>(eval_out::<Vec<i8>>(&expr, ctx));
         assert_eq!(expected, actual);
@@ -239,7 +239,7 @@ mod tests {
     #[test]
     fn test_eval_timestamp() {
         let expr = create_get_header_property_expr(sheader::TIMESTAMP_PROPERTY.clone());
-        let ctx = Rc::new(force_any_val::<Context>());
+        let ctx = Arc::new(force_any_val::<Context>());
         let expected = ctx.headers[HEADER_INDEX].timestamp as i64;
         let actual = eval_out::<i64>(&expr, ctx);
         assert_eq!(expected, actual);
@@ -248,7 +248,7 @@ mod tests {
     #[test]
     fn test_eval_n_bits() {
         let expr = create_get_header_property_expr(sheader::N_BITS_PROPERTY.clone());
-        let ctx = Rc::new(force_any_val::<Context>());
+        let ctx = Arc::new(force_any_val::<Context>());
         let expected = ctx.headers[HEADER_INDEX].n_bits as i64;
         let actual = eval_out::<i64>(&expr, ctx);
         assert_eq!(expected, actual);
@@ -257,7 +257,7 @@ mod tests {
     #[test]
     fn test_eval_height() {
         let expr = create_get_header_property_expr(sheader::HEIGHT_PROPERTY.clone());
-        let ctx = Rc::new(force_any_val::<Context>());
+        let ctx = Arc::new(force_any_val::<Context>());
         let expected = ctx.headers[HEADER_INDEX].height as i32;
         let actual = eval_out::<i32>(&expr, ctx);
         assert_eq!(expected, actual);
@@ -265,7 +265,7 @@ mod tests {
 
     #[test]
     fn test_eval_pks() {
-        let ctx = Rc::new(force_any_val::<Context>());
+        let ctx = Arc::new(force_any_val::<Context>());
         let expected = ctx
             .headers
             .get(HEADER_INDEX)
@@ -286,7 +286,7 @@ mod tests {
     #[test]
     fn test_eval_pow_distance() {
         let expr = create_get_header_property_expr(sheader::POW_DISTANCE_PROPERTY.clone());
-        let ctx = Rc::new(force_any_val::<Context>());
+        let ctx = Arc::new(force_any_val::<Context>());
         let expected = ctx.headers[HEADER_INDEX]
             .autolykos_solution
             .pow_distance
@@ -302,7 +302,7 @@ mod tests {
     #[test]
     fn test_eval_pow_nonce() {
         let expr = create_get_header_property_expr(sheader::POW_NONCE_PROPERTY.clone());
-        let ctx = Rc::new(force_any_val::<Context>());
+        let ctx = Arc::new(force_any_val::<Context>());
         let expected = ctx.headers[HEADER_INDEX].autolykos_solution.nonce.clone();
         let actual = eval_out::<Vec<i8>>(&expr, ctx).as_vec_u8();
         assert_eq!(expected, actual);
@@ -311,7 +311,7 @@ mod tests {
     #[test]
     fn test_eval_votes() {
         let expr = create_get_header_property_expr(sheader::VOTES_PROPERTY.clone());
-        let ctx = Rc::new(force_any_val::<Context>());
+        let ctx = Arc::new(force_any_val::<Context>());
         let expected = ctx.headers[HEADER_INDEX].votes.clone();
         let actual = {
             let votes_bytes = eval_out::<Vec<i8>>(&expr, ctx).as_vec_u8();
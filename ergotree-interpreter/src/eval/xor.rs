@@ -44,7 +44,7 @@ mod tests {
     use ergotree_ir::mir::expr::Expr;
     use proptest::prelude::*;
     use sigma_test_util::force_any_val;
-    use std::rc::Rc;
+    use std::sync::Arc;
 
     #[test]
     fn eval_1_xor_0() {
@@ -58,7 +58,7 @@ mod tests {
         }
         .into();
 
-        let ctx = Rc::new(force_any_val::<Context>());
+        let ctx = Arc::new(force_any_val::<Context>());
         assert_eq!(eval_out::<Vec<i8>>(&expr, ctx), expected_xor);
     }
 
@@ -74,7 +74,7 @@ mod tests {
         }
         .into();
 
-        let ctx = Rc::new(force_any_val::<Context>());
+        let ctx = Arc::new(force_any_val::<Context>());
         assert_eq!(eval_out::<Vec<i8>>(&expr, ctx), expected_xor);
     }
 
@@ -90,7 +90,7 @@ mod tests {
         }
         .into();
 
-        let ctx = Rc::new(force_any_val::<Context>());
+        let ctx = Arc::new(force_any_val::<Context>());
         assert_eq!(eval_out::<Vec<i8>>(&expr, ctx), expected_xor);
     }
 
@@ -106,7 +106,7 @@ mod tests {
         }
         .into();
 
-        let ctx = Rc::new(force_any_val::<Context>());
+        let ctx = Arc::new(force_any_val::<Context>());
         assert_eq!(eval_out::<Vec<i8>>(&expr, ctx), expected_xor);
     }
 
@@ -122,7 +122,7 @@ mod tests {
         }
         .into();
 
-        let ctx = Rc::new(force_any_val::<Context>());
+        let ctx = Arc::new(force_any_val::<Context>());
         assert_eq!(eval_out::<Vec<i8>>(&expr, ctx), expected_xor);
     }
 
@@ -139,7 +139,7 @@ mod tests {
             }
             .into();
 
-            let ctx = Rc::new(force_any_val::<Context>());
+            let ctx = Arc::new(force_any_val::<Context>());
             assert_eq!(eval_out::<Vec<i8>>(&expr, ctx), expected_xor);
         }
     }
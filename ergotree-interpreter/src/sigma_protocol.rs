@@ -12,7 +12,10 @@ pub mod dht_protocol;
 pub mod dlog_protocol;
 mod fiat_shamir;
 mod gf2_192;
+pub mod musig;
+pub mod proof_inspection;
 pub mod proof_tree;
+pub mod secret_sharing;
 pub mod sig_serializer;
 pub mod unchecked_tree;
 pub mod unproven_tree;
@@ -60,15 +60,13 @@ pub mod interactive_prover {
     /// Step 5 from <https://ergoplatform.org/docs/ErgoScript.pdf>
     /// For every leaf marked “simulated”, use the simulator of the sigma protocol for that leaf
     /// to compute the commitment "a" and the response "z", given the challenge "e" that
-    /// is already stored in the leaf
+    /// is already stored in the leaf. The response "z" is a random element of Zq.
     pub(crate) fn simulate(
         public_input: &ProveDhTuple,
         challenge: &Challenge,
+        z: Scalar,
     ) -> (FirstDhTupleProverMessage, SecondDhTupleProverMessage) {
         use ergo_chain_types::ec_point::exponentiate;
-        //SAMPLE a random z <- Zq
-        let z = dlog_group::random_scalar_in_group_range(crypto_utils::secure_rng());
-
         // COMPUTE a = g^z*u^(-e) and b = h^z*v^{-e}  (where -e here means -e mod q)
         let e: Scalar = challenge.clone().into();
         let minus_e = e.negate();
@@ -90,8 +88,17 @@ pub mod interactive_prover {
     ///
     /// In this case (DH tuple) "a" is also a tuple
     pub fn first_message(public_input: &ProveDhTuple) -> (Wscalar, FirstDhTupleProverMessage) {
-        use ergo_chain_types::ec_point::exponentiate;
         let r = dlog_group::random_scalar_in_group_range(crypto_utils::secure_rng());
+        first_message_with_randomness(public_input, r)
+    }
+
+    /// Step 6 with the given randomness "r" (must be a uniformly random element of Zq,
+    /// never reused with another challenge)
+    pub(crate) fn first_message_with_randomness(
+        public_input: &ProveDhTuple,
+        r: Scalar,
+    ) -> (Wscalar, FirstDhTupleProverMessage) {
+        use ergo_chain_types::ec_point::exponentiate;
         let a = exponentiate(&public_input.g, &r);
         let b = exponentiate(&public_input.h, &r);
         (r.into(), FirstDhTupleProverMessage::new(a, b))
@@ -55,14 +55,12 @@ pub mod interactive_prover {
     /// Step 5 from <https://ergoplatform.org/docs/ErgoScript.pdf>
     /// For every leaf marked “simulated”, use the simulator of the sigma protocol for that leaf
     /// to compute the commitment "a" and the response "z", given the challenge "e" that
-    /// is already stored in the leaf
+    /// is already stored in the leaf. The response "z" is a random element of Zq.
     pub(crate) fn simulate(
         public_input: &ProveDlog,
         challenge: &Challenge,
+        z: Scalar,
     ) -> (FirstDlogProverMessage, SecondDlogProverMessage) {
-        //SAMPLE a random z <- Zq
-        let z = dlog_group::random_scalar_in_group_range(crypto_utils::secure_rng());
-
         //COMPUTE a = g^z*h^(-e)  (where -e here means -e mod q)
         let e: Scalar = challenge.clone().into();
         let minus_e = e.negate();
@@ -80,6 +78,12 @@ pub mod interactive_prover {
     /// that leaf to compute the necessary randomness "r" and the commitment "a"
     pub fn first_message() -> (Wscalar, FirstDlogProverMessage) {
         let r = dlog_group::random_scalar_in_group_range(crypto_utils::secure_rng());
+        first_message_with_randomness(r)
+    }
+
+    /// Step 6 with the given randomness "r" (must be a uniformly random element of Zq,
+    /// never reused with another challenge)
+    pub(crate) fn first_message_with_randomness(r: Scalar) -> (Wscalar, FirstDlogProverMessage) {
         let g = generator();
         let a = exponentiate(&g, &r);
         (r.into(), FirstDlogProverMessage { a: a.into() })
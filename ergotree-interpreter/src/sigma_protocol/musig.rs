@@ -0,0 +1,540 @@
+//! MuSig2-style key and nonce aggregation for N-of-N groups of `ProveDlog` keys
+//!
+//! Instead of a `CAND` of N keys (and N Schnorr responses in the proof) the group is represented
+//! by a single aggregated `ProveDlog` key and signs with a single Schnorr proof, which is
+//! indistinguishable from a proof of a regular key.
+//!
+//! Signing takes two rounds:
+//! 1. Every signer generates a nonce pair with [`MusigSecretNonce::generate`] and shares the
+//! public part.
+//! 2. Once all public nonces are collected, every signer creates a [`MusigSession`] for the
+//! message and computes its partial signature with [`MusigSession::partial_sign`].
+//!
+//! Partial signatures are combined by [`MusigSession::into_signer`] into a [`MusigSigner`] that
+//! can be used as an [`ExternalSigner`] to produce the proof for the aggregated key.
+//!
+//! See <https://eprint.iacr.org/2020/1261>
+
+use std::convert::TryFrom;
+use std::fmt::Formatter;
+
+use elliptic_curve::generic_array::GenericArray;
+use elliptic_curve::ops::Reduce;
+use ergo_chain_types::ec_point::exponentiate;
+use ergo_chain_types::ec_point::generator;
+use ergo_chain_types::EcPoint;
+use ergotree_ir::serialization::SigmaSerializable;
+use ergotree_ir::sigma_protocol::dlog_group;
+use ergotree_ir::sigma_protocol::sigma_boolean::ProveDhTuple;
+use ergotree_ir::sigma_protocol::sigma_boolean::ProveDlog;
+use ergotree_ir::sigma_protocol::sigma_boolean::SigmaBoolean;
+use k256::Scalar;
+use k256::U256;
+use sigma_util::hash::blake2b256_hash;
+use thiserror::Error;
+use zeroize::Zeroize;
+
+use super::challenge::Challenge;
+use super::crypto_utils;
+use super::dht_protocol::SecondDhTupleProverMessage;
+use super::dlog_protocol::FirstDlogProverMessage;
+use super::dlog_protocol::SecondDlogProverMessage;
+use super::fiat_shamir::fiat_shamir_hash_fn;
+use super::fiat_shamir::fiat_shamir_tree_to_bytes;
+use super::private_input::DlogProverInput;
+use super::prover::external_signer::ExternalSigner;
+use super::prover::external_signer::ExternalSignerError;
+use super::unproven_tree::NodePosition;
+use super::unproven_tree::UnprovenSchnorr;
+use super::unproven_tree::UnprovenTree;
+use super::wscalar::Wscalar;
+use super::FirstProverMessage;
+
+const KEY_LIST_TAG: &[u8] = b"ergo/musig/keylist";
+const KEY_COEFFICIENT_TAG: &[u8] = b"ergo/musig/keycoef";
+const NONCE_COEFFICIENT_TAG: &[u8] = b"ergo/musig/noncecoef";
+
+/// MuSig errors
+#[derive(Error, PartialEq, Eq, Debug, Clone)]
+pub enum MusigError {
+    /// No keys to aggregate
+    #[error("no keys to aggregate")]
+    EmptyKeySet,
+    /// Key is listed more than once
+    #[error("duplicate key {0:?}")]
+    DuplicateKey(ProveDlog),
+    /// Key is not in the aggregated key set
+    #[error("key {0:?} is not in the aggregated key set")]
+    UnknownKey(ProveDlog),
+    /// No public nonce for the key
+    #[error("missing public nonce of {0:?}")]
+    MissingNonce(ProveDlog),
+    /// No partial signature for the key
+    #[error("missing partial signature of {0:?}")]
+    MissingPartialSignature(ProveDlog),
+    /// Partial signature does not match the key's nonce and the session challenge
+    #[error("invalid partial signature of {0:?}")]
+    InvalidPartialSignature(ProveDlog),
+    /// Failed to compute the Fiat-Shamir challenge
+    #[error("Fiat-Shamir serialization error: {0}")]
+    FiatShamirTreeSerializationError(String),
+}
+
+/// Aggregated key of an N-of-N group.
+/// The aggregated key is `X = X_1^a_1 * .. * X_n^a_n` where the coefficient `a_i` binds the key
+/// `X_i` to the whole key set (protects from rogue-key attacks).
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct MusigKeyAgg {
+    keys: Vec<ProveDlog>,
+    coefficients: Vec<Wscalar>,
+    aggregated: ProveDlog,
+}
+
+impl MusigKeyAgg {
+    /// Aggregate the keys of the group. The result does not depend on the order of the keys.
+    pub fn new(keys: Vec<ProveDlog>) -> Result<Self, MusigError> {
+        if keys.is_empty() {
+            return Err(MusigError::EmptyKeySet);
+        }
+        let mut keyed: Vec<(Vec<u8>, ProveDlog)> =
+            keys.into_iter().map(|k| (point_bytes(&k.h), k)).collect();
+        keyed.sort_by(|(a, _), (b, _)| a.cmp(b));
+        if let Some(w) = keyed.windows(2).find(|w| w[0].0 == w[1].0) {
+            return Err(MusigError::DuplicateKey(w[0].1.clone()));
+        }
+        let key_list_hash = tagged_hash(
+            KEY_LIST_TAG,
+            &keyed.iter().map(|(b, _)| b.as_slice()).collect::<Vec<_>>(),
+        );
+        let coefficients: Vec<Wscalar> = keyed
+            .iter()
+            .map(|(b, _)| {
+                hash_to_scalar(
+                    KEY_COEFFICIENT_TAG,
+                    &[key_list_hash.as_slice(), b.as_slice()],
+                )
+                .into()
+            })
+            .collect();
+        let aggregated = keyed
+            .iter()
+            .zip(coefficients.iter())
+            .map(|((_, k), a)| exponentiate(&k.h, a.as_scalar_ref()))
+            .fold(EcPoint::default(), |acc, p| acc * &p);
+        Ok(MusigKeyAgg {
+            keys: keyed.into_iter().map(|(_, k)| k).collect(),
+            coefficients,
+            aggregated: ProveDlog::new(aggregated),
+        })
+    }
+
+    /// Aggregated key
+    pub fn aggregated_key(&self) -> &ProveDlog {
+        &self.aggregated
+    }
+
+    /// Keys of the group (sorted)
+    pub fn keys(&self) -> &[ProveDlog] {
+        &self.keys
+    }
+
+    fn key_index(&self, key: &ProveDlog) -> Result<usize, MusigError> {
+        self.keys
+            .iter()
+            .position(|k| k == key)
+            .ok_or_else(|| MusigError::UnknownKey(key.clone()))
+    }
+}
+
+/// Secret nonce pair of a signer. Must be used for one signature only, so it can be neither
+/// cloned nor serialized and is consumed by [`MusigSession::partial_sign`].
+pub struct MusigSecretNonce {
+    r1: Wscalar,
+    r2: Wscalar,
+}
+
+impl MusigSecretNonce {
+    /// Generate a random nonce pair, returning the secret and the public part
+    pub fn generate() -> (MusigSecretNonce, MusigPublicNonce) {
+        let r1 = dlog_group::random_scalar_in_group_range(crypto_utils::secure_rng());
+        let r2 = dlog_group::random_scalar_in_group_range(crypto_utils::secure_rng());
+        let public = MusigPublicNonce {
+            r1: exponentiate(&generator(), &r1),
+            r2: exponentiate(&generator(), &r2),
+        };
+        (
+            MusigSecretNonce {
+                r1: r1.into(),
+                r2: r2.into(),
+            },
+            public,
+        )
+    }
+}
+
+impl std::fmt::Debug for MusigSecretNonce {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        // to avoid leaking it in error messages, logs, etc.
+        "MUSIGNONCE:***".fmt(f)
+    }
+}
+
+impl Drop for MusigSecretNonce {
+    fn drop(&mut self) {
+        self.r1.zeroize();
+        self.r2.zeroize();
+    }
+}
+
+/// Public nonce pair of a signer, shared with the other signers in the first round
+#[derive(PartialEq, Eq, Debug, Clone)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
+pub struct MusigPublicNonce {
+    /// `g^r1`
+    pub r1: EcPoint,
+    /// `g^r2`
+    pub r2: EcPoint,
+}
+
+/// Partial signature of a signer
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct MusigPartialSignature {
+    /// Key of the signer
+    pub key: ProveDlog,
+    /// Partial response `s_i = r1_i + b * r2_i + e * a_i * x_i`
+    pub s: Wscalar,
+}
+
+/// Second round of signing a message with the aggregated key
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct MusigSession {
+    key_agg: MusigKeyAgg,
+    nonces: Vec<MusigPublicNonce>,
+    nonce_coefficient: Wscalar,
+    commitment: FirstDlogProverMessage,
+    challenge: Challenge,
+}
+
+impl MusigSession {
+    /// Create a session for signing the message with the public nonces of all the signers.
+    /// The challenge is computed the same way the prover does for a proposition consisting of
+    /// the aggregated key alone.
+    pub fn new(
+        key_agg: MusigKeyAgg,
+        nonces: Vec<(ProveDlog, MusigPublicNonce)>,
+        message: &[u8],
+    ) -> Result<Self, MusigError> {
+        if let Some((key, _)) = nonces.iter().find(|(k, _)| !key_agg.keys.contains(k)) {
+            return Err(MusigError::UnknownKey(key.clone()));
+        }
+        let nonces = key_agg
+            .keys
+            .iter()
+            .map(|key| {
+                nonces
+                    .iter()
+                    .find(|(k, _)| k == key)
+                    .map(|(_, n)| n.clone())
+                    .ok_or_else(|| MusigError::MissingNonce(key.clone()))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let (r1, r2) = nonces
+            .iter()
+            .fold((EcPoint::default(), EcPoint::default()), |(r1, r2), n| {
+                (r1 * &n.r1, r2 * &n.r2)
+            });
+        let nonce_coefficient = hash_to_scalar(
+            NONCE_COEFFICIENT_TAG,
+            &[
+                point_bytes(&key_agg.aggregated.h).as_slice(),
+                point_bytes(&r1).as_slice(),
+                point_bytes(&r2).as_slice(),
+                message,
+            ],
+        );
+        let r = r1 * &exponentiate(&r2, &nonce_coefficient);
+        let commitment = FirstDlogProverMessage::from(r);
+        let tree: UnprovenTree = UnprovenSchnorr {
+            proposition: key_agg.aggregated.clone(),
+            commitment_opt: Some(commitment.clone()),
+            randomness_opt: None,
+            challenge_opt: None,
+            simulated: false,
+            position: NodePosition::crypto_tree_prefix(),
+        }
+        .into();
+        let mut s = fiat_shamir_tree_to_bytes(&tree.into())
+            .map_err(|e| MusigError::FiatShamirTreeSerializationError(e.to_string()))?;
+        s.extend_from_slice(message);
+        let challenge: Challenge = fiat_shamir_hash_fn(s.as_slice()).into();
+        Ok(MusigSession {
+            key_agg,
+            nonces,
+            nonce_coefficient: nonce_coefficient.into(),
+            commitment,
+            challenge,
+        })
+    }
+
+    /// Aggregated key
+    pub fn aggregated_key(&self) -> &ProveDlog {
+        self.key_agg.aggregated_key()
+    }
+
+    /// Keys of the group
+    pub fn keys(&self) -> &[ProveDlog] {
+        self.key_agg.keys()
+    }
+
+    /// Aggregated commitment ("a" message) of the proof
+    pub fn commitment(&self) -> &FirstDlogProverMessage {
+        &self.commitment
+    }
+
+    /// Challenge of the proof
+    pub fn challenge(&self) -> &Challenge {
+        &self.challenge
+    }
+
+    /// Compute the partial signature with the signer's secret and the secret nonce whose public
+    /// part was used to create the session
+    pub fn partial_sign(
+        &self,
+        secret: &DlogProverInput,
+        nonce: MusigSecretNonce,
+    ) -> Result<MusigPartialSignature, MusigError> {
+        let key = secret.public_image();
+        let a = &self.key_agg.coefficients[self.key_agg.key_index(&key)?];
+        let e: Scalar = self.challenge.clone().into();
+        let b = self.nonce_coefficient.as_scalar_ref();
+        let s = nonce.r1.as_scalar_ref()
+            + &(b * nonce.r2.as_scalar_ref())
+            + &(e * a.as_scalar_ref() * secret.w.as_scalar_ref());
+        Ok(MusigPartialSignature { key, s: s.into() })
+    }
+
+    /// Check the partial signature against the signer's key and public nonce:
+    /// `g^s_i == R1_i * R2_i^b * X_i^(e * a_i)`
+    pub fn verify_partial_signature(
+        &self,
+        partial: &MusigPartialSignature,
+    ) -> Result<(), MusigError> {
+        let idx = self.key_agg.key_index(&partial.key)?;
+        let a = &self.key_agg.coefficients[idx];
+        let nonce = &self.nonces[idx];
+        let e: Scalar = self.challenge.clone().into();
+        let expected = nonce.r1.clone()
+            * &exponentiate(&nonce.r2, self.nonce_coefficient.as_scalar_ref())
+            * &exponentiate(&partial.key.h, &(e * a.as_scalar_ref()));
+        if exponentiate(&generator(), partial.s.as_scalar_ref()) == expected {
+            Ok(())
+        } else {
+            Err(MusigError::InvalidPartialSignature(partial.key.clone()))
+        }
+    }
+
+    /// Verify and combine the partial signatures of all the signers into the response for the
+    /// aggregated key
+    pub fn aggregate(
+        &self,
+        partials: &[MusigPartialSignature],
+    ) -> Result<SecondDlogProverMessage, MusigError> {
+        if let Some(p) = partials
+            .iter()
+            .find(|p| !self.key_agg.keys.contains(&p.key))
+        {
+            return Err(MusigError::UnknownKey(p.key.clone()));
+        }
+        let mut z = Scalar::ZERO;
+        for key in &self.key_agg.keys {
+            let partial = partials
+                .iter()
+                .find(|p| &p.key == key)
+                .ok_or_else(|| MusigError::MissingPartialSignature(key.clone()))?;
+            self.verify_partial_signature(partial)?;
+            z += partial.s.as_scalar_ref();
+        }
+        Ok(SecondDlogProverMessage { z: z.into() })
+    }
+
+    /// Combine the partial signatures into a signer for the aggregated key (see [`MusigSigner`])
+    pub fn into_signer(
+        self,
+        partials: &[MusigPartialSignature],
+    ) -> Result<MusigSigner, MusigError> {
+        let response = self.aggregate(partials)?;
+        Ok(MusigSigner {
+            key: self.key_agg.aggregated,
+            commitment: self.commitment,
+            challenge: self.challenge,
+            response,
+        })
+    }
+}
+
+/// [`ExternalSigner`] for the aggregated key with the combined response of all the signers.
+/// Proves the aggregated key only for the message of the session and only when the key is the
+/// whole proposition (otherwise the challenge differs).
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct MusigSigner {
+    key: ProveDlog,
+    commitment: FirstDlogProverMessage,
+    challenge: Challenge,
+    response: SecondDlogProverMessage,
+}
+
+impl ExternalSigner for MusigSigner {
+    fn public_images(&self) -> Vec<SigmaBoolean> {
+        vec![self.key.clone().into()]
+    }
+
+    fn commit(
+        &self,
+        image: &SigmaBoolean,
+        _position: &NodePosition,
+    ) -> Result<FirstProverMessage, ExternalSignerError> {
+        if image != &SigmaBoolean::from(self.key.clone()) {
+            return Err(ExternalSignerError::UnknownKey);
+        }
+        Ok(self.commitment.clone().into())
+    }
+
+    fn respond_dlog(
+        &self,
+        image: &ProveDlog,
+        _position: &NodePosition,
+        challenge: &Challenge,
+    ) -> Result<SecondDlogProverMessage, ExternalSignerError> {
+        if image != &self.key {
+            return Err(ExternalSignerError::UnknownKey);
+        }
+        if challenge != &self.challenge {
+            return Err(ExternalSignerError::DeviceError(
+                "challenge differs from the one of the MuSig session".to_string(),
+            ));
+        }
+        Ok(self.response.clone())
+    }
+
+    fn respond_dh_tuple(
+        &self,
+        _image: &ProveDhTuple,
+        _position: &NodePosition,
+        _challenge: &Challenge,
+    ) -> Result<SecondDhTupleProverMessage, ExternalSignerError> {
+        Err(ExternalSignerError::UnknownKey)
+    }
+}
+
+fn point_bytes(p: &EcPoint) -> Vec<u8> {
+    #[allow(clippy::unwrap_used)]
+    // EcPoint serialization can only on OOM
+    p.sigma_serialize_bytes().unwrap()
+}
+
+fn tagged_hash(tag: &[u8], parts: &[&[u8]]) -> Box<[u8; 32]> {
+    let mut data = tag.to_vec();
+    for part in parts {
+        // length prefix to keep the encoding unambiguous
+        data.extend_from_slice(&u32::try_from(part.len()).unwrap_or(u32::MAX).to_be_bytes());
+        data.extend_from_slice(part);
+    }
+    blake2b256_hash(&data)
+}
+
+fn hash_to_scalar(tag: &[u8], parts: &[&[u8]]) -> Scalar {
+    let hash = tagged_hash(tag, parts);
+    <Scalar as Reduce<U256>>::from_be_bytes_reduced(GenericArray::clone_from_slice(hash.as_slice()))
+}
+
+#[allow(clippy::unwrap_used)]
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sigma_protocol::prover::hint::HintsBag;
+    use crate::sigma_protocol::prover::ExternalSignerProver;
+    use crate::sigma_protocol::prover::Prover;
+    use crate::sigma_protocol::verifier::verify_signature;
+
+    fn sign(secrets: &[DlogProverInput], message: &[u8]) -> (ProveDlog, Vec<u8>) {
+        let key_agg =
+            MusigKeyAgg::new(secrets.iter().map(DlogProverInput::public_image).collect()).unwrap();
+        let (secret_nonces, public_nonces): (Vec<_>, Vec<_>) =
+            secrets.iter().map(|_| MusigSecretNonce::generate()).unzip();
+        let nonces = secrets
+            .iter()
+            .map(DlogProverInput::public_image)
+            .zip(public_nonces)
+            .collect();
+        let session = MusigSession::new(key_agg, nonces, message).unwrap();
+        let partials: Vec<MusigPartialSignature> = secrets
+            .iter()
+            .zip(secret_nonces)
+            .map(|(secret, nonce)| session.partial_sign(secret, nonce).unwrap())
+            .collect();
+        let key = session.aggregated_key().clone();
+        let signer: Box<dyn ExternalSigner> = Box::new(session.into_signer(&partials).unwrap());
+        let prover = ExternalSignerProver {
+            secrets: vec![],
+            external_signers: vec![signer],
+        };
+        let proof = prover
+            .generate_proof(key.clone().into(), message, &HintsBag::empty())
+            .unwrap();
+        (key, proof.into())
+    }
+
+    #[test]
+    fn aggregated_signature() {
+        let secrets: Vec<DlogProverInput> = (0..3).map(|_| DlogProverInput::random()).collect();
+        let message = vec![1u8; 100];
+        let (key, signature) = sign(&secrets, &message);
+        // single Schnorr proof: challenge and response
+        assert_eq!(signature.len(), 24 + 32);
+        assert!(verify_signature(key.clone().into(), &message, &signature).unwrap());
+        assert!(!verify_signature(key.into(), &[2u8; 100], &signature).unwrap());
+    }
+
+    #[test]
+    fn key_aggregation_is_order_independent() {
+        let keys: Vec<ProveDlog> = (0..3)
+            .map(|_| DlogProverInput::random().public_image())
+            .collect();
+        let mut reversed = keys.clone();
+        reversed.reverse();
+        assert_eq!(
+            MusigKeyAgg::new(keys.clone()).unwrap().aggregated_key(),
+            MusigKeyAgg::new(reversed).unwrap().aggregated_key()
+        );
+        let mut duplicated = keys.clone();
+        duplicated.push(keys[0].clone());
+        assert_eq!(
+            MusigKeyAgg::new(duplicated),
+            Err(MusigError::DuplicateKey(keys[0].clone()))
+        );
+    }
+
+    #[test]
+    fn invalid_partial_signature() {
+        let secrets: Vec<DlogProverInput> = (0..2).map(|_| DlogProverInput::random()).collect();
+        let keys: Vec<ProveDlog> = secrets.iter().map(DlogProverInput::public_image).collect();
+        let key_agg = MusigKeyAgg::new(keys.clone()).unwrap();
+        let (nonce0, public0) = MusigSecretNonce::generate();
+        let (_, public1) = MusigSecretNonce::generate();
+        let session = MusigSession::new(
+            key_agg,
+            vec![(keys[0].clone(), public0), (keys[1].clone(), public1)],
+            &[0u8; 32],
+        )
+        .unwrap();
+        let valid = session.partial_sign(&secrets[0], nonce0).unwrap();
+        // signed with a nonce that was not shared
+        let (other_nonce, _) = MusigSecretNonce::generate();
+        let invalid = session.partial_sign(&secrets[1], other_nonce).unwrap();
+        assert_eq!(session.verify_partial_signature(&valid), Ok(()));
+        assert_eq!(
+            session.aggregate(&[valid, invalid]),
+            Err(MusigError::InvalidPartialSignature(keys[1].clone()))
+        );
+    }
+}
@@ -15,17 +15,21 @@ extern crate derive_more;
 use derive_more::From;
 use num_bigint::BigUint;
 use num_traits::ToPrimitive;
+use subtle::ConstantTimeEq;
+use zeroize::Zeroize;
+use zeroize::Zeroizing;
 
 use super::crypto_utils;
 use super::wscalar::Wscalar;
 
 /// Secret key of discrete logarithm signature protocol
-#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
-#[cfg_attr(feature = "json", serde(transparent))]
-#[derive(PartialEq, Eq, Clone, derive_more::From)]
+/// The secret is zeroed on drop, compared in constant time and is not `Clone` (see
+/// [`DlogProverInput::clone_secret`]). It is not serializable either, the only way to export it
+/// is [`DlogProverInput::expose_secret_bytes`] (and [`DlogProverInput::from_bytes`] to import)
+#[derive(derive_more::From)]
 pub struct DlogProverInput {
     /// secret key value
-    pub w: Wscalar,
+    pub(crate) w: Wscalar,
 }
 
 impl std::fmt::Debug for DlogProverInput {
@@ -35,6 +39,20 @@ impl std::fmt::Debug for DlogProverInput {
     }
 }
 
+impl Drop for DlogProverInput {
+    fn drop(&mut self) {
+        self.w.zeroize()
+    }
+}
+
+impl PartialEq for DlogProverInput {
+    fn eq(&self, other: &Self) -> bool {
+        self.w.as_scalar_ref().ct_eq(other.w.as_scalar_ref()).into()
+    }
+}
+
+impl Eq for DlogProverInput {}
+
 impl DlogProverInput {
     /// Scalar(secret key) size in bytes
     pub const SIZE_BYTES: usize = 32;
@@ -80,9 +98,26 @@ impl DlogProverInput {
         Self::from_bytes(&bytes)
     }
 
-    /// byte representation of the underlying scalar
-    pub fn to_bytes(&self) -> [u8; DlogProverInput::SIZE_BYTES] {
-        self.w.as_scalar_ref().to_bytes().into()
+    /// Byte representation of the underlying scalar.
+    /// Exports the secret, the returned copy is zeroed on drop
+    pub fn expose_secret_bytes(&self) -> Zeroizing<[u8; DlogProverInput::SIZE_BYTES]> {
+        Zeroizing::new(self.w.as_scalar_ref().to_bytes().into())
+    }
+
+    /// Underlying scalar.
+    /// Exports the secret, the returned copy is zeroed on drop
+    pub fn expose_secret_scalar(&self) -> Zeroizing<Wscalar> {
+        Zeroizing::new(self.w.clone())
+    }
+
+    /// Copy of the secret (`Clone` is not implemented to make the copies of the secret explicit)
+    pub fn clone_secret(&self) -> DlogProverInput {
+        DlogProverInput { w: self.w.clone() }
+    }
+
+    /// Sum of the secrets modulo the group order (e.g. BIP-32 child key derivation)
+    pub fn add(&self, other: &DlogProverInput) -> DlogProverInput {
+        Wscalar::from(self.w.as_scalar_ref() + other.w.as_scalar_ref()).into()
     }
 
     /// public key of discrete logarithm signature protocol
@@ -105,14 +140,14 @@ impl DlogProverInput {
 /// Used in a proof that of equality of discrete logarithms (i.e., a proof of a Diffie-Hellman tuple):
 /// given group elements g, h, u, v, the proof convinces a verifier that the prover knows `w` such
 /// that `u = g^w` and `v = h^w`, without revealing `w`
-#[derive(PartialEq, Eq, Clone)]
-#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
+/// The secret is zeroed on drop, compared in constant time and is not `Clone` (see
+/// [`DhTupleProverInput::clone_secret`]). It is not serializable either, the only way to export
+/// it is [`DhTupleProverInput::expose_secret_bytes`] (and [`DhTupleProverInput::from_bytes`] to
+/// import)
 pub struct DhTupleProverInput {
     /// Diffie-Hellman tuple's secret
-    #[cfg_attr(feature = "json", serde(rename = "secret"))]
-    pub w: Wscalar,
+    pub(crate) w: Wscalar,
     /// Diffie-Hellman tuple
-    #[cfg_attr(feature = "json", serde(flatten))]
     pub common_input: ProveDhTuple,
 }
 
@@ -123,10 +158,31 @@ impl std::fmt::Debug for DhTupleProverInput {
     }
 }
 
+impl Drop for DhTupleProverInput {
+    fn drop(&mut self) {
+        self.w.zeroize()
+    }
+}
+
+impl PartialEq for DhTupleProverInput {
+    fn eq(&self, other: &Self) -> bool {
+        // the tuple is public
+        bool::from(self.w.as_scalar_ref().ct_eq(other.w.as_scalar_ref()))
+            && self.common_input == other.common_input
+    }
+}
+
+impl Eq for DhTupleProverInput {}
+
 impl DhTupleProverInput {
     /// Size in bytes: 32(secret)+33(g)+33(h)+33(u)+33(v)=164 bytes
     pub const SIZE_BYTES: usize = DlogProverInput::SIZE_BYTES + EcPoint::GROUP_SIZE * 4;
 
+    /// Create from the secret and the Diffie-Hellman tuple (`u = g^w`, `v = h^w`)
+    pub fn new(w: Wscalar, common_input: ProveDhTuple) -> DhTupleProverInput {
+        DhTupleProverInput { w, common_input }
+    }
+
     /// Create random secret and Diffie-Hellman tuple
     #[allow(clippy::many_single_char_names)]
     pub fn random() -> DhTupleProverInput {
@@ -151,16 +207,38 @@ impl DhTupleProverInput {
         &self.common_input
     }
 
-    /// 32(secret)+33(g)+33(h)+33(u)+33(v)=164 bytes
+    /// 32(secret)+33(g)+33(h)+33(u)+33(v)=164 bytes.
+    /// Exports the secret, the returned copy is zeroed on drop
     #[allow(clippy::unwrap_used)]
-    pub fn to_bytes(&self) -> [u8; DhTupleProverInput::SIZE_BYTES] {
-        let mut bytes = Vec::with_capacity(DhTupleProverInput::SIZE_BYTES);
-        bytes.extend_from_slice(&self.w.as_scalar_ref().to_bytes());
-        bytes.extend_from_slice(&self.common_input.g.sigma_serialize_bytes().unwrap());
-        bytes.extend_from_slice(&self.common_input.h.sigma_serialize_bytes().unwrap());
-        bytes.extend_from_slice(&self.common_input.u.sigma_serialize_bytes().unwrap());
-        bytes.extend_from_slice(&self.common_input.v.sigma_serialize_bytes().unwrap());
-        bytes.try_into().unwrap()
+    pub fn expose_secret_bytes(&self) -> Zeroizing<[u8; DhTupleProverInput::SIZE_BYTES]> {
+        let mut bytes = Zeroizing::new([0u8; DhTupleProverInput::SIZE_BYTES]);
+        let points = [
+            &self.common_input.g,
+            &self.common_input.h,
+            &self.common_input.u,
+            &self.common_input.v,
+        ];
+        bytes[..DlogProverInput::SIZE_BYTES].copy_from_slice(&self.w.as_scalar_ref().to_bytes());
+        for (i, point) in points.iter().enumerate() {
+            let offset = DlogProverInput::SIZE_BYTES + i * EcPoint::GROUP_SIZE;
+            bytes[offset..offset + EcPoint::GROUP_SIZE]
+                .copy_from_slice(&point.sigma_serialize_bytes().unwrap());
+        }
+        bytes
+    }
+
+    /// Underlying scalar of the secret.
+    /// Exports the secret, the returned copy is zeroed on drop
+    pub fn expose_secret_scalar(&self) -> Zeroizing<Wscalar> {
+        Zeroizing::new(self.w.clone())
+    }
+
+    /// Copy of the secret (`Clone` is not implemented to make the copies of the secret explicit)
+    pub fn clone_secret(&self) -> DhTupleProverInput {
+        DhTupleProverInput {
+            w: self.w.clone(),
+            common_input: self.common_input.clone(),
+        }
     }
 
     /// Parse from bytes (32(secret)+33(g)+33(h)+33(u)+33(v)=164 bytes)
@@ -217,7 +295,7 @@ impl DhTupleProverInput {
 }
 
 /// Private inputs (secrets)
-#[derive(PartialEq, Eq, Debug, Clone, From)]
+#[derive(PartialEq, Eq, Debug, From)]
 pub enum PrivateInput {
     /// Discrete logarithm prover input
     DlogProverInput(DlogProverInput),
@@ -233,6 +311,14 @@ impl PrivateInput {
             PrivateInput::DhTupleProverInput(dht) => dht.public_image().clone().into(),
         }
     }
+
+    /// Copy of the secret (`Clone` is not implemented to make the copies of the secret explicit)
+    pub fn clone_secret(&self) -> PrivateInput {
+        match self {
+            PrivateInput::DlogProverInput(dl) => dl.clone_secret().into(),
+            PrivateInput::DhTupleProverInput(dht) => dht.clone_secret().into(),
+        }
+    }
 }
 
 #[cfg(feature = "arbitrary")]
@@ -240,20 +326,19 @@ impl PrivateInput {
 pub(crate) mod arbitrary {
 
     use super::*;
+    use ergo_chain_types::ec_point::exponentiate;
+    use ergo_chain_types::ec_point::generator;
     use proptest::prelude::*;
 
     impl Arbitrary for DlogProverInput {
         type Parameters = ();
         type Strategy = BoxedStrategy<Self>;
         fn arbitrary_with(_args: Self::Parameters) -> Self::Strategy {
-            prop_oneof![
-                Just(DlogProverInput::random()),
-                Just(DlogProverInput::random()),
-                Just(DlogProverInput::random()),
-                Just(DlogProverInput::random()),
-                Just(DlogProverInput::random()),
-            ]
-            .boxed()
+            any::<[u8; DlogProverInput::SIZE_BYTES]>()
+                .prop_filter_map("not a valid scalar", |bytes| {
+                    DlogProverInput::from_bytes(&bytes)
+                })
+                .boxed()
         }
     }
 
@@ -261,14 +346,18 @@ pub(crate) mod arbitrary {
         type Parameters = ();
         type Strategy = BoxedStrategy<Self>;
         fn arbitrary_with(_args: Self::Parameters) -> Self::Strategy {
-            prop_oneof![
-                Just(DhTupleProverInput::random()),
-                Just(DhTupleProverInput::random()),
-                Just(DhTupleProverInput::random()),
-                Just(DhTupleProverInput::random()),
-                Just(DhTupleProverInput::random()),
-            ]
-            .boxed()
+            (any::<DlogProverInput>(), any::<DlogProverInput>())
+                .prop_map(|(secret, h_exp)| {
+                    let g = generator();
+                    let h = exponentiate(&g, h_exp.w.as_scalar_ref());
+                    let u = exponentiate(&g, secret.w.as_scalar_ref());
+                    let v = exponentiate(&h, secret.w.as_scalar_ref());
+                    DhTupleProverInput {
+                        w: secret.w.clone(),
+                        common_input: ProveDhTuple::new(g, h, u, v),
+                    }
+                })
+                .boxed()
         }
     }
 
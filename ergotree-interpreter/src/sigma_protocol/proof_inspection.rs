@@ -0,0 +1,301 @@
+//! Inspection of proofs: the unchecked tree parsed from the proof bytes along with the commitments
+//! recomputed by the verifier. Mainly for debugging proofs that fail to verify (e.g. multi-signatures
+//! where one of the co-signers used wrong hints).
+
+use ergotree_ir::sigma_protocol::sigma_boolean::ProveDhTuple;
+use ergotree_ir::sigma_protocol::sigma_boolean::ProveDlog;
+use ergotree_ir::sigma_protocol::sigma_boolean::SigmaBoolean;
+
+use super::dht_protocol;
+use super::dht_protocol::FirstDhTupleProverMessage;
+use super::dlog_protocol;
+use super::dlog_protocol::FirstDlogProverMessage;
+use super::fiat_shamir::fiat_shamir_hash_fn;
+use super::fiat_shamir::fiat_shamir_tree_to_bytes;
+use super::sig_serializer::parse_sig_compute_challenges;
+use super::unchecked_tree::UncheckedConjecture;
+use super::unchecked_tree::UncheckedLeaf;
+use super::unchecked_tree::UncheckedTree;
+use super::unproven_tree::NodePosition;
+use super::verifier::compute_commitments;
+use super::verifier::VerifierError;
+use super::wscalar::Wscalar;
+use super::Challenge;
+
+/// Proof parsed for the given proposition and message
+#[derive(PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "json", derive(serde::Serialize))]
+pub struct ProofInspection {
+    /// Challenge at the root of the tree, as read from the proof
+    #[cfg_attr(feature = "json", serde(rename = "rootChallenge"))]
+    pub root_challenge: Challenge,
+    /// Fiat-Shamir hash of the tree with the recomputed commitments and the message, i.e. the
+    /// challenge expected at the root of the tree
+    #[cfg_attr(feature = "json", serde(rename = "expectedRootChallenge"))]
+    pub expected_root_challenge: Challenge,
+    /// `true` if the proof is valid, i.e. the root challenge is the expected one
+    #[cfg_attr(feature = "json", serde(rename = "valid"))]
+    pub valid: bool,
+    /// Nodes of the proof
+    #[cfg_attr(feature = "json", serde(rename = "tree"))]
+    pub tree: InspectedNode,
+}
+
+/// Node of the inspected proof tree
+#[derive(PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "json", derive(serde::Serialize))]
+#[cfg_attr(feature = "json", serde(tag = "type"))]
+pub enum InspectedNode {
+    /// Proof of knowledge of discrete logarithm
+    #[cfg_attr(feature = "json", serde(rename = "proveDlog"))]
+    Schnorr {
+        /// Position in the tree
+        #[cfg_attr(feature = "json", serde(rename = "position"))]
+        position: NodePosition,
+        /// Proposition
+        #[cfg_attr(feature = "json", serde(rename = "proposition"))]
+        proposition: ProveDlog,
+        /// Challenge (`e`)
+        #[cfg_attr(feature = "json", serde(rename = "challenge"))]
+        challenge: Challenge,
+        /// Response (`z`), as read from the proof
+        #[cfg_attr(feature = "json", serde(rename = "response"))]
+        response: Wscalar,
+        /// Commitment (`a`) recomputed from the challenge and the response
+        #[cfg_attr(feature = "json", serde(rename = "commitment"))]
+        commitment: FirstDlogProverMessage,
+    },
+    /// Proof of Diffie-Hellman tuple
+    #[cfg_attr(feature = "json", serde(rename = "proveDHTuple"))]
+    DhTuple {
+        /// Position in the tree
+        #[cfg_attr(feature = "json", serde(rename = "position"))]
+        position: NodePosition,
+        /// Proposition
+        #[cfg_attr(feature = "json", serde(rename = "proposition"))]
+        proposition: ProveDhTuple,
+        /// Challenge (`e`)
+        #[cfg_attr(feature = "json", serde(rename = "challenge"))]
+        challenge: Challenge,
+        /// Response (`z`), as read from the proof
+        #[cfg_attr(feature = "json", serde(rename = "response"))]
+        response: Wscalar,
+        /// Commitment (`a`, `b`) recomputed from the challenge and the response
+        #[cfg_attr(feature = "json", serde(rename = "commitment"))]
+        commitment: FirstDhTupleProverMessage,
+    },
+    /// AND conjecture
+    #[cfg_attr(feature = "json", serde(rename = "and"))]
+    And {
+        /// Position in the tree
+        #[cfg_attr(feature = "json", serde(rename = "position"))]
+        position: NodePosition,
+        /// Challenge
+        #[cfg_attr(feature = "json", serde(rename = "challenge"))]
+        challenge: Challenge,
+        /// Children
+        #[cfg_attr(feature = "json", serde(rename = "children"))]
+        children: Vec<InspectedNode>,
+    },
+    /// OR conjecture
+    #[cfg_attr(feature = "json", serde(rename = "or"))]
+    Or {
+        /// Position in the tree
+        #[cfg_attr(feature = "json", serde(rename = "position"))]
+        position: NodePosition,
+        /// Challenge
+        #[cfg_attr(feature = "json", serde(rename = "challenge"))]
+        challenge: Challenge,
+        /// Children
+        #[cfg_attr(feature = "json", serde(rename = "children"))]
+        children: Vec<InspectedNode>,
+    },
+    /// THRESHOLD(k) conjecture
+    #[cfg_attr(feature = "json", serde(rename = "threshold"))]
+    Threshold {
+        /// Position in the tree
+        #[cfg_attr(feature = "json", serde(rename = "position"))]
+        position: NodePosition,
+        /// Number of children that have to be proven
+        #[cfg_attr(feature = "json", serde(rename = "k"))]
+        k: u8,
+        /// Challenge
+        #[cfg_attr(feature = "json", serde(rename = "challenge"))]
+        challenge: Challenge,
+        /// Children
+        #[cfg_attr(feature = "json", serde(rename = "children"))]
+        children: Vec<InspectedNode>,
+    },
+}
+
+impl InspectedNode {
+    /// Position in the tree
+    pub fn position(&self) -> &NodePosition {
+        match self {
+            InspectedNode::Schnorr { position, .. }
+            | InspectedNode::DhTuple { position, .. }
+            | InspectedNode::And { position, .. }
+            | InspectedNode::Or { position, .. }
+            | InspectedNode::Threshold { position, .. } => position,
+        }
+    }
+
+    /// Challenge
+    pub fn challenge(&self) -> &Challenge {
+        match self {
+            InspectedNode::Schnorr { challenge, .. }
+            | InspectedNode::DhTuple { challenge, .. }
+            | InspectedNode::And { challenge, .. }
+            | InspectedNode::Or { challenge, .. }
+            | InspectedNode::Threshold { challenge, .. } => challenge,
+        }
+    }
+
+    fn from_unchecked(tree: &UncheckedTree, position: NodePosition) -> Self {
+        match tree {
+            UncheckedTree::UncheckedLeaf(UncheckedLeaf::UncheckedSchnorr(us)) => {
+                let a = dlog_protocol::interactive_prover::compute_commitment(
+                    &us.proposition,
+                    &us.challenge,
+                    &us.second_message,
+                );
+                InspectedNode::Schnorr {
+                    position,
+                    proposition: us.proposition.clone(),
+                    challenge: us.challenge.clone(),
+                    response: us.second_message.z.clone(),
+                    commitment: a.into(),
+                }
+            }
+            UncheckedTree::UncheckedLeaf(UncheckedLeaf::UncheckedDhTuple(udht)) => {
+                let (a, b) = dht_protocol::interactive_prover::compute_commitment(
+                    &udht.proposition,
+                    &udht.challenge,
+                    &udht.second_message,
+                );
+                InspectedNode::DhTuple {
+                    position,
+                    proposition: udht.proposition.clone(),
+                    challenge: udht.challenge.clone(),
+                    response: udht.second_message.z.clone(),
+                    commitment: FirstDhTupleProverMessage::new(a, b),
+                }
+            }
+            UncheckedTree::UncheckedConjecture(uc) => {
+                let children: Vec<InspectedNode> = uc
+                    .clone()
+                    .children_ust()
+                    .iter()
+                    .enumerate()
+                    .map(|(idx, child)| InspectedNode::from_unchecked(child, position.child(idx)))
+                    .collect();
+                match uc {
+                    UncheckedConjecture::CandUnchecked { challenge, .. } => InspectedNode::And {
+                        position,
+                        challenge: challenge.clone(),
+                        children,
+                    },
+                    UncheckedConjecture::CorUnchecked { challenge, .. } => InspectedNode::Or {
+                        position,
+                        challenge: challenge.clone(),
+                        children,
+                    },
+                    UncheckedConjecture::CthresholdUnchecked { challenge, k, .. } => {
+                        InspectedNode::Threshold {
+                            position,
+                            k: *k,
+                            challenge: challenge.clone(),
+                            children,
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Parse the proof of `sigma_tree` for `message` (Verifier Steps 1-3) and recompute the
+/// commitments and the expected root challenge (Verifier Steps 4-5) without rejecting an
+/// invalid proof, so that every node can be inspected.
+pub fn inspect_proof(
+    sigma_tree: &SigmaBoolean,
+    message: &[u8],
+    proof: &[u8],
+) -> Result<ProofInspection, VerifierError> {
+    let unchecked_tree = parse_sig_compute_challenges(sigma_tree, proof.to_vec())?;
+    let tree = InspectedNode::from_unchecked(&unchecked_tree, NodePosition::crypto_tree_prefix());
+    let root_challenge = unchecked_tree.challenge();
+    let with_commitments = compute_commitments(unchecked_tree);
+    let mut s = fiat_shamir_tree_to_bytes(&with_commitments.into())?;
+    s.extend_from_slice(message);
+    let expected_root_challenge: Challenge = fiat_shamir_hash_fn(s.as_slice()).into();
+    Ok(ProofInspection {
+        valid: root_challenge == expected_root_challenge,
+        root_challenge,
+        expected_root_challenge,
+        tree,
+    })
+}
+
+#[allow(clippy::unwrap_used)]
+#[allow(clippy::panic)]
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sigma_protocol::private_input::DlogProverInput;
+    use crate::sigma_protocol::prover::hint::HintsBag;
+    use crate::sigma_protocol::prover::Prover;
+    use crate::sigma_protocol::prover::TestProver;
+    use ergotree_ir::sigma_protocol::sigma_boolean::cor::Cor;
+    use std::convert::TryInto;
+
+    #[test]
+    fn inspect_or_proof() {
+        let secret = DlogProverInput::random();
+        let other = DlogProverInput::random();
+        let tree = Cor::normalized(
+            vec![secret.public_image().into(), other.public_image().into()]
+                .try_into()
+                .unwrap(),
+        );
+        let prover = TestProver {
+            secrets: vec![secret.clone_secret().into()],
+        };
+        let message = b"message";
+        let proof: Vec<u8> = prover
+            .generate_proof(tree.clone(), message, &HintsBag::empty())
+            .unwrap()
+            .into();
+
+        let inspection = inspect_proof(&tree, message, &proof).unwrap();
+        assert!(inspection.valid);
+        assert_eq!(inspection.tree.challenge(), &inspection.root_challenge);
+        match &inspection.tree {
+            InspectedNode::Or { children, .. } => {
+                assert_eq!(children.len(), 2);
+                assert_eq!(
+                    children[1].position(),
+                    &NodePosition::crypto_tree_prefix().child(1)
+                );
+                assert!(matches!(
+                    &children[0],
+                    InspectedNode::Schnorr { proposition, .. } if *proposition == secret.public_image()
+                ));
+            }
+            _ => panic!("expected OR, got {:?}", inspection.tree),
+        }
+
+        let wrong_message = inspect_proof(&tree, b"other message", &proof).unwrap();
+        assert!(!wrong_message.valid);
+        assert_eq!(wrong_message.tree, inspection.tree);
+
+        #[cfg(feature = "json")]
+        {
+            let json = serde_json::to_value(&inspection).unwrap();
+            assert_eq!(json["valid"], true);
+            assert_eq!(json["tree"]["type"], "or");
+            assert_eq!(json["tree"]["children"][0]["type"], "proveDlog");
+            assert_eq!(json["tree"]["children"][1]["position"], "0-1");
+        }
+    }
+}
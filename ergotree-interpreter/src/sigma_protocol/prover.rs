@@ -1,12 +1,13 @@
 //! Interpreter with enhanced functionality to prove statements.
 
 mod context_extension;
+mod nonce;
 mod prover_result;
 
+pub mod external_signer;
 pub mod hint;
 
 use crate::eval::reduce_to_crypto;
-use crate::sigma_protocol::crypto_utils::secure_random_bytes;
 use crate::sigma_protocol::fiat_shamir::fiat_shamir_hash_fn;
 use crate::sigma_protocol::fiat_shamir::fiat_shamir_tree_to_bytes;
 use crate::sigma_protocol::gf2_192::gf2_192poly_from_byte_array;
@@ -26,16 +27,21 @@ use gf2_192::gf2_192poly::Gf2_192Poly;
 use gf2_192::gf2_192poly::Gf2_192PolyError;
 use gf2_192::Gf2_192Error;
 use std::convert::TryInto;
-use std::rc::Rc;
+use std::sync::Arc;
 
 pub use context_extension::*;
 use ergotree_ir::ergo_tree::ErgoTree;
 use ergotree_ir::ergo_tree::ErgoTreeError;
 use ergotree_ir::sigma_protocol::sigma_boolean::SigmaConjecture;
 use ergotree_ir::sigma_protocol::sigma_boolean::SigmaProofOfKnowledgeTree;
+pub use nonce::NonceGeneration;
 pub use prover_result::*;
 
+use self::external_signer::ExternalSigner;
+use self::external_signer::ExternalSignerError;
 use self::hint::HintsBag;
+use self::nonce::NoncePurpose;
+use self::nonce::NonceSource;
 
 use super::dlog_protocol;
 use super::fiat_shamir::FiatShamirTreeSerializationError;
@@ -95,6 +101,9 @@ pub enum ProverError {
     /// Error while tree serialization for Fiat-Shamir hash
     #[error("Fiat-Shamir tree serialization error: {0}")]
     FiatShamirTreeSerializationError(FiatShamirTreeSerializationError),
+    /// External signer failed to provide a commitment or a response
+    #[error("External signer error: {0}")]
+    ExternalSignerError(ExternalSignerError),
 }
 
 impl From<ErgoTreeError> for ProverError {
@@ -109,6 +118,12 @@ impl From<FiatShamirTreeSerializationError> for ProverError {
     }
 }
 
+impl From<ExternalSignerError> for ProverError {
+    fn from(e: ExternalSignerError) -> Self {
+        ProverError::ExternalSignerError(e)
+    }
+}
+
 impl From<Gf2_192Error> for ProverError {
     fn from(e: Gf2_192Error) -> Self {
         ProverError::Gf2_192Error(e)
@@ -129,6 +144,16 @@ pub trait Prover {
     /// Add an extra secret to the prover
     fn append_secret(&mut self, input: PrivateInput);
 
+    /// Signers holding secrets outside of the prover
+    fn external_signers(&self) -> &[Box<dyn ExternalSigner>] {
+        &[]
+    }
+
+    /// How the randomness of the proofs is generated
+    fn nonce_generation(&self) -> NonceGeneration {
+        NonceGeneration::Random
+    }
+
     /// The comments in this section are taken from the algorithm for the
     /// Sigma-protocol prover as described in the ErgoScript white-paper
     /// <https://ergoplatform.org/docs/ErgoScript.pdf>, Appendix A
@@ -138,7 +163,7 @@ pub trait Prover {
         &self,
         tree: &ErgoTree,
         env: &Env,
-        ctx: Rc<Context>,
+        ctx: Arc<Context>,
         message: &[u8],
         hints_bag: &HintsBag,
     ) -> Result<ProverResult, ProverError> {
@@ -164,8 +189,9 @@ pub trait Prover {
             SigmaBoolean::TrivialProp(true) => Ok(None),
             SigmaBoolean::TrivialProp(false) => Err(ProverError::ReducedToFalse),
             sb => {
+                let nonces = NonceSource::new(self, &sb, message, hints_bag)?;
                 let tree = convert_to_unproven(sb)?;
-                let unchecked_tree = prove_to_unchecked(self, tree, message, hints_bag)?;
+                let unchecked_tree = prove_to_unchecked(self, tree, message, hints_bag, &nonces)?;
                 Ok(Some(unchecked_tree))
             }
         }?;
@@ -190,6 +216,7 @@ fn prove_to_unchecked<P: Prover + ?Sized>(
     unproven_tree: UnprovenTree,
     message: &[u8],
     hints_bag: &HintsBag,
+    nonces: &NonceSource,
 ) -> Result<UncheckedTree, ProverError> {
     // Prover Step 1: Mark as real everything the prover can prove
     let step1 = mark_real(prover, unproven_tree, hints_bag)?;
@@ -209,7 +236,7 @@ fn prove_to_unchecked<P: Prover + ?Sized>(
 
     // Prover Steps 4, 5, and 6 together: find challenges for simulated nodes; simulate simulated leaves;
     // compute commitments for real leaves
-    let step6 = simulate_and_commit(step3, hints_bag)?;
+    let step6 = simulate_and_commit(prover, step3, hints_bag, nonces)?;
     // dbg!(&step6);
 
     // Prover Steps 7: convert the relevant information in the tree (namely, tree structure, node types,
@@ -249,13 +276,15 @@ fn mark_real<P: Prover + ?Sized>(
             ProofTree::UnprovenTree(unp) => match unp {
                 UnprovenTree::UnprovenLeaf(unp_leaf) => {
                     // If the node is a leaf, mark it "real'' if either the witness for it is
-                    // available or a hint shows the secret is known to an external participant in multi-signing;
+                    // available (to the prover or to an external signer) or a hint shows the secret
+                    // is known to an external participant in multi-signing;
                     // else mark it "simulated"
                     let secret_known = hints_bag.real_images().contains(&unp_leaf.proposition())
                         || prover
                             .secrets()
                             .iter()
-                            .any(|s| s.public_image() == unp_leaf.proposition());
+                            .any(|s| s.public_image() == unp_leaf.proposition())
+                        || external_signer_for(prover, &unp_leaf.proposition()).is_some();
                     Some(unp_leaf.clone().with_simulated(!secret_known).into())
                 }
                 UnprovenTree::UnprovenConjecture(unp_conj) => match unp_conj {
@@ -447,6 +476,7 @@ fn polish_simulated<P: Prover + ?Sized>(
 fn step4_real_conj(
     uc: UnprovenConjecture,
     hints_bag: &HintsBag,
+    nonces: &NonceSource,
 ) -> Result<Option<ProofTree>, ProverError> {
     assert!(uc.is_real());
     match uc {
@@ -466,7 +496,7 @@ fn step4_real_conj(
                             .into_iter()
                             .find(|p| p.position() == c.position())
                             .map(|p| p.challenge().clone())
-                            .unwrap_or_else(Challenge::secure_random);
+                            .unwrap_or_else(|| nonces.challenge(c.position()));
                         c.with_challenge(new_challenge)
                     }
                 })
@@ -504,7 +534,10 @@ fn step4_simulated_and_conj(cand: CandUnproven) -> Result<Option<ProofTree>, Pro
     }
 }
 
-fn step4_simulated_or_conj(cor: CorUnproven) -> Result<Option<ProofTree>, ProverError> {
+fn step4_simulated_or_conj(
+    cor: CorUnproven,
+    nonces: &NonceSource,
+) -> Result<Option<ProofTree>, ProverError> {
     // If the node is OR, then each of its children except one gets a fresh uniformly random
     // challenge in {0,1}^t. The remaining child gets a challenge computed as an XOR of the challenges of all
     // the other children and e_0.
@@ -515,7 +548,10 @@ fn step4_simulated_or_conj(cor: CorUnproven) -> Result<Option<ProofTree>, Prover
             .clone()
             .into_iter()
             .skip(1)
-            .map(|it| it.with_challenge(Challenge::secure_random()))
+            .map(|it| {
+                let challenge = nonces.challenge(it.position());
+                it.with_challenge(challenge)
+            })
             .collect();
         let mut xored_challenge = challenge;
         for it in &tail {
@@ -551,6 +587,7 @@ fn step4_simulated_or_conj(cor: CorUnproven) -> Result<Option<ProofTree>, Prover
 
 fn step4_simulated_threshold_conj(
     ct: CthresholdUnproven,
+    nonces: &NonceSource,
 ) -> Result<Option<ProofTree>, ProverError> {
     // The faster algorithm is as follows. Pick n-k fresh uniformly random values
     // q_1, ..., q_{n-k} from {0,1}^t and let q_0=e_0.
@@ -563,7 +600,11 @@ fn step4_simulated_threshold_conj(
         let n = ct.children.len();
         let q = gf2_192poly_from_byte_array(
             challenge,
-            secure_random_bytes(SOUNDNESS_BYTES * (n - ct.k as usize)),
+            nonces.bytes(
+                &ct.position,
+                NoncePurpose::SimulatedPolynomial,
+                SOUNDNESS_BYTES * (n - ct.k as usize),
+            ),
         )?;
         let new_children = unproven_children
             .enumerated()
@@ -585,9 +626,11 @@ fn step4_simulated_threshold_conj(
     }
 }
 
-fn step5_schnorr(
+fn step5_schnorr<P: Prover + ?Sized>(
+    prover: &P,
     us: UnprovenSchnorr,
     hints_bag: &HintsBag,
+    nonces: &NonceSource,
 ) -> Result<Option<ProofTree>, ProverError> {
     // Steps 5 & 6: first try pulling out commitment from the hints bag. If it exists proceed with it,
     // otherwise, compute the commitment (if the node is real) or simulate it (if the node is simulated)
@@ -616,8 +659,11 @@ fn step5_schnorr(
             if us.simulated {
                 // Step 5 (simulated leaf -- complete the simulation)
                 if let Some(challenge) = us.challenge_opt.clone() {
-                    let (fm, sm) =
-                        dlog_protocol::interactive_prover::simulate(&us.proposition, &challenge);
+                    let (fm, sm) = dlog_protocol::interactive_prover::simulate(
+                        &us.proposition,
+                        &challenge,
+                        nonces.scalar(&us.position, NoncePurpose::SimulatedResponse),
+                    );
                     Ok(ProofTree::UncheckedTree(
                         UncheckedSchnorr {
                             proposition: us.proposition.clone(),
@@ -630,9 +676,31 @@ fn step5_schnorr(
                 } else {
                     Err(ProverError::SimulatedLeafWithoutChallenge)
                 }
+            } else if let Some(signer) = external_signer_for(prover, &us.proposition.clone().into())
+            {
+                // Step 6 (real leaf -- the commitment a is computed by the external signer,
+                // the randomness is kept by the signer)
+                let commitment = signer
+                    .commit(&us.proposition.clone().into(), &us.position)?
+                    .try_into()
+                    .map_err(|_| {
+                        ProverError::Unexpected(
+                            "step5_schnorr: external signer returned non-DLOG commitment",
+                        )
+                    })?;
+                Ok(ProofTree::UnprovenTree(
+                    UnprovenSchnorr {
+                        commitment_opt: Some(commitment),
+                        ..us.clone()
+                    }
+                    .into(),
+                ))
             } else {
                 // Step 6 (real leaf -- compute the commitment a)
-                let (r, commitment) = dlog_protocol::interactive_prover::first_message();
+                let (r, commitment) =
+                    dlog_protocol::interactive_prover::first_message_with_randomness(
+                        nonces.scalar(&us.position, NoncePurpose::Commitment),
+                    );
                 Ok(ProofTree::UnprovenTree(
                     UnprovenSchnorr {
                         commitment_opt: Some(commitment),
@@ -647,9 +715,11 @@ fn step5_schnorr(
     Ok(Some(res))
 }
 
-fn step5_diffie_hellman_tuple(
+fn step5_diffie_hellman_tuple<P: Prover + ?Sized>(
+    prover: &P,
     dhu: UnprovenDhTuple,
     hints_bag: &HintsBag,
+    nonces: &NonceSource,
 ) -> Result<Option<ProofTree>, ProverError> {
     //Steps 5 & 6: pull out commitment from the hints bag, otherwise, compute the commitment(if the node is real),
     // or simulate it (if the node is simulated)
@@ -679,6 +749,7 @@ fn step5_diffie_hellman_tuple(
                     let (fm, sm) = dht_protocol::interactive_prover::simulate(
                         &dhu.proposition,
                         &dhu_challenge,
+                        nonces.scalar(&dhu.position, NoncePurpose::SimulatedResponse),
                     );
                     Ok(UncheckedDhTuple {
                         proposition: dhu.proposition.clone(),
@@ -690,10 +761,19 @@ fn step5_diffie_hellman_tuple(
                 } else {
                     Err(ProverError::SimulatedLeafWithoutChallenge)
                 }
+            } else if let Some(signer) = external_signer_for(prover, &dhu.proposition.clone().into()) {
+                // Step 6 -- the commitment is computed by the external signer
+                let fm: dht_protocol::FirstDhTupleProverMessage = signer
+                    .commit(&dhu.proposition.clone().into(), &dhu.position)?
+                    .try_into()
+                    .map_err(|_| ProverError::Unexpected("Step 6 for UnprovenDhTuple: external signer returned non-DHT commitment"))?;
+                Ok(dhu.clone().with_commitment(fm).into())
             } else {
                 // Step 6 -- compute the commitment
-                let (r, fm) =
-                    dht_protocol::interactive_prover::first_message(&dhu.proposition);
+                let (r, fm) = dht_protocol::interactive_prover::first_message_with_randomness(
+                    &dhu.proposition,
+                    nonces.scalar(&dhu.position, NoncePurpose::Commitment),
+                );
                 Ok(UnprovenDhTuple {
                     commitment_opt: Some(fm),
                     randomness_opt: Some(r),
@@ -712,9 +792,11 @@ to compute the commitment $a$ and the response z, given the challenge e that is
 Prover Step 6: For every leaf marked "real", use the first prover step of the Sigma-protocol for that leaf to
 compute the commitment a.
  */
-fn simulate_and_commit(
+fn simulate_and_commit<P: Prover + ?Sized>(
+    prover: &P,
     unproven_tree: UnprovenTree,
     hints_bag: &HintsBag,
+    nonces: &NonceSource,
 ) -> Result<UnprovenTree, ProverError> {
     proof_tree::rewrite_td(unproven_tree.into(), &|tree| {
         match tree {
@@ -722,7 +804,7 @@ fn simulate_and_commit(
             // random challenge in {0,1}^t.
             ProofTree::UnprovenTree(UnprovenTree::UnprovenConjecture(uc)) => {
                 if uc.is_real() {
-                    step4_real_conj(uc.clone(), hints_bag)
+                    step4_real_conj(uc.clone(), hints_bag, nonces)
                 } else {
                     match uc {
                         // Step 4 part 2: If the node is marked "simulated", let e_0 be the challenge computed for it.
@@ -732,10 +814,10 @@ fn simulate_and_commit(
                             step4_simulated_and_conj(cand.clone())
                         }
                         UnprovenConjecture::CorUnproven(cor) => {
-                            step4_simulated_or_conj(cor.clone())
+                            step4_simulated_or_conj(cor.clone(), nonces)
                         }
                         UnprovenConjecture::CthresholdUnproven(ct) => {
-                            step4_simulated_threshold_conj(ct.clone())
+                            step4_simulated_threshold_conj(ct.clone(), nonces)
                         }
                     }
                 }
@@ -743,11 +825,11 @@ fn simulate_and_commit(
 
             ProofTree::UnprovenTree(UnprovenTree::UnprovenLeaf(UnprovenLeaf::UnprovenSchnorr(
                 us,
-            ))) => step5_schnorr(us.clone(), hints_bag),
+            ))) => step5_schnorr(prover, us.clone(), hints_bag, nonces),
 
             ProofTree::UnprovenTree(UnprovenTree::UnprovenLeaf(UnprovenLeaf::UnprovenDhTuple(
                 dhu,
-            ))) => step5_diffie_hellman_tuple(dhu.clone(), hints_bag),
+            ))) => step5_diffie_hellman_tuple(prover, dhu.clone(), hints_bag, nonces),
             ProofTree::UncheckedTree(_) => Ok(None),
         }
     })?
@@ -899,27 +981,31 @@ fn step9_real_schnorr<P: Prover + ?Sized>(
                     "step9_real_schnorr: Expected DLOG prover input in prover secrets, got DhTupleProverInput",
                 ));
             }
-            None => match hints_bag
-                .real_proofs()
-                .into_iter()
-                .find(|comm| comm.position == us.position)
-            {
-                Some(tree) => {
-                    let unchecked_tree = tree.unchecked_tree;
-                    if let UncheckedTree::UncheckedLeaf(UncheckedLeaf::UncheckedSchnorr(
-                        unchecked_schnorr,
-                    )) = unchecked_tree
-                    {
-                        unchecked_schnorr.second_message
-                    } else {
-                        return Err(ProverError::SecretNotFound);
+            None => match external_signer_for(prover, &us.proposition.clone().into()) {
+                Some(signer) => signer.respond_dlog(&us.proposition, &us.position, &challenge)?,
+                None => match hints_bag
+                    .real_proofs()
+                    .into_iter()
+                    .find(|comm| comm.position == us.position)
+                {
+                    Some(tree) => {
+                        let unchecked_tree = tree.unchecked_tree;
+                        if let UncheckedTree::UncheckedLeaf(UncheckedLeaf::UncheckedSchnorr(
+                            unchecked_schnorr,
+                        )) = unchecked_tree
+                        {
+                            unchecked_schnorr.second_message
+                        } else {
+                            return Err(ProverError::SecretNotFound);
+                        }
                     }
-                }
-                None => {
-                    let bs =
-                        dlog_group::random_scalar_in_group_range(crypto_utils::secure_rng()).into();
-                    SecondDlogProverMessage { z: bs }
-                }
+                    None => {
+                        let bs =
+                            dlog_group::random_scalar_in_group_range(crypto_utils::secure_rng())
+                                .into();
+                        SecondDlogProverMessage { z: bs }
+                    }
+                },
             },
         };
         Ok(Some(
@@ -976,27 +1062,33 @@ fn step9_real_dh_tuple<P: Prover + ?Sized>(
             Some(PrivateInput::DlogProverInput(_)) => {
                 return Err(ProverError::Unexpected("step9_real_dh_tuple: Expected DhTupleProverInput  in prover secrets, got DlogProverInput"));
             }
-            None => match hints_bag
-                .real_proofs()
-                .iter()
-                .find(|c| c.position == dhu.position)
-            {
-                Some(proof) => {
-                    let unchecked_tree = proof.clone().unchecked_tree;
-                    if let UncheckedTree::UncheckedLeaf(UncheckedLeaf::UncheckedDhTuple(
-                        unchecked_dht,
-                    )) = unchecked_tree
-                    {
-                        unchecked_dht.second_message
-                    } else {
-                        return Err(ProverError::Unexpected("step9_real_dh_tuple: Expected unchecked DH tuple in proof.unchecked_tree"));
-                    }
-                }
-                None => {
-                    let z =
-                        dlog_group::random_scalar_in_group_range(crypto_utils::secure_rng()).into();
-                    SecondDhTupleProverMessage { z }
+            None => match external_signer_for(prover, &dhu.proposition.clone().into()) {
+                Some(signer) => {
+                    signer.respond_dh_tuple(&dhu.proposition, &dhu.position, &dhu_challenge)?
                 }
+                None => match hints_bag
+                    .real_proofs()
+                    .iter()
+                    .find(|c| c.position == dhu.position)
+                {
+                    Some(proof) => {
+                        let unchecked_tree = proof.clone().unchecked_tree;
+                        if let UncheckedTree::UncheckedLeaf(UncheckedLeaf::UncheckedDhTuple(
+                            unchecked_dht,
+                        )) = unchecked_tree
+                        {
+                            unchecked_dht.second_message
+                        } else {
+                            return Err(ProverError::Unexpected("step9_real_dh_tuple: Expected unchecked DH tuple in proof.unchecked_tree"));
+                        }
+                    }
+                    None => {
+                        let z =
+                            dlog_group::random_scalar_in_group_range(crypto_utils::secure_rng())
+                                .into();
+                        SecondDhTupleProverMessage { z }
+                    }
+                },
             },
         };
         Ok(Some(
@@ -1073,6 +1165,18 @@ fn proving<P: Prover + ?Sized>(
     })
 }
 
+/// External signer holding a secret for the given public image
+fn external_signer_for<'a, P: Prover + ?Sized>(
+    prover: &'a P,
+    image: &SigmaBoolean,
+) -> Option<&'a dyn ExternalSigner> {
+    prover
+        .external_signers()
+        .iter()
+        .find(|signer| signer.public_images().contains(image))
+        .map(|signer| signer.as_ref())
+}
+
 fn convert_to_unproven(sb: SigmaBoolean) -> Result<UnprovenTree, ProverError> {
     Ok(match sb {
         SigmaBoolean::ProofOfKnowledge(pok) => match pok {
@@ -1196,12 +1300,58 @@ impl Prover for TestProver {
     }
 }
 
+/// Prover delegating proofs for the secrets it does not hold to external signers
+pub struct ExternalSignerProver {
+    /// secrets to be used in proofs generation
+    pub secrets: Vec<PrivateInput>,
+    /// signers holding the rest of the secrets
+    pub external_signers: Vec<Box<dyn ExternalSigner>>,
+}
+
+impl Prover for ExternalSignerProver {
+    fn secrets(&self) -> &[PrivateInput] {
+        self.secrets.as_ref()
+    }
+
+    fn append_secret(&mut self, input: PrivateInput) {
+        self.secrets.push(input)
+    }
+
+    fn external_signers(&self) -> &[Box<dyn ExternalSigner>] {
+        self.external_signers.as_ref()
+    }
+}
+
+/// Prover deriving the randomness of the proofs from the secrets and the message
+/// (see [`NonceGeneration::Deterministic`])
+pub struct DeterministicNonceProver {
+    /// secrets to be used in proofs generation
+    pub secrets: Vec<PrivateInput>,
+}
+
+impl Prover for DeterministicNonceProver {
+    fn secrets(&self) -> &[PrivateInput] {
+        self.secrets.as_ref()
+    }
+
+    fn append_secret(&mut self, input: PrivateInput) {
+        self.secrets.push(input)
+    }
+
+    fn nonce_generation(&self) -> NonceGeneration {
+        NonceGeneration::Deterministic
+    }
+}
+
 #[allow(clippy::unwrap_used)]
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::sigma_protocol::private_input::DhTupleProverInput;
     use crate::sigma_protocol::private_input::DlogProverInput;
+    use crate::sigma_protocol::prover::external_signer::MockExternalSigner;
+    use crate::sigma_protocol::prover::hint::RealCommitment;
+    use crate::sigma_protocol::verifier::verify_signature;
     use ergotree_ir::mir::atleast::Atleast;
     use ergotree_ir::mir::collection::Collection;
     use ergotree_ir::mir::constant::Constant;
@@ -1209,11 +1359,13 @@ mod tests {
     use ergotree_ir::mir::expr::Expr;
     use ergotree_ir::mir::sigma_and::SigmaAnd;
     use ergotree_ir::mir::sigma_or::SigmaOr;
+    use ergotree_ir::sigma_protocol::sigma_boolean::cand::Cand;
+    use ergotree_ir::sigma_protocol::sigma_boolean::cor::Cor;
     use ergotree_ir::sigma_protocol::sigma_boolean::SigmaProp;
     use ergotree_ir::types::stype::SType;
     use sigma_test_util::force_any_val;
     use std::convert::TryFrom;
-    use std::rc::Rc;
+    use std::sync::Arc;
 
     #[test]
     fn test_prove_true_prop() {
@@ -1228,7 +1380,7 @@ mod tests {
         let res = prover.prove(
             &bool_true_tree,
             &Env::empty(),
-            Rc::new(force_any_val::<Context>()),
+            Arc::new(force_any_val::<Context>()),
             message.as_slice(),
             &HintsBag::empty(),
         );
@@ -1249,7 +1401,7 @@ mod tests {
         let res = prover.prove(
             &bool_false_tree,
             &Env::empty(),
-            Rc::new(force_any_val::<Context>()),
+            Arc::new(force_any_val::<Context>()),
             message.as_slice(),
             &HintsBag::empty(),
         );
@@ -1269,7 +1421,7 @@ mod tests {
         let res = prover.prove(
             &tree,
             &Env::empty(),
-            Rc::new(force_any_val::<Context>()),
+            Arc::new(force_any_val::<Context>()),
             message.as_slice(),
             &HintsBag::empty(),
         );
@@ -1295,7 +1447,7 @@ mod tests {
         let res = prover.prove(
             &tree,
             &Env::empty(),
-            Rc::new(force_any_val::<Context>()),
+            Arc::new(force_any_val::<Context>()),
             message.as_slice(),
             &HintsBag::empty(),
         );
@@ -1327,7 +1479,7 @@ mod tests {
         let res = prover.prove(
             &tree,
             &Env::empty(),
-            Rc::new(force_any_val::<Context>()),
+            Arc::new(force_any_val::<Context>()),
             message.as_slice(),
             &HintsBag::empty(),
         );
@@ -1352,7 +1504,7 @@ mod tests {
         let res = prover.prove(
             &tree,
             &Env::empty(),
-            Rc::new(force_any_val::<Context>()),
+            Arc::new(force_any_val::<Context>()),
             message.as_slice(),
             &HintsBag::empty(),
         );
@@ -1384,7 +1536,7 @@ mod tests {
         let res = prover.prove(
             &tree,
             &Env::empty(),
-            Rc::new(force_any_val::<Context>()),
+            Arc::new(force_any_val::<Context>()),
             message.as_slice(),
             &HintsBag::empty(),
         );
@@ -1404,7 +1556,7 @@ mod tests {
         let res = prover.prove(
             &tree,
             &Env::empty(),
-            Rc::new(force_any_val::<Context>()),
+            Arc::new(force_any_val::<Context>()),
             message.as_slice(),
             &HintsBag::empty(),
         );
@@ -1452,7 +1604,7 @@ mod tests {
         };
 
         let message = vec![0u8; 100];
-        let ctx: Rc<Context> = force_any_val::<Context>().into();
+        let ctx: Arc<Context> = force_any_val::<Context>().into();
         let res = prover.prove(
             &tree,
             &Env::empty(),
@@ -1462,4 +1614,102 @@ mod tests {
         );
         assert!(res.is_err());
     }
+
+    #[test]
+    fn test_prove_with_external_signer() {
+        let local_secret = DlogProverInput::random();
+        let device_dlog = DlogProverInput::random();
+        let device_dht = DhTupleProverInput::random();
+        let sb = Cand::normalized(
+            vec![
+                local_secret.public_image().into(),
+                device_dlog.public_image().into(),
+                device_dht.public_image().clone().into(),
+            ]
+            .try_into()
+            .unwrap(),
+        );
+        let message = vec![0u8; 100];
+
+        let prover = ExternalSignerProver {
+            secrets: vec![local_secret.into()],
+            external_signers: vec![Box::new(MockExternalSigner::new(vec![
+                device_dlog.into(),
+                device_dht.into(),
+            ]))],
+        };
+        let proof = prover
+            .generate_proof(sb.clone(), message.as_slice(), &HintsBag::empty())
+            .unwrap();
+        assert!(verify_signature(sb, message.as_slice(), Vec::from(proof).as_slice()).unwrap());
+    }
+
+    #[test]
+    fn test_prove_without_external_signer_fails() {
+        let local_secret = DlogProverInput::random();
+        let device_dlog = DlogProverInput::random();
+        let sb = Cand::normalized(
+            vec![
+                local_secret.public_image().into(),
+                device_dlog.public_image().into(),
+            ]
+            .try_into()
+            .unwrap(),
+        );
+        let prover = ExternalSignerProver {
+            secrets: vec![local_secret.into()],
+            external_signers: vec![],
+        };
+        assert!(prover
+            .generate_proof(sb, &[0u8; 100], &HintsBag::empty())
+            .is_err());
+    }
+
+    #[test]
+    fn test_prove_with_deterministic_nonces() {
+        let secret = DlogProverInput::random();
+        let other_dlog = DlogProverInput::random();
+        let other_dht = DhTupleProverInput::random();
+        // the branch of the other keys is simulated
+        let sb = Cor::normalized(
+            vec![
+                secret.public_image().into(),
+                Cand::normalized(
+                    vec![
+                        other_dlog.public_image().into(),
+                        other_dht.public_image().clone().into(),
+                    ]
+                    .try_into()
+                    .unwrap(),
+                ),
+            ]
+            .try_into()
+            .unwrap(),
+        );
+        let prover = DeterministicNonceProver {
+            secrets: vec![secret.into()],
+        };
+        let sign = |message: &[u8], hints_bag: &HintsBag| -> Vec<u8> {
+            let proof = prover
+                .generate_proof(sb.clone(), message, hints_bag)
+                .unwrap();
+            let proof = Vec::from(proof);
+            assert!(verify_signature(sb.clone(), message, proof.as_slice()).unwrap());
+            proof
+        };
+        let empty = HintsBag::empty();
+        assert_eq!(sign(&[0u8; 100], &empty), sign(&[0u8; 100], &empty));
+        assert_ne!(sign(&[0u8; 100], &empty), sign(&[1u8; 100], &empty));
+
+        // fresh randomness when hints are given
+        let hints = HintsBag {
+            hints: vec![RealCommitment {
+                image: DlogProverInput::random().public_image().into(),
+                commitment: dlog_protocol::interactive_prover::first_message().1.into(),
+                position: NodePosition::crypto_tree_prefix().child(5),
+            }
+            .into()],
+        };
+        assert_ne!(sign(&[0u8; 100], &hints), sign(&[0u8; 100], &hints));
+    }
 }
@@ -0,0 +1,216 @@
+//! Signers holding secrets outside of the prover (hardware wallets, HSMs, etc.)
+
+use std::sync::Mutex;
+
+use ergotree_ir::sigma_protocol::sigma_boolean::ProveDhTuple;
+use ergotree_ir::sigma_protocol::sigma_boolean::ProveDlog;
+use ergotree_ir::sigma_protocol::sigma_boolean::SigmaBoolean;
+use thiserror::Error;
+
+use crate::sigma_protocol::challenge::Challenge;
+use crate::sigma_protocol::dht_protocol;
+use crate::sigma_protocol::dht_protocol::SecondDhTupleProverMessage;
+use crate::sigma_protocol::dlog_protocol;
+use crate::sigma_protocol::dlog_protocol::SecondDlogProverMessage;
+use crate::sigma_protocol::private_input::PrivateInput;
+use crate::sigma_protocol::unproven_tree::NodePosition;
+use crate::sigma_protocol::wscalar::Wscalar;
+use crate::sigma_protocol::FirstProverMessage;
+
+/// External signer errors
+#[derive(Error, PartialEq, Eq, Debug, Clone)]
+pub enum ExternalSignerError {
+    /// Signer does not hold a secret for the requested public image
+    #[error("signer does not hold a secret for the requested public image")]
+    UnknownKey,
+    /// Response is requested without a prior commitment for the given position
+    #[error("no commitment was generated for position {0:?}")]
+    MissingCommitment(NodePosition),
+    /// Device-specific failure (connection, user rejection, etc.)
+    #[error("device error: {0}")]
+    DeviceError(String),
+}
+
+/// Signer that keeps its secrets outside of the prover and computes the commitments and responses
+/// of the Sigma-protocol on request.
+/// For every "real" leaf of the proof tree with a public image returned by
+/// [`ExternalSigner::public_images`] the prover first asks for a commitment (step 6) and then,
+/// after the challenge for the leaf is known, for a response (step 9) at the same position.
+/// The randomness used for a commitment must never leave the signer and must be used only once.
+/// Signers are shared with the prover across threads, hence must be `Send + Sync`.
+pub trait ExternalSigner: Send + Sync {
+    /// Public images of the secrets held by the signer
+    fn public_images(&self) -> Vec<SigmaBoolean>;
+
+    /// Generate randomness and a commitment ("a" message) for the leaf with the given public image
+    /// at the given position in the tree
+    fn commit(
+        &self,
+        image: &SigmaBoolean,
+        position: &NodePosition,
+    ) -> Result<FirstProverMessage, ExternalSignerError>;
+
+    /// Compute the response ("z" message) for the Schnorr leaf at the given position using the
+    /// randomness of the commitment generated for it
+    fn respond_dlog(
+        &self,
+        image: &ProveDlog,
+        position: &NodePosition,
+        challenge: &Challenge,
+    ) -> Result<SecondDlogProverMessage, ExternalSignerError>;
+
+    /// Compute the response ("z" message) for the Diffie-Hellman tuple leaf at the given position
+    /// using the randomness of the commitment generated for it
+    fn respond_dh_tuple(
+        &self,
+        image: &ProveDhTuple,
+        position: &NodePosition,
+        challenge: &Challenge,
+    ) -> Result<SecondDhTupleProverMessage, ExternalSignerError>;
+}
+
+/// Software implementation of [`ExternalSigner`] holding secrets in memory, for testing
+pub struct MockExternalSigner {
+    secrets: Vec<PrivateInput>,
+    pending_randomness: Mutex<Vec<(SigmaBoolean, NodePosition, Wscalar)>>,
+}
+
+impl MockExternalSigner {
+    /// Create a signer holding the given secrets
+    pub fn new(secrets: Vec<PrivateInput>) -> Self {
+        MockExternalSigner {
+            secrets,
+            pending_randomness: Mutex::new(Vec::new()),
+        }
+    }
+
+    fn secret(&self, image: &SigmaBoolean) -> Result<&PrivateInput, ExternalSignerError> {
+        self.secrets
+            .iter()
+            .find(|s| &s.public_image() == image)
+            .ok_or(ExternalSignerError::UnknownKey)
+    }
+
+    /// Remove the randomness of the commitment so that it cannot be used twice
+    fn take_randomness(
+        &self,
+        image: &SigmaBoolean,
+        position: &NodePosition,
+    ) -> Result<Wscalar, ExternalSignerError> {
+        let mut pending = self
+            .pending_randomness
+            .lock()
+            .map_err(|_| ExternalSignerError::DeviceError("randomness lock poisoned".into()))?;
+        let idx = pending
+            .iter()
+            .position(|(i, p, _)| i == image && p == position)
+            .ok_or_else(|| ExternalSignerError::MissingCommitment(position.clone()))?;
+        Ok(pending.remove(idx).2)
+    }
+}
+
+impl ExternalSigner for MockExternalSigner {
+    fn public_images(&self) -> Vec<SigmaBoolean> {
+        self.secrets
+            .iter()
+            .map(PrivateInput::public_image)
+            .collect()
+    }
+
+    fn commit(
+        &self,
+        image: &SigmaBoolean,
+        position: &NodePosition,
+    ) -> Result<FirstProverMessage, ExternalSignerError> {
+        let (r, commitment) = match self.secret(image)? {
+            PrivateInput::DlogProverInput(_) => {
+                let (r, fm) = dlog_protocol::interactive_prover::first_message();
+                (r, fm.into())
+            }
+            PrivateInput::DhTupleProverInput(dht) => {
+                let (r, fm) = dht_protocol::interactive_prover::first_message(dht.public_image());
+                (r, fm.into())
+            }
+        };
+        let mut pending = self
+            .pending_randomness
+            .lock()
+            .map_err(|_| ExternalSignerError::DeviceError("randomness lock poisoned".into()))?;
+        pending.retain(|(i, p, _)| !(i == image && p == position));
+        pending.push((image.clone(), position.clone(), r));
+        Ok(commitment)
+    }
+
+    fn respond_dlog(
+        &self,
+        image: &ProveDlog,
+        position: &NodePosition,
+        challenge: &Challenge,
+    ) -> Result<SecondDlogProverMessage, ExternalSignerError> {
+        let image: SigmaBoolean = image.clone().into();
+        match self.secret(&image)? {
+            PrivateInput::DlogProverInput(secret) => {
+                let r = self.take_randomness(&image, position)?;
+                Ok(dlog_protocol::interactive_prover::second_message(
+                    secret, r, challenge,
+                ))
+            }
+            PrivateInput::DhTupleProverInput(_) => Err(ExternalSignerError::UnknownKey),
+        }
+    }
+
+    fn respond_dh_tuple(
+        &self,
+        image: &ProveDhTuple,
+        position: &NodePosition,
+        challenge: &Challenge,
+    ) -> Result<SecondDhTupleProverMessage, ExternalSignerError> {
+        let image: SigmaBoolean = image.clone().into();
+        match self.secret(&image)? {
+            PrivateInput::DhTupleProverInput(secret) => {
+                let r = self.take_randomness(&image, position)?;
+                Ok(dht_protocol::interactive_prover::second_message(
+                    secret, &r, challenge,
+                ))
+            }
+            PrivateInput::DlogProverInput(_) => Err(ExternalSignerError::UnknownKey),
+        }
+    }
+}
+
+#[allow(clippy::unwrap_used)]
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sigma_protocol::private_input::DlogProverInput;
+
+    #[test]
+    fn response_without_commitment() {
+        let secret = DlogProverInput::random();
+        let image = secret.public_image();
+        let signer = MockExternalSigner::new(vec![secret.into()]);
+        let position = NodePosition::crypto_tree_prefix();
+        assert_eq!(
+            signer.respond_dlog(&image, &position, &Challenge::secure_random()),
+            Err(ExternalSignerError::MissingCommitment(position.clone()))
+        );
+        signer.commit(&image.clone().into(), &position).unwrap();
+        assert!(signer
+            .respond_dlog(&image, &position, &Challenge::secure_random())
+            .is_ok());
+        // randomness is used only once
+        assert!(signer
+            .respond_dlog(&image, &position, &Challenge::secure_random())
+            .is_err());
+    }
+
+    #[test]
+    fn unknown_key() {
+        let signer = MockExternalSigner::new(vec![DlogProverInput::random().into()]);
+        let other: SigmaBoolean = DlogProverInput::random().public_image().into();
+        assert_eq!(
+            signer.commit(&other, &NodePosition::crypto_tree_prefix()),
+            Err(ExternalSignerError::UnknownKey)
+        );
+    }
+}
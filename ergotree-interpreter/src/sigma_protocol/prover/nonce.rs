@@ -0,0 +1,220 @@
+//! Generation of the prover's randomness: commitment randomness of real leaves, challenges of
+//! simulated nodes and responses of simulated leaves
+
+use std::convert::TryFrom;
+
+use elliptic_curve::generic_array::GenericArray;
+use elliptic_curve::ops::Reduce;
+use ergotree_ir::serialization::SigmaSerializable;
+use ergotree_ir::sigma_protocol::dlog_group;
+use ergotree_ir::sigma_protocol::sigma_boolean::SigmaBoolean;
+use k256::Scalar;
+use k256::U256;
+use sigma_util::hash::blake2b256_hash;
+use zeroize::Zeroize;
+
+use crate::sigma_protocol::crypto_utils;
+use crate::sigma_protocol::fiat_shamir::FiatShamirHash;
+use crate::sigma_protocol::private_input::PrivateInput;
+use crate::sigma_protocol::unproven_tree::NodePosition;
+use crate::sigma_protocol::Challenge;
+use crate::sigma_protocol::SOUNDNESS_BYTES;
+
+use super::hint::HintsBag;
+use super::Prover;
+use super::ProverError;
+
+/// How the prover generates the randomness of the proofs
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Default)]
+pub enum NonceGeneration {
+    /// Fresh randomness from the OS-seeded RNG for every proof
+    #[default]
+    Random,
+    /// Randomness derived (in the spirit of RFC 6979) from the prover's secrets, the proposition,
+    /// the message and the position in the tree, so the same message signed twice gives the same
+    /// signature and a bad RNG can't leak the secrets.
+    /// Only used when the prover computes the whole proof by itself, i.e. the hints bag is empty
+    /// and there are no external signers. Otherwise the commitments of the other parties change
+    /// the challenge while the derived randomness stays the same, which would leak the secrets,
+    /// so fresh randomness is used instead.
+    Deterministic,
+}
+
+/// Purpose of the randomness, to derive independent values for the same position
+#[derive(Clone, Copy)]
+pub(crate) enum NoncePurpose {
+    /// Randomness of the commitment of a real leaf
+    Commitment = 0,
+    /// Response of a simulated leaf
+    SimulatedResponse = 1,
+    /// Challenge of a simulated node
+    SimulatedChallenge = 2,
+    /// Polynomial coefficients of a simulated threshold node
+    SimulatedPolynomial = 3,
+}
+
+/// Source of the prover's randomness for one proof
+pub(crate) struct NonceSource {
+    /// `None` for fresh randomness
+    seed: Option<[u8; 32]>,
+}
+
+impl NonceSource {
+    /// Randomness for the proof of `sigma_tree` for `message` by the prover
+    pub(crate) fn new<P: Prover + ?Sized>(
+        prover: &P,
+        sigma_tree: &SigmaBoolean,
+        message: &[u8],
+        hints_bag: &HintsBag,
+    ) -> Result<Self, ProverError> {
+        if prover.nonce_generation() == NonceGeneration::Random
+            || !hints_bag.hints.is_empty()
+            || !prover.external_signers().is_empty()
+        {
+            return Ok(NonceSource { seed: None });
+        }
+        let mut secrets: Vec<u8> = Vec::new();
+        for secret in prover.secrets() {
+            match secret {
+                PrivateInput::DlogProverInput(dlog) => {
+                    secrets.extend_from_slice(dlog.expose_secret_bytes().as_ref())
+                }
+                PrivateInput::DhTupleProverInput(dht) => {
+                    secrets.extend_from_slice(dht.expose_secret_bytes().as_ref())
+                }
+            }
+        }
+        let tree_bytes = sigma_tree.sigma_serialize_bytes().map_err(|_| {
+            ProverError::Unexpected("NonceSource: failed to serialize the sigma tree")
+        })?;
+        let mut seed = tagged_hash(
+            b"ergo-sigma/nonce-seed",
+            &[secrets.as_slice(), tree_bytes.as_slice(), message],
+        );
+        secrets.zeroize();
+        let res = NonceSource { seed: Some(*seed) };
+        (*seed).zeroize();
+        Ok(res)
+    }
+
+    /// Scalar for the node at the given position
+    pub(crate) fn scalar(&self, position: &NodePosition, purpose: NoncePurpose) -> Scalar {
+        match &self.seed {
+            Some(seed) => {
+                let mut hash = derive(seed, position, purpose, 0);
+                let res = <Scalar as Reduce<U256>>::from_be_bytes_reduced(
+                    GenericArray::clone_from_slice(hash.as_slice()),
+                );
+                (*hash).zeroize();
+                res
+            }
+            None => dlog_group::random_scalar_in_group_range(crypto_utils::secure_rng()),
+        }
+    }
+
+    /// Challenge for the simulated node at the given position
+    pub(crate) fn challenge(&self, position: &NodePosition) -> Challenge {
+        match &self.seed {
+            Some(_) => {
+                let bytes = self.bytes(position, NoncePurpose::SimulatedChallenge, SOUNDNESS_BYTES);
+                #[allow(clippy::unwrap_used)] // since we set the correct size
+                FiatShamirHash::try_from(bytes.as_slice()).unwrap().into()
+            }
+            None => Challenge::secure_random(),
+        }
+    }
+
+    /// `how_many` bytes for the node at the given position
+    pub(crate) fn bytes(
+        &self,
+        position: &NodePosition,
+        purpose: NoncePurpose,
+        how_many: usize,
+    ) -> Vec<u8> {
+        match &self.seed {
+            Some(seed) => {
+                let mut res = Vec::with_capacity(how_many + 32);
+                let mut counter = 0u32;
+                while res.len() < how_many {
+                    res.extend_from_slice(derive(seed, position, purpose, counter).as_slice());
+                    counter += 1;
+                }
+                res.truncate(how_many);
+                res
+            }
+            None => crypto_utils::secure_random_bytes(how_many),
+        }
+    }
+}
+
+impl Drop for NonceSource {
+    fn drop(&mut self) {
+        self.seed.zeroize();
+    }
+}
+
+/// Blake2b256 of the seed, the purpose, the position and the counter
+fn derive(
+    seed: &[u8; 32],
+    position: &NodePosition,
+    purpose: NoncePurpose,
+    counter: u32,
+) -> Box<[u8; 32]> {
+    let position_bytes: Vec<u8> = position
+        .positions
+        .iter()
+        .flat_map(|p| (*p as u64).to_be_bytes())
+        .collect();
+    tagged_hash(
+        b"ergo-sigma/nonce",
+        &[
+            seed,
+            &[purpose as u8],
+            position_bytes.as_slice(),
+            &counter.to_be_bytes(),
+        ],
+    )
+}
+
+fn tagged_hash(tag: &[u8], parts: &[&[u8]]) -> Box<[u8; 32]> {
+    let mut data = tag.to_vec();
+    for part in parts {
+        // length prefix to keep the encoding unambiguous
+        data.extend_from_slice(&u32::try_from(part.len()).unwrap_or(u32::MAX).to_be_bytes());
+        data.extend_from_slice(part);
+    }
+    let res = blake2b256_hash(&data);
+    data.zeroize();
+    res
+}
+
+#[allow(clippy::unwrap_used)]
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sigma_protocol::private_input::DlogProverInput;
+    use crate::sigma_protocol::prover::DeterministicNonceProver;
+
+    #[test]
+    fn derived_values_depend_on_position_and_purpose() {
+        let secret = DlogProverInput::random();
+        let tree: SigmaBoolean = secret.public_image().into();
+        let prover = DeterministicNonceProver {
+            secrets: vec![secret.into()],
+        };
+        let nonces = NonceSource::new(&prover, &tree, b"msg", &HintsBag::empty()).unwrap();
+        let root = NodePosition::crypto_tree_prefix();
+        let child = root.child(0);
+        let r = nonces.scalar(&root, NoncePurpose::Commitment);
+        assert_eq!(r, nonces.scalar(&root, NoncePurpose::Commitment));
+        assert_ne!(r, nonces.scalar(&child, NoncePurpose::Commitment));
+        assert_ne!(r, nonces.scalar(&root, NoncePurpose::SimulatedResponse));
+        assert_eq!(
+            nonces.bytes(&root, NoncePurpose::SimulatedPolynomial, 100)[..32],
+            nonces.bytes(&root, NoncePurpose::SimulatedPolynomial, 32)[..]
+        );
+
+        let other_message = NonceSource::new(&prover, &tree, b"msg2", &HintsBag::empty()).unwrap();
+        assert_ne!(r, other_message.scalar(&root, NoncePurpose::Commitment));
+    }
+}
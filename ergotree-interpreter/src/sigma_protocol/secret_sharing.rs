@@ -0,0 +1,374 @@
+//! Shamir secret sharing of `ProveDlog` secrets with Feldman commitments (verifiable secret
+//! sharing) over the secp256k1 scalar field
+//!
+//! A secret `w` is split into `n` shares so that any `k` of them reconstruct it and `k - 1`
+//! reveal nothing about it. The dealer samples a random polynomial `f` of degree `k - 1` with
+//! `f(0) = w`, gives `f(i)` to the holder of share `i` and publishes the commitments `g^a_j` to
+//! the coefficients of `f`, so that every holder can check its share without learning the secret.
+//! The first commitment `g^w` is the public key of the secret.
+
+use std::fmt::Formatter;
+
+use ergo_chain_types::ec_point::exponentiate;
+use ergo_chain_types::ec_point::generator;
+use ergo_chain_types::EcPoint;
+use ergotree_ir::serialization::sigma_byte_reader::SigmaByteRead;
+use ergotree_ir::serialization::sigma_byte_writer::SigmaByteWrite;
+use ergotree_ir::serialization::SigmaParsingError;
+use ergotree_ir::serialization::SigmaSerializable;
+use ergotree_ir::serialization::SigmaSerializeResult;
+use ergotree_ir::sigma_protocol::dlog_group;
+use ergotree_ir::sigma_protocol::sigma_boolean::ProveDlog;
+use k256::Scalar;
+use subtle::ConstantTimeEq;
+use thiserror::Error;
+use zeroize::Zeroize;
+
+use super::crypto_utils;
+use super::private_input::DlogProverInput;
+use super::wscalar::Wscalar;
+
+/// Secret sharing errors
+#[derive(Error, PartialEq, Eq, Debug, Clone)]
+pub enum SecretSharingError {
+    /// Threshold is zero or greater than the number of shares
+    #[error("invalid threshold {threshold} for {shares} shares")]
+    InvalidThreshold {
+        /// Number of shares required to reconstruct the secret
+        threshold: u8,
+        /// Total number of shares
+        shares: u8,
+    },
+    /// No shares to reconstruct the secret from
+    #[error("no shares")]
+    NoShares,
+    /// Share index is zero (the secret itself)
+    #[error("share index must be greater than zero")]
+    ZeroShareIndex,
+    /// Several shares with the same index
+    #[error("duplicate share index {0}")]
+    DuplicateShareIndex(u8),
+    /// Fewer shares than the threshold of the commitments
+    #[error("not enough shares: {provided} provided, {required} required")]
+    NotEnoughShares {
+        /// Number of shares provided
+        provided: usize,
+        /// Number of shares required
+        required: usize,
+    },
+    /// Share does not match the commitments
+    #[error("share {0} does not match the commitments")]
+    InvalidShare(u8),
+    /// Reconstructed secret does not match the public key of the commitments
+    #[error("reconstructed secret does not match the public key")]
+    PublicKeyMismatch,
+}
+
+/// Share of a secret: evaluation of the sharing polynomial at `index`
+/// The value is zeroed on drop, compared in constant time and is not `Clone` (see
+/// [`SecretShare::clone_secret`])
+pub struct SecretShare {
+    index: u8,
+    value: Wscalar,
+}
+
+impl SecretShare {
+    /// Index of the share (1-based)
+    pub fn index(&self) -> u8 {
+        self.index
+    }
+
+    /// Copy of the share (`Clone` is not implemented to make the copies of the secret explicit)
+    pub fn clone_secret(&self) -> SecretShare {
+        SecretShare {
+            index: self.index,
+            value: self.value.clone(),
+        }
+    }
+}
+
+impl PartialEq for SecretShare {
+    fn eq(&self, other: &Self) -> bool {
+        (self.index.ct_eq(&other.index)
+            & self
+                .value
+                .as_scalar_ref()
+                .ct_eq(other.value.as_scalar_ref()))
+        .into()
+    }
+}
+
+impl Eq for SecretShare {}
+
+impl std::fmt::Debug for SecretShare {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        // to avoid leaking it in error messages, logs, etc.
+        write!(f, "SecretShare({}, ***)", self.index)
+    }
+}
+
+impl Drop for SecretShare {
+    fn drop(&mut self) {
+        self.value.zeroize()
+    }
+}
+
+impl SigmaSerializable for SecretShare {
+    fn sigma_serialize<W: SigmaByteWrite>(&self, w: &mut W) -> SigmaSerializeResult {
+        w.put_u8(self.index)?;
+        let mut bytes: [u8; DlogProverInput::SIZE_BYTES] =
+            self.value.as_scalar_ref().to_bytes().into();
+        w.write_all(&bytes)?;
+        bytes.zeroize();
+        Ok(())
+    }
+
+    fn sigma_parse<R: SigmaByteRead>(r: &mut R) -> Result<Self, SigmaParsingError> {
+        let index = r.get_u8()?;
+        if index == 0 {
+            return Err(SigmaParsingError::ValueOutOfBounds(
+                "share index must be greater than zero".to_string(),
+            ));
+        }
+        let mut bytes = [0u8; DlogProverInput::SIZE_BYTES];
+        r.read_exact(&mut bytes)?;
+        let value = DlogProverInput::from_bytes(&bytes)
+            .ok_or_else(|| {
+                SigmaParsingError::ValueOutOfBounds("share value is not a scalar".to_string())
+            })?
+            .w
+            .clone();
+        bytes.zeroize();
+        Ok(SecretShare { index, value })
+    }
+}
+
+/// Feldman commitments `g^a_0, .., g^a_(k-1)` to the coefficients of the sharing polynomial
+#[derive(PartialEq, Eq, Debug, Clone)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "json", serde(transparent))]
+pub struct ShareCommitments(Vec<EcPoint>);
+
+impl ShareCommitments {
+    /// Number of shares required to reconstruct the secret
+    pub fn threshold(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Public key of the shared secret
+    pub fn public_key(&self) -> ProveDlog {
+        ProveDlog::new(self.0.first().cloned().unwrap_or_default())
+    }
+
+    /// Check that the share is the evaluation of the committed polynomial at the share's index:
+    /// `g^f(i) == C_0 * C_1^i * .. * C_(k-1)^(i^(k-1))`
+    pub fn verify_share(&self, share: &SecretShare) -> Result<(), SecretSharingError> {
+        if share.index == 0 {
+            return Err(SecretSharingError::ZeroShareIndex);
+        }
+        let x = Scalar::from(share.index as u64);
+        let (expected, _) = self
+            .0
+            .iter()
+            .fold((EcPoint::default(), Scalar::ONE), |(acc, x_pow), c| {
+                (acc * &exponentiate(c, &x_pow), x_pow * x)
+            });
+        if exponentiate(&generator(), share.value.as_scalar_ref()) == expected {
+            Ok(())
+        } else {
+            Err(SecretSharingError::InvalidShare(share.index))
+        }
+    }
+}
+
+impl SigmaSerializable for ShareCommitments {
+    fn sigma_serialize<W: SigmaByteWrite>(&self, w: &mut W) -> SigmaSerializeResult {
+        // threshold fits u8 since it's limited by the number of shares
+        w.put_u8(self.0.len() as u8)?;
+        self.0.iter().try_for_each(|c| c.sigma_serialize(w))
+    }
+
+    fn sigma_parse<R: SigmaByteRead>(r: &mut R) -> Result<Self, SigmaParsingError> {
+        let count = r.get_u8()?;
+        if count == 0 {
+            return Err(SigmaParsingError::ValueOutOfBounds(
+                "no share commitments".to_string(),
+            ));
+        }
+        (0..count)
+            .map(|_| EcPoint::sigma_parse(r))
+            .collect::<Result<Vec<_>, _>>()
+            .map(ShareCommitments)
+    }
+}
+
+/// Split the secret into `shares` shares so that any `threshold` of them reconstruct it.
+/// Returns the shares (with indices `1..=shares`) and the commitments to verify them.
+pub fn split_secret(
+    secret: &DlogProverInput,
+    threshold: u8,
+    shares: u8,
+) -> Result<(Vec<SecretShare>, ShareCommitments), SecretSharingError> {
+    if threshold == 0 || threshold > shares {
+        return Err(SecretSharingError::InvalidThreshold { threshold, shares });
+    }
+    let mut coefficients: Vec<Wscalar> = vec![secret.w.clone()];
+    coefficients.extend((1..threshold).map(|_| {
+        Wscalar::from(dlog_group::random_scalar_in_group_range(
+            crypto_utils::secure_rng(),
+        ))
+    }));
+    let commitments = ShareCommitments(
+        coefficients
+            .iter()
+            .map(|a| exponentiate(&generator(), a.as_scalar_ref()))
+            .collect(),
+    );
+    let secret_shares = (1..=shares)
+        .map(|index| {
+            let x = Scalar::from(index as u64);
+            // Horner's method
+            let value = coefficients
+                .iter()
+                .rev()
+                .fold(Scalar::ZERO, |acc, a| acc * x + a.as_scalar_ref());
+            SecretShare {
+                index,
+                value: value.into(),
+            }
+        })
+        .collect();
+    coefficients.iter_mut().for_each(Zeroize::zeroize);
+    Ok((secret_shares, commitments))
+}
+
+/// Reconstruct the secret from the shares by Lagrange interpolation at zero.
+/// The result is correct only if at least the threshold number of valid shares is given, use
+/// [`reconstruct_verified_secret`] to check it.
+pub fn reconstruct_secret(shares: &[SecretShare]) -> Result<DlogProverInput, SecretSharingError> {
+    if shares.is_empty() {
+        return Err(SecretSharingError::NoShares);
+    }
+    let mut indices: Vec<u8> = Vec::with_capacity(shares.len());
+    for share in shares {
+        if share.index == 0 {
+            return Err(SecretSharingError::ZeroShareIndex);
+        }
+        if indices.contains(&share.index) {
+            return Err(SecretSharingError::DuplicateShareIndex(share.index));
+        }
+        indices.push(share.index);
+    }
+    let secret = shares.iter().fold(Scalar::ZERO, |acc, share| {
+        let x_i = Scalar::from(share.index as u64);
+        let (num, den) = shares
+            .iter()
+            .filter(|other| other.index != share.index)
+            .fold((Scalar::ONE, Scalar::ONE), |(num, den), other| {
+                let x_j = Scalar::from(other.index as u64);
+                (num * x_j, den * (x_j - x_i))
+            });
+        // indices are distinct, so the denominator is never zero
+        let den_inv: Scalar = Option::from(den.invert()).unwrap_or(Scalar::ZERO);
+        acc + share.value.as_scalar_ref() * &(num * den_inv)
+    });
+    Ok(DlogProverInput::from(Wscalar::from(secret)))
+}
+
+/// Verify the shares against the commitments and reconstruct the secret
+pub fn reconstruct_verified_secret(
+    shares: &[SecretShare],
+    commitments: &ShareCommitments,
+) -> Result<DlogProverInput, SecretSharingError> {
+    if shares.len() < commitments.threshold() {
+        return Err(SecretSharingError::NotEnoughShares {
+            provided: shares.len(),
+            required: commitments.threshold(),
+        });
+    }
+    shares
+        .iter()
+        .try_for_each(|share| commitments.verify_share(share))?;
+    let secret = reconstruct_secret(shares)?;
+    if secret.public_image() == commitments.public_key() {
+        Ok(secret)
+    } else {
+        Err(SecretSharingError::PublicKeyMismatch)
+    }
+}
+
+#[allow(clippy::unwrap_used)]
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ergotree_ir::serialization::sigma_serialize_roundtrip;
+
+    #[test]
+    fn split_and_reconstruct() {
+        let secret = DlogProverInput::random();
+        let (shares, commitments) = split_secret(&secret, 3, 5).unwrap();
+        assert_eq!(shares.len(), 5);
+        assert_eq!(commitments.public_key(), secret.public_image());
+        for share in &shares {
+            assert_eq!(commitments.verify_share(share), Ok(()));
+        }
+        let subset = vec![
+            shares[4].clone_secret(),
+            shares[0].clone_secret(),
+            shares[2].clone_secret(),
+        ];
+        assert_eq!(
+            reconstruct_verified_secret(&subset, &commitments).unwrap(),
+            secret
+        );
+        // fewer shares than the threshold give a different secret
+        assert_ne!(reconstruct_secret(&shares[..2]).unwrap(), secret);
+        assert_eq!(
+            reconstruct_verified_secret(&shares[..2], &commitments),
+            Err(SecretSharingError::NotEnoughShares {
+                provided: 2,
+                required: 3
+            })
+        );
+    }
+
+    #[test]
+    fn tampered_share() {
+        let secret = DlogProverInput::random();
+        let (mut shares, commitments) = split_secret(&secret, 2, 3).unwrap();
+        shares[1].value = DlogProverInput::random().w.clone();
+        assert_eq!(
+            reconstruct_verified_secret(&shares, &commitments),
+            Err(SecretSharingError::InvalidShare(2))
+        );
+    }
+
+    #[test]
+    fn invalid_parameters() {
+        let secret = DlogProverInput::random();
+        assert_eq!(
+            split_secret(&secret, 4, 3),
+            Err(SecretSharingError::InvalidThreshold {
+                threshold: 4,
+                shares: 3
+            })
+        );
+        let (shares, _) = split_secret(&secret, 2, 3).unwrap();
+        assert_eq!(
+            reconstruct_secret(&[shares[0].clone_secret(), shares[0].clone_secret()]),
+            Err(SecretSharingError::DuplicateShareIndex(1))
+        );
+    }
+
+    #[test]
+    fn serialization_roundtrip() {
+        let (shares, commitments) = split_secret(&DlogProverInput::random(), 2, 3).unwrap();
+        for share in shares {
+            assert_eq!(sigma_serialize_roundtrip(&share), share);
+            let bytes = share.sigma_serialize_bytes().unwrap();
+            assert_eq!(bytes.len(), 1 + DlogProverInput::SIZE_BYTES);
+            assert_eq!(SecretShare::sigma_parse_bytes(&bytes).unwrap(), share);
+        }
+        assert_eq!(sigma_serialize_roundtrip(&commitments), commitments);
+    }
+}
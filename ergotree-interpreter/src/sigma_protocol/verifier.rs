@@ -1,6 +1,8 @@
 //! Verifier
 
-use std::rc::Rc;
+pub mod batch;
+
+use std::sync::Arc;
 
 use super::dht_protocol;
 use super::dht_protocol::FirstDhTupleProverMessage;
@@ -61,7 +63,7 @@ pub trait Verifier {
         &self,
         tree: &ErgoTree,
         env: &Env,
-        ctx: Rc<Context>,
+        ctx: Arc<Context>,
         proof: ProofBytes,
         message: &[u8],
     ) -> Result<VerificationResult, VerifierError> {
@@ -216,14 +218,14 @@ mod tests {
             };
             let res = prover.prove(&tree,
                 &Env::empty(),
-                Rc::new(force_any_val::<Context>()),
+                Arc::new(force_any_val::<Context>()),
                 message.as_slice(),
                 &HintsBag::empty());
             let proof = res.unwrap().proof;
             let verifier = TestVerifier;
             prop_assert_eq!(verifier.verify(&tree,
                                             &Env::empty(),
-                                            Rc::new(force_any_val::<Context>()),
+                                            Arc::new(force_any_val::<Context>()),
                                             proof.clone(),
                                             message.as_slice())
                             .unwrap().result,
@@ -232,7 +234,7 @@ mod tests {
             // possible to append bytes
             prop_assert_eq!(verifier.verify(&tree,
                                             &Env::empty(),
-                                            Rc::new(force_any_val::<Context>()),
+                                            Arc::new(force_any_val::<Context>()),
                                             proof_append_some_byte(&proof),
                                             message.as_slice())
                             .unwrap().result,
@@ -241,7 +243,7 @@ mod tests {
             // wrong message
             prop_assert_eq!(verifier.verify(&tree,
                                             &Env::empty(),
-                                            Rc::new(force_any_val::<Context>()),
+                                            Arc::new(force_any_val::<Context>()),
                                             proof,
                                             vec![1u8; 100].as_slice())
                             .unwrap().result,
@@ -258,14 +260,14 @@ mod tests {
             };
             let res = prover.prove(&tree,
                 &Env::empty(),
-                Rc::new(force_any_val::<Context>()),
+                Arc::new(force_any_val::<Context>()),
                 message.as_slice(),
                 &HintsBag::empty());
             let proof = res.unwrap().proof;
             let verifier = TestVerifier;
             prop_assert_eq!(verifier.verify(&tree,
                                             &Env::empty(),
-                                            Rc::new(force_any_val::<Context>()),
+                                            Arc::new(force_any_val::<Context>()),
                                             proof.clone(),
                                             message.as_slice())
                             .unwrap().result,
@@ -274,7 +276,7 @@ mod tests {
             // possible to append bytes
             prop_assert_eq!(verifier.verify(&tree,
                                             &Env::empty(),
-                                            Rc::new(force_any_val::<Context>()),
+                                            Arc::new(force_any_val::<Context>()),
                                             proof_append_some_byte(&proof),
                                             message.as_slice())
                             .unwrap().result,
@@ -283,7 +285,7 @@ mod tests {
             // wrong message
             prop_assert_eq!(verifier.verify(&tree,
                                             &Env::empty(),
-                                            Rc::new(force_any_val::<Context>()),
+                                            Arc::new(force_any_val::<Context>()),
                                             proof,
                                             vec![1u8; 100].as_slice())
                             .unwrap().result,
@@ -305,14 +307,14 @@ mod tests {
             };
             let res = prover.prove(&tree,
                 &Env::empty(),
-                Rc::new(force_any_val::<Context>()),
+                Arc::new(force_any_val::<Context>()),
                 message.as_slice(),
                 &HintsBag::empty());
             let proof = res.unwrap().proof;
             let verifier = TestVerifier;
             let ver_res = verifier.verify(&tree,
                                           &Env::empty(),
-                                          Rc::new(force_any_val::<Context>()),
+                                          Arc::new(force_any_val::<Context>()),
                                           proof,
                                           message.as_slice());
             prop_assert_eq!(ver_res.unwrap().result, true);
@@ -336,14 +338,14 @@ mod tests {
             let prover = TestProver { secrets: vec![secret1, secret2, secret3] };
             let res = prover.prove(&tree,
                 &Env::empty(),
-                Rc::new(force_any_val::<Context>()),
+                Arc::new(force_any_val::<Context>()),
                 message.as_slice(),
                 &HintsBag::empty());
             let proof = res.unwrap().proof;
             let verifier = TestVerifier;
             let ver_res = verifier.verify(&tree,
                                           &Env::empty(),
-                                          Rc::new(force_any_val::<Context>()),
+                                          Arc::new(force_any_val::<Context>()),
                                           proof,
                                           message.as_slice());
             prop_assert_eq!(ver_res.unwrap().result, true);
@@ -363,18 +365,18 @@ mod tests {
             // any secret (out of 2) known to prover should be enough
             for secret in secrets {
                 let prover = TestProver {
-                    secrets: vec![secret.clone()],
+                    secrets: vec![secret.clone_secret()],
                 };
                 let res = prover.prove(&tree,
                     &Env::empty(),
-                    Rc::new(force_any_val::<Context>()),
+                    Arc::new(force_any_val::<Context>()),
                     message.as_slice(),
                     &HintsBag::empty());
                 let proof = res.unwrap_or_else(|_| panic!("proof failed for secret: {:?}", secret)).proof;
                 let verifier = TestVerifier;
                 let ver_res = verifier.verify(&tree,
                                               &Env::empty(),
-                                              Rc::new(force_any_val::<Context>()),
+                                              Arc::new(force_any_val::<Context>()),
                                               proof,
                                               message.as_slice());
                 prop_assert_eq!(ver_res.unwrap().result, true, "verify failed on secret: {:?}", &secret);
@@ -400,18 +402,18 @@ mod tests {
             // any secret (out of 3) known to prover should be enough
             for secret in secrets {
                 let prover = TestProver {
-                    secrets: vec![secret.clone()],
+                    secrets: vec![secret.clone_secret()],
                 };
                 let res = prover.prove(&tree,
                     &Env::empty(),
-                    Rc::new(force_any_val::<Context>()),
+                    Arc::new(force_any_val::<Context>()),
                     message.as_slice(),
                     &HintsBag::empty());
                 let proof = res.unwrap_or_else(|_| panic!("proof failed for secret: {:?}", secret)).proof;
                 let verifier = TestVerifier;
                 let ver_res = verifier.verify(&tree,
                                               &Env::empty(),
-                                              Rc::new(force_any_val::<Context>()),
+                                              Arc::new(force_any_val::<Context>()),
                                               proof,
                                               message.as_slice());
                 prop_assert_eq!(ver_res.unwrap().result, true, "verify failed on secret: {:?}", &secret);
@@ -448,14 +450,14 @@ mod tests {
 
             let res = prover.prove(&tree,
                 &Env::empty(),
-                Rc::new(force_any_val::<Context>()),
+                Arc::new(force_any_val::<Context>()),
                 message.as_slice(),
                 &HintsBag::empty());
             let proof = res.unwrap().proof;
             let verifier = TestVerifier;
             let ver_res = verifier.verify(&tree,
                                             &Env::empty(),
-                                            Rc::new(force_any_val::<Context>()),
+                                            Arc::new(force_any_val::<Context>()),
                                             proof,
                                             message.as_slice());
             prop_assert_eq!(ver_res.unwrap().result, true)
@@ -0,0 +1,161 @@
+//! Verification of many signatures at once (e.g. of all the inputs of a block)
+//!
+//! Ergo signatures are in the compact form: only challenges and responses are serialized and
+//! the verifier recomputes the commitments (`a = g^z * h^-e` for Schnorr, `(a, b)` for DHT) to
+//! check the Fiat-Shamir hash at the root of the tree. Since every recomputed commitment is hashed,
+//! the equations of different proofs can not be folded into a single randomized multi-scalar
+//! multiplication, so each signature is checked on its own and the result is reported per
+//! signature, which makes it possible to pinpoint the invalid ones without re-verification.
+
+use crate::sigma_protocol::SigmaBoolean;
+
+use super::verify_signature;
+use super::VerifierError;
+
+/// Signature added to the [`BatchVerifier`]
+#[derive(PartialEq, Eq, Debug, Clone)]
+struct BatchItem {
+    sigma_tree: SigmaBoolean,
+    message: Vec<u8>,
+    signature: Vec<u8>,
+}
+
+/// Collects signatures and verifies them in one go
+#[derive(PartialEq, Eq, Debug, Clone, Default)]
+pub struct BatchVerifier {
+    items: Vec<BatchItem>,
+}
+
+impl BatchVerifier {
+    /// Create an empty batch
+    pub fn new() -> Self {
+        BatchVerifier { items: Vec::new() }
+    }
+
+    /// Add the signature of `message` for `sigma_tree` to the batch.
+    /// Returns the index of the signature's result in [`BatchVerifier::verify`]
+    pub fn add(&mut self, sigma_tree: SigmaBoolean, message: Vec<u8>, signature: Vec<u8>) -> usize {
+        self.items.push(BatchItem {
+            sigma_tree,
+            message,
+            signature,
+        });
+        self.items.len() - 1
+    }
+
+    /// Number of signatures in the batch
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    /// Returns true if the batch has no signatures
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// Verify every signature of the batch, results are in the order the signatures were added
+    pub fn verify(&self) -> Vec<Result<bool, VerifierError>> {
+        self.items.iter().map(verify_item).collect()
+    }
+
+    /// Verify every signature of the batch concurrently on the rayon thread pool, results are in
+    /// the order the signatures were added
+    #[cfg(feature = "parallel")]
+    pub fn par_verify(&self) -> Vec<Result<bool, VerifierError>> {
+        use rayon::prelude::*;
+        self.items.par_iter().map(verify_item).collect()
+    }
+
+    /// Returns true if every signature of the batch is valid, stops at the first invalid one
+    pub fn verify_all(&self) -> bool {
+        self.items
+            .iter()
+            .all(|item| matches!(verify_item(item), Ok(true)))
+    }
+}
+
+fn verify_item(item: &BatchItem) -> Result<bool, VerifierError> {
+    verify_signature(
+        item.sigma_tree.clone(),
+        item.message.as_slice(),
+        item.signature.as_slice(),
+    )
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use crate::sigma_protocol::private_input::DhTupleProverInput;
+    use crate::sigma_protocol::private_input::DlogProverInput;
+    use crate::sigma_protocol::private_input::PrivateInput;
+    use crate::sigma_protocol::prover::hint::HintsBag;
+    use crate::sigma_protocol::prover::Prover;
+    use crate::sigma_protocol::prover::TestProver;
+
+    fn sign(secret: PrivateInput, message: &[u8]) -> (SigmaBoolean, Vec<u8>) {
+        let sigma_tree = secret.public_image();
+        let prover = TestProver {
+            secrets: vec![secret],
+        };
+        let proof = prover
+            .generate_proof(sigma_tree.clone(), message, &HintsBag::empty())
+            .unwrap();
+        (sigma_tree, proof.into())
+    }
+
+    #[test]
+    fn all_valid() {
+        let mut batch = BatchVerifier::new();
+        for i in 0..4u8 {
+            let message = vec![i; 32];
+            let (tree, sig) = sign(DlogProverInput::random().into(), &message);
+            batch.add(tree, message, sig);
+        }
+        let message = vec![42u8; 32];
+        let (tree, sig) = sign(DhTupleProverInput::random().into(), &message);
+        batch.add(tree, message, sig);
+        batch.add(SigmaBoolean::TrivialProp(true), vec![], vec![]);
+        assert_eq!(batch.len(), 6);
+        assert!(batch.verify_all());
+        assert!(batch.verify().into_iter().all(|r| r.unwrap()));
+    }
+
+    #[test]
+    fn invalid_signatures_are_pinpointed() {
+        let mut batch = BatchVerifier::new();
+        let message = vec![1u8; 32];
+        let (tree, sig) = sign(DlogProverInput::random().into(), &message);
+        batch.add(tree.clone(), message.clone(), sig.clone());
+        let wrong_message_idx = batch.add(tree.clone(), vec![2u8; 32], sig.clone());
+        let (other_tree, _) = sign(DlogProverInput::random().into(), &message);
+        let wrong_key_idx = batch.add(other_tree, message.clone(), sig);
+        let no_sig_idx = batch.add(tree, message, vec![]);
+        assert!(!batch.verify_all());
+        let invalid: Vec<usize> = batch
+            .verify()
+            .into_iter()
+            .enumerate()
+            .filter(|(_, r)| !matches!(r, Ok(true)))
+            .map(|(idx, _)| idx)
+            .collect();
+        assert_eq!(invalid, vec![wrong_message_idx, wrong_key_idx, no_sig_idx]);
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn par_verify_preserves_order() {
+        let mut batch = BatchVerifier::new();
+        for i in 0..16u8 {
+            let message = vec![i; 32];
+            let (tree, sig) = sign(DlogProverInput::random().into(), &message);
+            // every other signature is for a different message
+            let message = if i % 2 == 0 { message } else { vec![!i; 32] };
+            batch.add(tree, message, sig);
+        }
+        let expected: Vec<bool> = batch.verify().into_iter().map(|r| r.unwrap()).collect();
+        let par: Vec<bool> = batch.par_verify().into_iter().map(|r| r.unwrap()).collect();
+        assert_eq!(par, expected);
+        assert_eq!(expected.iter().filter(|valid| **valid).count(), 8);
+    }
+}
@@ -13,6 +13,7 @@ use ergo_chain_types::Base16DecodedBytes;
 use ergo_chain_types::Base16EncodedBytes;
 use k256::Scalar;
 use k256::U256;
+use zeroize::Zeroize;
 
 use super::challenge::Challenge;
 use super::GroupSizedBytes;
@@ -37,6 +38,12 @@ impl Wscalar {
     }
 }
 
+impl Zeroize for Wscalar {
+    fn zeroize(&mut self) {
+        self.0.zeroize()
+    }
+}
+
 impl From<GroupSizedBytes> for Wscalar {
     fn from(b: GroupSizedBytes) -> Self {
         let sl: &[u8] = b.0.as_ref();
@@ -15,7 +15,7 @@ use ergotree_ir::types::stype::SType;
 use num_bigint::BigUint;
 use sigma_test_util::force_any_val;
 use std::convert::TryInto;
-use std::rc::Rc;
+use std::sync::Arc;
 
 #[test]
 fn sig_test_vector_provedlog() {
@@ -44,7 +44,7 @@ fn sig_test_vector_provedlog() {
     let ver_res = verifier.verify(
         &expr.try_into().unwrap(),
         &Env::empty(),
-        Rc::new(force_any_val::<Context>()),
+        Arc::new(force_any_val::<Context>()),
         signature.into(),
         msg.as_slice(),
     );
@@ -71,7 +71,7 @@ fn sig_test_vector_prove_dht() {
     // let res = prover.prove(
     //     &tree,
     //     &Env::empty(),
-    //     Rc::new(force_any_val::<Context>()),
+    //     Arc::new(force_any_val::<Context>()),
     //     msg.as_slice(),
     //     &HintsBag::empty(),
     // );
@@ -82,7 +82,7 @@ fn sig_test_vector_prove_dht() {
     let ver_res = verifier.verify(
         &expr.try_into().unwrap(),
         &Env::empty(),
-        Rc::new(force_any_val::<Context>()),
+        Arc::new(force_any_val::<Context>()),
         signature.into(),
         msg.as_slice(),
     );
@@ -128,7 +128,7 @@ fn sig_test_vector_conj_and() {
     // let res = prover.prove(
     //     &tree,
     //     &Env::empty(),
-    //     Rc::new(force_any_val::<Context>()),
+    //     Arc::new(force_any_val::<Context>()),
     //     msg.as_slice(),
     //     &HintsBag::empty(),
     // );
@@ -139,7 +139,7 @@ fn sig_test_vector_conj_and() {
     let ver_res = verifier.verify(
         &tree,
         &Env::empty(),
-        Rc::new(force_any_val::<Context>()),
+        Arc::new(force_any_val::<Context>()),
         signature.into(),
         msg.as_slice(),
     );
@@ -185,7 +185,7 @@ fn sig_test_vector_conj_or() {
     // let res = prover.prove(
     //     &tree,
     //     &Env::empty(),
-    //     Rc::new(force_any_val::<Context>()),
+    //     Arc::new(force_any_val::<Context>()),
     //     msg.as_slice(),
     //     &HintsBag::empty(),
     // );
@@ -196,7 +196,7 @@ fn sig_test_vector_conj_or() {
     let ver_res = verifier.verify(
         &tree,
         &Env::empty(),
-        Rc::new(force_any_val::<Context>()),
+        Arc::new(force_any_val::<Context>()),
         signature.into(),
         msg.as_slice(),
     );
@@ -236,7 +236,7 @@ fn sig_test_vector_conj_or_prove_dht() {
     // let res = prover.prove(
     //     &tree,
     //     &Env::empty(),
-    //     Rc::new(force_any_val::<Context>()),
+    //     Arc::new(force_any_val::<Context>()),
     //     msg.as_slice(),
     //     &HintsBag::empty(),
     // );
@@ -247,7 +247,7 @@ fn sig_test_vector_conj_or_prove_dht() {
     let ver_res = verifier.verify(
         &expr.try_into().unwrap(),
         &Env::empty(),
-        Rc::new(force_any_val::<Context>()),
+        Arc::new(force_any_val::<Context>()),
         signature.into(),
         msg.as_slice(),
     );
@@ -307,7 +307,7 @@ fn sig_test_vector_conj_and_or() {
     // let res = prover.prove(
     //     &tree,
     //     &Env::empty(),
-    //     Rc::new(force_any_val::<Context>()),
+    //     Arc::new(force_any_val::<Context>()),
     //     msg.as_slice(),
     //     &HintsBag::empty(),
     // );
@@ -318,7 +318,7 @@ fn sig_test_vector_conj_and_or() {
     let ver_res = verifier.verify(
         &tree,
         &Env::empty(),
-        Rc::new(force_any_val::<Context>()),
+        Arc::new(force_any_val::<Context>()),
         signature.into(),
         msg.as_slice(),
     );
@@ -378,7 +378,7 @@ fn sig_test_vector_conj_or_and() {
     // let res = prover.prove(
     //     &tree,
     //     &Env::empty(),
-    //     Rc::new(force_any_val::<Context>()),
+    //     Arc::new(force_any_val::<Context>()),
     //     msg.as_slice(),
     //     &HintsBag::empty(),
     // );
@@ -389,7 +389,7 @@ fn sig_test_vector_conj_or_and() {
     let ver_res = verifier.verify(
         &tree,
         &Env::empty(),
-        Rc::new(force_any_val::<Context>()),
+        Arc::new(force_any_val::<Context>()),
         signature.into(),
         msg.as_slice(),
     );
@@ -458,7 +458,7 @@ fn sig_test_vector_threshold() {
     let ver_res = verifier.verify(
         &expr.try_into().unwrap(),
         &Env::empty(),
-        Rc::new(force_any_val::<Context>()),
+        Arc::new(force_any_val::<Context>()),
         signature.into(),
         msg.as_slice(),
     );
@@ -2,6 +2,8 @@
 use crate::mir::constant::Constant;
 use crate::mir::constant::TryExtractFromError;
 use crate::mir::expr::Expr;
+use crate::pretty_printer::print_expr;
+use crate::pretty_printer::PrintError;
 use crate::serialization::SigmaSerializationError;
 use crate::serialization::SigmaSerializeResult;
 use crate::serialization::{
@@ -108,6 +110,9 @@ pub enum ErgoTreeError {
     /// IO error
     #[error("IO error: {0:?}")]
     IoError(String),
+    /// Error on printing the tree as ErgoScript
+    #[error("Print error: {0}")]
+    PrintError(PrintError),
 }
 
 /// The root of ErgoScript IR. Serialized instances of this class are self sufficient and can be passed around.
@@ -175,22 +180,7 @@ impl ErgoTree {
     /// Creates a tree using provided header and root expression
     pub fn new(header: ErgoTreeHeader, expr: &Expr) -> Result<Self, ErgoTreeError> {
         Ok(if header.is_constant_segregation() {
-            let mut data = Vec::new();
-            let cs = ConstantStore::empty();
-            let mut w = SigmaByteWriter::new(&mut data, Some(cs));
-            expr.sigma_serialize(&mut w)?;
-            #[allow(clippy::unwrap_used)]
-            // We set constant store earlier
-            let constants = w.constant_store_mut_ref().unwrap().get_all();
-            let cursor = Cursor::new(&mut data[..]);
-            let new_cs = ConstantStore::new(constants.clone());
-            let mut sr = SigmaByteReader::new(cursor, new_cs);
-            let parsed_expr = Expr::sigma_parse(&mut sr)?;
-            ErgoTree::Parsed(ParsedErgoTree {
-                header,
-                constants,
-                root: parsed_expr,
-            })
+            Self::with_segregated_constants(header, expr, Vec::new())?
         } else {
             ErgoTree::Parsed(ParsedErgoTree {
                 header,
@@ -200,6 +190,32 @@ impl ErgoTree {
         })
     }
 
+    /// Creates a tree with constant segregation (`header` is expected to have the flag set),
+    /// where `constants` are the values of the ConstantPlaceholder nodes already in `expr`.
+    /// The rest of the constants in `expr` are segregated after them.
+    pub fn with_segregated_constants(
+        header: ErgoTreeHeader,
+        expr: &Expr,
+        constants: Vec<Constant>,
+    ) -> Result<Self, ErgoTreeError> {
+        let mut data = Vec::new();
+        let cs = ConstantStore::new(constants);
+        let mut w = SigmaByteWriter::new(&mut data, Some(cs));
+        expr.sigma_serialize(&mut w)?;
+        #[allow(clippy::unwrap_used)]
+        // We set constant store earlier
+        let constants = w.constant_store_mut_ref().unwrap().get_all();
+        let cursor = Cursor::new(&mut data[..]);
+        let new_cs = ConstantStore::new(constants.clone());
+        let mut sr = SigmaByteReader::new(cursor, new_cs);
+        let parsed_expr = Expr::sigma_parse(&mut sr)?;
+        Ok(ErgoTree::Parsed(ParsedErgoTree {
+            header,
+            constants,
+            root: parsed_expr,
+        }))
+    }
+
     /// Reasonable limit for the number of constants allowed in the ErgoTree
     pub const MAX_CONSTANTS_COUNT: usize = 4096;
 
@@ -233,6 +249,14 @@ impl ErgoTree {
         tree
     }
 
+    /// Decompiles the tree to ErgoScript source, with the constant placeholders replaced with
+    /// the constants of the tree (see [`crate::pretty_printer`] for the syntax that the
+    /// `ergoscript-compiler` crate does not support)
+    pub fn to_ergoscript(&self) -> Result<String, ErgoTreeError> {
+        let tree = self.parsed_tree()?;
+        Ok(print_expr(&tree.root, &tree.constants)?)
+    }
+
     /// Returns Base16-encoded serialized bytes
     pub fn to_base16_bytes(&self) -> Result<String, SigmaSerializationError> {
         let bytes = self.sigma_serialize_bytes()?;
@@ -677,6 +701,41 @@ mod tests {
         assert_eq!(new_ergo_tree.get_constant(0).unwrap().unwrap(), true.into());
     }
 
+    #[test]
+    fn test_segregated_constants_after_placeholders() {
+        use crate::mir::bin_op::{BinOp, RelationOp};
+        use crate::mir::constant::ConstantPlaceholder;
+        let expr: Expr = BinOp {
+            kind: RelationOp::Eq.into(),
+            left: Box::new(
+                ConstantPlaceholder {
+                    id: 0,
+                    tpe: SType::SInt,
+                }
+                .into(),
+            ),
+            right: Box::new(2i32.into()),
+        }
+        .into();
+        let ergo_tree =
+            ErgoTree::with_segregated_constants(ErgoTreeHeader::v0(true), &expr, vec![1i32.into()])
+                .unwrap();
+        assert_eq!(
+            ergo_tree.get_constants().unwrap(),
+            vec![1i32.into(), 2i32.into()]
+        );
+        let new_ergo_tree = ergo_tree.with_constant(0, 3i32.into()).unwrap();
+        assert_eq!(
+            new_ergo_tree.proposition().unwrap(),
+            BinOp {
+                kind: RelationOp::Eq.into(),
+                left: Box::new(3i32.into()),
+                right: Box::new(2i32.into()),
+            }
+            .into()
+        );
+    }
+
     #[test]
     fn dex_t2tpool_parse() {
         let base16_str = "19a3030f0400040204020404040404060406058080a0f6f4acdbe01b058080a0f6f4acdbe01b050004d00f0400040005000500d81ad601b2a5730000d602e4c6a70405d603db63087201d604db6308a7d605b27203730100d606b27204730200d607b27203730300d608b27204730400d609b27203730500d60ab27204730600d60b9973078c720602d60c999973088c720502720bd60d8c720802d60e998c720702720dd60f91720e7309d6108c720a02d6117e721006d6127e720e06d613998c7209027210d6147e720d06d615730ad6167e721306d6177e720c06d6187e720b06d6199c72127218d61a9c72167218d1edededededed93c27201c2a793e4c672010405720292c17201c1a793b27203730b00b27204730c00938c7205018c720601ed938c7207018c720801938c7209018c720a019593720c730d95720f929c9c721172127e7202069c7ef07213069a9c72147e7215067e9c720e720206929c9c721472167e7202069c7ef0720e069a9c72117e7215067e9c721372020695ed720f917213730e907217a19d721972149d721a7211ed9272199c7217721492721a9c72177211";
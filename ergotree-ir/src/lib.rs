@@ -26,6 +26,7 @@ pub mod bigint256;
 pub mod chain;
 pub mod ergo_tree;
 pub mod mir;
+pub mod pretty_printer;
 pub mod serialization;
 pub mod sigma_protocol;
 pub mod type_check;
@@ -0,0 +1,819 @@
+//! Pretty printer of ErgoTree expressions as ErgoScript source (decompiler)
+//!
+//! Vals and function arguments are named after their ids (`v1`, `v2`, etc.) and constant
+//! placeholders are replaced with the values of the tree's constants. Constants are printed as the
+//! ErgoScript expressions that produce them, e.g. `fromBase16("...")` for `Coll[Byte]` or
+//! `proveDlog(decodePoint(fromBase16("...")))` for a public key.
+//!
+//! The output follows the syntax of the reference (Scala) ErgoScript compiler, part of which the
+//! `ergoscript-compiler` crate does not support yet: the `%` operator, `max`/`min` and the numeric
+//! casts (`.toByte`, `.toShort`, `.toInt`, `.toLong` and `.toBigInt`, which are also used to print
+//! the `Byte` and `Short` constants, e.g. `5.toByte`). The source of a tree using them does not
+//! compile back with that crate.
+
+use std::collections::HashSet;
+
+use ergo_chain_types::EcPoint;
+use sigma_util::AsVecU8;
+use thiserror::Error;
+
+use crate::mir::bin_op::ArithOp;
+use crate::mir::bin_op::BinOpKind;
+use crate::mir::bin_op::BitOp;
+use crate::mir::bin_op::LogicalOp;
+use crate::mir::bin_op::RelationOp;
+use crate::mir::collection::Collection;
+use crate::mir::constant::Constant;
+use crate::mir::constant::Literal;
+use crate::mir::expr::Expr;
+use crate::mir::func_value::FuncValue;
+use crate::mir::global_vars::GlobalVars;
+use crate::mir::val_def::ValDef;
+use crate::mir::value::CollKind;
+use crate::mir::value::NativeColl;
+use crate::sigma_protocol::sigma_boolean::SigmaBoolean;
+use crate::sigma_protocol::sigma_boolean::SigmaConjecture;
+use crate::sigma_protocol::sigma_boolean::SigmaProofOfKnowledgeTree;
+use crate::types::stype::SType;
+
+/// Errors on printing an expression
+#[derive(Error, PartialEq, Eq, Debug, Clone)]
+pub enum PrintError {
+    /// Constant placeholder refers to a missing constant
+    #[error("Constant placeholder index {0} is out of bounds")]
+    PlaceholderOutOfBounds(u32),
+    /// Constant value has no ErgoScript syntax (e.g. `Box` or `Option`)
+    #[error("Constant of type {0:?} cannot be expressed in ErgoScript")]
+    UnsupportedConstant(SType),
+}
+
+/// Prints the expression as ErgoScript source, where `constants` are the values of the
+/// constant placeholders (the constants of the tree)
+pub fn print_expr(expr: &Expr, constants: &[Constant]) -> Result<String, PrintError> {
+    let mut printer = Printer {
+        constants,
+        indent: 0,
+        printed_vals: HashSet::new(),
+    };
+    Ok(printer.expr(expr)?.text)
+}
+
+/// Prints the constant as the ErgoScript expression producing its value
+pub fn print_constant(constant: &Constant) -> Result<String, PrintError> {
+    Ok(literal(&constant.v, &constant.tpe)?.text)
+}
+
+/// Type in the ErgoScript syntax (`Coll[Byte]`, `(Int, Long)`, etc.)
+pub fn print_type(tpe: &SType) -> String {
+    let names = |tpes: &[SType]| {
+        tpes.iter()
+            .map(print_type)
+            .collect::<Vec<String>>()
+            .join(", ")
+    };
+    match tpe {
+        SType::STypeVar(var) => var.as_string(),
+        SType::SAny => "Any".to_string(),
+        SType::SUnit => "Unit".to_string(),
+        SType::SBoolean => "Boolean".to_string(),
+        SType::SByte => "Byte".to_string(),
+        SType::SShort => "Short".to_string(),
+        SType::SInt => "Int".to_string(),
+        SType::SLong => "Long".to_string(),
+        SType::SBigInt => "BigInt".to_string(),
+        SType::SGroupElement => "GroupElement".to_string(),
+        SType::SSigmaProp => "SigmaProp".to_string(),
+        SType::SBox => "Box".to_string(),
+        SType::SAvlTree => "AvlTree".to_string(),
+        SType::SOption(elem) => format!("Option[{}]", print_type(elem)),
+        SType::SColl(elem) => format!("Coll[{}]", print_type(elem)),
+        SType::STuple(tuple) => format!("({})", names(tuple.items.as_slice())),
+        SType::SFunc(func) => format!("({}) => {}", names(&func.t_dom), print_type(&func.t_range)),
+        SType::SContext => "Context".to_string(),
+        SType::SHeader => "Header".to_string(),
+        SType::SPreHeader => "PreHeader".to_string(),
+        SType::SGlobal => "Global".to_string(),
+    }
+}
+
+const INDENT: &str = "  ";
+
+/// Precedence of `if` and lambdas, which are parenthesized in any operand position
+const LOWEST: u8 = 0;
+/// Precedence of the infix operators (the same as in Scala, by the first character)
+const OR: u8 = 1;
+const XOR: u8 = 2;
+const AND: u8 = 3;
+const EQUALITY: u8 = 4;
+const RELATION: u8 = 5;
+const ADDITIVE: u8 = 6;
+const MULTIPLICATIVE: u8 = 7;
+/// Precedence of `!x`, `-x`, `~x` (and negative literals)
+const PREFIX: u8 = 8;
+/// Precedence of literals, names, calls and `x.field`
+const ATOM: u8 = 9;
+
+/// Printed expression with the precedence of its outermost operator
+struct Doc {
+    text: String,
+    prec: u8,
+}
+
+impl Doc {
+    fn new(text: String, prec: u8) -> Self {
+        Doc { text, prec }
+    }
+
+    fn atom(text: String) -> Self {
+        Doc::new(text, ATOM)
+    }
+
+    /// Text parenthesized if the expression binds weaker than `prec`
+    fn operand(self, prec: u8) -> String {
+        if self.prec < prec {
+            format!("({})", self.text)
+        } else {
+            self.text
+        }
+    }
+}
+
+struct Printer<'a> {
+    constants: &'a [Constant],
+    /// Nesting level of the blocks
+    indent: usize,
+    /// Ids of the vals referenced in the printed text
+    printed_vals: HashSet<u32>,
+}
+
+impl<'a> Printer<'a> {
+    fn expr(&mut self, expr: &Expr) -> Result<Doc, PrintError> {
+        Ok(match expr {
+            Expr::Const(c) => literal(&c.v, &c.tpe)?,
+            Expr::ConstPlaceholder(ph) => match self.constants.get(ph.id as usize) {
+                Some(c) => literal(&c.v, &c.tpe)?,
+                None => return Err(PrintError::PlaceholderOutOfBounds(ph.id)),
+            },
+            Expr::SubstConstants(op) => self.call(
+                "substConstants",
+                &[&op.script_bytes, &op.positions, &op.new_values],
+            )?,
+            Expr::ByteArrayToLong(op) => self.call("byteArrayToLong", &[&op.input])?,
+            Expr::ByteArrayToBigInt(op) => self.call("byteArrayToBigInt", &[&op.input])?,
+            Expr::LongToByteArray(op) => self.call("longToByteArray", &[&op.input])?,
+            Expr::Collection(Collection::BoolConstants(bools)) => Doc::atom(coll(
+                bools.iter().map(|b| b.to_string()).collect(),
+                &SType::SBoolean,
+            )),
+            Expr::Collection(Collection::Exprs { elem_tpe, items }) => {
+                let items = self.list(items.iter())?;
+                Doc::atom(coll(items, elem_tpe))
+            }
+            Expr::Tuple(op) => Doc::atom(format!("({})", self.list(op.items.iter())?.join(", "))),
+            Expr::CalcBlake2b256(op) => self.call("blake2b256", &[&op.input])?,
+            Expr::CalcSha256(op) => self.call("sha256", &[&op.input])?,
+            Expr::Context => Doc::atom("CONTEXT".to_string()),
+            Expr::Global => Doc::atom("Global".to_string()),
+            Expr::GlobalVars(v) => Doc::atom(
+                match v {
+                    GlobalVars::Inputs => "INPUTS",
+                    GlobalVars::Outputs => "OUTPUTS",
+                    GlobalVars::Height => "HEIGHT",
+                    GlobalVars::SelfBox => "SELF",
+                    GlobalVars::MinerPubKey => "minerPubKey",
+                    GlobalVars::GroupGenerator => "groupGenerator",
+                }
+                .to_string(),
+            ),
+            Expr::FuncValue(f) => self.func_value(f)?,
+            Expr::Apply(op) => {
+                let func = self.expr(&op.func)?.operand(ATOM);
+                let args = self.list(op.args.iter())?;
+                Doc::atom(format!("{}({})", func, args.join(", ")))
+            }
+            // methods of `Global` are called without the receiver, e.g. `xor(a, b)`
+            Expr::MethodCall(op) if *op.obj == Expr::Global => {
+                let args = self.list(op.args.iter())?;
+                Doc::atom(format!("{}({})", op.method.name(), args.join(", ")))
+            }
+            Expr::MethodCall(op) => {
+                let args: Vec<&Expr> = op.args.iter().collect();
+                self.method(&op.obj, op.method.name(), &args)?
+            }
+            Expr::ProperyCall(op) if *op.obj == Expr::Global => {
+                Doc::atom(op.method.name().to_string())
+            }
+            Expr::ProperyCall(op) => self.property(&op.obj, op.method.name())?,
+            Expr::BlockValue(block) => Doc::atom(self.block(&block.items, &block.result)?),
+            Expr::ValDef(def) => Doc::new(self.val_def(def)?, LOWEST),
+            Expr::ValUse(v) => {
+                self.printed_vals.insert(v.val_id.0);
+                Doc::atom(format!("v{}", v.val_id.0))
+            }
+            Expr::If(op) => {
+                let condition = self.expr(&op.condition)?.text;
+                let true_branch = self.expr(&op.true_branch)?.text;
+                let false_branch = self.expr(&op.false_branch)?.text;
+                Doc::new(
+                    format!("if ({}) {} else {}", condition, true_branch, false_branch),
+                    LOWEST,
+                )
+            }
+            Expr::BinOp(op) => match infix_op(op.kind) {
+                Some((symbol, prec)) => self.infix(&op.left, symbol, &op.right, prec)?,
+                None if op.kind == BinOpKind::Arith(ArithOp::Max) => {
+                    self.call("max", &[&op.left, &op.right])?
+                }
+                None => self.call("min", &[&op.left, &op.right])?,
+            },
+            Expr::And(op) => self.call("allOf", &[&op.input])?,
+            Expr::Or(op) => self.call("anyOf", &[&op.input])?,
+            Expr::Xor(op) => self.call("xor", &[&op.left, &op.right])?,
+            Expr::Atleast(op) => self.call("atLeast", &[&op.bound, &op.input])?,
+            Expr::LogicalNot(op) => self.prefix("!", &op.input)?,
+            Expr::Negation(op) => self.prefix("-", &op.input)?,
+            Expr::BitInversion(op) => self.prefix("~", &op.input)?,
+            Expr::OptionGet(op) => self.property(&op.input, "get")?,
+            Expr::OptionIsDefined(op) => self.property(&op.input, "isDefined")?,
+            Expr::OptionGetOrElse(op) => self.method(&op.input, "getOrElse", &[&op.default])?,
+            Expr::ExtractAmount(op) => self.property(&op.input, "value")?,
+            Expr::ExtractRegisterAs(op) => self.property(
+                &op.input,
+                &format!("R{}[{}]", op.register_id, print_type(&op.elem_tpe)),
+            )?,
+            Expr::ExtractBytes(op) => self.property(&op.input, "bytes")?,
+            Expr::ExtractBytesWithNoRef(op) => self.property(&op.input, "bytesWithoutRef")?,
+            Expr::ExtractScriptBytes(op) => self.property(&op.input, "propositionBytes")?,
+            Expr::ExtractCreationInfo(op) => self.property(&op.input, "creationInfo")?,
+            Expr::ExtractId(op) => self.property(&op.input, "id")?,
+            Expr::ByIndex(op) => match &op.default {
+                Some(default) => self.method(&op.input, "getOrElse", &[&op.index, default])?,
+                None => {
+                    let input = self.expr(&op.input)?.operand(ATOM);
+                    let index = self.expr(&op.index)?.text;
+                    Doc::atom(format!("{}({})", input, index))
+                }
+            },
+            Expr::SizeOf(op) => self.property(&op.input, "size")?,
+            Expr::Slice(op) => self.method(&op.input, "slice", &[&op.from, &op.until])?,
+            Expr::Fold(op) => self.method(&op.input, "fold", &[&op.zero, &op.fold_op])?,
+            Expr::Map(op) => self.method(&op.input, "map", &[&op.mapper])?,
+            Expr::Filter(op) => self.method(&op.input, "filter", &[&op.condition])?,
+            Expr::Exists(op) => self.method(&op.input, "exists", &[&op.condition])?,
+            Expr::ForAll(op) => self.method(&op.input, "forall", &[&op.condition])?,
+            Expr::SelectField(op) => self.property(
+                &op.input,
+                &format!("_{}", op.field_index.zero_based_index() + 1),
+            )?,
+            Expr::BoolToSigmaProp(op) => self.call("sigmaProp", &[&op.input])?,
+            Expr::Upcast(op) => self.property(&op.input, &numeric_cast(&op.tpe))?,
+            Expr::Downcast(op) => self.property(&op.input, &numeric_cast(&op.tpe))?,
+            Expr::CreateProveDlog(op) => self.call("proveDlog", &[&op.input])?,
+            Expr::CreateProveDhTuple(op) => {
+                self.call("proveDHTuple", &[&op.g, &op.h, &op.u, &op.v])?
+            }
+            Expr::SigmaPropBytes(op) => self.property(&op.input, "propBytes")?,
+            Expr::DecodePoint(op) => self.call("decodePoint", &[&op.input])?,
+            Expr::SigmaAnd(op) => self.infix_chain(op.items.iter(), "&&", AND)?,
+            Expr::SigmaOr(op) => self.infix_chain(op.items.iter(), "||", OR)?,
+            Expr::GetVar(op) => Doc::atom(format!(
+                "getVar[{}]({})",
+                print_type(&op.var_tpe),
+                op.var_id
+            )),
+            Expr::DeserializeRegister(op) => {
+                let mut args = vec![op.reg.to_string()];
+                if let Some(default) = &op.default {
+                    args.push(self.expr(default)?.text);
+                }
+                Doc::atom(format!(
+                    "executeFromSelfReg[{}]({})",
+                    print_type(&op.tpe),
+                    args.join(", ")
+                ))
+            }
+            Expr::DeserializeContext(op) => Doc::atom(format!(
+                "executeFromVar[{}]({})",
+                print_type(&op.tpe),
+                op.id
+            )),
+            Expr::MultiplyGroup(op) => self.method(&op.left, "multiply", &[&op.right])?,
+            Expr::Exponentiate(op) => self.method(&op.left, "exp", &[&op.right])?,
+            Expr::XorOf(op) => self.call("xorOf", &[&op.input])?,
+            Expr::TreeLookup(op) => self.method(&op.tree, "get", &[&op.key, &op.proof])?,
+            Expr::CreateAvlTree(op) => match &op.value_length {
+                Some(value_length) => self.call(
+                    "avlTree",
+                    &[&op.flags, &op.digest, &op.key_length, value_length],
+                )?,
+                None => self.call("avlTree", &[&op.flags, &op.digest, &op.key_length])?,
+            },
+            Expr::Append(op) => self.infix(&op.input, "++", &op.col_2, ADDITIVE)?,
+        })
+    }
+
+    fn list<'e>(
+        &mut self,
+        exprs: impl Iterator<Item = &'e Expr>,
+    ) -> Result<Vec<String>, PrintError> {
+        exprs.map(|e| Ok(self.expr(e)?.text)).collect()
+    }
+
+    /// `name(args)`
+    fn call(&mut self, name: &str, args: &[&Expr]) -> Result<Doc, PrintError> {
+        let args = self.list(args.iter().copied())?;
+        Ok(Doc::atom(format!("{}({})", name, args.join(", "))))
+    }
+
+    /// `obj.name`
+    fn property(&mut self, obj: &Expr, name: &str) -> Result<Doc, PrintError> {
+        let obj = self.expr(obj)?.operand(ATOM);
+        Ok(Doc::atom(format!("{}.{}", obj, name)))
+    }
+
+    /// `obj.name(args)`
+    fn method(&mut self, obj: &Expr, name: &str, args: &[&Expr]) -> Result<Doc, PrintError> {
+        let obj = self.expr(obj)?.operand(ATOM);
+        let args = self.list(args.iter().copied())?;
+        Ok(Doc::atom(format!("{}.{}({})", obj, name, args.join(", "))))
+    }
+
+    fn prefix(&mut self, op: &str, input: &Expr) -> Result<Doc, PrintError> {
+        let input = self.expr(input)?.operand(PREFIX);
+        // `- -x` rather than `--x`
+        let separator = if input.starts_with(op) { " " } else { "" };
+        Ok(Doc::new(format!("{}{}{}", op, separator, input), PREFIX))
+    }
+
+    /// Left-associative infix operator
+    fn infix(&mut self, left: &Expr, op: &str, right: &Expr, prec: u8) -> Result<Doc, PrintError> {
+        let left = self.expr(left)?.operand(prec);
+        let right = self.expr(right)?.operand(prec + 1);
+        Ok(Doc::new(format!("{} {} {}", left, op, right), prec))
+    }
+
+    /// Items of an associative operator
+    fn infix_chain<'e>(
+        &mut self,
+        items: impl Iterator<Item = &'e Expr>,
+        op: &str,
+        prec: u8,
+    ) -> Result<Doc, PrintError> {
+        let items = items
+            .map(|item| Ok(self.expr(item)?.operand(prec)))
+            .collect::<Result<Vec<String>, PrintError>>()?;
+        Ok(Doc::new(items.join(&format!(" {} ", op)), prec))
+    }
+
+    /// Block with one statement per line
+    fn block(&mut self, items: &[Expr], result: &Expr) -> Result<String, PrintError> {
+        self.indent += 1;
+        let pad = INDENT.repeat(self.indent);
+        let mut lines = Vec::with_capacity(items.len() + 1);
+        for item in items.iter().chain(std::iter::once(result)) {
+            lines.push(format!("{}{}", pad, self.expr(item)?.text));
+        }
+        self.indent -= 1;
+        Ok(format!(
+            "{{\n{}\n{}}}",
+            lines.join("\n"),
+            INDENT.repeat(self.indent)
+        ))
+    }
+
+    fn val_def(&mut self, def: &ValDef) -> Result<String, PrintError> {
+        Ok(format!("val v{} = {}", def.id.0, self.expr(&def.rhs)?.text))
+    }
+
+    fn func_value(&mut self, f: &FuncValue) -> Result<Doc, PrintError> {
+        if let Some(doc) = self.two_arg_lambda(f)? {
+            return Ok(doc);
+        }
+        let args = f
+            .args()
+            .iter()
+            .map(|arg| format!("v{}: {}", arg.idx.0, print_type(&arg.tpe)))
+            .collect::<Vec<String>>();
+        let body = self.expr(f.body())?.text;
+        Ok(Doc::new(
+            format!("({}) => {}", args.join(", "), body),
+            LOWEST,
+        ))
+    }
+
+    /// `(acc: A, x: B) => body` for a function of a single pair argument that is only used to
+    /// unpack its fields into the vals in the beginning of the body (as the compiler lowers a
+    /// `fold` op lambda)
+    fn two_arg_lambda(&mut self, f: &FuncValue) -> Result<Option<Doc>, PrintError> {
+        let (pair, block) = match (f.args(), f.body()) {
+            ([arg], Expr::BlockValue(block)) if matches!(&arg.tpe, SType::STuple(t) if t.items.len() == 2) => {
+                (arg.idx.0, block)
+            }
+            _ => return Ok(None),
+        };
+        let (first, second) = match block.items.as_slice() {
+            [Expr::ValDef(first), Expr::ValDef(second), ..]
+                if is_field_of(first, pair, 0) && is_field_of(second, pair, 1) =>
+            {
+                (first, second)
+            }
+            _ => return Ok(None),
+        };
+        self.printed_vals.remove(&pair);
+        let body = match &block.items[2..] {
+            [] => self.expr(&block.result)?.text,
+            rest => self.block(rest, &block.result)?,
+        };
+        if self.printed_vals.contains(&pair) {
+            return Ok(None);
+        }
+        Ok(Some(Doc::new(
+            format!(
+                "(v{}: {}, v{}: {}) => {}",
+                first.id.0,
+                print_type(&first.rhs.tpe()),
+                second.id.0,
+                print_type(&second.rhs.tpe()),
+                body
+            ),
+            LOWEST,
+        )))
+    }
+}
+
+/// Whether `def` is `val _ = pair._{index + 1}`
+fn is_field_of(def: &ValDef, pair: u32, index: usize) -> bool {
+    matches!(&*def.rhs, Expr::SelectField(select)
+        if select.field_index.zero_based_index() == index
+            && matches!(&*select.input, Expr::ValUse(v) if v.val_id.0 == pair))
+}
+
+/// Operator symbol and precedence, `None` for `max` and `min` (printed as calls)
+fn infix_op(kind: BinOpKind) -> Option<(&'static str, u8)> {
+    Some(match kind {
+        BinOpKind::Arith(ArithOp::Plus) => ("+", ADDITIVE),
+        BinOpKind::Arith(ArithOp::Minus) => ("-", ADDITIVE),
+        BinOpKind::Arith(ArithOp::Multiply) => ("*", MULTIPLICATIVE),
+        BinOpKind::Arith(ArithOp::Divide) => ("/", MULTIPLICATIVE),
+        BinOpKind::Arith(ArithOp::Modulo) => ("%", MULTIPLICATIVE),
+        BinOpKind::Arith(ArithOp::Max | ArithOp::Min) => return None,
+        BinOpKind::Relation(RelationOp::Eq) => ("==", EQUALITY),
+        BinOpKind::Relation(RelationOp::NEq) => ("!=", EQUALITY),
+        BinOpKind::Relation(RelationOp::Ge) => (">=", RELATION),
+        BinOpKind::Relation(RelationOp::Gt) => (">", RELATION),
+        BinOpKind::Relation(RelationOp::Le) => ("<=", RELATION),
+        BinOpKind::Relation(RelationOp::Lt) => ("<", RELATION),
+        BinOpKind::Logical(LogicalOp::And) => ("&&", AND),
+        BinOpKind::Logical(LogicalOp::Or) => ("||", OR),
+        BinOpKind::Logical(LogicalOp::Xor) => ("^", XOR),
+        BinOpKind::Bit(BitOp::BitOr) => ("|", OR),
+        BinOpKind::Bit(BitOp::BitAnd) => ("&", AND),
+        BinOpKind::Bit(BitOp::BitXor) => ("^", XOR),
+    })
+}
+
+/// `toInt`, `toLong`, etc.
+fn numeric_cast(tpe: &SType) -> String {
+    format!("to{}", print_type(tpe))
+}
+
+/// `Coll(items)`, or `Coll[T]()` if there are no items to infer the type from
+fn coll(items: Vec<String>, elem_tpe: &SType) -> String {
+    if items.is_empty() {
+        format!("Coll[{}]()", print_type(elem_tpe))
+    } else {
+        format!("Coll({})", items.join(", "))
+    }
+}
+
+fn base16(bytes: &[u8]) -> String {
+    format!("fromBase16(\"{}\")", base16::encode_lower(bytes))
+}
+
+/// Number literal (negative numbers bind as the prefix operator)
+fn number(text: String) -> Doc {
+    let prec = if text.starts_with('-') { PREFIX } else { ATOM };
+    Doc::new(text, prec)
+}
+
+/// Number converted from `Int` with the given method
+fn converted_number(value: i64, method: &str) -> Doc {
+    Doc::atom(format!(
+        "{}.{}",
+        number(value.to_string()).operand(ATOM),
+        method
+    ))
+}
+
+/// `tpe` is the type of the whole constant (to report an unsupported value)
+fn literal(v: &Literal, tpe: &SType) -> Result<Doc, PrintError> {
+    Ok(match v {
+        Literal::Unit => Doc::atom("()".to_string()),
+        Literal::Boolean(b) => Doc::atom(b.to_string()),
+        Literal::Byte(b) => converted_number(*b as i64, "toByte"),
+        Literal::Short(s) => converted_number(*s as i64, "toShort"),
+        Literal::Int(i) => number(i.to_string()),
+        Literal::Long(l) => number(format!("{}L", l)),
+        Literal::BigInt(b) => Doc::atom(format!("bigInt(\"{}\")", b)),
+        Literal::SigmaProp(sp) => sigma_boolean(sp.value()),
+        Literal::GroupElement(point) => Doc::atom(group_element(point)),
+        Literal::AvlTree(avl) => {
+            let mut args = vec![
+                converted_number(avl.tree_flags.serialize() as i64, "toByte").text,
+                base16(&avl.digest.0),
+                avl.key_length.to_string(),
+            ];
+            if let Some(value_length) = &avl.value_length_opt {
+                args.push(value_length.to_string());
+            }
+            Doc::atom(format!("avlTree({})", args.join(", ")))
+        }
+        Literal::Coll(CollKind::NativeColl(NativeColl::CollByte(bytes))) => {
+            Doc::atom(base16(&bytes.as_vec_u8()))
+        }
+        Literal::Coll(CollKind::WrappedColl { elem_tpe, items }) => {
+            let items = items
+                .iter()
+                .map(|item| Ok(literal(item, tpe)?.text))
+                .collect::<Result<Vec<String>, PrintError>>()?;
+            Doc::atom(coll(items, elem_tpe))
+        }
+        Literal::Tup(items) => {
+            let items = items
+                .iter()
+                .map(|item| Ok(literal(item, tpe)?.text))
+                .collect::<Result<Vec<String>, PrintError>>()?;
+            Doc::atom(format!("({})", items.join(", ")))
+        }
+        Literal::CBox(_) | Literal::Opt(_) => {
+            return Err(PrintError::UnsupportedConstant(tpe.clone()))
+        }
+    })
+}
+
+fn group_element(point: &EcPoint) -> String {
+    format!(
+        "decodePoint(fromBase16(\"{}\"))",
+        String::from(point.clone())
+    )
+}
+
+fn sigma_boolean(sb: &SigmaBoolean) -> Doc {
+    let items = |items: &mut dyn Iterator<Item = &SigmaBoolean>, prec: u8| {
+        items
+            .map(|item| sigma_boolean(item).operand(prec))
+            .collect::<Vec<String>>()
+    };
+    match sb {
+        SigmaBoolean::TrivialProp(b) => Doc::atom(format!("sigmaProp({})", b)),
+        SigmaBoolean::ProofOfKnowledge(SigmaProofOfKnowledgeTree::ProveDlog(pk)) => {
+            Doc::atom(format!("proveDlog({})", group_element(&pk.h)))
+        }
+        SigmaBoolean::ProofOfKnowledge(SigmaProofOfKnowledgeTree::ProveDhTuple(dht)) => {
+            Doc::atom(format!(
+                "proveDHTuple({}, {}, {}, {})",
+                group_element(&dht.g),
+                group_element(&dht.h),
+                group_element(&dht.u),
+                group_element(&dht.v)
+            ))
+        }
+        SigmaBoolean::SigmaConjecture(SigmaConjecture::Cand(cand)) => {
+            Doc::new(items(&mut cand.items.iter(), AND).join(" && "), AND)
+        }
+        SigmaBoolean::SigmaConjecture(SigmaConjecture::Cor(cor)) => {
+            Doc::new(items(&mut cor.items.iter(), OR).join(" || "), OR)
+        }
+        SigmaBoolean::SigmaConjecture(SigmaConjecture::Cthreshold(ct)) => Doc::atom(format!(
+            "atLeast({}, Coll({}))",
+            ct.k,
+            items(&mut ct.children.iter(), LOWEST).join(", ")
+        )),
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use crate::ergo_tree::ErgoTree;
+    use crate::mir::bin_op::BinOp;
+    use crate::mir::block::BlockValue;
+    use crate::mir::coll_fold::Fold;
+    use crate::mir::constant::ConstantPlaceholder;
+    use crate::mir::extract_amount::ExtractAmount;
+    use crate::mir::func_value::FuncArg;
+    use crate::mir::negation::Negation;
+    use crate::mir::select_field::SelectField;
+    use crate::mir::unary_op::OneArgOpTryBuild;
+    use crate::mir::upcast::Upcast;
+    use crate::mir::val_def::ValId;
+    use crate::mir::val_use::ValUse;
+    use crate::serialization::SigmaSerializable;
+    use crate::sigma_protocol::sigma_boolean::cand::Cand;
+    use crate::sigma_protocol::sigma_boolean::cor::Cor;
+    use crate::sigma_protocol::sigma_boolean::ProveDlog;
+    use crate::sigma_protocol::sigma_boolean::SigmaProp;
+    use crate::types::stuple::STuple;
+    use ergo_chain_types::EcPoint;
+    use expect_test::expect;
+    use expect_test::Expect;
+    use std::convert::TryInto;
+
+    fn check_tree(tree_hex: &str, expected: Expect) {
+        let bytes = base16::decode(tree_hex.as_bytes()).unwrap();
+        let tree = ErgoTree::sigma_parse_bytes(&bytes).unwrap();
+        expected.assert_eq(&tree.to_ergoscript().unwrap());
+    }
+
+    fn check(expr: Expr, expected: Expect) {
+        expected.assert_eq(&print_expr(&expr, &[]).unwrap());
+    }
+
+    fn bin_op(kind: BinOpKind, left: Expr, right: Expr) -> Expr {
+        BinOp {
+            kind,
+            left: left.into(),
+            right: right.into(),
+        }
+        .into()
+    }
+
+    fn val_use(id: u32, tpe: SType) -> Expr {
+        ValUse {
+            val_id: ValId(id),
+            tpe,
+        }
+        .into()
+    }
+
+    fn pk(hex: &str) -> SigmaBoolean {
+        ProveDlog::new(EcPoint::from_base16_str(hex.to_string()).unwrap()).into()
+    }
+
+    #[test]
+    fn test_miner_fee_contract() {
+        check_tree(
+            "1005040004000e36100204a00b08cd0279be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798ea02d192a39a8cc7a701730073011001020402d19683030193a38cc7b2a57300000193c2b2a57301007473027303830108cdeeac93b1a57304",
+            expect![[r#"sigmaProp(allOf(Coll(HEIGHT == OUTPUTS(0).creationInfo._1, OUTPUTS(0).propositionBytes == substConstants(fromBase16("100204a00b08cd0279be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798ea02d192a39a8cc7a70173007301"), Coll(1), Coll(proveDlog(decodePoint(minerPubKey)))), OUTPUTS.size == 1)))"#]],
+        );
+    }
+
+    #[test]
+    fn test_vals_and_registers() {
+        check_tree(
+            "100604000400050004000e20b662db51cf2dc39f110a021c2a31c74f0a1a18ffffbf73e8a051a7b8c0f09ebc0e2079974b2314c531e62776e6bc4babff35b37b178cebf0976fc0f416ff34ddbc4fd803d601b2a5730000d602e4c6a70407d603b2db6501fe730100ea02d1ededededed93e4c672010407720293e4c67201050ec5720391e4c672010605730293c27201c2a793db63087201db6308a7ed938cb2db6308720373030001730493cbc272037305cd7202",
+            expect![[r#"
+                {
+                  val v1 = OUTPUTS(0)
+                  val v2 = SELF.R4[GroupElement].get
+                  val v3 = CONTEXT.dataInputs(0)
+                  sigmaProp(v1.R4[GroupElement].get == v2 && v1.R5[Coll[Byte]].get == v3.id && v1.R6[Long].get > 0L && v1.propositionBytes == SELF.propositionBytes && v1.tokens == SELF.tokens && (v3.tokens(0)._1 == fromBase16("b662db51cf2dc39f110a021c2a31c74f0a1a18ffffbf73e8a051a7b8c0f09ebc") && blake2b256(v3.propositionBytes) == fromBase16("79974b2314c531e62776e6bc4babff35b37b178cebf0976fc0f416ff34ddbc4f"))) && proveDlog(v2)
+                }"#]],
+        );
+    }
+
+    #[test]
+    fn test_precedence() {
+        let height = || Expr::from(GlobalVars::Height);
+        let plus = bin_op(
+            BinOpKind::Arith(ArithOp::Plus),
+            height(),
+            Constant::from(1i32).into(),
+        );
+        check(
+            bin_op(
+                BinOpKind::Arith(ArithOp::Minus),
+                bin_op(
+                    BinOpKind::Arith(ArithOp::Multiply),
+                    plus,
+                    Constant::from(2i32).into(),
+                ),
+                bin_op(
+                    BinOpKind::Arith(ArithOp::Minus),
+                    height(),
+                    Constant::from(-1i32).into(),
+                ),
+            ),
+            expect!["(HEIGHT + 1) * 2 - (HEIGHT - -1)"],
+        );
+        check(
+            Negation {
+                input: Box::new(
+                    Negation {
+                        input: height().into(),
+                    }
+                    .into(),
+                ),
+            }
+            .into(),
+            expect!["- -HEIGHT"],
+        );
+        check(
+            Upcast::new(Constant::from(-5i32).into(), SType::SLong)
+                .unwrap()
+                .into(),
+            expect!["(-5).toLong"],
+        );
+        check(Constant::from(-5i8).into(), expect!["(-5).toByte"]);
+    }
+
+    #[test]
+    fn test_fold_lambda() {
+        let pair_tpe: SType = STuple::pair(SType::SLong, SType::SBox).into();
+        let field = |index: u8| -> Expr {
+            SelectField::new(val_use(0, pair_tpe.clone()), index.try_into().unwrap())
+                .unwrap()
+                .into()
+        };
+        let fold = |result: Expr| -> Expr {
+            let body = BlockValue {
+                items: vec![
+                    ValDef {
+                        id: ValId(1),
+                        rhs: field(1).into(),
+                    }
+                    .into(),
+                    ValDef {
+                        id: ValId(2),
+                        rhs: field(2).into(),
+                    }
+                    .into(),
+                ],
+                result: result.into(),
+            };
+            let op = FuncValue::new(
+                vec![FuncArg {
+                    idx: ValId(0),
+                    tpe: pair_tpe.clone(),
+                }],
+                body.into(),
+            );
+            Fold::new(
+                GlobalVars::Outputs.into(),
+                Constant::from(0i64).into(),
+                op.into(),
+            )
+            .unwrap()
+            .into()
+        };
+        let sum = bin_op(
+            BinOpKind::Arith(ArithOp::Plus),
+            val_use(1, SType::SLong),
+            ExtractAmount::try_build(val_use(2, SType::SBox))
+                .unwrap()
+                .into(),
+        );
+        check(
+            fold(sum),
+            expect!["OUTPUTS.fold(0L, (v1: Long, v2: Box) => v1 + v2.value)"],
+        );
+        // the pair itself is used
+        check(
+            fold(field(1)),
+            expect![[r#"
+            OUTPUTS.fold(0L, (v0: (Long, Box)) => {
+              val v1 = v0._1
+              val v2 = v0._2
+              v0._1
+            })"#]],
+        );
+    }
+
+    #[test]
+    fn test_sigma_prop_constant() {
+        let a = pk("0279be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798");
+        let b = pk("021dde34603426402615658f1d970cfa7c7bd92ac81a8b16eeebff264d59ce4604");
+        let sb: SigmaBoolean = Cand {
+            items: vec![
+                a.clone(),
+                Cor {
+                    items: vec![a, b].try_into().unwrap(),
+                }
+                .into(),
+            ]
+            .try_into()
+            .unwrap(),
+        }
+        .into();
+        check(
+            Constant::from(SigmaProp::from(sb)).into(),
+            expect![[
+                r#"proveDlog(decodePoint(fromBase16("0279be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798"))) && (proveDlog(decodePoint(fromBase16("0279be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798"))) || proveDlog(decodePoint(fromBase16("021dde34603426402615658f1d970cfa7c7bd92ac81a8b16eeebff264d59ce4604"))))"#
+            ]],
+        );
+    }
+
+    #[test]
+    fn test_placeholder_out_of_bounds() {
+        let expr: Expr = ConstantPlaceholder {
+            id: 1,
+            tpe: SType::SInt,
+        }
+        .into();
+        assert_eq!(
+            print_expr(&expr, &[Constant::from(1i32)]),
+            Err(PrintError::PlaceholderOutOfBounds(1))
+        );
+    }
+}
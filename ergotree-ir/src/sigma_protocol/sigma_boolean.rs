@@ -28,6 +28,7 @@ pub mod cthreshold;
 pub type SigmaConjectureItems<T> = BoundedVec<T, 2, 255>;
 
 /// Construct a new SigmaBoolean value representing public key of discrete logarithm signature protocol.
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
 #[derive(PartialEq, Eq, Debug, Clone)]
 pub struct ProveDlog {
     /// public key
@@ -96,7 +96,8 @@ impl SType {
         )
     }
 
-    pub(crate) fn with_subst(self, subst: &HashMap<STypeVar, SType>) -> Self {
+    /// Substitutes the type variables with the types from `subst`
+    pub fn with_subst(self, subst: &HashMap<STypeVar, SType>) -> Self {
         match self {
             SType::STypeVar(ref tpe_var) => subst.get(tpe_var).cloned().unwrap_or(self),
             SType::SOption(tpe) => SType::SOption(tpe.with_subst(subst).into()),